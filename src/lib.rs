@@ -1,11 +1,29 @@
 #[path = "xlsx/book.rs"]
 pub mod book;
+#[path = "xlsx/calc.rs"]
+pub mod calc;
 #[path = "xlsx/cell.rs"]
 pub mod cell;
+#[path = "xlsx/dimension.rs"]
+pub mod dimension;
+#[path = "xlsx/formula.rs"]
+pub mod formula;
+#[path = "xlsx/metrics.rs"]
+pub mod metrics;
+#[path = "xlsx/name.rs"]
+pub mod name;
+#[path = "xlsx/number_format.rs"]
+pub mod number_format;
+#[path = "xlsx/print.rs"]
+pub mod print;
+#[path = "xlsx/range.rs"]
+pub mod range;
 #[path = "xlsx/sheet.rs"]
 pub mod sheet;
 #[path = "xlsx/style.rs"]
 pub mod style;
+#[path = "xlsx/view.rs"]
+pub mod view;
 #[path = "xlsx/xml.rs"]
 pub mod xml;
 
@@ -13,19 +31,48 @@ pub mod xml;
 #[path = "xlsx/test_book.rs"]
 mod test_book;
 #[cfg(test)]
+#[path = "xlsx/test_calc.rs"]
+mod test_calc;
+#[cfg(test)]
 #[path = "xlsx/test_cell.rs"]
 mod test_cell;
 #[cfg(test)]
+#[path = "xlsx/test_dimension.rs"]
+mod test_dimension;
+#[cfg(test)]
+#[path = "xlsx/test_formula.rs"]
+mod test_formula;
+#[cfg(test)]
+#[path = "xlsx/test_name.rs"]
+mod test_name;
+#[cfg(test)]
+#[path = "xlsx/test_number_format.rs"]
+mod test_number_format;
+#[cfg(test)]
+#[path = "xlsx/test_print.rs"]
+mod test_print;
+#[cfg(test)]
+#[path = "xlsx/test_range.rs"]
+mod test_range;
+#[cfg(test)]
 #[path = "xlsx/test_sheet.rs"]
 mod test_sheet;
 #[cfg(test)]
+#[path = "xlsx/test_view.rs"]
+mod test_view;
+#[cfg(test)]
 #[path = "xlsx/test_xml.rs"]
 mod test_xml;
 
 use pyo3::prelude::*;
 
 use book::Book;
-use cell::Cell;
+use cell::{Cell, CellError};
+use dimension::{ColumnDimension, ColumnDimensions, RowDimension, RowDimensions};
+use formula::{FormulaNode, parse_formula, tokenize_formula};
+use name::{DefinedName, DefinedNames};
+use print::{HeaderFooter, HeaderFooterPart, PageMargins, PageSetup, PrintOptions};
+use range::{Range, RangeIterator};
 use sheet::Sheet;
 use style::{Font, PatternFill};
 use xml::{Xml, XmlElement};
@@ -44,9 +91,26 @@ pub fn load_workbook(path: String) -> Book {
 fn xlsx(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(hello_from_bin, m)?)?;
     m.add_function(wrap_pyfunction!(load_workbook, m)?)?;
+    m.add_function(wrap_pyfunction!(parse_formula, m)?)?;
+    m.add_function(wrap_pyfunction!(tokenize_formula, m)?)?;
     m.add_class::<Book>()?;
     m.add_class::<Sheet>()?;
+    m.add_class::<Range>()?;
+    m.add_class::<RangeIterator>()?;
     m.add_class::<Cell>()?;
+    m.add_class::<CellError>()?;
+    m.add_class::<FormulaNode>()?;
+    m.add_class::<RowDimensions>()?;
+    m.add_class::<RowDimension>()?;
+    m.add_class::<ColumnDimensions>()?;
+    m.add_class::<ColumnDimension>()?;
+    m.add_class::<DefinedNames>()?;
+    m.add_class::<DefinedName>()?;
+    m.add_class::<PageSetup>()?;
+    m.add_class::<PageMargins>()?;
+    m.add_class::<PrintOptions>()?;
+    m.add_class::<HeaderFooter>()?;
+    m.add_class::<HeaderFooterPart>()?;
     m.add_class::<Font>()?;
     m.add_class::<PatternFill>()?;
     m.add_class::<Xml>()?;
@@ -1,14 +1,29 @@
 from ._core import (
     Book,
     Cell,
+    CellError,
+    ColumnDimension,
+    ColumnDimensions,
+    DefinedName,
+    DefinedNames,
     Font,
+    FormulaNode,
+    HeaderFooter,
+    HeaderFooterPart,
+    PageMargins,
+    PageSetup,
     PatternFill,
+    PrintOptions,
+    Range,
+    RowDimension,
+    RowDimensions,
     Sheet,
     Xml,
     XmlElement,
     hello_from_bin,
     load_workbook,
 )
+from . import formula
 
 __all__ = [
     "hello",
@@ -16,10 +31,25 @@ __all__ = [
     "Book",
     "Sheet",
     "Cell",
+    "Range",
+    "CellError",
+    "RowDimensions",
+    "RowDimension",
+    "ColumnDimensions",
+    "ColumnDimension",
+    "FormulaNode",
+    "DefinedNames",
+    "DefinedName",
+    "PageSetup",
+    "PageMargins",
+    "PrintOptions",
+    "HeaderFooter",
+    "HeaderFooterPart",
     "Font",
     "PatternFill",
     "Xml",
     "XmlElement",
+    "formula",
 ]
 
 
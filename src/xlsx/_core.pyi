@@ -1,11 +1,15 @@
-from typing import Iterator, Optional
+from typing import Iterator, Literal, Optional, Union, overload
 
 def hello_from_bin() -> str: ...
 def load_workbook(path: str) -> "Book": ...
+def parse_formula(formula: str, r1c1: bool = False) -> "FormulaNode": ...
+def tokenize_formula(formula: str, r1c1: bool = False) -> list[tuple[str, str]]: ...
 
 class Book:
     path: str
     sheetnames: list[str]
+    visible_sheetnames: list[str]
+    full_calc_on_load: bool
 
     def __init__(self, path: str = ...) -> None: ...
     def __iter__(self) -> Iterator[str]: ...
@@ -15,21 +19,287 @@ class Book:
     def index(self, sheet: "Sheet") -> int: ...
     def remove(self, sheet: "Sheet") -> None: ...
     def create_sheet(self, title: str, index: int) -> "Sheet": ...
+    def copy_worksheet(self, sheet: "Sheet", title: Optional[str] = None) -> "Sheet": ...
+    def import_sheet(
+        self, other: "Book", name: str, title: Optional[str] = None
+    ) -> "Sheet": ...
     def copy(self, path: str) -> None: ...
+    def calculate(self) -> None: ...
+    def rename_sheet(self, old_name: str, new_name: str) -> None: ...
+    def move_sheet(self, sheet: Union["Sheet", int, str], offset: int) -> None: ...
+    def reorder_sheets(self, names: list[str]) -> None: ...
+    @property
+    def active(self) -> Optional["Sheet"]: ...
+    @active.setter
+    def active(self, sheet: Union["Sheet", int, str]) -> None: ...
+    @property
+    def defined_names(self) -> "DefinedNames": ...
+    def resolve_name(self, name: str, sheet: Optional[str] = None) -> list["Cell"]: ...
 
 class Sheet:
     @property
     def name(self) -> str: ...
-    def __getitem__(self, key: str) -> "Cell": ...
+    title: str
+    sheet_state: str
+    @overload
+    def __getitem__(self, key: int) -> list["Cell"]: ...
+    @overload
+    def __getitem__(
+        self, key: str
+    ) -> Union["Cell", list["Cell"], list[list["Cell"]]]: ...
+    def range(self, key: str) -> "Range": ...
     def cell(self, row: int, column: int) -> "Cell": ...
+    @property
+    def min_row(self) -> int: ...
+    @property
+    def max_row(self) -> int: ...
+    @property
+    def min_column(self) -> int: ...
+    @property
+    def max_column(self) -> int: ...
+    @property
+    def dimensions(self) -> str: ...
     def append(self, row: list[str]) -> None: ...
-    def iter_rows(self, values_only: bool = False) -> Iterator[list[str]]: ...
+    @overload
+    def iter_rows(
+        self,
+        min_row: Optional[int] = None,
+        max_row: Optional[int] = None,
+        min_col: Optional[int] = None,
+        max_col: Optional[int] = None,
+        values_only: Literal[False] = False,
+    ) -> Iterator[list["Cell"]]: ...
+    @overload
+    def iter_rows(
+        self,
+        min_row: Optional[int] = None,
+        max_row: Optional[int] = None,
+        min_col: Optional[int] = None,
+        max_col: Optional[int] = None,
+        *,
+        values_only: Literal[True],
+    ) -> Iterator[list[Optional[Union[str, "CellError"]]]]: ...
+    @overload
+    def iter_cols(
+        self,
+        min_row: Optional[int] = None,
+        max_row: Optional[int] = None,
+        min_col: Optional[int] = None,
+        max_col: Optional[int] = None,
+        values_only: Literal[False] = False,
+    ) -> Iterator[list["Cell"]]: ...
+    @overload
+    def iter_cols(
+        self,
+        min_row: Optional[int] = None,
+        max_row: Optional[int] = None,
+        min_col: Optional[int] = None,
+        max_col: Optional[int] = None,
+        *,
+        values_only: Literal[True],
+    ) -> Iterator[list[Optional[Union[str, "CellError"]]]]: ...
+    def set_array_formula(
+        self, range: str, formula: str, dynamic: bool = False
+    ) -> None: ...
+    @property
+    def row_dimensions(self) -> "RowDimensions": ...
+    @property
+    def column_dimensions(self) -> "ColumnDimensions": ...
+    def auto_fit_columns(self) -> None: ...
+    def merge_cells(self, range: str) -> None: ...
+    def unmerge_cells(self, range: str) -> None: ...
+    @property
+    def merged_cells(self) -> list[str]: ...
+    freeze_panes: Optional[str]
+    def split_panes(
+        self,
+        x_split: float = 0.0,
+        y_split: float = 0.0,
+        top_left_cell: Optional[str] = None,
+    ) -> None: ...
+    @property
+    def pane_split(self) -> Optional[tuple[float, float]]: ...
+    active_pane: str
+    def set_selection(
+        self,
+        sqref: str,
+        active_cell: Optional[str] = None,
+        pane: Optional[str] = None,
+    ) -> None: ...
+    def get_selection(self, pane: Optional[str] = None) -> Optional[tuple[str, str]]: ...
+    zoom_scale: int
+    show_grid_lines: bool
+    show_row_col_headers: bool
+    right_to_left: bool
+    view: str
+    active_cell: str
+    selected_cells: str
+    tab_color: Optional[str]
+    @property
+    def page_setup(self) -> "PageSetup": ...
+    @property
+    def page_margins(self) -> "PageMargins": ...
+    @property
+    def print_options(self) -> "PrintOptions": ...
+    @property
+    def header_footer(self) -> "HeaderFooter": ...
+    print_area: str | None
+    print_title_rows: str | None
+    print_title_cols: str | None
+    def add_page_break(self, row: int | None = None, col: int | None = None) -> None: ...
+    @property
+    def row_breaks(self) -> list[int]: ...
+    @property
+    def col_breaks(self) -> list[int]: ...
+    def insert_rows(self, idx: int, amount: int = 1) -> None: ...
+    def delete_rows(self, idx: int, amount: int = 1) -> None: ...
+    def insert_cols(self, idx: int, amount: int = 1) -> None: ...
+    def delete_cols(self, idx: int, amount: int = 1) -> None: ...
+
+class RowDimensions:
+    default_height: float
+    def __getitem__(self, index: int) -> "RowDimension": ...
+
+class RowDimension:
+    @property
+    def index(self) -> int: ...
+    height: Optional[float]
+    hidden: bool
+    outline_level: int
+    style: Optional[int]
+
+class ColumnDimensions:
+    default_width: float
+    def __getitem__(self, key: str) -> "ColumnDimension": ...
+
+class ColumnDimension:
+    @property
+    def index(self) -> int: ...
+    width: Optional[float]
+    hidden: bool
+    best_fit: bool
+    outline_level: int
+    style: Optional[int]
+
+class DefinedName:
+    name: str
+    value: str
+    local_sheet_id: Optional[int]
+    hidden: bool
+    comment: Optional[str]
+    def __init__(
+        self,
+        name: str,
+        value: str,
+        local_sheet_id: Optional[int] = None,
+        hidden: bool = False,
+        comment: Optional[str] = None,
+    ) -> None: ...
+    @property
+    def destinations(self) -> list[tuple[str, str]]: ...
+
+class DefinedNames:
+    def add(self, defined_name: DefinedName) -> None: ...
+    def remove(self, name: str, local_sheet_id: Optional[int] = None) -> None: ...
+    def get(
+        self, name: str, local_sheet_id: Optional[int] = None
+    ) -> Optional[DefinedName]: ...
+    def values(self) -> list[DefinedName]: ...
+    def __getitem__(self, name: str) -> DefinedName: ...
+    def __delitem__(self, name: str) -> None: ...
+    def __contains__(self, name: str) -> bool: ...
+    def __len__(self) -> int: ...
+    def __iter__(self) -> Iterator[DefinedName]: ...
+
+class PageSetup:
+    orientation: Optional[str]
+    paper_size: Optional[int]
+    fit_to_width: Optional[int]
+    fit_to_height: Optional[int]
+    scale: Optional[int]
+    fit_to_page: bool
+
+class PageMargins:
+    left: float
+    right: float
+    top: float
+    bottom: float
+    header: float
+    footer: float
+
+class PrintOptions:
+    grid_lines: bool
+    headings: bool
+    horizontal_centered: bool
+    vertical_centered: bool
+
+class HeaderFooterPart:
+    left: Optional[str]
+    center: Optional[str]
+    right: Optional[str]
+    font: Optional[str]
+    size: Optional[int]
+    color: Optional[str]
+    def __init__(
+        self,
+        left: Optional[str] = None,
+        center: Optional[str] = None,
+        right: Optional[str] = None,
+        font: Optional[str] = None,
+        size: Optional[int] = None,
+        color: Optional[str] = None,
+    ) -> None: ...
+    def build(self) -> str: ...
+    @staticmethod
+    def parse(text: str) -> "HeaderFooterPart": ...
+
+class HeaderFooter:
+    odd_header: Optional[HeaderFooterPart]
+    odd_footer: Optional[HeaderFooterPart]
+    even_header: Optional[HeaderFooterPart]
+    even_footer: Optional[HeaderFooterPart]
+    first_header: Optional[HeaderFooterPart]
+    first_footer: Optional[HeaderFooterPart]
+
+class Range:
+    @property
+    def min_row(self) -> int: ...
+    @property
+    def min_col(self) -> int: ...
+    @property
+    def max_row(self) -> int: ...
+    @property
+    def max_col(self) -> int: ...
+    @property
+    def coord(self) -> str: ...
+    @property
+    def shape(self) -> tuple[int, int]: ...
+    def rows(self) -> list[list["Cell"]]: ...
+    def cols(self) -> list[list["Cell"]]: ...
+    @property
+    def values(self) -> list[list[Optional[Union[str, "CellError"]]]]: ...
+    @property
+    def value(self) -> list[list[Optional[Union[str, "CellError"]]]]: ...
+    @value.setter
+    def value(self, values: list[list[Optional[Union[str, "CellError"]]]]) -> None: ...
+    def __iter__(self) -> Iterator[list["Cell"]]: ...
+    def __len__(self) -> int: ...
 
 class Cell:
     @property
-    def value(self) -> Optional[str]: ...
+    def value(self) -> Optional[Union[str, "CellError"]]: ...
     @value.setter
-    def value(self, value: str) -> None: ...
+    def value(self, value: Union[str, "CellError"]) -> None: ...
+    @property
+    def formula(self) -> Optional[str]: ...
+    @formula.setter
+    def formula(self, formula: Optional[str]) -> None: ...
+    def set_formula(
+        self,
+        formula: str,
+        cached_value: Optional[str] = ...,
+        cached_type: Optional[str] = ...,
+    ) -> None: ...
     @property
     def font(self) -> Optional["Font"]: ...
     @font.setter
@@ -39,6 +309,17 @@ class Cell:
     @fill.setter
     def fill(self, font: "PatternFill") -> None: ...
 
+class CellError:
+    code: str
+    def __init__(self, code: str) -> None: ...
+
+class FormulaNode:
+    kind: str
+    value: Optional[str]
+    sheet: Optional[str]
+    children: list["FormulaNode"]
+    formula: str
+
 class Font:
     name: Optional[str]
     size: Optional[float]
@@ -1,15 +1,30 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::{File, OpenOptions};
 use std::io::{BufReader, BufWriter, Cursor, Read, Write};
 use std::sync::{Arc, Mutex, MutexGuard};
 use zip::write::FileOptions;
 use zip::{ZipArchive, ZipWriter};
 
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use thiserror::Error;
 
+use crate::calc::{self, CalcError, Calculator, Value};
+use crate::cell::Cell;
+use crate::formula::{self, parse_range};
+use crate::name::{DefinedName, DefinedNames, NameError};
 use crate::sheet::Sheet;
+use crate::style;
+use crate::view;
 use crate::xml::{Xml, XmlElement};
 
+/// シート名の最大文字数
+const MAX_SHEET_NAME_LENGTH: usize = 31;
+/// シート名に使用できない文字
+const INVALID_SHEET_NAME_CHARS: [char; 7] = ['[', ']', ':', '*', '?', '/', '\\'];
+/// シートの表示状態 (`sheet` の `state`)
+pub const SHEET_STATES: [&str; 3] = ["visible", "hidden", "veryHidden"];
+
 /// XMLファイルのサフィックス
 const XML_SUFFIX: &str = ".xml";
 /// リレーションシップファイルのサフィックス
@@ -23,6 +38,14 @@ const WORKBOOK_FILENAME: &str = "xl/workbook.xml";
 const STYLES_FILENAME: &str = "xl/styles.xml";
 /// 共有文字列XMLのファイル名
 const SHARED_STRINGS_FILENAME: &str = "xl/sharedStrings.xml";
+/// セルメタデータXMLのファイル名
+const METADATA_FILENAME: &str = "xl/metadata.xml";
+/// コンテンツタイプXMLのファイル名
+const CONTENT_TYPES_FILENAME: &str = "[Content_Types].xml";
+/// ワークブックリレーションシップのファイル名
+const WORKBOOK_RELS_FILENAME: &str = "xl/_rels/workbook.xml.rels";
+/// アプリケーションのプロパティのファイル名
+const APP_PROPERTIES_FILENAME: &str = "docProps/app.xml";
 
 /// ワークブックリレーションシップのプレフィックス
 const WORKBOOK_RELS_PREFIX: &str = "xl/_rels/";
@@ -30,6 +53,8 @@ const WORKBOOK_RELS_PREFIX: &str = "xl/_rels/";
 const WORKSHEETS_RELS_PREFIX: &str = "xl/worksheets/_rels/";
 /// 図形のプレフィックス
 const DRAWINGS_PREFIX: &str = "xl/drawings/";
+/// グラフのプレフィックス
+const CHARTS_PREFIX: &str = "xl/charts/";
 /// テーマのプレフィックス
 const THEME_PREFIX: &str = "xl/theme/";
 /// ワークシートのプレフィックス
@@ -41,6 +66,673 @@ const PIVOT_TABLES_PREFIX: &str = "xl/pivotTables/";
 /// ピボットキャッシュのプレフィックス
 const PIVOT_CACHES_PREFIX: &str = "xl/pivotCache/";
 
+/// ワークシートのコンテンツタイプ
+const WORKSHEET_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml";
+/// テーブルのコンテンツタイプ
+const TABLE_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.table+xml";
+/// セルメタデータのコンテンツタイプ
+const METADATA_CONTENT_TYPE: &str =
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheetMetadata+xml";
+/// セルメタデータのリレーションシップタイプ
+const METADATA_REL_TYPE: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/sheetMetadata";
+
+/// `workbook.xml` 内の子要素の順序
+pub(crate) const WORKBOOK_CHILD_ORDER: [&str; 19] = [
+    "fileVersion",
+    "fileSharing",
+    "workbookPr",
+    "workbookProtection",
+    "bookViews",
+    "sheets",
+    "functionGroups",
+    "externalReferences",
+    "definedNames",
+    "calcPr",
+    "oleSize",
+    "customWorkbookViews",
+    "pivotCaches",
+    "smartTagPr",
+    "smartTagTypes",
+    "webPublishing",
+    "fileRecoveryPr",
+    "webPublishObjects",
+    "extLst",
+];
+
+/// ワークブックの操作のエラー
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum BookError {
+    #[error("No sheet named '{0}'")]
+    SheetNotFound(String),
+    #[error("Sheet index out of range: {0}")]
+    SheetIndexOutOfRange(usize),
+    #[error("Invalid sheet name: {0}")]
+    InvalidSheetName(String),
+    #[error("Sheet name already exists: {0}")]
+    DuplicateSheetName(String),
+    #[error("Sheet order must list every sheet exactly once")]
+    InvalidSheetOrder,
+    #[error("Invalid sheet state: {0}")]
+    InvalidSheetState(String),
+    #[error("Cannot hide the active sheet: {0}")]
+    ActiveSheetHidden(String),
+    #[error("A workbook must have at least one visible sheet")]
+    LastVisibleSheet,
+    #[error("Cannot activate a hidden sheet: {0}")]
+    HiddenSheetActivated(String),
+}
+
+impl From<BookError> for PyErr {
+    fn from(err: BookError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// シートの指定 (シート、シートの位置またはシート名)
+#[derive(FromPyObject)]
+pub enum SheetKey {
+    Sheet(Sheet),
+    Index(usize),
+    Name(String),
+}
+
+/// 複製するパーツの内容
+enum PartContent {
+    /// XMLとして管理しているパーツ
+    Xml(Xml),
+    /// その他のファイル
+    Raw(Vec<u8>),
+}
+
+/// パーツのコンテンツタイプ
+enum ContentType {
+    /// パーツ名ごとの `Override`
+    Override(String),
+    /// 拡張子ごとの `Default` (拡張子, コンテンツタイプ)
+    Default(String, String),
+}
+
+/// 複製のために収集したパーツ
+struct CopiedPart {
+    /// パーツの内容
+    content: PartContent,
+    /// 元のブックでのコンテンツタイプ
+    content_type: Option<ContentType>,
+    /// パーツのリレーションシップ (`Target` は元のパーツからの相対パス)
+    rels: Option<Xml>,
+}
+
+/// パスをキーとする共有のXMLパーツの一覧
+///
+/// `Book` とそこから取得した `Sheet` の間で共有し、どちらからの変更も保存時に反映される
+#[derive(Clone, Default)]
+pub struct XmlParts(Arc<Mutex<HashMap<String, Arc<Mutex<Xml>>>>>);
+
+impl XmlParts {
+    fn lock(&self) -> MutexGuard<'_, HashMap<String, Arc<Mutex<Xml>>>> {
+        self.0.lock().expect("Failed to lock xml parts")
+    }
+
+    /// パスによるパーツの取得
+    pub fn get(&self, path: &str) -> Option<Arc<Mutex<Xml>>> {
+        self.lock().get(path).cloned()
+    }
+
+    /// パーツが存在するかどうか
+    pub fn contains_key(&self, path: &str) -> bool {
+        self.lock().contains_key(path)
+    }
+
+    /// パーツの追加 (既存のパーツは置き換え)
+    pub fn insert(&self, path: String, xml: Xml) {
+        self.insert_shared(path, Arc::new(Mutex::new(xml)));
+    }
+
+    /// 共有済みのパーツの追加
+    pub fn insert_shared(&self, path: String, xml: Arc<Mutex<Xml>>) {
+        self.lock().insert(path, xml);
+    }
+
+    /// パーツの取得または作成
+    pub fn get_or_insert_with(&self, path: &str, f: impl FnOnce() -> Xml) -> Arc<Mutex<Xml>> {
+        self.lock()
+            .entry(path.to_string())
+            .or_insert_with(|| Arc::new(Mutex::new(f())))
+            .clone()
+    }
+
+    /// パーツの削除
+    pub fn remove(&self, path: &str) -> Option<Arc<Mutex<Xml>>> {
+        self.lock().remove(path)
+    }
+
+    /// パーツの数
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// パーツが空かどうか
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// パスの一覧 (昇順)
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.lock().keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    /// パスとパーツの一覧 (パスの昇順)
+    pub fn entries(&self) -> Vec<(String, Arc<Mutex<Xml>>)> {
+        let mut entries: Vec<(String, Arc<Mutex<Xml>>)> = self
+            .lock()
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        entries
+    }
+}
+
+/// シートと共有するワークブック全体のパーツ
+///
+/// シートの操作が他のシートや定義された名前に影響する場合に使用
+#[derive(Clone, Default)]
+pub struct BookParts {
+    /// `xl/workbook.xml`
+    pub workbook: Arc<Mutex<Xml>>,
+    /// `xl/_rels/` 内のXMLファイル
+    pub rels: XmlParts,
+    /// `xl/worksheets/` 内のXMLファイル
+    pub worksheets: XmlParts,
+    /// `xl/worksheets/_rels/` 内のXMLファイル
+    pub sheet_rels: XmlParts,
+    /// `xl/tables/` 内のXMLファイル
+    pub tables: XmlParts,
+    /// `xl/charts/` 内のXMLファイル
+    pub charts: XmlParts,
+}
+
+impl BookParts {
+    /// `xl/workbook.xml` のシートタグの一覧
+    pub fn sheet_tags(&self) -> Vec<XmlElement> {
+        self.workbook
+            .lock()
+            .ok()
+            .and_then(|workbook| {
+                workbook
+                    .elements
+                    .first()
+                    .and_then(|wb| wb.find_element("sheets"))
+                    .map(|sheets| sheets.children.clone())
+            })
+            .unwrap_or_default()
+    }
+
+    /// シート名の一覧 (シート順)
+    pub fn sheet_names(&self) -> Vec<String> {
+        self.sheet_tags()
+            .iter()
+            .filter_map(|x| x.attributes.get("name").cloned())
+            .collect()
+    }
+
+    /// 表示されているシート名の一覧 (シート順)
+    pub fn visible_sheet_names(&self) -> Vec<String> {
+        self.sheet_tags()
+            .iter()
+            .filter(|x| Self::tag_state(x) == "visible")
+            .filter_map(|x| x.attributes.get("name").cloned())
+            .collect()
+    }
+
+    /// `sheet` 要素の表示状態 (省略時は "visible")
+    fn tag_state(tag: &XmlElement) -> &str {
+        tag.get_attribute("state").map_or("visible", String::as_str)
+    }
+
+    /// シートの表示状態
+    pub fn sheet_state(&self, name: &str) -> String {
+        self.sheet_tags()
+            .iter()
+            .find(|x| x.get_attribute("name").is_some_and(|n| n == name))
+            .map_or("visible", Self::tag_state)
+            .to_string()
+    }
+
+    /// シートの表示状態の設定
+    ///
+    /// アクティブなシートと最後の表示されているシートは非表示にできない
+    pub fn set_sheet_state(&self, name: &str, state: &str) -> Result<(), BookError> {
+        if !SHEET_STATES.contains(&state) {
+            return Err(BookError::InvalidSheetState(state.to_string()));
+        }
+        let names: Vec<String> = self.sheet_names();
+        let index: usize = names
+            .iter()
+            .position(|n| n == name)
+            .ok_or_else(|| BookError::SheetNotFound(name.to_string()))?;
+        if state != "visible" && self.sheet_state(name) == "visible" {
+            if self.active_index() == index {
+                return Err(BookError::ActiveSheetHidden(name.to_string()));
+            }
+            if self.visible_sheet_names().len() <= 1 {
+                return Err(BookError::LastVisibleSheet);
+            }
+        }
+        let mut workbook: MutexGuard<Xml> = self.workbook.lock().unwrap();
+        if let Some(tag) = workbook
+            .elements
+            .first_mut()
+            .and_then(|wb| wb.find_element_mut("sheets"))
+            .and_then(|sheets| sheets.children.get_mut(index))
+        {
+            if state == "visible" {
+                tag.attributes.remove("state");
+            } else {
+                tag.attributes
+                    .insert("state".to_string(), state.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// `xl/_rels/workbook.xml.rels` のリレーションシップの一覧
+    pub fn relationships(&self) -> Vec<XmlElement> {
+        self.rels
+            .get(WORKBOOK_RELS_FILENAME)
+            .and_then(|rels| {
+                let rels: MutexGuard<Xml> = rels.lock().ok()?;
+                rels.elements.first().map(|r| r.children.clone())
+            })
+            .unwrap_or_default()
+    }
+
+    /// シート名とワークシートのパスのマップ
+    pub fn sheet_paths(&self) -> HashMap<String, String> {
+        let targets: HashMap<String, String> = self
+            .relationships()
+            .iter()
+            .filter_map(|rel| {
+                let id: String = rel.attributes.get("Id")?.clone();
+                let target: String = rel.attributes.get("Target")?.clone();
+                Some((id, target))
+            })
+            .collect();
+
+        self.sheet_tags()
+            .iter()
+            .filter_map(|tag| {
+                let name: String = tag.attributes.get("name")?.clone();
+                let r_id: &String = tag.attributes.get("r:id")?;
+                let path: &String = targets.get(r_id)?;
+                let trimmed_path: &str = path.trim_start_matches("/xl/").trim_start_matches("xl/");
+                Some((name, format!("xl/{trimmed_path}")))
+            })
+            .collect()
+    }
+
+    /// ワークシートのパーツのパスに対応するシート名
+    pub fn sheet_name(&self, path: &str) -> Option<String> {
+        self.sheet_paths()
+            .into_iter()
+            .find(|(_, p)| p == path)
+            .map(|(name, _)| name)
+    }
+
+    /// シート名とワークシートXMLの一覧 (シート順)
+    pub fn sheets(&self) -> Vec<(String, Arc<Mutex<Xml>>)> {
+        let sheet_paths: HashMap<String, String> = self.sheet_paths();
+        self.sheet_names()
+            .into_iter()
+            .filter_map(|name| {
+                let xml: Arc<Mutex<Xml>> = self.worksheets.get(sheet_paths.get(&name)?)?;
+                Some((name, xml))
+            })
+            .collect()
+    }
+
+    /// 定義された名前の値の取得 (名前の大文字と小文字は区別しない)
+    ///
+    /// `local_sheet_id` が `None` の場合はブック全体の名前
+    pub fn defined_name(&self, name: &str, local_sheet_id: Option<usize>) -> Option<String> {
+        let workbook: MutexGuard<Xml> = self.workbook.lock().unwrap();
+        workbook
+            .elements
+            .first()?
+            .find_element("definedNames")?
+            .children
+            .iter()
+            .find(|dn| Self::is_defined_name(dn, name, local_sheet_id))?
+            .text
+            .clone()
+    }
+
+    /// 定義された名前の設定 (`None` の場合は削除)
+    pub fn set_defined_name(
+        &self,
+        name: &str,
+        local_sheet_id: Option<usize>,
+        value: Option<String>,
+    ) {
+        let mut workbook: MutexGuard<Xml> = self.workbook.lock().unwrap();
+        let Some(wb) = workbook.elements.first_mut() else {
+            return;
+        };
+        let Some(value) = value else {
+            if let Some(defined_names) = wb.find_element_mut("definedNames") {
+                defined_names
+                    .children
+                    .retain(|dn| !Self::is_defined_name(dn, name, local_sheet_id));
+            }
+            wb.children
+                .retain(|c| c.name != "definedNames" || !c.children.is_empty());
+            return;
+        };
+        let defined_names: &mut XmlElement =
+            wb.get_or_create_child_ordered("definedNames", &WORKBOOK_CHILD_ORDER);
+        match defined_names
+            .children
+            .iter_mut()
+            .find(|dn| Self::is_defined_name(dn, name, local_sheet_id))
+        {
+            Some(defined_name) => defined_name.text = Some(value),
+            None => {
+                let mut defined_name: XmlElement = XmlElement::new("definedName");
+                defined_name
+                    .attributes
+                    .insert("name".to_string(), name.to_string());
+                if let Some(id) = local_sheet_id {
+                    defined_name
+                        .attributes
+                        .insert("localSheetId".to_string(), id.to_string());
+                }
+                defined_name.text = Some(value);
+                defined_names.children.push(defined_name);
+            }
+        }
+    }
+
+    /// シートの並び替え・削除に伴う `localSheetId` の付け替え
+    ///
+    /// `map` が `None` を返すシートに属する名前は削除
+    pub fn remap_local_sheet_ids(&self, map: impl Fn(usize) -> Option<usize>) {
+        let mut workbook: MutexGuard<Xml> = self.workbook.lock().unwrap();
+        let Some(wb) = workbook.elements.first_mut() else {
+            return;
+        };
+        if let Some(defined_names) = wb.find_element_mut("definedNames") {
+            defined_names.children.retain_mut(|dn| {
+                let Some(id) = dn
+                    .get_attribute("localSheetId")
+                    .and_then(|id| id.parse::<usize>().ok())
+                else {
+                    return true;
+                };
+                match map(id) {
+                    Some(new_id) => {
+                        dn.attributes
+                            .insert("localSheetId".to_string(), new_id.to_string());
+                        true
+                    }
+                    None => false,
+                }
+            });
+        }
+        wb.children
+            .retain(|c| c.name != "definedNames" || !c.children.is_empty());
+    }
+
+    /// ブックの表示設定 (`bookViews>workbookView`) のシートの位置の属性の取得
+    fn workbook_view_index(&self, key: &str) -> usize {
+        let workbook: MutexGuard<Xml> = self.workbook.lock().unwrap();
+        workbook
+            .elements
+            .first()
+            .and_then(|wb| wb.find_element("bookViews>workbookView"))
+            .and_then(|view| view.get_attribute(key)?.parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// ブックの表示設定のシートの位置の属性の設定 (0の場合は削除)
+    fn set_workbook_view_index(&self, key: &str, value: usize) {
+        let mut workbook: MutexGuard<Xml> = self.workbook.lock().unwrap();
+        let Some(wb) = workbook.elements.first_mut() else {
+            return;
+        };
+        if value == 0 && wb.find_element("bookViews>workbookView").is_none() {
+            return;
+        }
+        let book_views: &mut XmlElement =
+            wb.get_or_create_child_ordered("bookViews", &WORKBOOK_CHILD_ORDER);
+        if !book_views.children.iter().any(|c| c.name == "workbookView") {
+            book_views.children.push(XmlElement::new("workbookView"));
+        }
+        if let Some(view) = book_views.find_element_mut("workbookView") {
+            if value == 0 {
+                view.attributes.remove(key);
+            } else {
+                view.attributes.insert(key.to_string(), value.to_string());
+            }
+        }
+    }
+
+    /// アクティブなシートの位置 (`activeTab`)
+    pub fn active_index(&self) -> usize {
+        let count: usize = self.sheet_tags().len();
+        self.workbook_view_index("activeTab")
+            .min(count.saturating_sub(1))
+    }
+
+    /// アクティブなシートの設定
+    ///
+    /// 各シートの `tabSelected` を揃え、先頭に表示するシート (`firstSheet`) がアクティブなシートより後の場合は合わせる
+    pub fn set_active_index(&self, index: usize) {
+        self.set_workbook_view_index("activeTab", index);
+        if self.workbook_view_index("firstSheet") > index {
+            self.set_workbook_view_index("firstSheet", index);
+        }
+        // グラフシートも位置に数えるため、ワークシートに限らない `sheets` 要素の順で揃える
+        let sheet_paths: HashMap<String, String> = self.sheet_paths();
+        for (position, tag) in self.sheet_tags().iter().enumerate() {
+            let Some(xml) = tag
+                .get_attribute("name")
+                .and_then(|name| sheet_paths.get(name))
+                .and_then(|path| self.worksheets.get(path))
+            else {
+                continue;
+            };
+            let mut xml: MutexGuard<Xml> = xml.lock().unwrap();
+            let Some(worksheet) = xml.elements.first_mut() else {
+                continue;
+            };
+            let selected: bool = position == index;
+            if view::view_flag(worksheet, "tabSelected", false) != selected {
+                view::set_view_flag(worksheet, "tabSelected", selected, false);
+            }
+        }
+    }
+
+    /// シートの追加・削除・並び替えに伴うシートの位置の付け替え
+    ///
+    /// `sheets` 要素の更新後に呼び出し、定義された名前の `localSheetId`、
+    /// アクティブなシートと先頭に表示するシートを更新する。
+    /// `map` が `None` を返すシート (削除したシート) がアクティブな場合は同じ位置に最も近い表示されているシートをアクティブにする
+    pub fn reindex_sheets(&self, map: impl Fn(usize) -> Option<usize>) {
+        self.remap_local_sheet_ids(&map);
+        let last: usize = self.sheet_tags().len().saturating_sub(1);
+        let first: usize = self.workbook_view_index("firstSheet");
+        self.set_workbook_view_index("firstSheet", map(first).unwrap_or(first).min(last));
+        let active: usize = self.workbook_view_index("activeTab");
+        match map(active) {
+            Some(index) => self.set_workbook_view_index("activeTab", index.min(last)),
+            None if self.sheet_tags().is_empty() => self.set_workbook_view_index("activeTab", 0),
+            None => self.set_active_index(self.nearest_visible_index(active.min(last))),
+        }
+    }
+
+    /// `index` の位置に最も近い表示されているシートの位置 (後ろのシートを優先し、なければ前のシート)
+    fn nearest_visible_index(&self, index: usize) -> usize {
+        let visible: Vec<bool> = self
+            .sheet_tags()
+            .iter()
+            .map(|tag| Self::tag_state(tag) == "visible")
+            .collect();
+        (index..visible.len())
+            .chain((0..index).rev())
+            .find(|&i| visible[i])
+            .unwrap_or(index)
+    }
+
+    /// シート名の変更
+    ///
+    /// 全シートの数式・入力規則・条件付き書式・ハイパーリンク、定義された名前、グラフの系列の参照を書き換える
+    pub fn rename_sheet(&self, old_name: &str, new_name: &str) -> Result<(), BookError> {
+        let names: Vec<String> = self.sheet_names();
+        if !names.iter().any(|n| n == old_name) {
+            return Err(BookError::SheetNotFound(old_name.to_string()));
+        }
+        validate_sheet_name(new_name)?;
+        if names
+            .iter()
+            .any(|n| n != old_name && n.to_lowercase() == new_name.to_lowercase())
+        {
+            return Err(BookError::DuplicateSheetName(new_name.to_string()));
+        }
+        if old_name == new_name {
+            return Ok(());
+        }
+
+        let rename = |f: &str| formula::rename_sheet_in_formula(f, old_name, new_name);
+        for (_, xml) in self.sheets() {
+            if let Some(worksheet) = xml.lock().unwrap().elements.first_mut() {
+                Sheet::map_formulas(worksheet, &rename);
+                if let Some(hyperlinks) = worksheet.find_element_mut("hyperlinks") {
+                    for hyperlink in &mut hyperlinks.children {
+                        if let Some(location) = hyperlink.attributes.get_mut("location") {
+                            *location = rename(location);
+                        }
+                    }
+                }
+            }
+        }
+        for (_, xml) in self.charts.entries() {
+            if let Some(chart) = xml.lock().unwrap().elements.first_mut() {
+                Sheet::map_formulas(chart, &rename);
+            }
+        }
+
+        let mut workbook: MutexGuard<Xml> = self.workbook.lock().unwrap();
+        if let Some(wb) = workbook.elements.first_mut() {
+            if let Some(sheet) = wb.find_element_mut("sheets").and_then(|sheets| {
+                sheets
+                    .children
+                    .iter_mut()
+                    .find(|s| s.get_attribute("name").is_some_and(|n| n == old_name))
+            }) {
+                sheet
+                    .attributes
+                    .insert("name".to_string(), new_name.to_string());
+            }
+            if let Some(defined_names) = wb.find_element_mut("definedNames") {
+                for defined_name in &mut defined_names.children {
+                    if let Some(text) = &defined_name.text {
+                        defined_name.text = Some(rename(text));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 定義された名前の要素が名前と範囲に一致するかどうか
+    fn is_defined_name(element: &XmlElement, name: &str, local_sheet_id: Option<usize>) -> bool {
+        element
+            .get_attribute("name")
+            .is_some_and(|n| n.eq_ignore_ascii_case(name))
+            && element
+                .get_attribute("localSheetId")
+                .and_then(|id| id.parse::<usize>().ok())
+                == local_sheet_id
+    }
+
+    /// ワークシートのリレーションシップファイルのパス
+    pub fn sheet_rels_path(sheet_path: &str) -> String {
+        format!(
+            "{WORKSHEETS_RELS_PREFIX}{}.rels",
+            sheet_path.split('/').next_back().unwrap_or_default()
+        )
+    }
+
+    /// ワークシートに関連付けられたテーブルのパスの一覧
+    pub fn sheet_tables(&self, sheet_path: &str) -> Vec<String> {
+        let Some(rels) = self.sheet_rels.get(&Self::sheet_rels_path(sheet_path)) else {
+            return Vec::new();
+        };
+        let rels: MutexGuard<Xml> = rels.lock().unwrap();
+        rels.elements
+            .first()
+            .map(|relationships| {
+                relationships
+                    .children
+                    .iter()
+                    .filter(|r| {
+                        r.get_attribute("Type")
+                            .is_some_and(|t| t.ends_with("/relationships/table"))
+                    })
+                    .filter_map(|r| r.get_attribute("Target"))
+                    .map(|target| resolve_target(WORKSHEETS_PREFIX, target))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// リレーションシップの `Target` のパッケージ内のパスへの解決
+///
+/// `base_dir` はリレーションシップの元のパーツのディレクトリ (例: "xl/worksheets/")
+pub fn resolve_target(base_dir: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+    let mut parts: Vec<&str> = base_dir.split('/').filter(|p| !p.is_empty()).collect();
+    for segment in target.split('/') {
+        match segment {
+            ".." => {
+                parts.pop();
+            }
+            "." | "" => {}
+            segment => parts.push(segment),
+        }
+    }
+    parts.join("/")
+}
+
+/// パーツのリレーションシップファイルのパス (例: "xl/drawings/_rels/drawing1.xml.rels")
+pub fn rels_path(part: &str) -> String {
+    match part.rsplit_once('/') {
+        Some((dir, name)) => format!("{dir}/_rels/{name}.rels"),
+        None => format!("_rels/{part}.rels"),
+    }
+}
+
+/// シート名の検証 (31文字以内で `[]:*?/\` を含まず、空でないこと)
+pub fn validate_sheet_name(name: &str) -> Result<(), BookError> {
+    if name.trim().is_empty()
+        || name.chars().count() > MAX_SHEET_NAME_LENGTH
+        || name.contains(INVALID_SHEET_NAME_CHARS)
+        || name.starts_with('\'')
+        || name.ends_with('\'')
+    {
+        return Err(BookError::InvalidSheetName(name.to_string()));
+    }
+    Ok(())
+}
+
 /// Excelワークブック
 #[pyclass]
 pub struct Book {
@@ -49,13 +741,16 @@ pub struct Book {
     pub path: String,
 
     /// `xl/_rels/` 内のXMLファイル
-    pub rels: HashMap<String, Xml>,
+    pub rels: XmlParts,
 
     /// `xl/drawings/` 内のXMLファイル
     pub drawings: HashMap<String, Xml>,
 
     /// `xl/tables/` 内のXMLファイル
-    pub tables: HashMap<String, Xml>,
+    pub tables: XmlParts,
+
+    /// `xl/charts/` 内のXMLファイル
+    pub charts: XmlParts,
 
     /// `xl/pivotTables/` 内のXMLファイル
     pub pivot_tables: HashMap<String, Xml>,
@@ -67,10 +762,10 @@ pub struct Book {
     pub themes: HashMap<String, Xml>,
 
     /// `xl/worksheets/` 内のXMLファイル
-    pub worksheets: HashMap<String, Arc<Mutex<Xml>>>,
+    pub worksheets: XmlParts,
 
     /// `xl/worksheets/_rels/` 内のXMLファイル
-    pub sheet_rels: HashMap<String, Xml>,
+    pub sheet_rels: XmlParts,
 
     /// `xl/sharedStrings.xml` ファイル
     pub shared_strings: Arc<Mutex<Xml>>,
@@ -80,10 +775,22 @@ pub struct Book {
     pub styles: Arc<Mutex<Xml>>,
 
     /// `workbook.xml` ファイル
-    pub workbook: Xml,
+    pub workbook: Arc<Mutex<Xml>>,
+
+    /// `xl/metadata.xml` ファイル
+    pub metadata: Arc<Mutex<Xml>>,
+
+    /// `[Content_Types].xml` ファイル
+    pub content_types: Xml,
 
     /// `vbaProject.bin` ファイル
     pub vba_project: Option<Vec<u8>>,
+
+    /// 読み込み時に解析しないその他のファイル (複製したコメントや画像など)
+    pub files: HashMap<String, Vec<u8>>,
+
+    /// 削除したパーツ (保存時に元のファイルから書き出さない)
+    pub removed_parts: HashSet<String>,
 }
 
 #[pymethods]
@@ -111,71 +818,277 @@ impl Book {
             .collect()
     }
 
-    /// シート名のイテレータ
-    pub fn __iter__(&self) -> Vec<String> {
-        self.sheetnames()
+    /// ワークブック内の表示されているシート名の取得 (非表示と完全に非表示のシートを除く)
+    #[getter]
+    pub fn visible_sheetnames(&self) -> Vec<String> {
+        self.parts().visible_sheet_names()
     }
 
-    /// 指定された名前のシートがワークブックに存在するかどうかの確認
-    pub fn __contains__(&self, key: String) -> bool {
-        self.sheetnames().contains(&key)
+    /// 読み込み時に全数式を再計算するかどうか (`calcPr fullCalcOnLoad`)
+    #[getter]
+    pub fn full_calc_on_load(&self) -> bool {
+        self.workbook
+            .lock()
+            .unwrap()
+            .elements
+            .first()
+            .and_then(|wb| wb.find_element("calcPr"))
+            .and_then(|calc_pr| calc_pr.get_attribute("fullCalcOnLoad"))
+            .is_some_and(|v| v == "1" || v == "true")
     }
 
-    /// 名前によるシートの取得
-    pub fn __getitem__(&self, key: String) -> Sheet {
-        self.get_sheet_by_name(&key).unwrap_or_else(|| {
-            // ここでデフォルトのSheetを返すか、あるいはpanic!させるか
-            // テストコードを変更できないため、panic!させる
-            panic!("No sheet named '{key}'")
-        })
+    /// 読み込み時に全数式を再計算するかどうかの設定
+    #[setter]
+    pub fn set_full_calc_on_load(&mut self, value: bool) {
+        self.write_full_calc_on_load(value);
     }
 
-    /// ワークシートへのテーブルの追加
-    pub fn add_table(&mut self, sheet_name: String, name: String, table_ref: String) {
-        let table_id: usize = self.tables.len() + 1;
-        let table_filename: String = format!("xl/tables/table{table_id}.xml");
-
-        // テーブルXMLの作成
-        self.create_table_xml(&name, &table_ref, table_id, &table_filename);
-
-        // ワークシートへのテーブルパーツの追加
-        self.add_table_parts_to_worksheet(&sheet_name, table_id);
-
-        // ワークシートのリレーションシップへのリレーションシップの追加
-        self.add_table_relationship(&sheet_name, table_id);
+    /// 全シートの数式の計算とキャッシュ値 (`<v>`) の書き込み
+    ///
+    /// 循環参照がある場合はセルを変更せずにエラー
+    pub fn calculate(&self) -> PyResult<()> {
+        self.calculate_formulas()?;
+        Ok(())
     }
 
-    /// 名前によるシートの削除
-    pub fn __delitem__(&mut self, key: String) {
-        if let Some(sheet) = self.get_sheet_by_name(&key) {
-            self.remove(&sheet);
-        }
-        // panic! は避ける
+    /// シート名の変更
+    ///
+    /// 他のシートの数式や定義された名前などの参照も新しい名前に書き換える
+    pub fn rename_sheet(&self, old_name: &str, new_name: &str) -> Result<(), BookError> {
+        self.parts().rename_sheet(old_name, new_name)
+    }
+
+    /// アクティブなシート
+    #[getter]
+    pub fn active(&self) -> Option<Sheet> {
+        let name: String = self.sheetnames().get(self.parts().active_index())?.clone();
+        self.get_sheet_by_name(&name)
+    }
+
+    /// アクティブなシートの設定 (シート、シートの位置またはシート名)
+    ///
+    /// 非表示のシートはアクティブにできない
+    #[setter(active)]
+    pub fn set_active(&self, sheet: SheetKey) -> Result<(), BookError> {
+        let index: usize = self.sheet_position(&sheet)?;
+        let name: String = self.sheetnames()[index].clone();
+        if self.parts().sheet_state(&name) != "visible" {
+            return Err(BookError::HiddenSheetActivated(name));
+        }
+        self.parts().set_active_index(index);
+        Ok(())
+    }
+
+    /// シートの移動 (`offset` が負の場合は前、正の場合は後ろ、範囲外は先頭または末尾)
+    pub fn move_sheet(&mut self, sheet: SheetKey, offset: isize) -> Result<(), BookError> {
+        let index: usize = self.sheet_position(&sheet)?;
+        let mut order: Vec<usize> = (0..self.sheetnames().len()).collect();
+        let target: usize = index.saturating_add_signed(offset).min(order.len() - 1);
+        let moved: usize = order.remove(index);
+        order.insert(target, moved);
+        self.apply_sheet_order(&order);
+        Ok(())
+    }
+
+    /// シートの並べ替え (全てのシート名を新しい順序で指定)
+    pub fn reorder_sheets(&mut self, names: Vec<String>) -> Result<(), BookError> {
+        let current: Vec<String> = self.sheetnames();
+        let order: Vec<usize> = names
+            .iter()
+            .map(|name| {
+                current
+                    .iter()
+                    .position(|n| n == name)
+                    .ok_or_else(|| BookError::SheetNotFound(name.clone()))
+            })
+            .collect::<Result<_, _>>()?;
+        let unique: HashSet<usize> = order.iter().copied().collect();
+        if order.len() != current.len() || unique.len() != current.len() {
+            return Err(BookError::InvalidSheetOrder);
+        }
+        self.apply_sheet_order(&order);
+        Ok(())
+    }
+
+    /// 定義された名前の一覧
+    #[getter]
+    pub fn defined_names(&self) -> DefinedNames {
+        DefinedNames::new(self.parts())
+    }
+
+    /// 定義された名前の参照先のセルの一覧
+    ///
+    /// `sheet` を指定した場合はそのシートの範囲の名前を優先し、なければブック全体の名前を使用
+    #[pyo3(signature = (name, sheet=None))]
+    pub fn resolve_name(&self, name: &str, sheet: Option<&str>) -> Result<Vec<Cell>, NameError> {
+        let defined_names: DefinedNames = self.defined_names();
+        let local: Option<DefinedName> = sheet
+            .and_then(|sheet| self.sheetnames().iter().position(|n| n == sheet))
+            .and_then(|index| defined_names.get(name, Some(index)));
+        let defined_name: DefinedName = local
+            .or_else(|| defined_names.get(name, None))
+            .ok_or_else(|| NameError::NotFound(name.to_string()))?;
+        let destinations: Vec<(String, String)> = defined_name.destinations();
+        if destinations.is_empty() {
+            return Err(NameError::InvalidReference(defined_name.value));
+        }
+        let mut cells: Vec<Cell> = Vec::new();
+        for (sheet_name, range) in destinations {
+            let sheet: Sheet = self
+                .get_sheet_by_name(&sheet_name)
+                .ok_or_else(|| NameError::InvalidReference(defined_name.value.clone()))?;
+            let Some(((r1, c1), (r2, c2))) = parse_range(&range) else {
+                continue;
+            };
+            for row in r1..=r2 {
+                for col in c1..=c2 {
+                    cells.push(sheet.cell(row as usize, col as usize));
+                }
+            }
+        }
+        Ok(cells)
+    }
+
+    /// シート名のイテレータ
+    pub fn __iter__(&self) -> Vec<String> {
+        self.sheetnames()
+    }
+
+    /// 指定された名前のシートがワークブックに存在するかどうかの確認
+    pub fn __contains__(&self, key: String) -> bool {
+        self.sheetnames().contains(&key)
+    }
+
+    /// 名前によるシートの取得
+    pub fn __getitem__(&self, key: String) -> Sheet {
+        self.get_sheet_by_name(&key).unwrap_or_else(|| {
+            // ここでデフォルトのSheetを返すか、あるいはpanic!させるか
+            // テストコードを変更できないため、panic!させる
+            panic!("No sheet named '{key}'")
+        })
+    }
+
+    /// ワークシートへのテーブルの追加
+    pub fn add_table(&mut self, sheet_name: String, name: String, table_ref: String) {
+        let table_id: usize = self.next_table_id();
+        let table_filename: String = self.unique_part_name("xl/tables/table1.xml");
+
+        // テーブルXMLの作成
+        self.create_table_xml(&name, &table_ref, table_id, &table_filename);
+
+        // ワークシートのリレーションシップへのリレーションシップの追加
+        let r_id: String = self.add_table_relationship(&sheet_name, &table_filename);
+
+        // ワークシートへのテーブルパーツの追加
+        self.add_table_parts_to_worksheet(&sheet_name, &r_id);
+    }
+
+    /// ワークシートの複製
+    ///
+    /// 図形・グラフ・テーブル・コメント・画像などの関連するパーツも新しい名前で複製し、末尾に追加する。
+    /// `title` を省略した場合は "<元のシート名> Copy"
+    #[pyo3(signature = (sheet, title=None))]
+    pub fn copy_worksheet(
+        &mut self,
+        sheet: &Sheet,
+        title: Option<String>,
+    ) -> Result<Sheet, BookError> {
+        let name: String = sheet.name()?;
+        let source_path: String = sheet.path.clone();
+        let title: String = match title {
+            Some(title) => self.validate_new_title(title)?,
+            None => Self::copy_title(&name, &self.sheetnames()),
+        };
+
+        let worksheet: Xml = self
+            .worksheets
+            .get(&source_path)
+            .map(|xml| xml.lock().unwrap().clone())
+            .unwrap_or_default();
+        let parts: Vec<(String, CopiedPart)> = self.collect_parts(&source_path, true);
+        let local_names: Vec<DefinedName> = self.local_defined_names(&name);
+        Ok(self.add_copied_sheet((&name, &source_path), title, worksheet, parts, local_names))
+    }
+
+    /// 別のブックからのワークシートの取り込み
+    ///
+    /// セルの書式と共有文字列を取り込み先に合わせて付け替え、テーブルや図形などの関連するパーツも複製して末尾に追加する。
+    /// `title` を省略した場合は元のシート名 (重複する場合は "<元のシート名> Copy")
+    #[pyo3(signature = (other, name, title=None))]
+    pub fn import_sheet(
+        &mut self,
+        other: &Book,
+        name: &str,
+        title: Option<String>,
+    ) -> Result<Sheet, BookError> {
+        let source_path: String = other
+            .get_sheet_paths()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| BookError::SheetNotFound(name.to_string()))?;
+        let names: Vec<String> = self.sheetnames();
+        let title: String = match title {
+            Some(title) => self.validate_new_title(title)?,
+            None if names
+                .iter()
+                .any(|n| n.to_lowercase() == name.to_lowercase()) =>
+            {
+                Self::copy_title(name, &names)
+            }
+            None => name.to_string(),
+        };
+        let mut worksheet: Xml = other
+            .worksheets
+            .get(&source_path)
+            .map(|xml| xml.lock().unwrap().clone())
+            .unwrap_or_default();
+        if let Some(ws) = worksheet.elements.first_mut() {
+            self.remap_imported_worksheet(other, ws);
+        }
+        let parts: Vec<(String, CopiedPart)> = other.collect_parts(&source_path, false);
+        let local_names: Vec<DefinedName> = other.local_defined_names(name);
+        Ok(self.add_copied_sheet((name, &source_path), title, worksheet, parts, local_names))
+    }
+
+    /// 名前によるシートの削除
+    pub fn __delitem__(&mut self, key: String) -> Result<(), BookError> {
+        match self.get_sheet_by_name(&key) {
+            Some(sheet) => self.remove(&sheet),
+            // panic! は避ける
+            None => Ok(()),
+        }
     }
 
     /// シートのインデックス取得
     pub fn index(&self, sheet: &Sheet) -> usize {
+        let name: Option<String> = sheet.name().ok();
         self.sheetnames()
             .iter()
-            .position(|x| x == &sheet.name)
+            .position(|x| Some(x) == name.as_ref())
             .unwrap_or_default()
     }
 
     /// ワークブックからのシートの削除
-    pub fn remove(&mut self, sheet: &Sheet) {
-        let sheet_paths: HashMap<String, String> = self.get_sheet_paths();
-        let sheet_path: &String = match sheet_paths.get(&sheet.name) {
-            Some(path) => path,
-            None => panic!("No sheet named '{}'", sheet.name),
-        };
-
+    ///
+    /// 最後の表示されているシートは削除できない。アクティブなシートを削除した場合は近くの表示されているシートをアクティブにする
+    pub fn remove(&mut self, sheet: &Sheet) -> Result<(), BookError> {
+        let name: String = sheet.name()?;
+        let removed_index: Option<usize> = self.sheetnames().iter().position(|x| *x == name);
+        let sheet_path: &String = &sheet.path;
+
+        let visible: Vec<String> = self.parts().visible_sheet_names();
+        if visible.len() <= 1 && visible.contains(&name) {
+            return Err(BookError::LastVisibleSheet);
+        }
         if self.worksheets.remove(sheet_path).is_none() {
-            panic!("No sheet named '{}'", sheet.name);
+            return Err(BookError::SheetNotFound(name));
         }
 
         let mut rid_to_remove: Option<String> = None;
         if let Some(sheets_tag) = self
             .workbook
+            .lock()
+            .unwrap()
             .elements
             .first_mut()
             .and_then(|wb| wb.children.iter_mut().find(|x| x.name == "sheets"))
@@ -183,32 +1096,46 @@ impl Book {
             if let Some(sheet_to_remove) = sheets_tag
                 .children
                 .iter()
-                .find(|s| s.attributes.get("name") == Some(&sheet.name))
+                .find(|s| s.attributes.get("name") == Some(&name))
             {
                 rid_to_remove = sheet_to_remove.attributes.get("r:id").cloned();
             }
             sheets_tag
                 .children
-                .retain(|s| s.attributes.get("name") != Some(&sheet.name));
+                .retain(|s| s.attributes.get("name") != Some(&name));
         }
 
-        if let (Some(rid), Some(rels)) = (
-            rid_to_remove,
-            self.rels.get_mut("xl/_rels/workbook.xml.rels"),
-        ) {
-            if let Some(relationships_tag) = rels.elements.first_mut() {
-                relationships_tag
-                    .children
-                    .retain(|r| r.attributes.get("Id") != Some(&rid));
-            }
+        if let (Some(rid), Some(rels)) = (rid_to_remove, self.rels.get(WORKBOOK_RELS_FILENAME))
+            && let Some(relationships_tag) = rels.lock().unwrap().elements.first_mut()
+        {
+            relationships_tag
+                .children
+                .retain(|r| r.attributes.get("Id") != Some(&rid));
         }
+
+        // 削除したシートの名前 (印刷範囲など) を除去し、後続のシートの位置を詰める
+        if let Some(removed) = removed_index {
+            self.parts().reindex_sheets(|id| match id {
+                id if id == removed => None,
+                id if id > removed => Some(id - 1),
+                id => Some(id),
+            });
+        }
+
+        // シートだけが参照していたパーツ (リレーションシップ、図形、テーブル、コメントなど) の削除
+        let sheet_path: String = sheet_path.clone();
+        for part in self.unreferenced_parts(&sheet_path) {
+            self.delete_part(&part);
+        }
+        self.delete_part(&sheet_path);
+        Ok(())
     }
 
     /// ワークブックへの新しいシートの作成
     pub fn create_sheet(&mut self, title: String, index: usize) -> Sheet {
-        let next_sheet_id: usize = self.sheet_tags().len() + 1;
-        let next_rid: String = format!("rId{}", self.get_relationships().len() + 1);
-        let sheet_path: String = format!("xl/worksheets/sheet{next_sheet_id}.xml");
+        let next_sheet_id: usize = self.next_sheet_id();
+        let next_rid: String = Self::next_relationship_id(&self.get_relationships());
+        let sheet_path: String = self.unique_part_name("xl/worksheets/sheet1.xml");
 
         let worksheet_xml: Xml = Xml::new(
             r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
@@ -221,17 +1148,22 @@ impl Book {
 
         let arc_mutex_xml: Arc<Mutex<Xml>> = Arc::new(Mutex::new(worksheet_xml));
         self.worksheets
-            .insert(sheet_path.clone(), arc_mutex_xml.clone());
+            .insert_shared(sheet_path.clone(), arc_mutex_xml.clone());
 
+        let index: usize = index.min(self.sheet_tags().len());
         self.add_sheet_to_workbook_xml(&title, next_sheet_id, &next_rid, index);
-        self.add_sheet_relationship(&next_rid, next_sheet_id);
+        self.add_sheet_relationship(&next_rid, &sheet_path);
+        self.parts()
+            .reindex_sheets(|id| Some(if id >= index { id + 1 } else { id }));
 
         Sheet::new(
-            title,
+            sheet_path,
             arc_mutex_xml,
             self.shared_strings.clone(),
             self.shared_strings_map.clone(),
             self.styles.clone(),
+            self.metadata.clone(),
+            self.parts(),
         )
     }
 
@@ -295,12 +1227,12 @@ impl ToXml for &Arc<Mutex<Xml>> {
 impl Book {
     /// 新しい空のワークブックの作成
     fn new_empty_workbook() -> Self {
-        let mut rels: HashMap<String, Xml> = HashMap::with_capacity(1);
+        let rels: XmlParts = XmlParts::default();
         let workbook_rels: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
 </Relationships>"#;
         if let Ok(xml) = Xml::new(workbook_rels) {
-            rels.insert("xl/_rels/workbook.xml.rels".to_string(), xml);
+            rels.insert(WORKBOOK_RELS_FILENAME.to_string(), xml);
         }
 
         let workbook_xml: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
@@ -319,274 +1251,1242 @@ impl Book {
 <cellStyles count="1"><cellStyle name="Normal" xfId="0" builtinId="0"/></cellStyles>
 </styleSheet>"#;
 
+        let content_types_xml: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">
+<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>
+<Default Extension="xml" ContentType="application/xml"/>
+<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>
+<Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>
+<Override PartName="/xl/sharedStrings.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sharedStrings+xml"/>
+</Types>"#;
+
         Book {
             path: "".to_string(),
             rels,
             drawings: HashMap::new(),
-            tables: HashMap::new(),
+            tables: XmlParts::default(),
+            charts: XmlParts::default(),
             pivot_tables: HashMap::new(),
             pivot_caches: HashMap::new(),
             themes: HashMap::new(),
-            worksheets: HashMap::new(),
-            sheet_rels: HashMap::new(),
+            worksheets: XmlParts::default(),
+            sheet_rels: XmlParts::default(),
             shared_strings: Arc::new(Mutex::new(Xml::new(
                 r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?><sst xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" count="0" uniqueCount="0"></sst>"#,
             ).unwrap_or_default())),
             shared_strings_map: Arc::new(Mutex::new(HashMap::new())),
             styles: Arc::new(Mutex::new(Xml::new(styles_xml).unwrap_or_default())),
-            workbook: Xml::new(workbook_xml).unwrap_or_default(),
+            workbook: Arc::new(Mutex::new(Xml::new(workbook_xml).unwrap_or_default())),
+            metadata: Arc::new(Mutex::new(Xml::default())),
+            content_types: Xml::new(content_types_xml).unwrap_or_default(),
             vba_project: None,
+            files: HashMap::new(),
+            removed_parts: HashSet::new(),
+        }
+    }
+
+    /// ファイルからのワークブックの読み込み
+    fn from_file(path: &str) -> Self {
+        let mut book: Book = Self::new_empty_workbook();
+        if let Ok(file) = File::open(path) {
+            let reader: BufReader<File> = BufReader::new(file);
+            if let Ok(mut archive) = ZipArchive::new(reader) {
+                book.path = path.to_string();
+                let archive_len: usize = archive.len();
+                for i in 0..archive_len {
+                    if let Ok(mut file) = archive.by_index(i) {
+                        let name: String = file.name().to_string();
+
+                        if name.ends_with(XML_SUFFIX) || name.ends_with(XML_RELS_SUFFIX) {
+                            let mut contents: String = String::with_capacity(file.size() as usize);
+                            if file.read_to_string(&mut contents).is_ok()
+                                && let Ok(xml) = Xml::new(&contents)
+                            {
+                                match name.as_str() {
+                                    s if s.starts_with(DRAWINGS_PREFIX) => {
+                                        book.drawings.insert(name, xml);
+                                    }
+                                    s if s.starts_with(CHARTS_PREFIX) => {
+                                        book.charts.insert(name, xml);
+                                    }
+                                    s if s.starts_with(TABLES_PREFIX) => {
+                                        book.tables.insert(name, xml);
+                                    }
+                                    s if s.starts_with(PIVOT_TABLES_PREFIX) => {
+                                        book.pivot_tables.insert(name, xml);
+                                    }
+                                    s if s.starts_with(PIVOT_CACHES_PREFIX) => {
+                                        book.pivot_caches.insert(name, xml);
+                                    }
+                                    s if s.starts_with(THEME_PREFIX) => {
+                                        book.themes.insert(name, xml);
+                                    }
+                                    s if s.starts_with(WORKSHEETS_PREFIX) => {
+                                        let mut xml: Xml = xml;
+                                        if let Some(worksheet) = xml.elements.first_mut() {
+                                            Sheet::expand_shared_formulas(worksheet);
+                                        }
+                                        book.worksheets.insert(name, xml);
+                                    }
+                                    s if s.starts_with(WORKBOOK_RELS_PREFIX) => {
+                                        book.rels.insert(name, xml);
+                                    }
+                                    s if s.starts_with(WORKSHEETS_RELS_PREFIX) => {
+                                        book.sheet_rels.insert(name, xml);
+                                    }
+                                    WORKBOOK_FILENAME => book.workbook = Arc::new(Mutex::new(xml)),
+                                    METADATA_FILENAME => book.metadata = Arc::new(Mutex::new(xml)),
+                                    CONTENT_TYPES_FILENAME => book.content_types = xml,
+                                    STYLES_FILENAME => book.styles = Arc::new(Mutex::new(xml)),
+                                    SHARED_STRINGS_FILENAME => {
+                                        // 書式のない文字列のみ、重複する場合は最初の項目を対応表に登録
+                                        let mut map: HashMap<String, usize> = HashMap::new();
+                                        if let Some(sst) = xml.elements.first() {
+                                            for (i, si) in sst.children.iter().enumerate() {
+                                                if let Some(text) = Cell::plain_shared_string(si) {
+                                                    map.entry(text).or_insert(i);
+                                                }
+                                            }
+                                        }
+                                        book.shared_strings = Arc::new(Mutex::new(xml));
+                                        book.shared_strings_map = Arc::new(Mutex::new(map));
+                                    }
+                                    _ => {}
+                                }
+                            }
+                        } else if name == VBA_PROJECT_FILENAME {
+                            let mut contents: Vec<u8> = Vec::new();
+                            if file.read_to_end(&mut contents).is_ok() {
+                                book.vba_project = Some(contents);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        book
+    }
+
+    /// ワークブックの元のファイルパスへの保存
+    pub fn save(&self) {
+        let file: File = File::open(&self.path)
+            .unwrap_or_else(|_| panic!("Failed to open file for saving: {}", &self.path));
+        let reader: BufReader<File> = BufReader::new(file);
+        let mut archive: ZipArchive<BufReader<File>> =
+            ZipArchive::new(reader).expect("Failed to open zip archive for saving");
+
+        let buffer: Cursor<Vec<u8>> = Cursor::new(Vec::new());
+        let mut zip_writer: ZipWriter<Cursor<Vec<u8>>> = ZipWriter::new(buffer);
+        let options: FileOptions =
+            FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+        self.write_to_archive(Some(&mut archive), &mut zip_writer, &options);
+    }
+
+    /// ワークブックのzipアーカイブへの書き込み
+    fn write_to_archive<W: Write + std::io::Seek, R: Read + std::io::Seek>(
+        &self,
+        archive: Option<&mut ZipArchive<R>>,
+        zip_writer: &mut ZipWriter<W>,
+        options: &FileOptions,
+    ) {
+        let workbook_rels: Option<Xml> = self.workbook_rels_for_save();
+
+        // 各ワークシートの `<dimension ref>` を現在のセルの範囲に更新
+        for (_, xml) in self.worksheets.entries() {
+            if let Ok(mut xml) = xml.lock()
+                && let Some(worksheet) = xml.elements.first_mut()
+                && worksheet.name == "worksheet"
+            {
+                Sheet::update_dimension(worksheet);
+            }
+        }
+
+        // 全XMLファイルへの参照を一つのVecにまとめる
+        let mut xmls_with_paths: Vec<(String, Box<dyn ToXml + '_>)> = Vec::new();
+        xmls_with_paths.extend([
+            (
+                WORKBOOK_FILENAME.to_string(),
+                Box::new(&self.workbook) as Box<dyn ToXml>,
+            ),
+            (STYLES_FILENAME.to_string(), Box::new(&self.styles)),
+            (
+                SHARED_STRINGS_FILENAME.to_string(),
+                Box::new(&self.shared_strings),
+            ),
+            (
+                CONTENT_TYPES_FILENAME.to_string(),
+                Box::new(self.content_types_for_save()),
+            ),
+        ]);
+        if self.has_metadata() {
+            xmls_with_paths.push((METADATA_FILENAME.to_string(), Box::new(&self.metadata)));
+        }
+
+        for (k, v) in self.rels.entries() {
+            match &workbook_rels {
+                Some(patched) if k == WORKBOOK_RELS_FILENAME => {
+                    xmls_with_paths.push((k, Box::new(patched)))
+                }
+                _ => xmls_with_paths.push((k, Box::new(v))),
+            }
+        }
+        let shared_chain = self
+            .tables
+            .entries()
+            .into_iter()
+            .chain(self.sheet_rels.entries())
+            .chain(self.worksheets.entries())
+            .chain(self.charts.entries())
+            .map(|(k, v)| (k, Box::new(v) as Box<dyn ToXml>));
+        let owned_chain = self
+            .drawings
+            .iter()
+            .chain(self.pivot_tables.iter())
+            .chain(self.pivot_caches.iter())
+            .chain(self.themes.iter())
+            .map(|(k, v)| (k.clone(), Box::new(v) as Box<dyn ToXml>));
+        xmls_with_paths.extend(shared_chain);
+        xmls_with_paths.extend(owned_chain);
+
+        if let Some(archive) = archive {
+            let file_names: Vec<String> = archive.file_names().map(|s| s.to_string()).collect();
+            for filename in file_names {
+                if !xmls_with_paths.iter().any(|(path, _)| *path == filename)
+                    && !self.files.contains_key(&filename)
+                    && !self.removed_parts.contains(&filename)
+                    && Some(filename.as_str())
+                        != self.vba_project.as_ref().map(|_| VBA_PROJECT_FILENAME)
+                    && let Ok(mut file) = archive.by_name(&filename)
+                {
+                    let mut contents: Vec<u8> = Vec::new();
+                    if file.read_to_end(&mut contents).is_ok()
+                        && zip_writer.start_file(filename.clone(), *options).is_ok()
+                    {
+                        let _ = zip_writer.write_all(&contents);
+                    }
+                }
+            }
+        }
+
+        for (file_name, xml) in xmls_with_paths {
+            if zip_writer.start_file(file_name, *options).is_ok()
+                && let Ok(buf) = xml.to_buf()
+            {
+                let _ = zip_writer.write_all(&buf);
+            }
+        }
+
+        for (file_name, contents) in &self.files {
+            if zip_writer.start_file(file_name.clone(), *options).is_ok() {
+                let _ = zip_writer.write_all(contents);
+            }
+        }
+
+        if let Some(vba_project) = &self.vba_project
+            && zip_writer
+                .start_file(VBA_PROJECT_FILENAME, *options)
+                .is_ok()
+        {
+            let _ = zip_writer.write_all(vba_project);
+        }
+    }
+
+    /// セルメタデータが存在するかどうか
+    fn has_metadata(&self) -> bool {
+        self.metadata
+            .lock()
+            .is_ok_and(|metadata| !metadata.elements.is_empty())
+    }
+
+    /// 保存用のコンテンツタイプの作成
+    ///
+    /// 管理しているパーツのうち `Override` が登録されていないものを追加
+    fn content_types_for_save(&self) -> Xml {
+        let mut content_types: Xml = self.content_types.clone();
+        let mut parts: Vec<(String, &str)> = self
+            .worksheets
+            .keys()
+            .into_iter()
+            .map(|k| (k, WORKSHEET_CONTENT_TYPE))
+            .chain(
+                self.tables
+                    .keys()
+                    .into_iter()
+                    .map(|k| (k, TABLE_CONTENT_TYPE)),
+            )
+            .collect();
+        if self.has_metadata() {
+            parts.push((METADATA_FILENAME.to_string(), METADATA_CONTENT_TYPE));
+        }
+        parts.sort();
+
+        if let Some(types) = content_types.elements.first_mut() {
+            for (part, content_type) in parts {
+                let part_name: String = format!("/{part}");
+                if types.children.iter().any(|o| {
+                    o.name == "Override" && o.get_attribute("PartName") == Some(&part_name)
+                }) {
+                    continue;
+                }
+                let mut override_element: XmlElement = XmlElement::new("Override");
+                override_element
+                    .attributes
+                    .insert("PartName".to_string(), part_name);
+                override_element
+                    .attributes
+                    .insert("ContentType".to_string(), content_type.to_string());
+                types.children.push(override_element);
+            }
+        }
+        content_types
+    }
+
+    /// 保存用のワークブックリレーションシップの作成
+    ///
+    /// 未登録のセルメタデータがある場合のみリレーションシップを追加したものを返却
+    fn workbook_rels_for_save(&self) -> Option<Xml> {
+        let rels: Arc<Mutex<Xml>> = self.rels.get(WORKBOOK_RELS_FILENAME)?;
+        let rels: MutexGuard<Xml> = rels.lock().unwrap();
+        let registered: bool = rels
+            .elements
+            .first()?
+            .children
+            .iter()
+            .any(|r| r.get_attribute("Type").map(String::as_str) == Some(METADATA_REL_TYPE));
+        if registered || !self.has_metadata() {
+            return None;
+        }
+
+        let mut rels: Xml = rels.clone();
+        let relationships: &mut XmlElement = rels.elements.first_mut()?;
+        let mut relationship: XmlElement = XmlElement::new("Relationship");
+        relationship.attributes.insert(
+            "Id".to_string(),
+            Self::next_relationship_id(&relationships.children),
+        );
+        relationship
+            .attributes
+            .insert("Type".to_string(), METADATA_REL_TYPE.to_string());
+        relationship
+            .attributes
+            .insert("Target".to_string(), "metadata.xml".to_string());
+        relationships.children.push(relationship);
+        Some(rels)
+    }
+
+    /// リレーションシップの一覧で未使用の `rId` の取得
+    fn next_relationship_id(relationships: &[XmlElement]) -> String {
+        let max_id: usize = relationships
+            .iter()
+            .filter_map(|r| r.get_attribute("Id")?.strip_prefix("rId")?.parse().ok())
+            .max()
+            .unwrap_or(0);
+        format!("rId{}", max_id + 1)
+    }
+
+    /// 未使用の `sheetId` (既存の最大値の次)
+    fn next_sheet_id(&self) -> usize {
+        self.sheet_tags()
+            .iter()
+            .filter_map(|s| s.get_attribute("sheetId")?.parse::<usize>().ok())
+            .max()
+            .unwrap_or(0)
+            + 1
+    }
+
+    /// ワークブック内で未使用のテーブルの `id` (既存の最大値の次)
+    fn next_table_id(&self) -> usize {
+        self.tables
+            .entries()
+            .iter()
+            .filter_map(|(_, table)| {
+                table
+                    .lock()
+                    .unwrap()
+                    .elements
+                    .first()?
+                    .get_attribute("id")?
+                    .parse::<usize>()
+                    .ok()
+            })
+            .max()
+            .unwrap_or(0)
+            + 1
+    }
+
+    /// `calcPr` の `fullCalcOnLoad` の書き込み
+    fn write_full_calc_on_load(&self, value: bool) {
+        if let Some(wb) = self.workbook.lock().unwrap().elements.first_mut() {
+            let calc_pr: &mut XmlElement =
+                wb.get_or_create_child_ordered("calcPr", &WORKBOOK_CHILD_ORDER);
+            if value {
+                calc_pr
+                    .attributes
+                    .insert("fullCalcOnLoad".to_string(), "1".to_string());
+            } else {
+                calc_pr.attributes.remove("fullCalcOnLoad");
+            }
+        }
+    }
+
+    /// 全シートの数式の計算と結果の書き込み
+    ///
+    /// 計算したセルの数を返却。
+    /// 対応していない関数や参照を使う数式 (とそれに依存する数式) はキャッシュ値を変更せず、
+    /// 読み込み時に再計算されるよう `fullCalcOnLoad` を設定する
+    pub fn calculate_formulas(&self) -> Result<usize, CalcError> {
+        let sheets: Vec<(String, Arc<Mutex<Xml>>)> = self.parts().sheets();
+        // `localSheetId` はグラフシートも数える `sheets` 要素の位置のため、ワークシートの位置に付け替える
+        let sheet_names: Vec<String> = self.parts().sheet_names();
+        let worksheet_index = |local_sheet_id: usize| {
+            let name: &String = sheet_names.get(local_sheet_id)?;
+            sheets.iter().position(|(n, _)| n == name)
+        };
+        let defined_names: Vec<(String, Option<usize>, String)> = self
+            .workbook
+            .lock()
+            .unwrap()
+            .elements
+            .first()
+            .and_then(|wb| wb.find_element("definedNames"))
+            .map(|defined_names| {
+                defined_names
+                    .children
+                    .iter()
+                    .filter_map(|dn| {
+                        let name: String = dn.get_attribute("name")?.clone();
+                        let scope: Option<usize> = match dn.get_attribute("localSheetId") {
+                            // グラフシートのスコープの名前は数式から参照できない
+                            Some(id) => Some(worksheet_index(id.parse().ok()?)?),
+                            None => None,
+                        };
+                        Some((name, scope, dn.text.clone()?))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let skipped: bool;
+        let results: Vec<((usize, u32, u32), Value)> = {
+            let guards: Vec<MutexGuard<Xml>> =
+                sheets.iter().map(|(_, xml)| xml.lock().unwrap()).collect();
+            let sheet_xmls: Vec<(String, &Xml)> = sheets
+                .iter()
+                .zip(&guards)
+                .map(|((name, _), guard)| (name.clone(), &**guard))
+                .collect();
+            let shared_strings: MutexGuard<Xml> = self.shared_strings.lock().unwrap();
+            let mut calculator: Calculator =
+                Calculator::new(&sheet_xmls, &shared_strings, &defined_names);
+            let results: Vec<((usize, u32, u32), Value)> = calculator.calculate()?;
+            skipped = !calculator.skipped().is_empty();
+            results
+        };
+        for ((sheet, row, col), value) in &results {
+            let mut xml: MutexGuard<Xml> = sheets[*sheet].1.lock().unwrap();
+            if let Some(worksheet) = xml.elements.first_mut() {
+                calc::write_result(worksheet, *row, *col, value);
+            }
+        }
+        if skipped {
+            self.write_full_calc_on_load(true);
+        }
+        Ok(results.len())
+    }
+
+    /// `xl/workbook.xml` からのシートタグの取得
+    pub fn sheet_tags(&self) -> Vec<XmlElement> {
+        self.parts().sheet_tags()
+    }
+
+    /// `xl/workbook.xml.rels` からのリレーションシップのリスト取得
+    pub fn get_relationships(&self) -> Vec<XmlElement> {
+        self.parts().relationships()
+    }
+
+    /// シート名とそのパスのマップ取得
+    pub fn get_sheet_paths(&self) -> HashMap<String, String> {
+        self.parts().sheet_paths()
+    }
+
+    /// シートと共有するワークブック全体のパーツの取得
+    pub fn parts(&self) -> BookParts {
+        BookParts {
+            workbook: self.workbook.clone(),
+            rels: self.rels.clone(),
+            worksheets: self.worksheets.clone(),
+            sheet_rels: self.sheet_rels.clone(),
+            tables: self.tables.clone(),
+            charts: self.charts.clone(),
+        }
+    }
+
+    /// シートの指定からのシートの位置の取得
+    fn sheet_position(&self, sheet: &SheetKey) -> Result<usize, BookError> {
+        let names: Vec<String> = self.sheetnames();
+        match sheet {
+            SheetKey::Index(index) if *index < names.len() => Ok(*index),
+            SheetKey::Index(index) => Err(BookError::SheetIndexOutOfRange(*index)),
+            SheetKey::Sheet(sheet) => {
+                let name: String = sheet.name()?;
+                names
+                    .iter()
+                    .position(|n| *n == name)
+                    .ok_or(BookError::SheetNotFound(name))
+            }
+            SheetKey::Name(name) => names
+                .iter()
+                .position(|n| n == name)
+                .ok_or_else(|| BookError::SheetNotFound(name.clone())),
+        }
+    }
+
+    /// 名前によるシートの取得
+    pub fn get_sheet_by_name(&self, name: &str) -> Option<Sheet> {
+        let sheet_paths: HashMap<String, String> = self.get_sheet_paths();
+        sheet_paths.get(name).and_then(|sheet_path| {
+            self.worksheets.get(sheet_path).map(|xml| {
+                Sheet::new(
+                    sheet_path.clone(),
+                    xml.clone(),
+                    self.shared_strings.clone(),
+                    self.shared_strings_map.clone(),
+                    self.styles.clone(),
+                    self.metadata.clone(),
+                    self.parts(),
+                )
+            })
+        })
+    }
+
+    /// シートの順序の適用 (`order[新しい位置] = 元の位置`)
+    ///
+    /// `sheets` の並び、定義された名前の `localSheetId`、`activeTab`・`firstSheet` と `docProps/app.xml` のシート名の一覧を揃える
+    fn apply_sheet_order(&mut self, order: &[usize]) {
+        if let Some(sheets_tag) = self
+            .workbook
+            .lock()
+            .unwrap()
+            .elements
+            .first_mut()
+            .and_then(|wb| wb.children.iter_mut().find(|x| x.name == "sheets"))
+        {
+            let sheets: Vec<XmlElement> = std::mem::take(&mut sheets_tag.children);
+            sheets_tag.children = order
+                .iter()
+                .filter_map(|&i| sheets.get(i).cloned())
+                .collect();
+        }
+        let mut positions: Vec<usize> = vec![0; order.len()];
+        for (new, &old) in order.iter().enumerate() {
+            positions[old] = new;
+        }
+        self.parts()
+            .reindex_sheets(|id| Some(positions.get(id).copied().unwrap_or(id)));
+        self.update_app_titles();
+    }
+
+    /// `docProps/app.xml` のパーツ名の一覧 (`TitlesOfParts`) のシート名のワークブックの順序への並べ替え
+    fn update_app_titles(&mut self) {
+        let Some(mut app) = self.load_xml(APP_PROPERTIES_FILENAME) else {
+            return;
+        };
+        let Some(titles) = app
+            .elements
+            .first_mut()
+            .and_then(|props| props.find_element_mut("TitlesOfParts>vt:vector"))
+        else {
+            return;
+        };
+        let names: Vec<String> = self.sheetnames();
+        let slots: Vec<usize> = titles
+            .children
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.text.as_ref().is_some_and(|text| names.contains(text)))
+            .map(|(i, _)| i)
+            .collect();
+        let listed: Vec<String> = slots
+            .iter()
+            .filter_map(|&i| titles.children[i].text.clone())
+            .collect();
+        let ordered = names.iter().filter(|name| listed.contains(name));
+        for (&slot, name) in slots.iter().zip(ordered) {
+            titles.children[slot].text = Some(name.clone());
+        }
+        self.store_xml(APP_PROPERTIES_FILENAME.to_string(), app);
+    }
+
+    /// 新しいシート名の検証 (命名規則と大文字と小文字を区別しない重複)
+    fn validate_new_title(&self, title: String) -> Result<String, BookError> {
+        validate_sheet_name(&title)?;
+        if self
+            .sheetnames()
+            .iter()
+            .any(|n| n.to_lowercase() == title.to_lowercase())
+        {
+            return Err(BookError::DuplicateSheetName(title));
+        }
+        Ok(title)
+    }
+
+    /// 別のブックから取り込むワークシートのセルの書式・共有文字列・セルメタデータの付け替え
+    fn remap_imported_worksheet(&self, other: &Book, worksheet: &mut XmlElement) {
+        let source_styles: Xml = other.styles.lock().unwrap().clone();
+        let Some(source_styles) = source_styles.elements.first() else {
+            return;
+        };
+        let mut styles: MutexGuard<Xml> = self.styles.lock().unwrap();
+        let Some(styles) = styles.elements.first_mut() else {
+            return;
+        };
+        let mut xf_map: HashMap<usize, usize> = HashMap::new();
+        let mut remap_xf = |value: &str| -> String {
+            let index: usize = value.parse().unwrap_or(0);
+            xf_map
+                .entry(index)
+                .or_insert_with(|| style::import_cell_xf(styles, source_styles, index))
+                .to_string()
+        };
+
+        if let Some(cols) = worksheet.find_element_mut("cols") {
+            for col in &mut cols.children {
+                if let Some(style) = col.attributes.get_mut("style") {
+                    *style = remap_xf(style);
+                }
+            }
+        }
+        let source_strings: MutexGuard<Xml> = other.shared_strings.lock().unwrap();
+        let source_metadata: Xml = other.metadata.lock().unwrap().clone();
+        let mut cell_metadata: Option<usize> = None;
+        if let Some(sheet_data) = worksheet.find_element_mut("sheetData") {
+            for row in &mut sheet_data.children {
+                if let Some(style) = row.attributes.get_mut("s") {
+                    *style = remap_xf(style);
+                }
+                for cell in &mut row.children {
+                    if let Some(style) = cell.attributes.get_mut("s") {
+                        *style = remap_xf(style);
+                    }
+                    // 動的配列以外のセルメタデータは取り込まないため、参照を外す
+                    if let Some(cm) = cell.attributes.remove("cm")
+                        && cm
+                            .parse::<usize>()
+                            .is_ok_and(|cm| Sheet::is_dynamic_array_metadata(&source_metadata, cm))
+                    {
+                        let index: usize = *cell_metadata.get_or_insert_with(|| {
+                            Sheet::ensure_dynamic_array_metadata(&mut self.metadata.lock().unwrap())
+                        });
+                        cell.attributes.insert("cm".to_string(), index.to_string());
+                    }
+                    if cell.get_attribute("t").is_some_and(|t| t == "s")
+                        && let Some(v) = cell.children.iter_mut().find(|c| c.name == "v")
+                        && let Some(si) = v
+                            .text
+                            .as_ref()
+                            .and_then(|i| i.parse::<usize>().ok())
+                            .and_then(|i| source_strings.elements.first()?.children.get(i))
+                    {
+                        v.text = Some(self.intern_shared_string(si).to_string());
+                    }
+                }
+            }
+        }
+
+        // 条件付き書式の差分書式
+        let mut dxf_map: HashMap<usize, usize> = HashMap::new();
+        for formatting in worksheet
+            .children
+            .iter_mut()
+            .filter(|c| c.name == "conditionalFormatting")
+        {
+            for rule in &mut formatting.children {
+                if let Some(dxf_id) = rule.attributes.get_mut("dxfId") {
+                    let index: usize = dxf_id.parse().unwrap_or(0);
+                    *dxf_id = dxf_map
+                        .entry(index)
+                        .or_insert_with(|| style::import_dxf(styles, source_styles, index))
+                        .to_string();
+                }
+            }
+        }
+    }
+
+    /// 共有文字列の項目 (`si`) の登録とインデックスの取得
+    ///
+    /// 書式のない文字列は既存の同じ文字列を使用し、リッチテキストはそのまま追加する
+    fn intern_shared_string(&self, si: &XmlElement) -> usize {
+        let mut map: MutexGuard<HashMap<String, usize>> = self.shared_strings_map.lock().unwrap();
+        let mut strings: MutexGuard<Xml> = self.shared_strings.lock().unwrap();
+        Cell::intern_shared_string(&mut strings, &mut map, si)
+    }
+
+    /// 複製したシートの既定の名前 ("<元のシート名> Copy", 重複する場合は末尾に番号)
+    fn copy_title(name: &str, names: &[String]) -> String {
+        let exists = |title: &str| {
+            names
+                .iter()
+                .any(|n| n.to_lowercase() == title.to_lowercase())
+        };
+        (1..)
+            .map(|n: usize| {
+                let suffix: String = if n == 1 {
+                    " Copy".to_string()
+                } else {
+                    format!(" Copy{n}")
+                };
+                let base: String = name
+                    .chars()
+                    .take(MAX_SHEET_NAME_LENGTH - suffix.chars().count())
+                    .collect();
+                format!("{base}{suffix}")
+            })
+            .find(|title| !exists(title))
+            .expect("unused sheet title")
+    }
+
+    /// パッケージ内の全パーツの名前
+    fn part_names(&self) -> HashSet<String> {
+        self.part_names_in(self.open_archive().as_ref())
+    }
+
+    /// 元のファイルのzipアーカイブ (開けない場合は `None`)
+    fn open_archive(&self) -> Option<ZipArchive<BufReader<File>>> {
+        let file: File = File::open(&self.path).ok()?;
+        ZipArchive::new(BufReader::new(file)).ok()
+    }
+
+    /// 開いたアーカイブを使ったパーツ名の一覧
+    fn part_names_in(&self, archive: Option<&ZipArchive<BufReader<File>>>) -> HashSet<String> {
+        let mut names: HashSet<String> = HashSet::new();
+        for parts in [
+            &self.rels,
+            &self.worksheets,
+            &self.sheet_rels,
+            &self.tables,
+            &self.charts,
+        ] {
+            names.extend(parts.keys());
+        }
+        names.extend(
+            self.drawings
+                .keys()
+                .chain(self.pivot_tables.keys())
+                .chain(self.pivot_caches.keys())
+                .chain(self.themes.keys())
+                .chain(self.files.keys())
+                .cloned(),
+        );
+        if let Some(archive) = archive {
+            names.extend(
+                archive
+                    .file_names()
+                    .filter(|name| !self.removed_parts.contains(*name))
+                    .map(str::to_string),
+            );
+        }
+        names
+    }
+
+    /// 既存のパーツと重複しないパーツの名前
+    ///
+    /// `template` と同じディレクトリ・拡張子で末尾の番号のみ変えたもの (例: "xl/tables/table3.xml")
+    pub(crate) fn unique_part_name(&self, template: &str) -> String {
+        let (dir, file) = template.rsplit_once('/').unwrap_or(("", template));
+        let (stem, ext) = file.split_once('.').unwrap_or((file, ""));
+        let base: &str = stem.trim_end_matches(|c: char| c.is_ascii_digit());
+        let names: HashSet<String> = self.part_names();
+        (1..)
+            .map(|n: usize| {
+                let name: String = format!("{base}{n}.{ext}");
+                if dir.is_empty() {
+                    name
+                } else {
+                    format!("{dir}/{name}")
+                }
+            })
+            .find(|name| !names.contains(name))
+            .expect("unused part name")
+    }
+
+    /// XMLとして管理しているパーツまたはその他のファイルのXMLの取得
+    fn load_xml(&self, path: &str) -> Option<Xml> {
+        match self.load_part(path)? {
+            PartContent::Xml(xml) => Some(xml),
+            PartContent::Raw(contents) => Xml::new(&String::from_utf8(contents).ok()?).ok(),
         }
     }
 
-    /// ファイルからのワークブックの読み込み
-    fn from_file(path: &str) -> Self {
-        let mut book: Book = Self::new_empty_workbook();
-        if let Ok(file) = File::open(path) {
-            let reader: BufReader<File> = BufReader::new(file);
-            if let Ok(mut archive) = ZipArchive::new(reader) {
-                book.path = path.to_string();
-                let archive_len: usize = archive.len();
-                for i in 0..archive_len {
-                    if let Ok(mut file) = archive.by_index(i) {
-                        let name: String = file.name().to_string();
+    /// パーツの内容の取得 (XMLとして管理していないものはバイト列)
+    fn load_part(&self, path: &str) -> Option<PartContent> {
+        self.loaded_part(path)
+            .or_else(|| self.read_file(path).map(PartContent::Raw))
+    }
 
-                        if name.ends_with(XML_SUFFIX) || name.ends_with(XML_RELS_SUFFIX) {
-                            let mut contents: String = String::with_capacity(file.size() as usize);
-                            if file.read_to_string(&mut contents).is_ok() {
-                                if let Ok(xml) = Xml::new(&contents) {
-                                    match name.as_str() {
-                                        s if s.starts_with(DRAWINGS_PREFIX) => {
-                                            book.drawings.insert(name, xml);
-                                        }
-                                        s if s.starts_with(TABLES_PREFIX) => {
-                                            book.tables.insert(name, xml);
-                                        }
-                                        s if s.starts_with(PIVOT_TABLES_PREFIX) => {
-                                            book.pivot_tables.insert(name, xml);
-                                        }
-                                        s if s.starts_with(PIVOT_CACHES_PREFIX) => {
-                                            book.pivot_caches.insert(name, xml);
-                                        }
-                                        s if s.starts_with(THEME_PREFIX) => {
-                                            book.themes.insert(name, xml);
-                                        }
-                                        s if s.starts_with(WORKSHEETS_PREFIX) => {
-                                            book.worksheets.insert(name, Arc::new(Mutex::new(xml)));
-                                        }
-                                        s if s.starts_with(WORKBOOK_RELS_PREFIX) => {
-                                            book.rels.insert(name, xml);
-                                        }
-                                        s if s.starts_with(WORKSHEETS_RELS_PREFIX) => {
-                                            book.sheet_rels.insert(name, xml);
-                                        }
-                                        WORKBOOK_FILENAME => book.workbook = xml,
-                                        STYLES_FILENAME => book.styles = Arc::new(Mutex::new(xml)),
-                                        SHARED_STRINGS_FILENAME => {
-                                            let mut map: HashMap<String, usize> = HashMap::new();
-                                            if !xml.elements.is_empty() {
-                                                for (i, si) in
-                                                    xml.elements[0].children.iter().enumerate()
-                                                {
-                                                    let s: String =
-                                                        si.get_element("t").get_text().to_string();
-                                                    map.insert(s, i);
-                                                }
-                                            }
-                                            book.shared_strings = Arc::new(Mutex::new(xml));
-                                            book.shared_strings_map = Arc::new(Mutex::new(map));
-                                        }
-                                        _ => {}
-                                    }
-                                }
-                            }
-                        } else if name == VBA_PROJECT_FILENAME {
-                            let mut contents: Vec<u8> = Vec::new();
-                            if file.read_to_end(&mut contents).is_ok() {
-                                book.vba_project = Some(contents);
-                            }
-                        }
-                    }
-                }
+    /// XMLとして管理しているパーツの内容の取得
+    fn loaded_part(&self, path: &str) -> Option<PartContent> {
+        for parts in [
+            &self.worksheets,
+            &self.sheet_rels,
+            &self.tables,
+            &self.charts,
+        ] {
+            if let Some(xml) = parts.get(path) {
+                return Some(PartContent::Xml(xml.lock().unwrap().clone()));
             }
         }
-        book
+        if let Some(xml) = self
+            .drawings
+            .get(path)
+            .or_else(|| self.pivot_tables.get(path))
+        {
+            return Some(PartContent::Xml(xml.clone()));
+        }
+        None
     }
 
-    /// ワークブックの元のファイルパスへの保存
-    pub fn save(&self) {
-        let file: File = File::open(&self.path)
-            .unwrap_or_else(|_| panic!("Failed to open file for saving: {}", &self.path));
-        let reader: BufReader<File> = BufReader::new(file);
-        let mut archive: ZipArchive<BufReader<File>> =
-            ZipArchive::new(reader).expect("Failed to open zip archive for saving");
-
-        let buffer: Cursor<Vec<u8>> = Cursor::new(Vec::new());
-        let mut zip_writer: ZipWriter<Cursor<Vec<u8>>> = ZipWriter::new(buffer);
-        let options: FileOptions =
-            FileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    /// パーツのXMLの格納 (読み込み時と同じ分類で格納し、それ以外はその他のファイル)
+    fn store_xml(&mut self, path: String, xml: Xml) {
+        let parsed: bool = path.ends_with(XML_SUFFIX) || path.ends_with(XML_RELS_SUFFIX);
+        match path.as_str() {
+            p if parsed && p.starts_with(DRAWINGS_PREFIX) => {
+                self.drawings.insert(path, xml);
+            }
+            p if parsed && p.starts_with(CHARTS_PREFIX) => self.charts.insert(path, xml),
+            p if parsed && p.starts_with(TABLES_PREFIX) => self.tables.insert(path, xml),
+            p if parsed && p.starts_with(PIVOT_TABLES_PREFIX) => {
+                self.pivot_tables.insert(path, xml);
+            }
+            p if parsed && p.starts_with(WORKSHEETS_RELS_PREFIX) => {
+                self.sheet_rels.insert(path, xml)
+            }
+            _ => {
+                if let Ok(buf) = xml.to_buf() {
+                    self.files.insert(path, buf);
+                }
+            }
+        }
+    }
 
-        self.write_to_archive(Some(&mut archive), &mut zip_writer, &options);
+    /// 解析していないファイルの内容の取得 (複製したファイルまたは元のファイル内)
+    fn read_file(&self, path: &str) -> Option<Vec<u8>> {
+        if let Some(contents) = self.files.get(path) {
+            return Some(contents.clone());
+        }
+        self.read_file_in(self.open_archive().as_mut(), path)
     }
 
-    /// ワークブックのzipアーカイブへの書き込み
-    fn write_to_archive<W: Write + std::io::Seek, R: Read + std::io::Seek>(
+    /// 開いたアーカイブを使った解析していないファイルの内容の取得
+    fn read_file_in(
         &self,
-        archive: Option<&mut ZipArchive<R>>,
-        zip_writer: &mut ZipWriter<W>,
-        options: &FileOptions,
-    ) {
-        // 全XMLファイルへの参照を一つのVecにまとめる
-        let mut xmls_with_paths: Vec<(&String, Box<dyn ToXml>)> = Vec::new();
-        let workbook_filename_str: String = WORKBOOK_FILENAME.to_string();
-        let styles_filename_str: String = STYLES_FILENAME.to_string();
-        let shared_strings_filename_str: String = SHARED_STRINGS_FILENAME.to_string();
-
-        xmls_with_paths.extend([
-            (
-                &workbook_filename_str,
-                Box::new(&self.workbook) as Box<dyn ToXml>,
-            ),
-            (&styles_filename_str, Box::new(&self.styles)),
-            (&shared_strings_filename_str, Box::new(&self.shared_strings)),
-        ]);
+        archive: Option<&mut ZipArchive<BufReader<File>>>,
+        path: &str,
+    ) -> Option<Vec<u8>> {
+        if let Some(contents) = self.files.get(path) {
+            return Some(contents.clone());
+        }
+        if self.removed_parts.contains(path) {
+            return None;
+        }
+        let mut file = archive?.by_name(path).ok()?;
+        let mut contents: Vec<u8> = Vec::new();
+        file.read_to_end(&mut contents).ok()?;
+        Some(contents)
+    }
 
-        let iter_chain = self
-            .rels
+    /// パーツのコンテンツタイプ (`Override` または拡張子の `Default`)
+    fn content_type_of(&self, path: &str) -> Option<ContentType> {
+        let types: &XmlElement = self.content_types.elements.first()?;
+        let part_name: String = format!("/{path}");
+        if let Some(content_type) = types
+            .children
             .iter()
-            .map(|(k, v)| (k, Box::new(v) as Box<dyn ToXml>))
-            .chain(
-                self.drawings
-                    .iter()
-                    .map(|(k, v)| (k, Box::new(v) as Box<dyn ToXml>)),
-            )
-            .chain(
-                self.tables
-                    .iter()
-                    .map(|(k, v)| (k, Box::new(v) as Box<dyn ToXml>)),
-            )
-            .chain(
-                self.pivot_tables
-                    .iter()
-                    .map(|(k, v)| (k, Box::new(v) as Box<dyn ToXml>)),
-            )
-            .chain(
-                self.pivot_caches
-                    .iter()
-                    .map(|(k, v)| (k, Box::new(v) as Box<dyn ToXml>)),
-            )
-            .chain(
-                self.sheet_rels
-                    .iter()
-                    .map(|(k, v)| (k, Box::new(v) as Box<dyn ToXml>)),
-            )
-            .chain(
-                self.worksheets
-                    .iter()
-                    .map(|(k, v)| (k, Box::new(v) as Box<dyn ToXml>)),
-            )
-            .chain(
-                self.themes
+            .find(|o| o.name == "Override" && o.get_attribute("PartName") == Some(&part_name))
+            .and_then(|o| o.get_attribute("ContentType"))
+        {
+            return Some(ContentType::Override(content_type.clone()));
+        }
+        let extension: &str = path.rsplit_once('.')?.1;
+        types
+            .children
+            .iter()
+            .find(|d| {
+                d.name == "Default"
+                    && d.get_attribute("Extension")
+                        .is_some_and(|e| e.eq_ignore_ascii_case(extension))
+            })
+            .and_then(|d| d.get_attribute("ContentType"))
+            .map(|content_type| ContentType::Default(extension.to_string(), content_type.clone()))
+    }
+
+    /// コンテンツタイプの登録 (登録済みの場合は何もしない)
+    fn register_content_type(&mut self, path: &str, content_type: ContentType) {
+        let Some(types) = self.content_types.elements.first_mut() else {
+            return;
+        };
+        let (tag, key, value, content_type) = match content_type {
+            ContentType::Override(content_type) => {
+                ("Override", "PartName", format!("/{path}"), content_type)
+            }
+            ContentType::Default(extension, content_type) => {
+                ("Default", "Extension", extension, content_type)
+            }
+        };
+        if types.children.iter().any(|c| {
+            c.name == tag
+                && c.get_attribute(key)
+                    .is_some_and(|v| v.eq_ignore_ascii_case(&value))
+        }) {
+            return;
+        }
+        let mut element: XmlElement = XmlElement::new(tag);
+        element.attributes.insert(key.to_string(), value);
+        element
+            .attributes
+            .insert("ContentType".to_string(), content_type);
+        match tag {
+            // `Default` は `Override` より前に置く
+            "Default" => {
+                let position: usize = types
+                    .children
                     .iter()
-                    .map(|(k, v)| (k, Box::new(v) as Box<dyn ToXml>)),
-            );
+                    .position(|c| c.name == "Override")
+                    .unwrap_or(types.children.len());
+                types.children.insert(position, element);
+            }
+            _ => types.children.push(element),
+        }
+    }
 
-        xmls_with_paths.extend(iter_chain);
+    /// 各パーツのリレーションシップで参照するパーツのパスの一覧 (外部参照を除く)
+    ///
+    /// 元のファイルのアーカイブは一度だけ開き、全てのリレーションシップを読み込む
+    fn part_targets(&self) -> HashMap<String, Vec<String>> {
+        let mut archive: Option<ZipArchive<BufReader<File>>> = self.open_archive();
+        let mut targets: HashMap<String, Vec<String>> = HashMap::new();
+        for name in self.part_names_in(archive.as_ref()) {
+            let Some((dir, file)) = name.rsplit_once("_rels/") else {
+                continue;
+            };
+            let Some(file) = file.strip_suffix(".rels") else {
+                continue;
+            };
+            let owner: String = format!("{dir}{file}");
+            let rels: Option<Xml> = match self.loaded_part(&name) {
+                Some(PartContent::Xml(xml)) => Some(xml),
+                _ => self
+                    .read_file_in(archive.as_mut(), &name)
+                    .and_then(|contents| String::from_utf8(contents).ok())
+                    .and_then(|contents| Xml::new(&contents).ok()),
+            };
+            let Some(relationships) = rels.as_ref().and_then(|rels| rels.elements.first()) else {
+                continue;
+            };
+            let base_dir: &str = owner.rsplit_once('/').map_or("", |(dir, _)| dir);
+            let owner_targets: Vec<String> = relationships
+                .children
+                .iter()
+                .filter(|r| {
+                    r.get_attribute("TargetMode")
+                        .is_none_or(|mode| mode != "External")
+                })
+                .filter_map(|r| Some(resolve_target(base_dir, r.get_attribute("Target")?)))
+                .collect();
+            targets.insert(owner, owner_targets);
+        }
+        targets
+    }
 
-        if let Some(archive) = archive {
-            let file_names: Vec<String> = archive.file_names().map(|s| s.to_string()).collect();
-            for filename in file_names {
-                if !xmls_with_paths
-                    .iter()
-                    .any(|(path, _)| path.as_str() == filename)
-                    && Some(filename.as_str())
-                        != self.vba_project.as_ref().map(|_| VBA_PROJECT_FILENAME)
-                {
-                    if let Ok(mut file) = archive.by_name(&filename) {
-                        let mut contents: Vec<u8> = Vec::new();
-                        if file.read_to_end(&mut contents).is_ok()
-                            && zip_writer.start_file(filename.clone(), *options).is_ok()
-                        {
-                            let _ = zip_writer.write_all(&contents);
-                        }
+    /// 削除するパーツからのみ参照されるパーツの一覧 (`root` 自身を除く)
+    ///
+    /// `root` からリレーションシップをたどれるパーツのうち、それ以外のパーツのリレーションシップで参照されないもの
+    fn unreferenced_parts(&self, root: &str) -> Vec<String> {
+        let targets: HashMap<String, Vec<String>> = self.part_targets();
+        let targets_of = |path: &str| targets.get(path).into_iter().flatten().cloned();
+
+        let mut reachable: Vec<String> = Vec::new();
+        let mut pending: Vec<String> = targets_of(root).collect();
+        while let Some(path) = pending.pop() {
+            if path == root || reachable.contains(&path) {
+                continue;
+            }
+            pending.extend(targets_of(&path));
+            reachable.push(path);
+        }
+
+        // 残るパーツから参照されるものを除き、参照元がなくなるまで繰り返す
+        loop {
+            let referenced: HashSet<String> = targets
+                .iter()
+                .filter(|(owner, _)| owner.as_str() != root && !reachable.contains(owner))
+                .flat_map(|(_, owner_targets)| owner_targets.iter().cloned())
+                .collect();
+            let before: usize = reachable.len();
+            reachable.retain(|part| !referenced.contains(part));
+            if reachable.len() == before {
+                return reachable;
+            }
+        }
+    }
+
+    /// パーツとそのリレーションシップ、コンテンツタイプの `Override` の削除
+    fn delete_part(&mut self, path: &str) {
+        for part in [path.to_string(), rels_path(path)] {
+            for parts in [
+                &self.rels,
+                &self.worksheets,
+                &self.sheet_rels,
+                &self.tables,
+                &self.charts,
+            ] {
+                parts.remove(&part);
+            }
+            for parts in [
+                &mut self.drawings,
+                &mut self.pivot_tables,
+                &mut self.pivot_caches,
+                &mut self.themes,
+            ] {
+                parts.remove(&part);
+            }
+            self.files.remove(&part);
+            self.removed_parts.insert(part);
+        }
+        let part_name: String = format!("/{path}");
+        if let Some(types) = self.content_types.elements.first_mut() {
+            types.children.retain(|o| {
+                !(o.name == "Override" && o.get_attribute("PartName") == Some(&part_name))
+            });
+        }
+    }
+
+    /// パーツとリレーションシップで参照するパーツの収集 (複製の準備)
+    ///
+    /// 先頭は `root` 自身。外部参照とブック全体で共有するピボットキャッシュは対象外とし、
+    /// 別のブックへの取り込み (`same_book` が偽) ではピボットテーブルへのリレーションシップを除く
+    fn collect_parts(&self, root: &str, same_book: bool) -> Vec<(String, CopiedPart)> {
+        let mut parts: Vec<(String, CopiedPart)> = Vec::new();
+        let mut pending: Vec<String> = vec![root.to_string()];
+        while let Some(path) = pending.pop() {
+            if parts.iter().any(|(p, _)| *p == path) {
+                continue;
+            }
+            let Some(content) = self.load_part(&path) else {
+                continue;
+            };
+            let base_dir: &str = path.rsplit_once('/').map_or("", |(dir, _)| dir);
+            let mut rels: Option<Xml> = self.load_xml(&rels_path(&path));
+            if let Some(relationships) = rels.as_mut().and_then(|r| r.elements.first_mut()) {
+                if !same_book {
+                    relationships.children.retain(|r| {
+                        !r.get_attribute("Type")
+                            .is_some_and(|t| t.ends_with("/pivotTable"))
+                    });
+                }
+                for relationship in &relationships.children {
+                    let shared: bool = relationship
+                        .get_attribute("TargetMode")
+                        .is_some_and(|mode| mode == "External")
+                        || relationship
+                            .get_attribute("Type")
+                            .is_some_and(|t| t.ends_with("/pivotCacheDefinition"));
+                    if let Some(target) = relationship.get_attribute("Target")
+                        && !shared
+                    {
+                        pending.push(resolve_target(base_dir, target));
                     }
                 }
             }
+            let content_type: Option<ContentType> = self.content_type_of(&path);
+            parts.push((
+                path,
+                CopiedPart {
+                    content,
+                    content_type,
+                    rels,
+                },
+            ));
         }
+        parts
+    }
 
-        for (file_name, xml) in xmls_with_paths {
-            if zip_writer.start_file(file_name, *options).is_ok() {
-                if let Ok(buf) = xml.to_buf() {
-                    let _ = zip_writer.write_all(&buf);
+    /// 収集したパーツの新しい名前での格納
+    ///
+    /// 先頭のパーツ (`root`) は `root_target` に対応付け、内容は格納しない。
+    /// 元のパーツのパスと新しいパスの対応を返す
+    fn store_copied_parts(
+        &mut self,
+        root_target: &str,
+        parts: Vec<(String, CopiedPart)>,
+    ) -> HashMap<String, String> {
+        let mut copied: HashMap<String, String> = HashMap::new();
+        let mut rels: Vec<(String, Xml)> = Vec::new();
+        for (index, (source, part)) in parts.into_iter().enumerate() {
+            let target: String = if index == 0 {
+                root_target.to_string()
+            } else {
+                let target: String = self.unique_part_name(&source);
+                match part.content {
+                    PartContent::Xml(mut xml) => {
+                        if source.starts_with(TABLES_PREFIX) {
+                            self.rename_copied_table(&mut xml);
+                        }
+                        self.store_xml(target.clone(), xml);
+                    }
+                    PartContent::Raw(contents) => {
+                        self.files.insert(target.clone(), contents);
+                    }
                 }
+                if let Some(content_type) = part.content_type {
+                    self.register_content_type(&target, content_type);
+                }
+                target
+            };
+            if let Some(xml) = part.rels {
+                rels.push((source.clone(), xml));
             }
+            copied.insert(source, target);
         }
 
-        if let Some(vba_project) = &self.vba_project {
-            if zip_writer
-                .start_file(VBA_PROJECT_FILENAME, *options)
-                .is_ok()
-            {
-                let _ = zip_writer.write_all(vba_project);
+        // リレーションシップの参照先の付け替え
+        for (source, mut xml) in rels {
+            let base_dir: &str = source.rsplit_once('/').map_or("", |(dir, _)| dir);
+            if let Some(relationships) = xml.elements.first_mut() {
+                for relationship in &mut relationships.children {
+                    let Some(old_target) = relationship.get_attribute("Target").cloned() else {
+                        continue;
+                    };
+                    if relationship
+                        .get_attribute("TargetMode")
+                        .is_some_and(|mode| mode == "External")
+                    {
+                        continue;
+                    }
+                    let Some(new_part) = copied.get(&resolve_target(base_dir, &old_target)) else {
+                        continue;
+                    };
+                    let new_name: &str = new_part.rsplit('/').next().unwrap_or_default();
+                    let new_target: String = match old_target.rsplit_once('/') {
+                        Some((dir, _)) => format!("{dir}/{new_name}"),
+                        None => new_name.to_string(),
+                    };
+                    relationship
+                        .attributes
+                        .insert("Target".to_string(), new_target);
+                }
             }
+            self.store_xml(rels_path(&copied[&source]), xml);
         }
+        copied
     }
 
-    /// `xl/workbook.xml` からのシートタグの取得
-    pub fn sheet_tags(&self) -> &[XmlElement] {
-        self.workbook
-            .elements
-            .first()
-            .and_then(|wb| wb.children.iter().find(|c| c.name == "sheets"))
-            .map_or(&[], |sheets| &sheets.children)
+    /// シートの範囲の定義された名前の一覧
+    fn local_defined_names(&self, sheet_name: &str) -> Vec<DefinedName> {
+        let Some(index) = self.sheetnames().iter().position(|n| n == sheet_name) else {
+            return Vec::new();
+        };
+        self.defined_names()
+            .values()
+            .into_iter()
+            .filter(|dn| dn.local_sheet_id == Some(index))
+            .collect()
     }
 
-    /// `xl/workbook.xml.rels` からのリレーションシップのリスト取得
-    pub fn get_relationships(&self) -> &[XmlElement] {
-        self.rels
-            .get("xl/_rels/workbook.xml.rels")
-            .and_then(|rels| rels.elements.first())
-            .map_or(&[], |r| &r.children)
-    }
+    /// 複製または取り込んだワークシートの末尾への追加
+    ///
+    /// `source` は元のシートの名前とパス。関連するパーツを格納し、グラフの系列と
+    /// シートの範囲の名前の参照を新しいシートに付け替える
+    fn add_copied_sheet(
+        &mut self,
+        source: (&str, &str),
+        title: String,
+        mut worksheet: Xml,
+        parts: Vec<(String, CopiedPart)>,
+        local_names: Vec<DefinedName>,
+    ) -> Sheet {
+        let (source_name, source_path) = source;
+        if let Some(ws) = worksheet.elements.first_mut()
+            && view::view_flag(ws, "tabSelected", false)
+        {
+            view::set_view_flag(ws, "tabSelected", false, false);
+        }
+        let target_path: String = self.unique_part_name(source_path);
+        let xml: Arc<Mutex<Xml>> = Arc::new(Mutex::new(worksheet));
+        self.worksheets
+            .insert_shared(target_path.clone(), xml.clone());
+        let copied: HashMap<String, String> = self.store_copied_parts(&target_path, parts);
 
-    /// シート名とそのパスのマップ取得
-    pub fn get_sheet_paths(&self) -> HashMap<String, String> {
-        let relationships: &[XmlElement] = self.get_relationships();
-        let sheet_paths: HashMap<String, String> = relationships
-            .iter()
-            .filter_map(|rel| {
-                let id: String = rel.attributes.get("Id")?.clone();
-                let target: String = rel.attributes.get("Target")?.clone();
-                Some((id, target))
-            })
-            .collect();
+        let rename = |f: &str| formula::rename_sheet_in_formula(f, source_name, &title);
+        for target in copied.values() {
+            if let Some(chart) = self.charts.get(target)
+                && let Some(chart) = chart.lock().unwrap().elements.first_mut()
+            {
+                Sheet::map_formulas(chart, &rename);
+            }
+        }
 
-        self.sheet_tags()
-            .iter()
-            .filter_map(|tag| {
-                let name: String = tag.attributes.get("name")?.clone();
-                let r_id: &String = tag.attributes.get("r:id")?;
-                let path: &String = sheet_paths.get(r_id)?;
-                let trimmed_path: &str = path.trim_start_matches("/xl/").trim_start_matches("xl/");
-                Some((name, format!("xl/{trimmed_path}")))
-            })
-            .collect()
+        // ワークブックへの登録
+        let index: usize = self.sheet_tags().len();
+        let sheet_id: usize = self.next_sheet_id();
+        let r_id: String = Self::next_relationship_id(&self.get_relationships());
+        self.add_sheet_to_workbook_xml(&title, sheet_id, &r_id, index);
+        self.add_sheet_relationship(&r_id, &target_path);
+
+        // 元のシートの範囲の名前 (印刷範囲など) の複製
+        let parts: BookParts = self.parts();
+        for defined_name in local_names {
+            let value: String = rename(&defined_name.value);
+            parts.set_defined_name(&defined_name.name, Some(index), Some(value));
+        }
+
+        Sheet::new(
+            target_path,
+            xml,
+            self.shared_strings.clone(),
+            self.shared_strings_map.clone(),
+            self.styles.clone(),
+            self.metadata.clone(),
+            parts,
+        )
     }
 
-    /// 名前によるシートの取得
-    pub fn get_sheet_by_name(&self, name: &str) -> Option<Sheet> {
-        let sheet_paths: HashMap<String, String> = self.get_sheet_paths();
-        sheet_paths.get(name).and_then(|sheet_path| {
-            self.worksheets.get(sheet_path).map(|xml| {
-                Sheet::new(
-                    name.to_string(),
-                    xml.clone(),
-                    self.shared_strings.clone(),
-                    self.shared_strings_map.clone(),
-                    self.styles.clone(),
-                )
-            })
-        })
+    /// 複製したテーブルの名前 (重複する場合) とIDの重複しないものへの変更
+    fn rename_copied_table(&self, xml: &mut Xml) {
+        let mut names: HashSet<String> = HashSet::new();
+        for (_, table) in self.tables.entries() {
+            if let Some(table) = table.lock().unwrap().elements.first() {
+                for key in ["name", "displayName"] {
+                    if let Some(name) = table.get_attribute(key) {
+                        names.insert(name.to_lowercase());
+                    }
+                }
+            }
+        }
+        let table_id: usize = self.next_table_id();
+        let Some(table) = xml.elements.first_mut() else {
+            return;
+        };
+        let name: String = table
+            .get_attribute("displayName")
+            .or_else(|| table.get_attribute("name"))
+            .cloned()
+            .unwrap_or_else(|| "Table".to_string());
+        let new_name: String = if names.contains(&name.to_lowercase()) {
+            (2..)
+                .map(|n: usize| format!("{name}_{n}"))
+                .find(|candidate| !names.contains(&candidate.to_lowercase()))
+                .expect("unused table name")
+        } else {
+            name
+        };
+        table
+            .attributes
+            .insert("id".to_string(), table_id.to_string());
+        table
+            .attributes
+            .insert("name".to_string(), new_name.clone());
+        table.attributes.insert("displayName".to_string(), new_name);
     }
 
     /// テーブルXMLの作成
@@ -615,51 +2515,53 @@ impl Book {
             .insert(table_filename.to_string(), new_table_xml);
     }
 
-    /// ワークシートへのテーブルパーツの追加
-    fn add_table_parts_to_worksheet(&mut self, sheet_name: &str, table_id: usize) {
+    /// ワークシートへのテーブルパーツの追加 (既存の `tableParts` がある場合は末尾に追加)
+    fn add_table_parts_to_worksheet(&mut self, sheet_name: &str, r_id: &str) {
         let sheet_path: String = self
             .get_sheet_paths()
             .get(sheet_name)
             .unwrap_or_else(|| panic!("Sheet {sheet_name} not found"))
             .clone();
-        if let Some(sheet_xml_mutex) = self.worksheets.get_mut(&sheet_path) {
+        if let Some(sheet_xml_mutex) = self.worksheets.get(&sheet_path) {
             let mut sheet_xml: MutexGuard<Xml> = sheet_xml_mutex
                 .lock()
                 .unwrap_or_else(|_| panic!("Failed to lock sheet xml for {sheet_name}"));
             if let Some(worksheet) = sheet_xml.elements.get_mut(0) {
-                let mut attributes: HashMap<String, String> = HashMap::with_capacity(1);
-                attributes.insert("count".to_string(), "1".to_string());
-
-                let mut table_part_attributes: HashMap<String, String> = HashMap::with_capacity(1);
-                table_part_attributes.insert("r:id".to_string(), format!("rId{table_id}"));
-
-                let table_part: XmlElement = XmlElement {
-                    name: "tablePart".to_string(),
-                    attributes: table_part_attributes,
-                    ..Default::default()
-                };
-
-                worksheet.children.push(XmlElement {
-                    name: "tableParts".to_string(),
-                    attributes,
-                    children: vec![table_part],
-                    text: None,
-                });
+                if !worksheet.children.iter().any(|c| c.name == "tableParts") {
+                    let position: usize = worksheet
+                        .children
+                        .iter()
+                        .position(|c| c.name == "extLst")
+                        .unwrap_or(worksheet.children.len());
+                    worksheet
+                        .children
+                        .insert(position, XmlElement::new("tableParts"));
+                }
+                if let Some(table_parts) = worksheet.find_element_mut("tableParts") {
+                    let mut table_part: XmlElement = XmlElement::new("tablePart");
+                    table_part
+                        .attributes
+                        .insert("r:id".to_string(), r_id.to_string());
+                    table_parts.children.push(table_part);
+                    let count: usize = table_parts.children.len();
+                    table_parts
+                        .attributes
+                        .insert("count".to_string(), count.to_string());
+                }
             }
         }
     }
 
     /// ワークシートのリレーションシップへのテーブルリレーションシップの追加
-    fn add_table_relationship(&mut self, sheet_name: &str, table_id: usize) {
+    ///
+    /// 追加したリレーションシップの `rId` を返却
+    fn add_table_relationship(&mut self, sheet_name: &str, table_filename: &str) -> String {
         let sheet_paths: HashMap<String, String> = self.get_sheet_paths();
         let sheet_path: &String = sheet_paths
             .get(sheet_name)
             .unwrap_or_else(|| panic!("Sheet path not found for sheet {sheet_name}"));
-        let rels_filename: String = format!(
-            "xl/worksheets/_rels/{}.rels",
-            sheet_path.split('/').next_back().unwrap_or_default()
-        );
-        let rels: &mut Xml = self.sheet_rels.entry(rels_filename).or_insert_with(|| {
+        let rels_filename: String = rels_path(sheet_path);
+        let rels: Arc<Mutex<Xml>> = self.sheet_rels.get_or_insert_with(&rels_filename, || {
             Xml::new(
                 r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
@@ -667,6 +2569,7 @@ impl Book {
             )
             .unwrap_or_default()
         });
+        let mut rels: MutexGuard<Xml> = rels.lock().unwrap();
 
         if rels.elements.is_empty() {
             rels.elements.push(XmlElement {
@@ -674,25 +2577,28 @@ impl Book {
                 ..Default::default()
             });
         }
-        if let Some(relationships) = rels.elements.get_mut(0) {
-            let mut attributes: HashMap<String, String> = HashMap::with_capacity(3);
-            attributes.insert("Id".to_string(), format!("rId{table_id}"));
-            attributes.insert(
-                "Type".to_string(),
-                "http://schemas.openxmlformats.org/officeDocument/2006/relationships/table"
-                    .to_string(),
-            );
-            attributes.insert(
-                "Target".to_string(),
-                format!("../tables/table{table_id}.xml"),
-            );
+        let relationships: &mut XmlElement = &mut rels.elements[0];
+        let r_id: String = Self::next_relationship_id(&relationships.children);
+        let mut attributes: HashMap<String, String> = HashMap::with_capacity(3);
+        attributes.insert("Id".to_string(), r_id.clone());
+        attributes.insert(
+            "Type".to_string(),
+            "http://schemas.openxmlformats.org/officeDocument/2006/relationships/table".to_string(),
+        );
+        attributes.insert(
+            "Target".to_string(),
+            format!(
+                "../tables/{}",
+                table_filename.rsplit('/').next().unwrap_or_default()
+            ),
+        );
 
-            relationships.children.push(XmlElement {
-                name: "Relationship".to_string(),
-                attributes,
-                ..Default::default()
-            });
-        }
+        relationships.children.push(XmlElement {
+            name: "Relationship".to_string(),
+            attributes,
+            ..Default::default()
+        });
+        r_id
     }
 
     /// workbook.xml へのシートの追加
@@ -705,6 +2611,8 @@ impl Book {
     ) {
         if let Some(sheets_tag) = self
             .workbook
+            .lock()
+            .unwrap()
             .elements
             .first_mut()
             .and_then(|wb| wb.children.iter_mut().find(|x| x.name == "sheets"))
@@ -733,11 +2641,9 @@ impl Book {
     }
 
     /// ワークブックのリレーションシップへのシートリレーションシップの追加
-    fn add_sheet_relationship(&mut self, r_id: &str, sheet_id: usize) {
-        if let Some(relationships_tag) = self
-            .rels
-            .get_mut("xl/_rels/workbook.xml.rels")
-            .and_then(|rels| rels.elements.first_mut())
+    fn add_sheet_relationship(&mut self, r_id: &str, sheet_path: &str) {
+        if let Some(rels) = self.rels.get(WORKBOOK_RELS_FILENAME)
+            && let Some(relationships_tag) = rels.lock().unwrap().elements.first_mut()
         {
             let mut relationship_element: XmlElement = XmlElement {
                 name: "Relationship".to_string(),
@@ -754,7 +2660,10 @@ impl Book {
             );
             relationship_element.attributes.insert(
                 "Target".to_string(),
-                format!("worksheets/sheet{sheet_id}.xml"),
+                sheet_path
+                    .strip_prefix("xl/")
+                    .unwrap_or(sheet_path)
+                    .to_string(),
             );
             relationships_tag.children.push(relationship_element);
         }
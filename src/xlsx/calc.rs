@@ -0,0 +1,1663 @@
+use crate::cell::Cell;
+use crate::formula::{
+    self, Area, BinaryOp, CellRef, Coord, Expr, MAX_COL, MAX_ROW, Reference, UnaryOp,
+    parse_address, parse_range, to_address,
+};
+use crate::xml::{Xml, XmlElement};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, Timelike};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use thiserror::Error;
+
+/// 数式の計算エラー
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum CalcError {
+    #[error("Circular reference: {0}")]
+    CircularReference(String),
+}
+
+impl From<CalcError> for PyErr {
+    fn from(err: CalcError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// 計算中の値
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    /// エラー値 (例: "#N/A")
+    Error(String),
+    /// 空のセル
+    Empty,
+    /// 範囲または配列 (行ごとの要素)
+    Array(Vec<Vec<Value>>),
+}
+
+/// 計算を中断するエラー値
+type Res<T> = Result<T, String>;
+
+const DIV0: &str = "#DIV/0!";
+const NA: &str = "#N/A";
+const NAME: &str = "#NAME?";
+const NUM: &str = "#NUM!";
+const REF: &str = "#REF!";
+const VALUE: &str = "#VALUE!";
+
+/// セルの位置 (シートのインデックス, 行番号, 列番号)
+type CellKey = (usize, u32, u32);
+
+/// 矩形の範囲 (上, 左, 下, 右)
+type Rect = (u32, u32, u32, u32);
+
+/// 数式セルごとのそのセルを参照する数式セル
+type Dependents = HashMap<CellKey, Vec<CellKey>>;
+
+/// 数式の評価位置
+#[derive(Clone, Copy)]
+struct Ctx {
+    sheet: usize,
+    row: u32,
+    col: u32,
+}
+
+/// 数式セル
+struct FormulaCell {
+    expr: Expr,
+    /// 配列数式の範囲
+    array_range: Option<Rect>,
+}
+
+/// シートごとの数式セルの索引 (依存関係の解析用)
+#[derive(Default)]
+struct FormulaIndex {
+    /// 単一セルの数式の位置 (行番号, 列番号)
+    by_row: BTreeSet<(u32, u32)>,
+    /// 単一セルの数式の位置 (列番号, 行番号)
+    by_col: BTreeSet<(u32, u32)>,
+    /// 配列数式のセルと範囲
+    arrays: Vec<(CellKey, Rect)>,
+}
+
+impl FormulaIndex {
+    /// 範囲と重なる数式セルの収集
+    ///
+    /// 行数と列数の少ない方の向きの索引で範囲内の行 (列) だけを走査する
+    fn overlapping(&self, sheet: usize, area: Rect, found: &mut HashSet<CellKey>) {
+        let (top, left, bottom, right) = area;
+        if bottom.saturating_sub(top) <= right.saturating_sub(left) {
+            found.extend(
+                self.by_row
+                    .range((top, left)..=(bottom, right))
+                    .filter(|(_, col)| (left..=right).contains(col))
+                    .map(|&(row, col)| (sheet, row, col)),
+            );
+        } else {
+            found.extend(
+                self.by_col
+                    .range((left, top)..=(right, bottom))
+                    .filter(|(_, row)| (top..=bottom).contains(row))
+                    .map(|&(col, row)| (sheet, row, col)),
+            );
+        }
+        found.extend(
+            self.arrays
+                .iter()
+                .filter(|(_, (t, l, b, r))| top <= *b && *t <= bottom && left <= *r && *l <= right)
+                .map(|(key, _)| *key),
+        );
+    }
+}
+
+/// ワークブック内の数式の計算
+///
+/// 全シートのセルの値と数式を読み込み、依存関係の順に数式を評価する。
+/// 対応していない関数や参照 (構造化参照、外部ブック) を使う数式とそれに依存する数式は評価しない
+pub struct Calculator {
+    sheet_names: Vec<String>,
+    values: HashMap<CellKey, Value>,
+    formulas: HashMap<CellKey, FormulaCell>,
+    /// 定義された名前 (シートスコープの場合はシートのインデックス付き, 名前は大文字)
+    names: HashMap<(Option<usize>, String), Expr>,
+    /// シートごとの使用範囲の右下 (行番号, 列番号)
+    extents: Vec<(u32, u32)>,
+    /// 評価中の数式が対応していない関数や参照を使ったかどうか
+    unsupported: std::cell::Cell<bool>,
+    /// 評価しなかった数式セル
+    skipped: Vec<CellKey>,
+}
+
+impl Calculator {
+    /// シートのXML, 共有文字列, 定義された名前からの作成
+    ///
+    /// `defined_names` は (名前, シートのインデックス, 数式) の組
+    pub fn new(
+        sheets: &[(String, &Xml)],
+        shared_strings: &Xml,
+        defined_names: &[(String, Option<usize>, String)],
+    ) -> Self {
+        let strings: Vec<String> = shared_strings
+            .elements
+            .first()
+            .map(|sst| sst.children.iter().map(shared_string_text).collect())
+            .unwrap_or_default();
+        let mut calculator: Calculator = Calculator {
+            sheet_names: sheets.iter().map(|(name, _)| name.clone()).collect(),
+            values: HashMap::new(),
+            formulas: HashMap::new(),
+            names: HashMap::new(),
+            extents: vec![(1, 1); sheets.len()],
+            unsupported: std::cell::Cell::new(false),
+            skipped: Vec::new(),
+        };
+        for (index, (_, xml)) in sheets.iter().enumerate() {
+            calculator.load_sheet(index, xml, &strings);
+        }
+        for (name, scope, text) in defined_names {
+            if let Ok(expr) = formula::parse(text) {
+                calculator
+                    .names
+                    .insert((*scope, name.to_ascii_uppercase()), expr);
+            }
+        }
+        calculator
+    }
+
+    /// ワークシートのセルの読み込み
+    fn load_sheet(&mut self, index: usize, xml: &Xml, strings: &[String]) {
+        let Some(sheet_data) = xml
+            .elements
+            .first()
+            .and_then(|ws| ws.find_element("sheetData"))
+        else {
+            return;
+        };
+        for cell in sheet_data
+            .children
+            .iter()
+            .flat_map(|row| row.children.iter().filter(|c| c.name == "c"))
+        {
+            let Some((row, col)) = cell.get_attribute("r").and_then(|r| parse_address(r)) else {
+                continue;
+            };
+            let extent: &mut (u32, u32) = &mut self.extents[index];
+            *extent = (extent.0.max(row), extent.1.max(col));
+            self.values
+                .insert((index, row, col), cell_value(cell, strings));
+            let Some(f) = cell.find_element("f") else {
+                continue;
+            };
+            let Some(expr) = f.text.as_deref().and_then(|text| formula::parse(text).ok()) else {
+                continue;
+            };
+            let array_range: Option<Rect> = (f.get_attribute("t").map(String::as_str)
+                == Some("array"))
+            .then(|| f.get_attribute("ref").and_then(|r| parse_range(r)))
+            .flatten()
+            .map(|((top, left), (bottom, right))| (top, left, bottom, right));
+            if let Some((_, _, bottom, right)) = array_range {
+                let extent: &mut (u32, u32) = &mut self.extents[index];
+                *extent = (extent.0.max(bottom), extent.1.max(right));
+            }
+            self.formulas
+                .insert((index, row, col), FormulaCell { expr, array_range });
+        }
+    }
+
+    /// 依存関係の順に並べた数式セルと、各数式セルを参照する数式セルの取得
+    ///
+    /// 循環参照がある場合は循環に含まれるセルを列挙したエラー
+    fn evaluation_order(&self) -> Result<(Vec<CellKey>, Dependents), CalcError> {
+        let keys: Vec<CellKey> = self.formulas.keys().copied().collect();
+        let mut dependents: Dependents = HashMap::new();
+        let mut in_degree: HashMap<CellKey, usize> = keys.iter().map(|k| (*k, 0)).collect();
+        let mut index: HashMap<usize, FormulaIndex> = HashMap::new();
+        for key in &keys {
+            let sheet: &mut FormulaIndex = index.entry(key.0).or_default();
+            match self.formulas[key].array_range {
+                Some(range) => sheet.arrays.push((*key, range)),
+                None => {
+                    sheet.by_row.insert((key.1, key.2));
+                    sheet.by_col.insert((key.2, key.1));
+                }
+            }
+        }
+        for key in &keys {
+            let ctx: Ctx = Ctx {
+                sheet: key.0,
+                row: key.1,
+                col: key.2,
+            };
+            let mut areas: Vec<(usize, Rect)> = Vec::new();
+            self.collect_areas(
+                &self.formulas[key].expr,
+                ctx,
+                &mut areas,
+                &mut HashSet::new(),
+            );
+            let mut precedents: HashSet<CellKey> = HashSet::new();
+            for (sheet, area) in areas {
+                if let Some(sheet_index) = index.get(&sheet) {
+                    sheet_index.overlapping(sheet, area, &mut precedents);
+                }
+            }
+            for precedent in precedents {
+                dependents.entry(precedent).or_default().push(*key);
+                *in_degree.entry(*key).or_default() += 1;
+            }
+        }
+
+        let mut queue: VecDeque<CellKey> = keys
+            .iter()
+            .filter(|k| in_degree[*k] == 0)
+            .copied()
+            .collect();
+        let mut order: Vec<CellKey> = Vec::new();
+        while let Some(key) = queue.pop_front() {
+            order.push(key);
+            for dependent in dependents.get(&key).into_iter().flatten() {
+                let degree: &mut usize = in_degree.entry(*dependent).or_default();
+                *degree -= 1;
+                if *degree == 0 {
+                    queue.push_back(*dependent);
+                }
+            }
+        }
+        if order.len() < keys.len() {
+            let mut cyclic: Vec<CellKey> = keys.into_iter().filter(|k| in_degree[k] > 0).collect();
+            cyclic.sort();
+            let names: Vec<String> = cyclic
+                .iter()
+                .map(|(sheet, row, col)| {
+                    format!(
+                        "{}!{}",
+                        formula::quote_sheet_name(&self.sheet_names[*sheet]),
+                        to_address(*row, *col)
+                    )
+                })
+                .collect();
+            return Err(CalcError::CircularReference(names.join(", ")));
+        }
+        Ok((order, dependents))
+    }
+
+    /// 数式が参照する範囲の収集 (定義された名前の参照先を含む)
+    fn collect_areas(
+        &self,
+        expr: &Expr,
+        ctx: Ctx,
+        areas: &mut Vec<(usize, Rect)>,
+        visited: &mut HashSet<String>,
+    ) {
+        match expr {
+            Expr::Reference(reference) => {
+                areas.extend(self.resolve(reference, ctx).unwrap_or_default());
+            }
+            Expr::Name { name, .. } => {
+                if visited.insert(name.to_ascii_uppercase())
+                    && let Some(target) = self.lookup_name(name, ctx)
+                {
+                    self.collect_areas(target, ctx, areas, visited);
+                }
+            }
+            Expr::Function { args, .. } => {
+                for arg in args {
+                    self.collect_areas(arg, ctx, areas, visited);
+                }
+            }
+            Expr::Unary { operand, .. } | Expr::Percent(operand) | Expr::Group(operand) => {
+                self.collect_areas(operand, ctx, areas, visited);
+            }
+            Expr::Binary { left, right, .. } => {
+                self.collect_areas(left, ctx, areas, visited);
+                self.collect_areas(right, ctx, areas, visited);
+            }
+            _ => {}
+        }
+    }
+
+    /// 全数式の評価と結果の取得
+    ///
+    /// 配列数式は範囲内の各セルの値を返却。
+    /// 対応していない関数や参照を使う数式とそれに依存する数式は結果に含めず、[`Calculator::skipped`] に記録する
+    pub fn calculate(&mut self) -> Result<Vec<(CellKey, Value)>, CalcError> {
+        let (order, dependents) = self.evaluation_order()?;
+        let mut skipped: HashSet<CellKey> = HashSet::new();
+        let mut results: Vec<(CellKey, Value)> = Vec::new();
+        for key in order {
+            let ctx: Ctx = Ctx {
+                sheet: key.0,
+                row: key.1,
+                col: key.2,
+            };
+            let formula: &FormulaCell = &self.formulas[&key];
+            let value: Option<Value> = if skipped.contains(&key) {
+                None
+            } else {
+                self.unsupported.set(false);
+                let value: Value = self.eval(&formula.expr, ctx);
+                (!self.unsupported.get()).then_some(value)
+            };
+            let Some(value) = value else {
+                skipped.insert(key);
+                skipped.extend(dependents.get(&key).into_iter().flatten().copied());
+                self.skipped.push(key);
+                continue;
+            };
+            let cells: Vec<(CellKey, Value)> = match formula.array_range {
+                Some((top, left, bottom, right)) => (top..=bottom)
+                    .flat_map(|row| (left..=right).map(move |col| (row, col)))
+                    .map(|(row, col)| {
+                        let element: Value = match &value {
+                            Value::Array(rows) => {
+                                array_element(rows, (row - top) as usize, (col - left) as usize)
+                            }
+                            scalar => scalar.clone(),
+                        };
+                        ((key.0, row, col), finalize(element))
+                    })
+                    .collect(),
+                None => vec![(key, finalize(value))],
+            };
+            for (cell_key, cell_value) in cells {
+                self.values.insert(cell_key, cell_value.clone());
+                results.push((cell_key, cell_value));
+            }
+        }
+        Ok(results)
+    }
+
+    /// 評価しなかった数式セル (対応していない関数や参照を使うもの、およびそれに依存するもの)
+    pub fn skipped(&self) -> &[CellKey] {
+        &self.skipped
+    }
+
+    /// 名前の参照先の検索 (シートスコープを優先)
+    fn lookup_name(&self, name: &str, ctx: Ctx) -> Option<&Expr> {
+        let upper: String = name.to_ascii_uppercase();
+        self.names
+            .get(&(Some(ctx.sheet), upper.clone()))
+            .or_else(|| self.names.get(&(None, upper)))
+    }
+
+    /// 参照のシートと範囲への解決
+    fn resolve(&self, reference: &Reference, ctx: Ctx) -> Res<Vec<(usize, Rect)>> {
+        if reference.workbook.is_some() {
+            self.unsupported.set(true);
+            return Err(REF.to_string());
+        }
+        let find = |name: &str| {
+            self.sheet_names
+                .iter()
+                .position(|s| s.to_lowercase() == name.to_lowercase())
+                .ok_or_else(|| REF.to_string())
+        };
+        let first: usize = match &reference.sheet {
+            Some(sheet) => find(sheet)?,
+            None => ctx.sheet,
+        };
+        let last: usize = match &reference.last_sheet {
+            Some(sheet) => find(sheet)?,
+            None => first,
+        };
+        let coord = |coord: &Coord, base: u32| {
+            if reference.r1c1 && !coord.absolute {
+                base as i64 + coord.index
+            } else {
+                coord.index
+            }
+        };
+        let cell = |cell: &CellRef| (coord(&cell.row, ctx.row), coord(&cell.col, ctx.col));
+        let (top, left, bottom, right) = match &reference.area {
+            Area::Cell(c) => {
+                let (row, col) = cell(c);
+                (row, col, row, col)
+            }
+            Area::Range(start, end) => {
+                let (r1, c1) = cell(start);
+                let (r2, c2) = cell(end);
+                (r1.min(r2), c1.min(c2), r1.max(r2), c1.max(c2))
+            }
+            Area::Columns(start, end) => {
+                let (c1, c2) = (coord(start, ctx.col), coord(end, ctx.col));
+                (1, c1.min(c2), MAX_ROW as i64, c1.max(c2))
+            }
+            Area::Rows(start, end) => {
+                let (r1, r2) = (coord(start, ctx.row), coord(end, ctx.row));
+                (r1.min(r2), 1, r1.max(r2), MAX_COL as i64)
+            }
+        };
+        if top < 1 || left < 1 || bottom > MAX_ROW as i64 || right > MAX_COL as i64 {
+            return Err(REF.to_string());
+        }
+        let rect: Rect = (top as u32, left as u32, bottom as u32, right as u32);
+        Ok((first.min(last)..=first.max(last))
+            .map(|sheet| (sheet, rect))
+            .collect())
+    }
+
+    /// 範囲の値の配列としての取得
+    ///
+    /// 行全体・列全体の参照はシートの使用範囲までに制限
+    fn range_values(&self, sheet: usize, rect: Rect) -> Value {
+        let (top, left, bottom, right) = rect;
+        let (max_row, max_col) = self.extents[sheet];
+        let bottom: u32 = if bottom == MAX_ROW {
+            max_row.max(top)
+        } else {
+            bottom
+        };
+        let right: u32 = if right == MAX_COL {
+            max_col.max(left)
+        } else {
+            right
+        };
+        Value::Array(
+            (top..=bottom)
+                .map(|row| {
+                    (left..=right)
+                        .map(|col| {
+                            self.values
+                                .get(&(sheet, row, col))
+                                .cloned()
+                                .unwrap_or(Value::Empty)
+                        })
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    /// 参照の値の取得 (単一セルでも配列として返却)
+    fn reference_values(&self, reference: &Reference, ctx: Ctx) -> Value {
+        match self.resolve(reference, ctx) {
+            Ok(areas) => {
+                let mut rows: Vec<Vec<Value>> = Vec::new();
+                for (sheet, rect) in areas {
+                    if let Value::Array(area_rows) = self.range_values(sheet, rect) {
+                        rows.extend(area_rows);
+                    }
+                }
+                Value::Array(rows)
+            }
+            Err(code) => Value::Error(code),
+        }
+    }
+
+    /// 範囲として扱う引数の評価
+    ///
+    /// 参照は単一セルでも配列として、それ以外は評価結果をそのまま返却
+    fn eval_range(&self, expr: &Expr, ctx: Ctx) -> Value {
+        match expr {
+            Expr::Reference(reference) => self.reference_values(reference, ctx),
+            Expr::Name { name, .. } => match self.lookup_name(name, ctx) {
+                Some(target) => self.eval_range(target, ctx),
+                None => Value::Error(NAME.to_string()),
+            },
+            Expr::Group(inner) => self.eval_range(inner, ctx),
+            Expr::Binary {
+                op: BinaryOp::Range | BinaryOp::Intersection,
+                ..
+            } => match self.reference_area(expr, ctx) {
+                Ok((sheet, rect)) => self.range_values(sheet, rect),
+                Err(code) => Value::Error(code),
+            },
+            _ => self.eval(expr, ctx),
+        }
+    }
+
+    /// 参照演算子 (`:` と空白) で組み立てた範囲の解決
+    fn reference_area(&self, expr: &Expr, ctx: Ctx) -> Res<(usize, Rect)> {
+        match expr {
+            Expr::Reference(reference) => {
+                let areas: Vec<(usize, Rect)> = self.resolve(reference, ctx)?;
+                match areas.as_slice() {
+                    [area] => Ok(*area),
+                    _ => Err(REF.to_string()),
+                }
+            }
+            Expr::Group(inner) => self.reference_area(inner, ctx),
+            Expr::Name { name, .. } => {
+                let target: &Expr = self.lookup_name(name, ctx).ok_or(NAME.to_string())?;
+                self.reference_area(target, ctx)
+            }
+            Expr::Binary { op, left, right } => {
+                let (s1, (t1, l1, b1, r1)) = self.reference_area(left, ctx)?;
+                let (s2, (t2, l2, b2, r2)) = self.reference_area(right, ctx)?;
+                if s1 != s2 {
+                    return Err(VALUE.to_string());
+                }
+                match op {
+                    BinaryOp::Range => Ok((s1, (t1.min(t2), l1.min(l2), b1.max(b2), r1.max(r2)))),
+                    BinaryOp::Intersection => {
+                        let rect: Rect = (t1.max(t2), l1.max(l2), b1.min(b2), r1.min(r2));
+                        if rect.0 > rect.2 || rect.1 > rect.3 {
+                            Err("#NULL!".to_string())
+                        } else {
+                            Ok((s1, rect))
+                        }
+                    }
+                    _ => Err(VALUE.to_string()),
+                }
+            }
+            _ => Err(VALUE.to_string()),
+        }
+    }
+
+    /// 式の評価
+    fn eval(&self, expr: &Expr, ctx: Ctx) -> Value {
+        match expr {
+            Expr::Number(n) => Value::Number(*n),
+            Expr::Text(text) => Value::Text(text.clone()),
+            Expr::Bool(b) => Value::Bool(*b),
+            Expr::Error(code) => Value::Error(code.clone()),
+            Expr::Missing => Value::Empty,
+            Expr::Reference(reference) => match self.reference_values(reference, ctx) {
+                Value::Array(rows) if rows.len() == 1 && rows[0].len() == 1 => rows[0][0].clone(),
+                other => other,
+            },
+            Expr::Name { name, .. } => match self.lookup_name(name, ctx) {
+                Some(target) => self.eval(target, ctx),
+                None => Value::Error(NAME.to_string()),
+            },
+            Expr::StructuredReference { .. } => {
+                self.unsupported.set(true);
+                Value::Error(REF.to_string())
+            }
+            Expr::Group(inner) => self.eval(inner, ctx),
+            Expr::Array(rows) => Value::Array(
+                rows.iter()
+                    .map(|row| row.iter().map(|v| self.eval(v, ctx)).collect())
+                    .collect(),
+            ),
+            Expr::Unary { op, operand } => {
+                let value: Value = self.eval(operand, ctx);
+                match op {
+                    UnaryOp::Plus => value,
+                    UnaryOp::Minus => map_value(value, |v| Ok(Value::Number(-to_number(&v)?))),
+                    UnaryOp::ImplicitIntersection => match value {
+                        Value::Array(rows) => array_element(&rows, 0, 0),
+                        scalar => scalar,
+                    },
+                }
+            }
+            Expr::Percent(operand) => map_value(self.eval(operand, ctx), |v| {
+                Ok(Value::Number(to_number(&v)? / 100.0))
+            }),
+            Expr::Binary {
+                op: BinaryOp::Range | BinaryOp::Intersection,
+                ..
+            } => match self.eval_range(expr, ctx) {
+                Value::Array(rows) if rows.len() == 1 && rows[0].len() == 1 => rows[0][0].clone(),
+                other => other,
+            },
+            Expr::Binary {
+                op: BinaryOp::Union,
+                left,
+                right,
+            } => {
+                let values: Vec<Value> = [left, right]
+                    .iter()
+                    .flat_map(|e| flatten(&self.eval_range(e, ctx)))
+                    .collect();
+                Value::Array(vec![values])
+            }
+            Expr::Binary { op, left, right } => {
+                let left: Value = self.eval(left, ctx);
+                let right: Value = self.eval(right, ctx);
+                broadcast(left, right, |a, b| binary(*op, &a, &b))
+            }
+            Expr::Function { name, args } => {
+                let upper: String = name.to_ascii_uppercase();
+                let name: &str = upper
+                    .trim_start_matches("_XLFN.")
+                    .trim_start_matches("_XLWS.");
+                self.call(name, args, ctx).unwrap_or_else(Value::Error)
+            }
+        }
+    }
+
+    /// 関数の呼び出し
+    fn call(&self, name: &str, args: &[Expr], ctx: Ctx) -> Res<Value> {
+        let arg = |i: usize| args.get(i).map_or(Value::Empty, |e| self.eval(e, ctx));
+        let has = |i: usize| args.get(i).is_some_and(|e| *e != Expr::Missing);
+        let number = |i: usize| to_number(&scalar(arg(i))?);
+        let text = |i: usize| to_text(&scalar(arg(i))?);
+        let integer = |i: usize| number(i).map(f64::trunc);
+        let range = |i: usize| {
+            args.get(i)
+                .map_or(Value::Empty, |e| self.eval_range(e, ctx))
+        };
+        let all_values = || -> Res<Vec<Value>> {
+            let mut values: Vec<Value> = Vec::new();
+            for expr in args {
+                match self.eval_range(expr, ctx) {
+                    Value::Array(rows) => values.extend(
+                        rows.into_iter()
+                            .flatten()
+                            .filter(|v| matches!(v, Value::Number(_) | Value::Error(_))),
+                    ),
+                    Value::Empty => {}
+                    scalar => values.push(Value::Number(to_number(&scalar)?)),
+                }
+            }
+            Ok(values)
+        };
+        let numbers = || -> Res<Vec<f64>> {
+            all_values()?
+                .iter()
+                .map(to_number)
+                .collect::<Res<Vec<f64>>>()
+        };
+
+        Ok(match name {
+            // 集計
+            "SUM" => Value::Number(numbers()?.iter().sum()),
+            "PRODUCT" => Value::Number(numbers()?.iter().product()),
+            "AVERAGE" => {
+                let numbers: Vec<f64> = numbers()?;
+                if numbers.is_empty() {
+                    return Err(DIV0.to_string());
+                }
+                Value::Number(numbers.iter().sum::<f64>() / numbers.len() as f64)
+            }
+            "MIN" => Value::Number(numbers()?.into_iter().reduce(f64::min).unwrap_or(0.0)),
+            "MAX" => Value::Number(numbers()?.into_iter().reduce(f64::max).unwrap_or(0.0)),
+            "COUNT" => Value::Number(
+                args.iter()
+                    .flat_map(|e| flatten(&self.eval_range(e, ctx)))
+                    .filter(|v| matches!(v, Value::Number(_)))
+                    .count() as f64,
+            ),
+            "COUNTA" => Value::Number(
+                args.iter()
+                    .flat_map(|e| flatten(&self.eval_range(e, ctx)))
+                    .filter(|v| *v != Value::Empty)
+                    .count() as f64,
+            ),
+            "COUNTBLANK" => Value::Number(
+                flatten(&range(0))
+                    .iter()
+                    .filter(|v| matches!(v, Value::Empty) || **v == Value::Text(String::new()))
+                    .count() as f64,
+            ),
+            "SUMPRODUCT" => {
+                let arrays: Vec<Vec<Value>> = args
+                    .iter()
+                    .map(|e| flatten(&self.eval_range(e, ctx)))
+                    .collect();
+                let len: usize = arrays.first().map_or(0, Vec::len);
+                if arrays.iter().any(|a| a.len() != len) {
+                    return Err(VALUE.to_string());
+                }
+                let mut total: f64 = 0.0;
+                for i in 0..len {
+                    let mut product: f64 = 1.0;
+                    for array in &arrays {
+                        product *= match &array[i] {
+                            Value::Number(n) => *n,
+                            Value::Error(code) => return Err(code.clone()),
+                            _ => 0.0,
+                        };
+                    }
+                    total += product;
+                }
+                Value::Number(total)
+            }
+            "COUNTIF" | "COUNTIFS" | "SUMIF" | "SUMIFS" | "AVERAGEIF" | "AVERAGEIFS" => {
+                self.conditional_aggregate(name, args, ctx)?
+            }
+
+            // 数学
+            "ABS" => Value::Number(number(0)?.abs()),
+            "INT" => Value::Number(number(0)?.floor()),
+            "SQRT" => {
+                let n: f64 = number(0)?;
+                if n < 0.0 {
+                    return Err(NUM.to_string());
+                }
+                Value::Number(n.sqrt())
+            }
+            "POWER" => Value::Number(number(0)?.powf(number(1)?)),
+            "MOD" => {
+                let (n, d) = (number(0)?, number(1)?);
+                if d == 0.0 {
+                    return Err(DIV0.to_string());
+                }
+                Value::Number(n - d * (n / d).floor())
+            }
+            "ROUND" | "ROUNDUP" | "ROUNDDOWN" => {
+                let digits: i32 = if has(1) { integer(1)? as i32 } else { 0 };
+                let factor: f64 = 10f64.powi(digits);
+                let scaled: f64 = number(0)? * factor;
+                // 2進数の誤差で 2.675 が 2.67 にならないように補正
+                let scaled: f64 = (scaled * 1e9).round() / 1e9;
+                let rounded: f64 = match name {
+                    "ROUND" => scaled.abs().round() * scaled.signum(),
+                    "ROUNDUP" => scaled.abs().ceil() * scaled.signum(),
+                    _ => scaled.trunc(),
+                };
+                Value::Number(rounded / factor)
+            }
+
+            // 論理
+            "TRUE" => Value::Bool(true),
+            "FALSE" => Value::Bool(false),
+            "IF" => {
+                if to_bool(&scalar(arg(0))?)? {
+                    if has(1) { arg(1) } else { Value::Number(0.0) }
+                } else if args.len() > 2 {
+                    if has(2) { arg(2) } else { Value::Number(0.0) }
+                } else {
+                    Value::Bool(false)
+                }
+            }
+            "IFS" => {
+                for pair in args.chunks(2) {
+                    if pair.len() == 2 && to_bool(&scalar(self.eval(&pair[0], ctx))?)? {
+                        return Ok(self.eval(&pair[1], ctx));
+                    }
+                }
+                return Err(NA.to_string());
+            }
+            "AND" | "OR" => {
+                let mut values: Vec<bool> = Vec::new();
+                for expr in args {
+                    for value in flatten(&self.eval_range(expr, ctx)) {
+                        match value {
+                            Value::Bool(b) => values.push(b),
+                            Value::Number(n) => values.push(n != 0.0),
+                            Value::Error(code) => return Err(code),
+                            _ => {}
+                        }
+                    }
+                }
+                if values.is_empty() {
+                    return Err(VALUE.to_string());
+                }
+                Value::Bool(if name == "AND" {
+                    values.iter().all(|b| *b)
+                } else {
+                    values.iter().any(|b| *b)
+                })
+            }
+            "NOT" => Value::Bool(!to_bool(&scalar(arg(0))?)?),
+            "IFERROR" => match arg(0) {
+                Value::Error(_) => arg(1),
+                value => value,
+            },
+            "IFNA" => match arg(0) {
+                Value::Error(code) if code == NA => arg(1),
+                value => value,
+            },
+            "NA" => return Err(NA.to_string()),
+            "ISBLANK" => Value::Bool(matches!(arg(0), Value::Empty)),
+            "ISNUMBER" => Value::Bool(matches!(arg(0), Value::Number(_))),
+            "ISTEXT" => Value::Bool(matches!(arg(0), Value::Text(_))),
+            "ISERROR" => Value::Bool(matches!(arg(0), Value::Error(_))),
+            "ISNA" => Value::Bool(matches!(arg(0), Value::Error(code) if code == NA)),
+
+            // 文字列
+            "CONCATENATE" => {
+                let mut result: String = String::new();
+                for i in 0..args.len() {
+                    result.push_str(&text(i)?);
+                }
+                Value::Text(result)
+            }
+            "CONCAT" | "TEXTJOIN" => {
+                let (delimiter, ignore_empty, start) = if name == "TEXTJOIN" {
+                    (text(0)?, to_bool(&scalar(arg(1))?)?, 2)
+                } else {
+                    (String::new(), false, 0)
+                };
+                let mut parts: Vec<String> = Vec::new();
+                for expr in &args[start.min(args.len())..] {
+                    for value in flatten(&self.eval_range(expr, ctx)) {
+                        let part: String = to_text(&value)?;
+                        if !(ignore_empty && part.is_empty()) {
+                            parts.push(part);
+                        }
+                    }
+                }
+                Value::Text(parts.join(&delimiter))
+            }
+            "LEN" => Value::Number(text(0)?.chars().count() as f64),
+            "UPPER" => Value::Text(text(0)?.to_uppercase()),
+            "LOWER" => Value::Text(text(0)?.to_lowercase()),
+            "TRIM" => Value::Text(
+                text(0)?
+                    .split(' ')
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+            "LEFT" | "RIGHT" => {
+                let chars: Vec<char> = text(0)?.chars().collect();
+                let count: f64 = if has(1) { integer(1)? } else { 1.0 };
+                if count < 0.0 {
+                    return Err(VALUE.to_string());
+                }
+                let count: usize = (count as usize).min(chars.len());
+                Value::Text(if name == "LEFT" {
+                    chars[..count].iter().collect()
+                } else {
+                    chars[chars.len() - count..].iter().collect()
+                })
+            }
+            "MID" => {
+                let chars: Vec<char> = text(0)?.chars().collect();
+                let (start, count) = (integer(1)?, integer(2)?);
+                if start < 1.0 || count < 0.0 {
+                    return Err(VALUE.to_string());
+                }
+                Value::Text(
+                    chars
+                        .iter()
+                        .skip(start as usize - 1)
+                        .take(count as usize)
+                        .collect(),
+                )
+            }
+            "REPT" => {
+                let count: f64 = integer(1)?;
+                if count < 0.0 {
+                    return Err(VALUE.to_string());
+                }
+                Value::Text(text(0)?.repeat(count as usize))
+            }
+            "SUBSTITUTE" => {
+                let (source, old, new) = (text(0)?, text(1)?, text(2)?);
+                if old.is_empty() {
+                    return Ok(Value::Text(source));
+                }
+                if !has(3) {
+                    return Ok(Value::Text(source.replace(&old, &new)));
+                }
+                let instance: usize = integer(3)?.max(0.0) as usize;
+                if instance == 0 {
+                    return Err(VALUE.to_string());
+                }
+                Value::Text(match source.match_indices(&old).nth(instance - 1) {
+                    Some((pos, _)) => {
+                        format!("{}{new}{}", &source[..pos], &source[pos + old.len()..])
+                    }
+                    None => source,
+                })
+            }
+            "FIND" | "SEARCH" => {
+                let (needle, haystack) = (text(0)?, text(1)?);
+                let (needle, haystack) = if name == "SEARCH" {
+                    (needle.to_lowercase(), haystack.to_lowercase())
+                } else {
+                    (needle, haystack)
+                };
+                let start: usize = if has(2) {
+                    integer(2)?.max(0.0) as usize
+                } else {
+                    1
+                };
+                let chars: Vec<char> = haystack.chars().collect();
+                if start < 1 || start > chars.len() + 1 {
+                    return Err(VALUE.to_string());
+                }
+                let rest: String = chars[start - 1..].iter().collect();
+                match rest.find(&needle) {
+                    Some(pos) => Value::Number((rest[..pos].chars().count() + start) as f64),
+                    None => return Err(VALUE.to_string()),
+                }
+            }
+            "EXACT" => Value::Bool(text(0)? == text(1)?),
+            "VALUE" => Value::Number(to_number(&Value::Text(text(0)?))?),
+
+            // 検索
+            "VLOOKUP" | "HLOOKUP" => {
+                let lookup: Value = scalar(arg(0))?;
+                let mut table: Vec<Vec<Value>> = as_rows(range(1))?;
+                if name == "HLOOKUP" {
+                    table = transpose(&table);
+                }
+                let index: usize = integer(2)?.max(0.0) as usize;
+                if index < 1 {
+                    return Err(VALUE.to_string());
+                }
+                if table.first().is_some_and(|row| index > row.len()) {
+                    return Err(REF.to_string());
+                }
+                let approximate: bool = !has(3) || to_bool(&scalar(arg(3))?)?;
+                let keys: Vec<Value> = table.iter().map(|row| row[0].clone()).collect();
+                let position: usize = if approximate {
+                    approximate_match(&lookup, &keys, 1)
+                } else {
+                    exact_match(&lookup, &keys, false)
+                }
+                .ok_or(NA.to_string())?;
+                table[position][index - 1].clone()
+            }
+            "MATCH" => {
+                let lookup: Value = scalar(arg(0))?;
+                let keys: Vec<Value> = flatten(&range(1));
+                let match_type: f64 = if has(2) { number(2)? } else { 1.0 };
+                let position: Option<usize> = if match_type == 0.0 {
+                    exact_match(&lookup, &keys, false)
+                } else {
+                    approximate_match(&lookup, &keys, match_type.signum() as i32)
+                };
+                Value::Number((position.ok_or(NA.to_string())? + 1) as f64)
+            }
+            "XLOOKUP" => {
+                let lookup: Value = scalar(arg(0))?;
+                let lookup_rows: Vec<Vec<Value>> = as_rows(range(1))?;
+                let return_rows: Vec<Vec<Value>> = as_rows(range(2))?;
+                let vertical: bool =
+                    lookup_rows.len() > 1 || lookup_rows.first().is_none_or(|r| r.len() == 1);
+                let keys: Vec<Value> = flatten(&Value::Array(lookup_rows));
+                let match_mode: i32 = if has(4) { integer(4)? as i32 } else { 0 };
+                let reverse: bool = has(5) && integer(5)? < 0.0;
+                let position: Option<usize> = match match_mode {
+                    0 | 2 => {
+                        if reverse {
+                            let reversed: Vec<Value> = keys.iter().rev().cloned().collect();
+                            exact_match(&lookup, &reversed, match_mode == 2)
+                                .map(|p| keys.len() - 1 - p)
+                        } else {
+                            exact_match(&lookup, &keys, match_mode == 2)
+                        }
+                    }
+                    -1 | 1 => nearest_match(&lookup, &keys, match_mode),
+                    _ => return Err(VALUE.to_string()),
+                };
+                match position {
+                    Some(p) if vertical => match return_rows.get(p) {
+                        Some(row) if row.len() == 1 => row[0].clone(),
+                        Some(row) => Value::Array(vec![row.clone()]),
+                        None => return Err(VALUE.to_string()),
+                    },
+                    Some(p) => {
+                        let column: Vec<Vec<Value>> = return_rows
+                            .iter()
+                            .map(|row| row.get(p).cloned().map(|v| vec![v]))
+                            .collect::<Option<Vec<Vec<Value>>>>()
+                            .ok_or(VALUE.to_string())?;
+                        if column.len() == 1 {
+                            column[0][0].clone()
+                        } else {
+                            Value::Array(column)
+                        }
+                    }
+                    None if has(3) => arg(3),
+                    None => return Err(NA.to_string()),
+                }
+            }
+            "INDEX" => {
+                let rows: Vec<Vec<Value>> = as_rows(range(0))?;
+                let row: usize = integer(1)?.max(0.0) as usize;
+                let col: usize = if has(2) {
+                    integer(2)?.max(0.0) as usize
+                } else {
+                    0
+                };
+                // 1行の範囲で列番号が省略された場合は行番号を列番号として扱う
+                let (row, col) = if !has(2) && rows.len() == 1 {
+                    (1, row)
+                } else {
+                    (row, col)
+                };
+                let height: usize = rows.len();
+                let width: usize = rows.first().map_or(0, Vec::len);
+                if row > height || col > width {
+                    return Err(REF.to_string());
+                }
+                match (row, col) {
+                    (0, 0) => Value::Array(rows),
+                    (0, c) => Value::Array(rows.iter().map(|r| vec![r[c - 1].clone()]).collect()),
+                    (r, 0) if width == 1 => rows[r - 1][0].clone(),
+                    (r, 0) => Value::Array(vec![rows[r - 1].clone()]),
+                    (r, c) => rows[r - 1][c - 1].clone(),
+                }
+            }
+            "CHOOSE" => {
+                let index: usize = integer(0)?.max(0.0) as usize;
+                if index < 1 || index >= args.len() {
+                    return Err(VALUE.to_string());
+                }
+                self.eval(&args[index], ctx)
+            }
+
+            // 日付
+            "DATE" => {
+                let (year, month, day) = (integer(0)?, integer(1)?, integer(2)?);
+                let year: f64 = if (0.0..1900.0).contains(&year) {
+                    year + 1900.0
+                } else {
+                    year
+                };
+                let months: i64 = year as i64 * 12 + month as i64 - 1;
+                let first: NaiveDate = NaiveDate::from_ymd_opt(
+                    months.div_euclid(12) as i32,
+                    months.rem_euclid(12) as u32 + 1,
+                    1,
+                )
+                .ok_or(NUM.to_string())?;
+                let date: NaiveDate = first
+                    .checked_add_signed(Duration::days(day as i64 - 1))
+                    .ok_or(NUM.to_string())?;
+                Value::Number(date_to_serial(date)?)
+            }
+            "TIME" => {
+                let seconds: f64 = integer(0)? * 3600.0 + integer(1)? * 60.0 + integer(2)?;
+                if seconds < 0.0 {
+                    return Err(NUM.to_string());
+                }
+                Value::Number((seconds / 86400.0).fract())
+            }
+            "YEAR" => Value::Number(serial_to_date(number(0)?)?.year() as f64),
+            "MONTH" => Value::Number(serial_to_date(number(0)?)?.month() as f64),
+            "DAY" => Value::Number(serial_to_date(number(0)?)?.day() as f64),
+            "HOUR" | "MINUTE" | "SECOND" => {
+                let seconds: u32 = (number(0)?.fract() * 86400.0).round() as u32 % 86400;
+                Value::Number(match name {
+                    "HOUR" => seconds / 3600,
+                    "MINUTE" => seconds / 60 % 60,
+                    _ => seconds % 60,
+                } as f64)
+            }
+            "WEEKDAY" => {
+                let weekday = serial_to_date(number(0)?)?.weekday();
+                let return_type: f64 = if has(1) { integer(1)? } else { 1.0 };
+                Value::Number(match return_type as i32 {
+                    1 => weekday.number_from_sunday(),
+                    2 => weekday.number_from_monday(),
+                    3 => weekday.num_days_from_monday(),
+                    _ => return Err(NUM.to_string()),
+                } as f64)
+            }
+            "TODAY" => Value::Number(date_to_serial(Local::now().date_naive())?),
+            "NOW" => {
+                let now: NaiveDateTime = Local::now().naive_local();
+                Value::Number(
+                    date_to_serial(now.date())?
+                        + now.time().num_seconds_from_midnight() as f64 / 86400.0,
+                )
+            }
+            "DAYS" => Value::Number(number(0)?.floor() - number(1)?.floor()),
+            "EDATE" | "EOMONTH" => {
+                let date: NaiveDate = serial_to_date(number(0)?)?;
+                let months: i64 =
+                    date.year() as i64 * 12 + date.month0() as i64 + integer(1)? as i64;
+                let (year, month) = (
+                    months.div_euclid(12) as i32,
+                    months.rem_euclid(12) as u32 + 1,
+                );
+                let last_day: u32 = last_day_of_month(year, month).ok_or(NUM.to_string())?;
+                let day: u32 = if name == "EOMONTH" {
+                    last_day
+                } else {
+                    date.day().min(last_day)
+                };
+                let result: NaiveDate =
+                    NaiveDate::from_ymd_opt(year, month, day).ok_or(NUM.to_string())?;
+                Value::Number(date_to_serial(result)?)
+            }
+
+            _ => {
+                self.unsupported.set(true);
+                return Err(NAME.to_string());
+            }
+        })
+    }
+
+    /// 条件付き集計 (COUNTIF, SUMIFS など) の評価
+    fn conditional_aggregate(&self, name: &str, args: &[Expr], ctx: Ctx) -> Res<Value> {
+        let (target, pairs): (Option<&Expr>, Vec<(&Expr, &Expr)>) = match name {
+            "COUNTIF" | "COUNTIFS" => (None, args.chunks(2).filter_map(pair).collect()),
+            "SUMIF" | "AVERAGEIF" => {
+                let range: &Expr = args.first().ok_or(VALUE.to_string())?;
+                let criteria: &Expr = args.get(1).ok_or(VALUE.to_string())?;
+                (Some(args.get(2).unwrap_or(range)), vec![(range, criteria)])
+            }
+            _ => (
+                args.first(),
+                args.get(1..)
+                    .unwrap_or_default()
+                    .chunks(2)
+                    .filter_map(pair)
+                    .collect(),
+            ),
+        };
+        if pairs.is_empty() {
+            return Err(VALUE.to_string());
+        }
+        let mut matched: Option<Vec<bool>> = None;
+        for (range, criteria) in pairs {
+            let values: Vec<Value> = flatten(&self.eval_range(range, ctx));
+            let criteria: Criteria = Criteria::new(&scalar(self.eval(criteria, ctx))?);
+            let current: Vec<bool> = values.iter().map(|v| criteria.matches(v)).collect();
+            matched = Some(match matched {
+                None => current,
+                Some(previous) if previous.len() == current.len() => {
+                    previous.iter().zip(current).map(|(a, b)| *a && b).collect()
+                }
+                Some(_) => return Err(VALUE.to_string()),
+            });
+        }
+        let matched: Vec<bool> = matched.unwrap_or_default();
+        let Some(target) = target else {
+            return Ok(Value::Number(matched.iter().filter(|m| **m).count() as f64));
+        };
+        let targets: Vec<Value> = flatten(&self.eval_range(target, ctx));
+        let mut numbers: Vec<f64> = Vec::new();
+        for (value, _) in targets.iter().zip(&matched).filter(|(_, m)| **m) {
+            match value {
+                Value::Number(n) => numbers.push(*n),
+                Value::Error(code) => return Err(code.clone()),
+                _ => {}
+            }
+        }
+        let sum: f64 = numbers.iter().sum();
+        if name.starts_with("AVERAGE") {
+            if numbers.is_empty() {
+                return Err(DIV0.to_string());
+            }
+            return Ok(Value::Number(sum / numbers.len() as f64));
+        }
+        Ok(Value::Number(sum))
+    }
+}
+
+/// 引数の組 (範囲, 条件) の取得
+fn pair(chunk: &[Expr]) -> Option<(&Expr, &Expr)> {
+    match chunk {
+        [range, criteria] => Some((range, criteria)),
+        _ => None,
+    }
+}
+
+/// COUNTIF などの条件 (例: ">=10", "a*")
+struct Criteria {
+    op: BinaryOp,
+    value: Value,
+}
+
+impl Criteria {
+    fn new(criteria: &Value) -> Self {
+        let Value::Text(text) = criteria else {
+            return Criteria {
+                op: BinaryOp::Eq,
+                value: criteria.clone(),
+            };
+        };
+        let (op, rest) = [
+            ("<=", BinaryOp::Le),
+            (">=", BinaryOp::Ge),
+            ("<>", BinaryOp::Ne),
+            ("<", BinaryOp::Lt),
+            (">", BinaryOp::Gt),
+            ("=", BinaryOp::Eq),
+        ]
+        .iter()
+        .find_map(|(prefix, op)| text.strip_prefix(prefix).map(|rest| (*op, rest)))
+        .unwrap_or((BinaryOp::Eq, text.as_str()));
+        let value: Value = if let Ok(n) = rest.trim().parse::<f64>() {
+            Value::Number(n)
+        } else if rest.eq_ignore_ascii_case("TRUE") || rest.eq_ignore_ascii_case("FALSE") {
+            Value::Bool(rest.eq_ignore_ascii_case("TRUE"))
+        } else {
+            Value::Text(rest.to_string())
+        };
+        Criteria { op, value }
+    }
+
+    fn matches(&self, candidate: &Value) -> bool {
+        let equal: bool = match (&self.value, candidate) {
+            (Value::Text(pattern), Value::Empty) => pattern.is_empty(),
+            (Value::Text(pattern), Value::Text(text)) => wildcard_match(pattern, text),
+            (Value::Number(n), Value::Text(text)) => text.trim().parse::<f64>() == Ok(*n),
+            (expected, candidate) => expected == candidate,
+        };
+        match self.op {
+            BinaryOp::Eq => equal,
+            BinaryOp::Ne => !equal,
+            op => {
+                let comparable: bool = matches!(
+                    (&self.value, candidate),
+                    (Value::Number(_), Value::Number(_))
+                        | (Value::Text(_), Value::Text(_))
+                        | (Value::Bool(_), Value::Bool(_))
+                );
+                comparable
+                    && matches!(
+                        (op, compare(candidate, &self.value)),
+                        (BinaryOp::Lt, Ordering::Less)
+                            | (BinaryOp::Gt, Ordering::Greater)
+                            | (BinaryOp::Le, Ordering::Less | Ordering::Equal)
+                            | (BinaryOp::Ge, Ordering::Greater | Ordering::Equal)
+                    )
+            }
+        }
+    }
+}
+
+/// ワイルドカード (`*`, `?`, `~` によるエスケープ) を含む文字列の照合 (大文字小文字を区別しない)
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    fn matches(pattern: &[char], text: &[char]) -> bool {
+        match pattern.split_first() {
+            None => text.is_empty(),
+            Some(('*', rest)) => (0..=text.len()).any(|i| matches(rest, &text[i..])),
+            Some(('?', rest)) => !text.is_empty() && matches(rest, &text[1..]),
+            Some(('~', [escaped, rest @ ..])) => {
+                text.first() == Some(escaped) && matches(rest, &text[1..])
+            }
+            Some((c, rest)) => text.first() == Some(c) && matches(rest, &text[1..]),
+        }
+    }
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+    matches(&pattern, &text)
+}
+
+/// 完全一致の検索
+fn exact_match(lookup: &Value, keys: &[Value], wildcard: bool) -> Option<usize> {
+    keys.iter().position(|key| match (lookup, key) {
+        (Value::Text(pattern), Value::Text(text)) if wildcard || pattern.contains(['*', '?']) => {
+            wildcard_match(pattern, text)
+        }
+        _ => compare(lookup, key) == Ordering::Equal && same_type(lookup, key),
+    })
+}
+
+/// 並べ替え済みの範囲での近似一致の検索
+///
+/// `direction` が 1 の場合は以下の最大値, -1 の場合は以上の最小値 (降順の範囲)
+fn approximate_match(lookup: &Value, keys: &[Value], direction: i32) -> Option<usize> {
+    let mut found: Option<usize> = None;
+    for (i, key) in keys.iter().enumerate() {
+        if !same_type(lookup, key) {
+            continue;
+        }
+        let ordering: Ordering = compare(key, lookup);
+        let accept: bool = if direction >= 0 {
+            ordering != Ordering::Greater
+        } else {
+            ordering != Ordering::Less
+        };
+        if !accept {
+            break;
+        }
+        found = Some(i);
+    }
+    found
+}
+
+/// 並べ替えを前提としない近似一致の検索 (XLOOKUP)
+///
+/// `mode` が -1 の場合は完全一致または次に小さい値, 1 の場合は次に大きい値
+fn nearest_match(lookup: &Value, keys: &[Value], mode: i32) -> Option<usize> {
+    let mut best: Option<usize> = None;
+    for (i, key) in keys.iter().enumerate() {
+        if !same_type(lookup, key) {
+            continue;
+        }
+        let ordering: Ordering = compare(key, lookup);
+        if ordering == Ordering::Equal {
+            return Some(i);
+        }
+        let candidate: bool =
+            (mode < 0 && ordering == Ordering::Less) || (mode > 0 && ordering == Ordering::Greater);
+        let better: bool = best.is_none_or(|b| {
+            let against: Ordering = compare(key, &keys[b]);
+            (mode < 0 && against == Ordering::Greater) || (mode > 0 && against == Ordering::Less)
+        });
+        if candidate && better {
+            best = Some(i);
+        }
+    }
+    best
+}
+
+/// 値の型が同じかどうか (検索関数での比較対象)
+fn same_type(a: &Value, b: &Value) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
+/// 値の比較 (数値 < 文字列 < 論理値, 文字列は大文字小文字を区別しない)
+fn compare(a: &Value, b: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Number(_) | Value::Empty => 0,
+            Value::Text(_) => 1,
+            Value::Bool(_) => 2,
+            _ => 3,
+        }
+    }
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.partial_cmp(y).unwrap_or(Ordering::Equal),
+        (Value::Text(x), Value::Text(y)) => x.to_lowercase().cmp(&y.to_lowercase()),
+        (Value::Bool(x), Value::Bool(y)) => x.cmp(y),
+        (Value::Empty, Value::Empty) => Ordering::Equal,
+        (Value::Empty, other) => compare(&empty_like(other), other),
+        (other, Value::Empty) => compare(other, &empty_like(other)),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// 比較相手の型に合わせた空のセルの値
+fn empty_like(other: &Value) -> Value {
+    match other {
+        Value::Text(_) => Value::Text(String::new()),
+        Value::Bool(_) => Value::Bool(false),
+        _ => Value::Number(0.0),
+    }
+}
+
+/// 二項演算の評価 (スカラー同士)
+fn binary(op: BinaryOp, a: &Value, b: &Value) -> Res<Value> {
+    if let Value::Error(code) = a {
+        return Err(code.clone());
+    }
+    if let Value::Error(code) = b {
+        return Err(code.clone());
+    }
+    let ordering = || compare(a, b);
+    Ok(match op {
+        BinaryOp::Add => Value::Number(to_number(a)? + to_number(b)?),
+        BinaryOp::Sub => Value::Number(to_number(a)? - to_number(b)?),
+        BinaryOp::Mul => Value::Number(to_number(a)? * to_number(b)?),
+        BinaryOp::Div => {
+            let divisor: f64 = to_number(b)?;
+            if divisor == 0.0 {
+                return Err(DIV0.to_string());
+            }
+            Value::Number(to_number(a)? / divisor)
+        }
+        BinaryOp::Pow => {
+            let result: f64 = to_number(a)?.powf(to_number(b)?);
+            if !result.is_finite() {
+                return Err(NUM.to_string());
+            }
+            Value::Number(result)
+        }
+        BinaryOp::Concat => Value::Text(to_text(a)? + &to_text(b)?),
+        BinaryOp::Eq => Value::Bool(ordering() == Ordering::Equal),
+        BinaryOp::Ne => Value::Bool(ordering() != Ordering::Equal),
+        BinaryOp::Lt => Value::Bool(ordering() == Ordering::Less),
+        BinaryOp::Le => Value::Bool(ordering() != Ordering::Greater),
+        BinaryOp::Gt => Value::Bool(ordering() == Ordering::Greater),
+        BinaryOp::Ge => Value::Bool(ordering() != Ordering::Less),
+        BinaryOp::Range | BinaryOp::Intersection | BinaryOp::Union => {
+            return Err(VALUE.to_string());
+        }
+    })
+}
+
+/// 配列の要素ごとの二項演算 (1x1 や1行・1列の配列は拡張)
+fn broadcast(left: Value, right: Value, f: impl Fn(Value, Value) -> Res<Value>) -> Value {
+    let apply = |a: Value, b: Value| f(a, b).unwrap_or_else(Value::Error);
+    match (left, right) {
+        (Value::Array(a), Value::Array(b)) => {
+            let height: usize = a.len().max(b.len());
+            let width: usize = a
+                .first()
+                .map_or(0, Vec::len)
+                .max(b.first().map_or(0, Vec::len));
+            Value::Array(
+                (0..height)
+                    .map(|r| {
+                        (0..width)
+                            .map(|c| {
+                                apply(broadcast_element(&a, r, c), broadcast_element(&b, r, c))
+                            })
+                            .collect()
+                    })
+                    .collect(),
+            )
+        }
+        (Value::Array(a), b) => Value::Array(
+            a.into_iter()
+                .map(|row| row.into_iter().map(|v| apply(v, b.clone())).collect())
+                .collect(),
+        ),
+        (a, Value::Array(b)) => Value::Array(
+            b.into_iter()
+                .map(|row| row.into_iter().map(|v| apply(a.clone(), v)).collect())
+                .collect(),
+        ),
+        (a, b) => apply(a, b),
+    }
+}
+
+/// 拡張を考慮した配列の要素の取得
+fn broadcast_element(rows: &[Vec<Value>], row: usize, col: usize) -> Value {
+    let row: usize = if rows.len() == 1 { 0 } else { row };
+    let width: usize = rows.first().map_or(0, Vec::len);
+    let col: usize = if width == 1 { 0 } else { col };
+    array_element(rows, row, col)
+}
+
+/// 配列の要素の取得 (範囲外は `#N/A`)
+fn array_element(rows: &[Vec<Value>], row: usize, col: usize) -> Value {
+    rows.get(row)
+        .and_then(|r| r.get(col))
+        .cloned()
+        .unwrap_or(Value::Error(NA.to_string()))
+}
+
+/// 要素ごとの単項演算
+fn map_value(value: Value, f: impl Fn(Value) -> Res<Value> + Copy) -> Value {
+    match value {
+        Value::Array(rows) => Value::Array(
+            rows.into_iter()
+                .map(|row| row.into_iter().map(|v| map_value(v, f)).collect())
+                .collect(),
+        ),
+        Value::Error(code) => Value::Error(code),
+        scalar => f(scalar).unwrap_or_else(Value::Error),
+    }
+}
+
+/// 配列の要素の一覧
+fn flatten(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Array(rows) => rows.iter().flatten().cloned().collect(),
+        scalar => vec![scalar.clone()],
+    }
+}
+
+/// 配列またはスカラーの行の一覧への変換
+fn as_rows(value: Value) -> Res<Vec<Vec<Value>>> {
+    match value {
+        Value::Array(rows) => Ok(rows),
+        Value::Error(code) => Err(code),
+        scalar => Ok(vec![vec![scalar]]),
+    }
+}
+
+/// 行と列の入れ替え
+fn transpose(rows: &[Vec<Value>]) -> Vec<Vec<Value>> {
+    let width: usize = rows.first().map_or(0, Vec::len);
+    (0..width)
+        .map(|c| rows.iter().map(|row| row[c].clone()).collect())
+        .collect()
+}
+
+/// スカラー値の取得 (配列は左上の要素, エラーは中断)
+fn scalar(value: Value) -> Res<Value> {
+    match value {
+        Value::Array(rows) => scalar(array_element(&rows, 0, 0)),
+        Value::Error(code) => Err(code),
+        value => Ok(value),
+    }
+}
+
+/// 数値への変換
+fn to_number(value: &Value) -> Res<f64> {
+    match value {
+        Value::Number(n) => Ok(*n),
+        Value::Bool(b) => Ok(if *b { 1.0 } else { 0.0 }),
+        Value::Empty => Ok(0.0),
+        Value::Text(text) => text.trim().parse().map_err(|_| VALUE.to_string()),
+        Value::Error(code) => Err(code.clone()),
+        Value::Array(rows) => to_number(&array_element(rows, 0, 0)),
+    }
+}
+
+/// 文字列への変換
+fn to_text(value: &Value) -> Res<String> {
+    match value {
+        Value::Number(n) => Ok(format_number(*n)),
+        Value::Bool(b) => Ok(if *b { "TRUE" } else { "FALSE" }.to_string()),
+        Value::Empty => Ok(String::new()),
+        Value::Text(text) => Ok(text.clone()),
+        Value::Error(code) => Err(code.clone()),
+        Value::Array(rows) => to_text(&array_element(rows, 0, 0)),
+    }
+}
+
+/// 論理値への変換
+fn to_bool(value: &Value) -> Res<bool> {
+    match value {
+        Value::Bool(b) => Ok(*b),
+        Value::Number(n) => Ok(*n != 0.0),
+        Value::Empty => Ok(false),
+        Value::Text(text) if text.eq_ignore_ascii_case("TRUE") => Ok(true),
+        Value::Text(text) if text.eq_ignore_ascii_case("FALSE") => Ok(false),
+        Value::Text(_) => Err(VALUE.to_string()),
+        Value::Error(code) => Err(code.clone()),
+        Value::Array(rows) => to_bool(&array_element(rows, 0, 0)),
+    }
+}
+
+/// 数値の文字列表現 (有効桁数15桁)
+fn format_number(n: f64) -> String {
+    if n.fract() == 0.0 && n.abs() < 1e15 {
+        return format!("{}", n as i64);
+    }
+    let magnitude: i32 = n.abs().log10().floor() as i32;
+    let decimals: usize = (14 - magnitude).max(0) as usize;
+    let text: String = format!("{n:.decimals$}");
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
+}
+
+/// 日付のExcelのシリアル値への変換 (1900年2月29日の扱いを含む)
+fn date_to_serial(date: NaiveDate) -> Res<f64> {
+    let epoch: NaiveDate = NaiveDate::from_ymd_opt(1899, 12, 31).ok_or(NUM.to_string())?;
+    let days: i64 = (date - epoch).num_days();
+    if days < 0 {
+        return Err(NUM.to_string());
+    }
+    Ok((if days >= 60 { days + 1 } else { days }) as f64)
+}
+
+/// Excelのシリアル値の日付への変換
+fn serial_to_date(serial: f64) -> Res<NaiveDate> {
+    if serial < 0.0 {
+        return Err(NUM.to_string());
+    }
+    let days: i64 = serial.floor() as i64;
+    let epoch: NaiveDate = NaiveDate::from_ymd_opt(1899, 12, 31).ok_or(NUM.to_string())?;
+    epoch
+        .checked_add_signed(Duration::days(if days >= 60 { days - 1 } else { days }))
+        .ok_or(NUM.to_string())
+}
+
+/// 月の最終日
+fn last_day_of_month(year: i32, month: u32) -> Option<u32> {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)?
+        .pred_opt()
+        .map(|d| d.day())
+}
+
+/// セルに書き込む計算結果への変換 (配列は左上の要素, 空は0)
+fn finalize(value: Value) -> Value {
+    match value {
+        Value::Array(rows) => finalize(array_element(&rows, 0, 0)),
+        Value::Empty => Value::Number(0.0),
+        Value::Number(n) if !n.is_finite() => Value::Error(NUM.to_string()),
+        value => value,
+    }
+}
+
+/// 共有文字列の項目 (`si`) の文字列 (リッチテキストは連結)
+fn shared_string_text(si: &XmlElement) -> String {
+    match si.find_element("t") {
+        Some(t) => t.text.clone().unwrap_or_default(),
+        None => si
+            .children
+            .iter()
+            .filter(|r| r.name == "r")
+            .filter_map(|r| r.find_element("t")?.text.clone())
+            .collect(),
+    }
+}
+
+/// セル要素の値
+fn cell_value(cell: &XmlElement, strings: &[String]) -> Value {
+    let text: Option<&String> = cell.find_element("v").and_then(|v| v.text.as_ref());
+    match (cell.get_attribute("t").map(String::as_str), text) {
+        (Some("inlineStr"), _) => Value::Text(
+            cell.find_element("is>t")
+                .and_then(|t| t.text.clone())
+                .unwrap_or_default(),
+        ),
+        (_, None) => Value::Empty,
+        (Some("s"), Some(index)) => index
+            .parse::<usize>()
+            .ok()
+            .and_then(|i| strings.get(i))
+            .map_or(Value::Empty, |s| Value::Text(s.clone())),
+        (Some("str"), Some(text)) => Value::Text(text.clone()),
+        (Some("b"), Some(text)) => Value::Bool(text == "1"),
+        (Some("e"), Some(text)) => Value::Error(text.clone()),
+        (_, Some(text)) => text
+            .parse()
+            .map_or(Value::Error(VALUE.to_string()), Value::Number),
+    }
+}
+
+/// 計算結果のセル要素への書き込み
+///
+/// 数式セルは `<f>` の直後に `<v>` を置き、値の型に応じて `t` 属性を設定
+pub fn write_result(worksheet: &mut XmlElement, row: u32, col: u32, value: &Value) {
+    let Some(sheet_data) = worksheet.find_element_mut("sheetData") else {
+        return;
+    };
+    let row_element: &mut XmlElement = Cell::get_or_create_row(sheet_data, row);
+    let cell: &mut XmlElement = Cell::get_or_create_cell(row_element, &to_address(row, col));
+    let (t, text): (Option<&str>, String) = match value {
+        Value::Number(n) => (None, format_number(*n)),
+        Value::Text(text) => (Some("str"), text.clone()),
+        Value::Bool(b) => (Some("b"), if *b { "1" } else { "0" }.to_string()),
+        Value::Error(code) => (Some("e"), code.clone()),
+        Value::Empty | Value::Array(_) => (None, "0".to_string()),
+    };
+    match t {
+        Some(t) => {
+            cell.attributes.insert("t".to_string(), t.to_string());
+        }
+        None => {
+            cell.attributes.remove("t");
+        }
+    }
+    cell.children.retain(|c| c.name != "v" && c.name != "is");
+    let position: usize = cell
+        .children
+        .iter()
+        .position(|c| c.name == "f")
+        .map_or(0, |i| i + 1);
+    let mut v_element: XmlElement = XmlElement::new("v");
+    v_element.text = Some(text);
+    cell.children.insert(position, v_element);
+}
@@ -1,9 +1,67 @@
+use crate::formula::{self, FormulaError, parse_address};
 use crate::style::{Font, PatternFill};
 use crate::xml::{Xml, XmlElement};
 use chrono::{NaiveDate, NaiveDateTime};
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex, MutexGuard};
 
+/// Excelのエラー値の一覧
+pub(crate) const ERROR_CODES: [&str; 9] = [
+    "#NULL!", "#DIV/0!", "#VALUE!", "#REF!", "#NAME?", "#NUM!", "#N/A", "#SPILL!", "#CALC!",
+];
+
+/// Excelのエラー値 (例: "#N/A")
+#[pyclass(eq, frozen, hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CellError {
+    /// エラーコード
+    #[pyo3(get)]
+    pub code: String,
+}
+
+#[pymethods]
+impl CellError {
+    /// 新しい `CellError` インスタンスの作成
+    #[new]
+    fn py_new(code: &str) -> PyResult<Self> {
+        Self::from_code(code)
+            .ok_or_else(|| PyValueError::new_err(format!("Invalid error value: {code}")))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CellError('{}')", self.code)
+    }
+
+    fn __str__(&self) -> String {
+        self.code.clone()
+    }
+}
+
+impl CellError {
+    /// エラーコードからの `CellError` の作成
+    ///
+    /// 未知のエラーコードの場合は `None`
+    pub fn from_code(code: &str) -> Option<Self> {
+        ERROR_CODES
+            .iter()
+            .find(|c| c.eq_ignore_ascii_case(code))
+            .map(|c| CellError {
+                code: c.to_string(),
+            })
+    }
+}
+
+/// Pythonとやり取りするセルの値
+#[derive(Clone, Debug, PartialEq, FromPyObject, IntoPyObject)]
+pub enum CellValue {
+    /// エラー値
+    Error(CellError),
+    /// 文字列で表現された値
+    Text(String),
+}
+
 /// ワークシートの単一セル
 #[pyclass]
 pub struct Cell {
@@ -11,6 +69,8 @@ pub struct Cell {
     sheet_xml: Arc<Mutex<Xml>>,
     /// 共有文字列のXML
     shared_strings: Arc<Mutex<Xml>>,
+    /// 共有文字列のマップ
+    shared_strings_map: Arc<Mutex<HashMap<String, usize>>>,
     /// スタイルのXML
     styles: Arc<Mutex<Xml>>,
     /// セルのアドレス (例: "A1")
@@ -24,36 +84,67 @@ pub struct Cell {
 #[pymethods]
 impl Cell {
     /// セルの値の取得
+    ///
+    /// エラー値のセル (`t="e"`) は `CellError` として返却
     #[getter]
-    pub fn value(&self) -> Option<String> {
+    pub fn get_value(&self) -> Option<CellValue> {
         let xml: MutexGuard<Xml> = self.sheet_xml.lock().ok()?;
-        let worksheet: &XmlElement = xml.elements.first()?;
-        let sheet_data: &XmlElement = worksheet.get_element("sheetData");
-
-        sheet_data
-            .get_elements("row")
-            .iter()
-            .flat_map(|row| row.get_elements("c"))
-            .find(|cell_element| cell_element.attributes.get("r") == Some(&self.address))
-            .and_then(|cell_element| self.get_value_from_cell_element(cell_element))
+        let cell_element: &XmlElement = self.find_cell_element(&xml)?;
+        let shared_strings: MutexGuard<Xml> = self.shared_strings.lock().ok()?;
+        Self::read_value(cell_element, &shared_strings)
     }
 
     /// セルの値の設定
     ///
-    /// 値の型は自動的に検出
-    #[setter]
-    pub fn set_value(&mut self, value: String) {
-        if let Some(formula) = value.strip_prefix('=') {
-            self.set_formula_value(formula);
-        } else if let Ok(num) = value.parse::<f64>() {
-            self.set_number_value(num);
-        } else if let Ok(b) = value.parse::<bool>() {
-            self.set_bool_value(b);
-        } else if let Ok(dt) = NaiveDateTime::parse_from_str(&value, "%Y-%m-%d %H:%M:%S") {
-            self.set_datetime_value(dt);
-        } else {
-            self.set_string_value(&value);
+    /// `CellError` はエラー値のセルとして書き込み
+    #[setter(value)]
+    pub fn set_cell_value(&mut self, value: CellValue) -> PyResult<()> {
+        match value {
+            CellValue::Error(error) => self.set_error_value(&error),
+            CellValue::Text(text) => self.set_value(text)?,
         }
+        Ok(())
+    }
+
+    /// セルの数式の取得 (先頭の "=" は含まない)
+    #[getter]
+    pub fn formula(&self) -> Option<String> {
+        let xml: MutexGuard<Xml> = self.sheet_xml.lock().ok()?;
+        self.find_cell_element(&xml)?
+            .find_element("f")?
+            .text
+            .clone()
+    }
+
+    /// セルの数式の設定
+    ///
+    /// `None` の場合は数式を削除し、キャッシュ値のみを保持
+    #[setter(formula)]
+    pub fn set_formula_property(&mut self, formula: Option<String>) -> PyResult<()> {
+        match formula {
+            Some(formula) => self.set_formula_value(&formula)?,
+            None => self.remove_formula(),
+        }
+        Ok(())
+    }
+
+    /// キャッシュ値と型を指定した数式の設定
+    ///
+    /// `cached_type` は "n" (数値), "str", "b", "e" のいずれか
+    #[pyo3(signature = (formula, cached_value=None, cached_type=None))]
+    pub fn set_formula(
+        &mut self,
+        formula: &str,
+        cached_value: Option<String>,
+        cached_type: Option<String>,
+    ) -> PyResult<()> {
+        if let Some(t) = cached_type.as_deref()
+            && !matches!(t, "n" | "str" | "b" | "e")
+        {
+            return Err(PyValueError::new_err(format!("Invalid cached type: {t}")));
+        }
+        self.set_formula_with_cache(formula, cached_value.as_deref(), cached_type.as_deref())?;
+        Ok(())
     }
 
     /// セルのフォントの取得
@@ -104,12 +195,14 @@ impl Cell {
     pub fn new(
         sheet_xml: Arc<Mutex<Xml>>,
         shared_strings: Arc<Mutex<Xml>>,
+        shared_strings_map: Arc<Mutex<HashMap<String, usize>>>,
         styles: Arc<Mutex<Xml>>,
         address: String,
     ) -> Self {
         Cell {
             sheet_xml,
             shared_strings,
+            shared_strings_map,
             styles,
             address,
             font: None,
@@ -117,18 +210,55 @@ impl Cell {
         }
     }
 
+    /// セルの値の文字列としての取得
+    pub fn value(&self) -> Option<String> {
+        let xml: MutexGuard<Xml> = self.sheet_xml.lock().ok()?;
+        self.find_cell_element(&xml)
+            .and_then(|cell_element| self.get_value_from_cell_element(cell_element))
+    }
+
+    /// セルの値の設定
+    ///
+    /// 値の型は自動的に検出し、"=" で始まる場合は数式として構文を検証
+    pub fn set_value(&mut self, value: String) -> Result<(), FormulaError> {
+        if let Some(formula) = value.strip_prefix('=') {
+            self.set_formula_value(formula)?;
+        } else if let Ok(num) = value.parse::<f64>() {
+            self.set_number_value(num);
+        } else if let Ok(b) = value.parse::<bool>() {
+            self.set_bool_value(b);
+        } else if let Ok(dt) = NaiveDateTime::parse_from_str(&value, "%Y-%m-%d %H:%M:%S") {
+            self.set_datetime_value(dt);
+        } else {
+            self.set_string_value(&value);
+        }
+        Ok(())
+    }
+
+    /// ワークシートXML内のセル要素の検索
+    fn find_cell_element<'a>(&self, xml: &'a Xml) -> Option<&'a XmlElement> {
+        let worksheet: &XmlElement = xml.elements.first()?;
+        let sheet_data: &XmlElement = worksheet.get_element("sheetData");
+
+        sheet_data
+            .get_elements("row")
+            .into_iter()
+            .flat_map(|row| row.get_elements("c"))
+            .find(|cell_element| cell_element.attributes.get("r") == Some(&self.address))
+    }
+
     /// セル要素からの値の取得
     fn get_value_from_cell_element(&self, cell_element: &XmlElement) -> Option<String> {
         match cell_element.attributes.get("t").map(String::as_str) {
             Some("s") => self.get_shared_string_value(cell_element),
             Some("inlineStr") => self.get_inline_string_value(cell_element),
-            _ => cell_element.get_element("v").text.clone(),
+            _ => cell_element.find_element("v")?.text.clone(),
         }
     }
 
     /// 共有文字列の値の取得
     fn get_shared_string_value(&self, cell_element: &XmlElement) -> Option<String> {
-        let v_element: &XmlElement = cell_element.get_element("v");
+        let v_element: &XmlElement = cell_element.find_element("v")?;
         let idx: usize = v_element.text.as_ref()?.parse::<usize>().ok()?;
         let shared_strings_xml: MutexGuard<Xml> = self.shared_strings.lock().ok()?;
         let sst: &XmlElement = shared_strings_xml.elements.first()?;
@@ -138,7 +268,7 @@ impl Cell {
 
     /// インライン文字列の値の取得
     fn get_inline_string_value(&self, cell_element: &XmlElement) -> Option<String> {
-        cell_element.get_element("is>t").text.clone()
+        cell_element.find_element("is>t")?.text.clone()
     }
 
     /// スタイルXMLへのフォントの追加とフォントIDの返却
@@ -303,46 +433,34 @@ impl Cell {
     pub fn set_number_value(&mut self, value: f64) {
         let mut xml: MutexGuard<Xml> = self.sheet_xml.lock().expect("Failed to lock sheet xml");
         let cell_element: &mut XmlElement = self.get_or_create_cell_element(&mut xml);
-        cell_element.attributes.remove("t");
-        cell_element.children.retain(|c| c.name != "f");
-        if let Some(v) = cell_element.children.iter_mut().find(|c| c.name == "v") {
-            v.text = Some(value.to_string());
-        } else {
-            let mut v_element: XmlElement = XmlElement::new("v");
-            v_element.text = Some(value.to_string());
-            cell_element.children.push(v_element);
-        }
+        Self::write_cached_value(cell_element, None, value.to_string());
     }
 
     /// セルの値の文字列としての設定
     pub fn set_string_value(&mut self, value: &str) {
-        let sst_index: usize = self.get_or_create_shared_string(value);
+        let sst_index: usize = {
+            let mut map: MutexGuard<HashMap<String, usize>> = self
+                .shared_strings_map
+                .lock()
+                .expect("Failed to lock shared strings map");
+            let mut shared_strings: MutexGuard<Xml> = self
+                .shared_strings
+                .lock()
+                .expect("Failed to lock shared strings");
+            Self::intern_shared_string(
+                &mut shared_strings,
+                &mut map,
+                &Self::shared_string_element(value),
+            )
+        };
         let mut xml: MutexGuard<Xml> = self.sheet_xml.lock().expect("Failed to lock sheet xml");
         let cell_element: &mut XmlElement = self.get_or_create_cell_element(&mut xml);
-        cell_element
-            .attributes
-            .insert("t".to_string(), "s".to_string());
-        cell_element.children.retain(|c| c.name != "f");
-        if let Some(v) = cell_element.children.iter_mut().find(|c| c.name == "v") {
-            v.text = Some(sst_index.to_string());
-        } else {
-            let mut v_element: XmlElement = XmlElement::new("v");
-            v_element.text = Some(sst_index.to_string());
-            cell_element.children.push(v_element);
-        }
+        Self::write_cached_value(cell_element, Some("s"), sst_index.to_string());
     }
 
     /// セルの値の日時としての設定
     pub fn set_datetime_value(&mut self, value: NaiveDateTime) {
-        // 日時をExcelのシリアル値に変換
-        // https://stackoverflow.com/questions/61546133/int-to-datetime-excel に基づく
-        let excel_epoch: NaiveDateTime = NaiveDate::from_ymd_opt(1899, 12, 30)
-            .unwrap_or_default()
-            .and_hms_opt(0, 0, 0)
-            .unwrap_or_default();
-        let duration = value.signed_duration_since(excel_epoch);
-        let serial: f64 = duration.num_seconds() as f64 / 86400.0;
-        self.set_number_value(serial);
+        self.set_number_value(Self::datetime_to_serial(value));
         // TODO: 日付フォーマットのスタイルを設定
     }
 
@@ -350,32 +468,189 @@ impl Cell {
     pub fn set_bool_value(&mut self, value: bool) {
         let mut xml: MutexGuard<Xml> = self.sheet_xml.lock().expect("Failed to lock sheet xml");
         let cell_element: &mut XmlElement = self.get_or_create_cell_element(&mut xml);
-        cell_element
-            .attributes
-            .insert("t".to_string(), "b".to_string());
-        cell_element.children.retain(|c| c.name != "f");
-        if let Some(v) = cell_element.children.iter_mut().find(|c| c.name == "v") {
-            v.text = Some((if value { "1" } else { "0" }).to_string());
-        } else {
-            let mut v_element: XmlElement = XmlElement::new("v");
-            v_element.text = Some((if value { "1" } else { "0" }).to_string());
-            cell_element.children.push(v_element);
-        }
+        Self::write_cached_value(
+            cell_element,
+            Some("b"),
+            (if value { "1" } else { "0" }).to_string(),
+        );
+    }
+
+    /// セルの値のエラー値としての設定
+    pub fn set_error_value(&mut self, error: &CellError) {
+        let mut xml: MutexGuard<Xml> = self.sheet_xml.lock().expect("Failed to lock sheet xml");
+        let cell_element: &mut XmlElement = self.get_or_create_cell_element(&mut xml);
+        Self::write_cached_value(cell_element, Some("e"), error.code.clone());
     }
 
     /// セルの値の数式としての設定
-    pub fn set_formula_value(&mut self, formula: &str) {
+    ///
+    /// 既存のキャッシュ値は削除し、構文が不正な数式はエラー
+    pub fn set_formula_value(&mut self, formula: &str) -> Result<(), FormulaError> {
+        self.set_formula_with_cache(formula, None, None)
+    }
+
+    /// キャッシュ値付きの数式の設定
+    ///
+    /// `cached_type` はセルの `t` 属性 ("str", "b", "e" または数値の場合は `None`)
+    pub fn set_formula_with_cache(
+        &mut self,
+        formula: &str,
+        cached_value: Option<&str>,
+        cached_type: Option<&str>,
+    ) -> Result<(), FormulaError> {
+        let formula: &str = formula.strip_prefix('=').unwrap_or(formula);
+        formula::parse(formula)?;
         let mut xml: MutexGuard<Xml> = self.sheet_xml.lock().expect("Failed to lock sheet xml");
         let cell_element: &mut XmlElement = self.get_or_create_cell_element(&mut xml);
-        cell_element.attributes.remove("t");
-        cell_element.children.retain(|c| c.name != "v");
-        if let Some(f) = cell_element.children.iter_mut().find(|c| c.name == "f") {
-            f.text = Some(formula.to_string());
+        Self::write_formula(cell_element, formula, cached_value, cached_type);
+        Ok(())
+    }
+
+    /// セル要素への値の書き込み (ロック済みのワークシートと共有文字列への一括書き込み用)
+    ///
+    /// `set_value` と同じく値の型を自動的に検出し、`None` の場合は値と数式を削除して書式のみ残す
+    pub(crate) fn write_value(
+        cell_element: &mut XmlElement,
+        value: Option<&CellValue>,
+        shared_strings: &mut Xml,
+        shared_strings_map: &mut HashMap<String, usize>,
+    ) -> Result<(), FormulaError> {
+        let text: &str = match value {
+            None => {
+                cell_element.attributes.remove("t");
+                cell_element
+                    .children
+                    .retain(|c| c.name != "f" && c.name != "v" && c.name != "is");
+                return Ok(());
+            }
+            Some(CellValue::Error(error)) => {
+                Self::write_cached_value(cell_element, Some("e"), error.code.clone());
+                return Ok(());
+            }
+            Some(CellValue::Text(text)) => text,
+        };
+        if let Some(formula) = text.strip_prefix('=') {
+            formula::parse(formula)?;
+            Self::write_formula(cell_element, formula, None, None);
+        } else if let Ok(num) = text.parse::<f64>() {
+            Self::write_cached_value(cell_element, None, num.to_string());
+        } else if let Ok(b) = text.parse::<bool>() {
+            Self::write_cached_value(
+                cell_element,
+                Some("b"),
+                (if b { "1" } else { "0" }).to_string(),
+            );
+        } else if let Ok(dt) = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S") {
+            Self::write_cached_value(cell_element, None, Self::datetime_to_serial(dt).to_string());
         } else {
-            let mut f_element: XmlElement = XmlElement::new("f");
-            f_element.text = Some(formula.to_string());
-            cell_element.children.push(f_element);
+            let index: usize = Self::intern_shared_string(
+                shared_strings,
+                shared_strings_map,
+                &Self::shared_string_element(text),
+            );
+            Self::write_cached_value(cell_element, Some("s"), index.to_string());
+        }
+        Ok(())
+    }
+
+    /// セル要素の値の取得 (ロック済みの共有文字列からの一括読み込み用)
+    ///
+    /// エラー値のセル (`t="e"`) は `CellError` として返却
+    pub(crate) fn read_value(cell_element: &XmlElement, shared_strings: &Xml) -> Option<CellValue> {
+        let value: String = match cell_element.attributes.get("t").map(String::as_str) {
+            Some("s") => {
+                let idx: usize = cell_element
+                    .find_element("v")?
+                    .text
+                    .as_ref()?
+                    .parse()
+                    .ok()?;
+                let si: &XmlElement = shared_strings.elements.first()?.children.get(idx)?;
+                si.get_element("t").text.clone()?
+            }
+            Some("inlineStr") => cell_element.find_element("is>t")?.text.clone()?,
+            _ => cell_element.find_element("v")?.text.clone()?,
+        };
+        if cell_element.attributes.get("t").map(String::as_str) == Some("e")
+            && let Some(error) = CellError::from_code(&value)
+        {
+            return Some(CellValue::Error(error));
         }
+        Some(CellValue::Text(value))
+    }
+
+    /// セル要素へのキャッシュ値と型 (`t` 属性、数値の場合は `None`) の書き込み
+    ///
+    /// 数式、インライン文字列と動的配列のメタデータ (`cm`) は削除する
+    fn write_cached_value(cell_element: &mut XmlElement, cell_type: Option<&str>, value: String) {
+        cell_element.attributes.remove("cm");
+        match cell_type {
+            Some(t) => {
+                cell_element
+                    .attributes
+                    .insert("t".to_string(), t.to_string());
+            }
+            None => {
+                cell_element.attributes.remove("t");
+            }
+        }
+        cell_element
+            .children
+            .retain(|c| c.name != "f" && c.name != "v" && c.name != "is");
+        let mut v_element: XmlElement = XmlElement::new("v");
+        v_element.text = Some(value);
+        cell_element.children.insert(0, v_element);
+    }
+
+    /// セル要素への数式とキャッシュ値の書き込み (数式の構文は検証済みであること)
+    fn write_formula(
+        cell_element: &mut XmlElement,
+        formula: &str,
+        cached_value: Option<&str>,
+        cached_type: Option<&str>,
+    ) {
+        cell_element
+            .children
+            .retain(|c| c.name != "f" && c.name != "v" && c.name != "is");
+        let mut f_element: XmlElement = XmlElement::new("f");
+        f_element.text = Some(formula.to_string());
+        cell_element.children.insert(0, f_element);
+
+        match cached_type {
+            Some(t) if cached_value.is_some() && t != "n" => {
+                cell_element
+                    .attributes
+                    .insert("t".to_string(), t.to_string());
+            }
+            _ => {
+                cell_element.attributes.remove("t");
+            }
+        }
+        if let Some(value) = cached_value {
+            let mut v_element: XmlElement = XmlElement::new("v");
+            v_element.text = Some(value.to_string());
+            cell_element.children.insert(1, v_element);
+        }
+    }
+
+    /// 日時のExcelのシリアル値への変換
+    fn datetime_to_serial(value: NaiveDateTime) -> f64 {
+        // https://stackoverflow.com/questions/61546133/int-to-datetime-excel に基づく
+        let excel_epoch: NaiveDateTime = NaiveDate::from_ymd_opt(1899, 12, 30)
+            .unwrap_or_default()
+            .and_hms_opt(0, 0, 0)
+            .unwrap_or_default();
+        let duration = value.signed_duration_since(excel_epoch);
+        duration.num_seconds() as f64 / 86400.0
+    }
+
+    /// セルの数式の削除
+    ///
+    /// キャッシュ値はセルの値として保持
+    pub fn remove_formula(&mut self) {
+        let mut xml: MutexGuard<Xml> = self.sheet_xml.lock().expect("Failed to lock sheet xml");
+        let cell_element: &mut XmlElement = self.get_or_create_cell_element(&mut xml);
+        cell_element.children.retain(|c| c.name != "f");
     }
 
     /// ワークシートXML内のセル要素の取得または作成
@@ -391,29 +666,60 @@ impl Cell {
         Self::get_or_create_cell(row, &self.address)
     }
 
-    fn get_or_create_row(sheet_data: &mut XmlElement, row_num: u32) -> &mut XmlElement {
+    /// `sheetData` 内の行要素の取得または作成
+    ///
+    /// 新しい行は行番号の順序を保つ位置に挿入
+    pub(crate) fn get_or_create_row(sheet_data: &mut XmlElement, row_num: u32) -> &mut XmlElement {
+        let r: String = row_num.to_string();
+        let number = |row: &XmlElement| -> u32 {
+            row.attributes
+                .get("r")
+                .and_then(|n| n.parse::<u32>().ok())
+                .unwrap_or(0)
+        };
+        // 行は行番号順に並ぶため二分探索し、見つからない場合のみ全体を探す
         let position = sheet_data
             .children
-            .iter()
-            .position(|r| r.name == "row" && r.attributes.get("r") == Some(&row_num.to_string()));
+            .binary_search_by_key(&row_num, number)
+            .ok()
+            .filter(|&pos| sheet_data.children[pos].name == "row")
+            .or_else(|| {
+                sheet_data
+                    .children
+                    .iter()
+                    .position(|row| row.name == "row" && row.attributes.get("r") == Some(&r))
+            });
 
         if let Some(pos) = position {
             &mut sheet_data.children[pos]
         } else {
             let mut new_row = XmlElement::new("row");
-            new_row
-                .attributes
-                .insert("r".to_string(), row_num.to_string());
-            sheet_data.children.push(new_row);
-            sheet_data.children.last_mut().unwrap()
+            new_row.attributes.insert("r".to_string(), r);
+            let insert_pos: usize = sheet_data
+                .children
+                .iter()
+                .position(|r| {
+                    r.attributes
+                        .get("r")
+                        .and_then(|n| n.parse::<u32>().ok())
+                        .is_some_and(|n| n > row_num)
+                })
+                .unwrap_or(sheet_data.children.len());
+            sheet_data.children.insert(insert_pos, new_row);
+            &mut sheet_data.children[insert_pos]
         }
     }
 
-    fn get_or_create_cell<'a>(row: &'a mut XmlElement, address: &str) -> &'a mut XmlElement {
-        let position = row
-            .children
-            .iter()
-            .position(|c| c.name == "c" && c.attributes.get("r") == Some(&address.to_string()));
+    /// 行要素内のセル要素の取得または作成
+    ///
+    /// 新しいセルは列番号の順序を保つ位置に挿入
+    pub(crate) fn get_or_create_cell<'a>(
+        row: &'a mut XmlElement,
+        address: &str,
+    ) -> &'a mut XmlElement {
+        let position = row.children.iter().position(|c| {
+            c.name == "c" && c.attributes.get("r").map(String::as_str) == Some(address)
+        });
 
         if let Some(pos) = position {
             &mut row.children[pos]
@@ -422,40 +728,72 @@ impl Cell {
             new_cell
                 .attributes
                 .insert("r".to_string(), address.to_string());
-            row.children.push(new_cell);
-            row.children.last_mut().unwrap()
+            let col: u32 = parse_address(address).map_or(0, |(_, col)| col);
+            let insert_pos: usize = row
+                .children
+                .iter()
+                .position(|c| {
+                    c.attributes
+                        .get("r")
+                        .and_then(|r| parse_address(r))
+                        .is_some_and(|(_, c)| c > col)
+                })
+                .unwrap_or(row.children.len());
+            row.children.insert(insert_pos, new_cell);
+            &mut row.children[insert_pos]
         }
     }
 
-    /// 共有文字列XML内の共有文字列の取得または作成
-    fn get_or_create_shared_string(&mut self, text: &str) -> usize {
-        let mut shared_strings_xml: MutexGuard<Xml> = self
-            .shared_strings
-            .lock()
-            .expect("Failed to lock shared strings");
-
-        if shared_strings_xml.elements.is_empty() {
-            shared_strings_xml.elements.push(XmlElement::new("sst"));
-        }
-        let sst_element: &mut XmlElement = shared_strings_xml
-            .elements
-            .first_mut()
-            .expect("sst element not found in shared strings");
-
-        if let Some(index) = sst_element
-            .children
-            .iter()
-            .position(|si| si.children.first().and_then(|t| t.text.as_deref()) == Some(text))
+    /// 共有文字列の項目 (`si`) の追加とインデックスの取得
+    ///
+    /// 書式のない文字列は対応表から既存の項目を探し、追加した項目も対応表に登録する
+    pub(crate) fn intern_shared_string(
+        shared_strings: &mut Xml,
+        map: &mut HashMap<String, usize>,
+        si: &XmlElement,
+    ) -> usize {
+        let plain: Option<String> = Self::plain_shared_string(si);
+        if let Some(text) = &plain
+            && let Some(&index) = map.get(text)
         {
             return index;
         }
+        if shared_strings.elements.is_empty() {
+            let mut sst: XmlElement = XmlElement::new("sst");
+            sst.attributes.insert(
+                "xmlns".to_string(),
+                "http://schemas.openxmlformats.org/spreadsheetml/2006/main".to_string(),
+            );
+            shared_strings.elements.push(sst);
+        }
+        let sst: &mut XmlElement = &mut shared_strings.elements[0];
+        sst.children.push(si.clone());
+        let count: usize = sst.children.len();
+        sst.attributes
+            .insert("count".to_string(), count.to_string());
+        sst.attributes
+            .insert("uniqueCount".to_string(), count.to_string());
+        if let Some(text) = plain {
+            map.insert(text, count - 1);
+        }
+        count - 1
+    }
+
+    /// 書式のない共有文字列の項目のテキスト (書式付きの項目は `None`)
+    pub(crate) fn plain_shared_string(si: &XmlElement) -> Option<String> {
+        match si.children.as_slice() {
+            [t] if t.name == "t" => Some(t.text.clone().unwrap_or_default()),
+            _ => None,
+        }
+    }
 
-        let mut t_element: XmlElement = XmlElement::new("t");
-        t_element.text = Some(text.to_string());
-        let mut si_element: XmlElement = XmlElement::new("si");
-        si_element.children.push(t_element);
-        sst_element.children.push(si_element);
-        sst_element.children.len() - 1
+    /// 文字列の共有文字列の項目 (`<si><t>...</t></si>`)
+    pub(crate) fn shared_string_element(text: &str) -> XmlElement {
+        let mut t: XmlElement = XmlElement::new("t");
+        t.text = Some(text.to_string());
+        let mut si: XmlElement = XmlElement::new("si");
+        si.children.push(t);
+        si
     }
 
     /// セルアドレス (例: "A1") の行と列の番号へのデコード
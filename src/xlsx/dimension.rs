@@ -0,0 +1,472 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::cell::Cell;
+use crate::formula::{MAX_COL, MAX_ROW, string_to_col};
+use crate::sheet::WORKSHEET_CHILD_ORDER;
+use crate::xml::{Xml, XmlElement};
+
+/// `sheetFormatPr` がない場合の既定の行の高さ (ポイント)
+pub const DEFAULT_ROW_HEIGHT: f64 = 15.0;
+/// `sheetFormatPr` がない場合の既定の列の幅 (文字数)
+pub const DEFAULT_COLUMN_WIDTH: f64 = 8.43;
+
+/// ワークシートの行の設定の一覧 (`sheet.row_dimensions[n]`)
+#[pyclass]
+pub struct RowDimensions {
+    /// ワークシートのXML
+    xml: Arc<Mutex<Xml>>,
+}
+
+#[pymethods]
+impl RowDimensions {
+    /// 行番号 (1始まり) による行の設定の取得
+    pub fn __getitem__(&self, index: u32) -> PyResult<RowDimension> {
+        if !(1..=MAX_ROW).contains(&index) {
+            return Err(PyValueError::new_err(format!("Invalid row index: {index}")));
+        }
+        Ok(RowDimension {
+            xml: self.xml.clone(),
+            index,
+        })
+    }
+
+    /// 既定の行の高さ (`sheetFormatPr@defaultRowHeight`)
+    #[getter]
+    pub fn get_default_height(&self) -> f64 {
+        sheet_format_attribute(&self.xml, "defaultRowHeight").unwrap_or(DEFAULT_ROW_HEIGHT)
+    }
+
+    #[setter(default_height)]
+    pub fn set_default_height(&self, height: f64) {
+        set_sheet_format_attribute(&self.xml, "defaultRowHeight", height);
+    }
+}
+
+impl RowDimensions {
+    /// 新しい `RowDimensions` インスタンスの作成
+    pub fn new(xml: Arc<Mutex<Xml>>) -> Self {
+        RowDimensions { xml }
+    }
+}
+
+/// 行の設定 (`<row ht hidden outlineLevel s>`)
+#[pyclass]
+pub struct RowDimension {
+    /// ワークシートのXML
+    xml: Arc<Mutex<Xml>>,
+    /// 行番号 (1始まり)
+    #[pyo3(get)]
+    index: u32,
+}
+
+#[pymethods]
+impl RowDimension {
+    /// 行の高さ (ポイント、未設定の場合は `None`)
+    #[getter]
+    pub fn get_height(&self) -> Option<f64> {
+        self.attribute("ht")?.parse().ok()
+    }
+
+    #[setter(height)]
+    pub fn set_height(&self, height: Option<f64>) {
+        self.update(|row| {
+            set_flagged(row, "ht", height.map(|h| h.to_string()), "customHeight");
+        });
+    }
+
+    /// 非表示かどうか
+    #[getter]
+    pub fn get_hidden(&self) -> bool {
+        self.attribute("hidden").is_some_and(|h| is_true(&h))
+    }
+
+    #[setter(hidden)]
+    pub fn set_hidden(&self, hidden: bool) {
+        self.update(|row| set_bool(row, "hidden", hidden));
+    }
+
+    /// アウトラインのレベル (0 から 7)
+    #[getter]
+    pub fn get_outline_level(&self) -> u8 {
+        self.attribute("outlineLevel")
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(0)
+    }
+
+    #[setter(outline_level)]
+    pub fn set_outline_level(&self, level: u8) -> PyResult<()> {
+        validate_outline_level(level)?;
+        self.update(|row| set_outline_level(row, level));
+        Ok(())
+    }
+
+    /// 行の既定のスタイル (`cellXfs` のインデックス)
+    #[getter]
+    pub fn get_style(&self) -> Option<u32> {
+        self.attribute("s")?.parse().ok()
+    }
+
+    #[setter(style)]
+    pub fn set_style(&self, style: Option<u32>) {
+        self.update(|row| {
+            set_flagged(row, "s", style.map(|s| s.to_string()), "customFormat");
+        });
+    }
+}
+
+impl RowDimension {
+    /// 行要素の属性の取得
+    fn attribute(&self, key: &str) -> Option<String> {
+        let xml: MutexGuard<Xml> = self.xml.lock().unwrap();
+        let index: String = self.index.to_string();
+        xml.elements
+            .first()?
+            .find_element("sheetData")?
+            .children
+            .iter()
+            .find(|r| r.get_attribute("r") == Some(&index))?
+            .get_attribute(key)
+            .cloned()
+    }
+
+    /// 行要素の更新 (存在しない場合は作成)
+    ///
+    /// 更新後にセルも設定もない行要素は除去
+    fn update(&self, f: impl FnOnce(&mut XmlElement)) {
+        let mut xml: MutexGuard<Xml> = self.xml.lock().unwrap();
+        if let Some(worksheet) = xml.elements.first_mut() {
+            let sheet_data: &mut XmlElement =
+                worksheet.get_or_create_child_ordered("sheetData", &WORKSHEET_CHILD_ORDER);
+            let row: &mut XmlElement = Cell::get_or_create_row(sheet_data, self.index);
+            f(row);
+            let empty: bool = row.children.is_empty()
+                && row
+                    .attributes
+                    .keys()
+                    .all(|k| matches!(k.as_str(), "r" | "spans"));
+            if empty {
+                let index: String = self.index.to_string();
+                sheet_data
+                    .children
+                    .retain(|r| r.get_attribute("r") != Some(&index) || !r.children.is_empty());
+            }
+        }
+    }
+}
+
+/// ワークシートの列の設定の一覧 (`sheet.column_dimensions["B"]`)
+#[pyclass]
+pub struct ColumnDimensions {
+    /// ワークシートのXML
+    xml: Arc<Mutex<Xml>>,
+}
+
+#[pymethods]
+impl ColumnDimensions {
+    /// 列名 (例: "B") による列の設定の取得
+    pub fn __getitem__(&self, key: &str) -> PyResult<ColumnDimension> {
+        let index: u32 = string_to_col(key)
+            .ok_or_else(|| PyValueError::new_err(format!("Invalid column: {key}")))?;
+        Ok(ColumnDimension {
+            xml: self.xml.clone(),
+            index,
+        })
+    }
+
+    /// 既定の列の幅 (`sheetFormatPr@defaultColWidth`)
+    #[getter]
+    pub fn get_default_width(&self) -> f64 {
+        sheet_format_attribute(&self.xml, "defaultColWidth").unwrap_or(DEFAULT_COLUMN_WIDTH)
+    }
+
+    #[setter(default_width)]
+    pub fn set_default_width(&self, width: f64) {
+        set_sheet_format_attribute(&self.xml, "defaultColWidth", width);
+    }
+}
+
+impl ColumnDimensions {
+    /// 新しい `ColumnDimensions` インスタンスの作成
+    pub fn new(xml: Arc<Mutex<Xml>>) -> Self {
+        ColumnDimensions { xml }
+    }
+}
+
+/// 列の設定 (`<col min max width hidden bestFit outlineLevel style>`)
+#[pyclass]
+pub struct ColumnDimension {
+    /// ワークシートのXML
+    xml: Arc<Mutex<Xml>>,
+    /// 列番号 (1始まり)
+    #[pyo3(get)]
+    index: u32,
+}
+
+#[pymethods]
+impl ColumnDimension {
+    /// 列の幅 (文字数、未設定の場合は `None`)
+    #[getter]
+    pub fn get_width(&self) -> Option<f64> {
+        self.attribute("width")?.parse().ok()
+    }
+
+    #[setter(width)]
+    pub fn set_width(&self, width: Option<f64>) {
+        self.update(|col| {
+            set_flagged(col, "width", width.map(|w| w.to_string()), "customWidth");
+        });
+    }
+
+    /// 非表示かどうか
+    #[getter]
+    pub fn get_hidden(&self) -> bool {
+        self.attribute("hidden").is_some_and(|h| is_true(&h))
+    }
+
+    #[setter(hidden)]
+    pub fn set_hidden(&self, hidden: bool) {
+        self.update(|col| set_bool(col, "hidden", hidden));
+    }
+
+    /// 内容に合わせた幅かどうか
+    #[getter]
+    pub fn get_best_fit(&self) -> bool {
+        self.attribute("bestFit").is_some_and(|b| is_true(&b))
+    }
+
+    #[setter(best_fit)]
+    pub fn set_best_fit(&self, best_fit: bool) {
+        self.update(|col| set_bool(col, "bestFit", best_fit));
+    }
+
+    /// アウトラインのレベル (0 から 7)
+    #[getter]
+    pub fn get_outline_level(&self) -> u8 {
+        self.attribute("outlineLevel")
+            .and_then(|l| l.parse().ok())
+            .unwrap_or(0)
+    }
+
+    #[setter(outline_level)]
+    pub fn set_outline_level(&self, level: u8) -> PyResult<()> {
+        validate_outline_level(level)?;
+        self.update(|col| set_outline_level(col, level));
+        Ok(())
+    }
+
+    /// 列の既定のスタイル (`cellXfs` のインデックス)
+    #[getter]
+    pub fn get_style(&self) -> Option<u32> {
+        self.attribute("style")?.parse().ok()
+    }
+
+    #[setter(style)]
+    pub fn set_style(&self, style: Option<u32>) {
+        self.update(|col| match style {
+            Some(style) => {
+                col.attributes
+                    .insert("style".to_string(), style.to_string());
+            }
+            None => {
+                col.attributes.remove("style");
+            }
+        });
+    }
+}
+
+impl ColumnDimension {
+    /// 列を含む `col` 要素の属性の取得
+    fn attribute(&self, key: &str) -> Option<String> {
+        let xml: MutexGuard<Xml> = self.xml.lock().unwrap();
+        xml.elements
+            .first()?
+            .find_element("cols")?
+            .children
+            .iter()
+            .find(|col| col_span(col).is_some_and(|(min, max)| (min..=max).contains(&self.index)))?
+            .get_attribute(key)
+            .cloned()
+    }
+
+    /// 列の `col` 要素の更新
+    fn update(&self, f: impl FnOnce(&mut XmlElement)) {
+        let mut xml: MutexGuard<Xml> = self.xml.lock().unwrap();
+        if let Some(worksheet) = xml.elements.first_mut() {
+            update_column(worksheet, self.index, f);
+        }
+    }
+}
+
+/// 列の `col` 要素の更新
+///
+/// 列を含む範囲は列の前後で分割し、更新後は同じ設定の隣接する範囲を結合
+pub(crate) fn update_column(
+    worksheet: &mut XmlElement,
+    index: u32,
+    f: impl FnOnce(&mut XmlElement),
+) {
+    let cols: &mut XmlElement =
+        worksheet.get_or_create_child_ordered("cols", &WORKSHEET_CHILD_ORDER);
+    let position: usize = match cols
+        .children
+        .iter()
+        .position(|col| col_span(col).is_some_and(|(min, max)| (min..=max).contains(&index)))
+    {
+        Some(pos) => {
+            let (min, max) = col_span(&cols.children[pos]).unwrap_or((index, index));
+            let original: XmlElement = cols.children.remove(pos);
+            let mut pieces: Vec<XmlElement> = Vec::with_capacity(3);
+            for (start, end) in [(min, index - 1), (index, index), (index + 1, max)] {
+                if start <= end {
+                    let mut piece: XmlElement = original.clone();
+                    set_col_span(&mut piece, start, end);
+                    pieces.push(piece);
+                }
+            }
+            let target: usize = pos + usize::from(min < index);
+            cols.children.splice(pos..pos, pieces);
+            target
+        }
+        None => {
+            let mut col: XmlElement = XmlElement::new("col");
+            set_col_span(&mut col, index, index);
+            let pos: usize = cols
+                .children
+                .iter()
+                .position(|c| col_span(c).is_some_and(|(min, _)| min > index))
+                .unwrap_or(cols.children.len());
+            cols.children.insert(pos, col);
+            pos
+        }
+    };
+    f(&mut cols.children[position]);
+    normalize_columns(cols);
+    if cols.children.is_empty() {
+        worksheet.children.retain(|c| c.name != "cols");
+    }
+}
+
+/// 設定のない `col` 要素の除去と同じ設定の隣接する範囲の結合
+fn normalize_columns(cols: &mut XmlElement) {
+    let same_settings = |a: &XmlElement, b: &XmlElement| {
+        let settings = |col: &XmlElement| {
+            let mut attributes: Vec<(String, String)> = col
+                .attributes
+                .iter()
+                .filter(|(k, _)| k.as_str() != "min" && k.as_str() != "max")
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+            attributes.sort();
+            attributes
+        };
+        settings(a) == settings(b)
+    };
+    cols.children.retain(|col| col.attributes.len() > 2);
+    let mut merged: Vec<XmlElement> = Vec::with_capacity(cols.children.len());
+    for col in cols.children.drain(..) {
+        if let Some(last) = merged.last_mut()
+            && let (Some((min, max)), Some((next_min, next_max))) = (col_span(last), col_span(&col))
+            && max + 1 == next_min
+            && same_settings(last, &col)
+        {
+            set_col_span(last, min, next_max);
+            continue;
+        }
+        merged.push(col);
+    }
+    cols.children = merged;
+}
+
+/// `col` 要素の列の範囲 (`min`, `max`)
+pub(crate) fn col_span(col: &XmlElement) -> Option<(u32, u32)> {
+    let min: u32 = col.get_attribute("min")?.parse().ok()?;
+    let max: u32 = col.get_attribute("max")?.parse().ok()?;
+    Some((min, max.min(MAX_COL)))
+}
+
+/// `col` 要素の列の範囲の設定
+fn set_col_span(col: &mut XmlElement, min: u32, max: u32) {
+    col.attributes.insert("min".to_string(), min.to_string());
+    col.attributes.insert("max".to_string(), max.to_string());
+}
+
+/// 値と、値が設定されていることを示すフラグ属性 (例: `customHeight`) の設定
+fn set_flagged(element: &mut XmlElement, key: &str, value: Option<String>, flag: &str) {
+    match value {
+        Some(value) => {
+            element.attributes.insert(key.to_string(), value);
+            element.attributes.insert(flag.to_string(), "1".to_string());
+        }
+        None => {
+            element.attributes.remove(key);
+            element.attributes.remove(flag);
+        }
+    }
+}
+
+/// 真偽値の属性の設定 (偽の場合は属性を削除)
+fn set_bool(element: &mut XmlElement, key: &str, value: bool) {
+    if value {
+        element.attributes.insert(key.to_string(), "1".to_string());
+    } else {
+        element.attributes.remove(key);
+    }
+}
+
+/// アウトラインのレベルの設定 (0 の場合は属性を削除)
+fn set_outline_level(element: &mut XmlElement, level: u8) {
+    if level == 0 {
+        element.attributes.remove("outlineLevel");
+    } else {
+        element
+            .attributes
+            .insert("outlineLevel".to_string(), level.to_string());
+    }
+}
+
+/// アウトラインのレベルの検証
+fn validate_outline_level(level: u8) -> PyResult<()> {
+    if level > 7 {
+        return Err(PyValueError::new_err(format!(
+            "Outline level must be between 0 and 7: {level}"
+        )));
+    }
+    Ok(())
+}
+
+/// XMLの真偽値の判定
+fn is_true(value: &str) -> bool {
+    value == "1" || value.eq_ignore_ascii_case("true")
+}
+
+/// `sheetFormatPr` の数値属性の取得
+fn sheet_format_attribute(xml: &Arc<Mutex<Xml>>, key: &str) -> Option<f64> {
+    xml.lock()
+        .unwrap()
+        .elements
+        .first()?
+        .find_element("sheetFormatPr")?
+        .get_attribute(key)?
+        .parse()
+        .ok()
+}
+
+/// `sheetFormatPr` の数値属性の設定
+///
+/// `defaultRowHeight` は必須属性のため、要素の作成時に既定値を設定
+fn set_sheet_format_attribute(xml: &Arc<Mutex<Xml>>, key: &str, value: f64) {
+    let mut xml: MutexGuard<Xml> = xml.lock().unwrap();
+    let Some(worksheet) = xml.elements.first_mut() else {
+        return;
+    };
+    let format: &mut XmlElement =
+        worksheet.get_or_create_child_ordered("sheetFormatPr", &WORKSHEET_CHILD_ORDER);
+    format
+        .attributes
+        .entry("defaultRowHeight".to_string())
+        .or_insert_with(|| DEFAULT_ROW_HEIGHT.to_string());
+    format.attributes.insert(key.to_string(), value.to_string());
+}
@@ -0,0 +1,11 @@
+from ._core import FormulaNode, parse_formula, tokenize_formula
+
+__all__ = ["FormulaNode", "parse", "tokenize"]
+
+
+def parse(formula: str, r1c1: bool = False) -> FormulaNode:
+    return parse_formula(formula, r1c1)
+
+
+def tokenize(formula: str, r1c1: bool = False) -> list[tuple[str, str]]:
+    return tokenize_formula(formula, r1c1)
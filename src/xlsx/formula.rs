@@ -0,0 +1,1762 @@
+use crate::cell::ERROR_CODES;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use std::fmt;
+use thiserror::Error;
+
+/// Excelの最大行数
+pub const MAX_ROW: u32 = 1_048_576;
+/// Excelの最大列数
+pub const MAX_COL: u32 = 16_384;
+
+/// 列番号のアルファベットへの変換 (例: 1 -> "A")
+pub fn col_to_string(col: u32) -> String {
+    let mut result: String = String::new();
+    let mut n: u32 = col;
+    while n > 0 {
+        let rem: u32 = (n - 1) % 26;
+        result.insert(0, (b'A' + rem as u8) as char);
+        n = (n - 1) / 26;
+    }
+    result
+}
+
+/// アルファベットの列番号への変換 (例: "A" -> 1)
+///
+/// 列として不正な文字列の場合は `None`
+pub fn string_to_col(s: &str) -> Option<u32> {
+    if s.is_empty() || s.len() > 3 || !s.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let col: u32 = s
+        .to_ascii_uppercase()
+        .bytes()
+        .fold(0, |acc, b| acc * 26 + (b - b'A' + 1) as u32);
+    (col <= MAX_COL).then_some(col)
+}
+
+/// セルアドレス (例: "B3", "$B$3") の行と列の番号への変換
+pub fn parse_address(address: &str) -> Option<(u32, u32)> {
+    let address: String = address.replace('$', "");
+    let split: usize = address.find(|c: char| c.is_ascii_digit())?;
+    let (col_str, row_str) = address.split_at(split);
+    let col: u32 = string_to_col(col_str)?;
+    let row: u32 = row_str.parse().ok()?;
+    (1..=MAX_ROW).contains(&row).then_some((row, col))
+}
+
+/// 範囲アドレス (例: "A1:C3") の左上と右下の行と列の番号への変換
+///
+/// 単一セルのアドレスは1セルの範囲として扱う
+pub fn parse_range(range: &str) -> Option<((u32, u32), (u32, u32))> {
+    let (start, end) = range.split_once(':').unwrap_or((range, range));
+    let (r1, c1) = parse_address(start)?;
+    let (r2, c2) = parse_address(end)?;
+    Some(((r1.min(r2), c1.min(c2)), (r1.max(r2), c1.max(c2))))
+}
+
+/// 行と列の番号のセルアドレス文字列への変換
+pub fn to_address(row: u32, col: u32) -> String {
+    format!("{}{}", col_to_string(col), row)
+}
+
+/// 数式内の相対参照の移動
+///
+/// `$` で固定されていない行と列を `row_delta` と `col_delta` だけずらす。
+/// 共有数式の展開や数式のコピーで使用し、範囲外になった参照は `#REF!` に置き換える
+pub fn translate(formula: &str, row_delta: i64, col_delta: i64) -> String {
+    let Ok(tokens) = tokenize(formula) else {
+        return formula.to_string();
+    };
+    tokens
+        .iter()
+        .map(|token| match token.kind {
+            TokenKind::Reference => {
+                let (prefix, body) = split_sheet_prefix(&token.text);
+                let parts: Vec<&str> = body.split(':').collect();
+                let in_range: bool = parts.len() > 1;
+                let shifted: Vec<String> = parts
+                    .iter()
+                    .map(|part| shift_word(part, in_range, row_delta, col_delta))
+                    .collect();
+                if shifted.iter().any(|part| part == "#REF!") {
+                    format!("{prefix}#REF!")
+                } else {
+                    format!("{prefix}{}", shifted.join(":"))
+                }
+            }
+            _ => token.text.clone(),
+        })
+        .collect()
+}
+
+/// 参照テキストのシート接頭辞 (`!` を含む) と本体への分割
+pub fn split_sheet_prefix(text: &str) -> (&str, &str) {
+    let search: &str = text.strip_suffix("#REF!").unwrap_or(text);
+    match search.rfind('!') {
+        Some(pos) => text.split_at(pos + 1),
+        None => ("", text),
+    }
+}
+
+/// 参照の構成要素として扱う文字かどうか
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '$' | '.' | '\\' | '?')
+}
+
+/// 引用符で囲まれた部分の終端位置の取得
+///
+/// 引用符の二重化 (`""` や `''`) はエスケープとして扱い、閉じられていない場合は `None`
+fn skip_quoted(chars: &[char], start: usize, quote: char) -> Option<usize> {
+    let mut i: usize = start + 1;
+    while i < chars.len() {
+        if chars[i] == quote {
+            if chars.get(i + 1) == Some(&quote) {
+                i += 2;
+                continue;
+            }
+            return Some(i + 1);
+        }
+        i += 1;
+    }
+    None
+}
+
+/// 角括弧で囲まれた部分の終端位置の取得 (入れ子に対応)
+///
+/// 閉じられていない場合は `None`
+fn skip_brackets(chars: &[char], start: usize) -> Option<usize> {
+    let mut depth: usize = 0;
+    let mut i: usize = start;
+    while i < chars.len() {
+        match chars[i] {
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            // 構造化参照内の `'` は次の文字のエスケープ
+            '\'' => i += 1,
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// 参照と判定された語の移動
+///
+/// セル参照でも行・列の範囲でもない語はそのまま返却
+fn shift_word(word: &str, in_range: bool, row_delta: i64, col_delta: i64) -> String {
+    if let Some((col_abs, col, row_abs, row)) = split_cell_ref(word) {
+        let new_col: Option<u32> = shift_index(col, col_abs, col_delta, MAX_COL);
+        let new_row: Option<u32> = shift_index(row, row_abs, row_delta, MAX_ROW);
+        return match (new_col, new_row) {
+            (Some(c), Some(r)) => format!(
+                "{}{}{}{}",
+                if col_abs { "$" } else { "" },
+                col_to_string(c),
+                if row_abs { "$" } else { "" },
+                r
+            ),
+            _ => "#REF!".to_string(),
+        };
+    }
+    if in_range {
+        let (abs, body) = match word.strip_prefix('$') {
+            Some(body) => (true, body),
+            None => (false, word),
+        };
+        if let Ok(row) = body.parse::<u32>() {
+            return shift_index(row, abs, row_delta, MAX_ROW).map_or_else(
+                || "#REF!".to_string(),
+                |r| format!("{}{r}", if abs { "$" } else { "" }),
+            );
+        }
+        if let Some(col) = string_to_col(body) {
+            return shift_index(col, abs, col_delta, MAX_COL).map_or_else(
+                || "#REF!".to_string(),
+                |c| format!("{}{}", if abs { "$" } else { "" }, col_to_string(c)),
+            );
+        }
+    }
+    word.to_string()
+}
+
+/// セル参照 (例: "$A1") の列の固定, 列番号, 行の固定, 行番号への分解
+pub(crate) fn split_cell_ref(word: &str) -> Option<(bool, u32, bool, u32)> {
+    let (col_abs, rest) = match word.strip_prefix('$') {
+        Some(rest) => (true, rest),
+        None => (false, word),
+    };
+    let split: usize = rest.find(|c: char| !c.is_ascii_alphabetic())?;
+    let (col_str, rest) = rest.split_at(split);
+    let (row_abs, row_str) = match rest.strip_prefix('$') {
+        Some(row_str) => (true, row_str),
+        None => (false, rest),
+    };
+    if row_str.is_empty() || !row_str.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let col: u32 = string_to_col(col_str)?;
+    let row: u32 = row_str.parse().ok()?;
+    (1..=MAX_ROW)
+        .contains(&row)
+        .then_some((col_abs, col, row_abs, row))
+}
+
+/// 行または列の番号の移動
+///
+/// 固定されている場合は移動せず、範囲外になる場合は `None`
+fn shift_index(index: u32, absolute: bool, delta: i64, max: u32) -> Option<u32> {
+    if absolute {
+        return Some(index);
+    }
+    let shifted: i64 = index as i64 + delta;
+    (1..=max as i64)
+        .contains(&shifted)
+        .then_some(shifted as u32)
+}
+
+/// 行または列の挿入・削除の方向
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    /// 行
+    Row,
+    /// 列
+    Column,
+}
+
+impl Axis {
+    /// 方向ごとの最大の番号
+    pub fn max(self) -> u32 {
+        match self {
+            Axis::Row => MAX_ROW,
+            Axis::Column => MAX_COL,
+        }
+    }
+}
+
+/// 挿入・削除に伴う番号の範囲 (`start..=end`) の移動
+///
+/// `amount` が正の場合は `index` の位置への挿入、負の場合は `index` からの削除。
+/// 範囲全体が削除される場合や、シートの外に押し出される場合は `None`
+pub fn shift_span(start: u32, end: u32, index: u32, amount: i64, max: u32) -> Option<(u32, u32)> {
+    if amount >= 0 {
+        let shift = |i: u32| -> i64 {
+            if i >= index {
+                i as i64 + amount
+            } else {
+                i as i64
+            }
+        };
+        let start: i64 = shift(start);
+        if start > max as i64 {
+            return None;
+        }
+        return Some((start as u32, shift(end).min(max as i64) as u32));
+    }
+    let count: u32 = amount.unsigned_abs().min(max as u64) as u32;
+    let last: u32 = index.saturating_add(count - 1);
+    let new_start: u32 = match start {
+        s if s < index => s,
+        s if s > last => s - count,
+        _ => index,
+    };
+    let new_end: u32 = match end {
+        e if e < index => e,
+        e if e > last => e - count,
+        _ => index - 1,
+    };
+    (new_start <= new_end).then_some((new_start, new_end))
+}
+
+/// 領域の端点 (セル, 列, 行のいずれか) の列と行 (固定の有無と番号)
+#[derive(Clone, Copy)]
+struct AreaPart {
+    col: Option<(bool, u32)>,
+    row: Option<(bool, u32)>,
+}
+
+impl AreaPart {
+    /// 端点の解析 (例: "$A1", "B", "$3")
+    fn parse(text: &str) -> Option<Self> {
+        if let Some((col_abs, col, row_abs, row)) = split_cell_ref(text) {
+            return Some(AreaPart {
+                col: Some((col_abs, col)),
+                row: Some((row_abs, row)),
+            });
+        }
+        let (abs, body) = match text.strip_prefix('$') {
+            Some(body) => (true, body),
+            None => (false, text),
+        };
+        if let Ok(row) = body.parse::<u32>() {
+            return (1..=MAX_ROW).contains(&row).then_some(AreaPart {
+                col: None,
+                row: Some((abs, row)),
+            });
+        }
+        string_to_col(body).map(|col| AreaPart {
+            col: Some((abs, col)),
+            row: None,
+        })
+    }
+
+    /// 指定した方向の座標の可変参照
+    fn coord_mut(&mut self, axis: Axis) -> &mut Option<(bool, u32)> {
+        match axis {
+            Axis::Row => &mut self.row,
+            Axis::Column => &mut self.col,
+        }
+    }
+}
+
+impl fmt::Display for AreaPart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dollar = |abs: bool| if abs { "$" } else { "" };
+        if let Some((abs, col)) = self.col {
+            write!(f, "{}{}", dollar(abs), col_to_string(col))?;
+        }
+        if let Some((abs, row)) = self.row {
+            write!(f, "{}{row}", dollar(abs))?;
+        }
+        Ok(())
+    }
+}
+
+/// 行または列の挿入・削除に伴うA1形式の領域 (例: "A1", "$A$1:B3", "A:C", "1:3") の移動
+///
+/// 固定 (`$`) の有無に関わらず移動し、領域全体が削除される場合は `None`。
+/// 領域として解釈できないテキストはそのまま返却
+pub fn shift_area_text(body: &str, axis: Axis, index: u32, amount: i64) -> Option<String> {
+    let (first, last) = body.split_once(':').unwrap_or((body, body));
+    let (Some(mut start), Some(mut end)) = (AreaPart::parse(first), AreaPart::parse(last)) else {
+        return Some(body.to_string());
+    };
+    if let (Some((_, a)), Some((_, b))) = (start.coord_mut(axis), end.coord_mut(axis)) {
+        let (lo, hi) = shift_span((*a).min(*b), (*a).max(*b), index, amount, axis.max())?;
+        if *a <= *b {
+            (*a, *b) = (lo, hi);
+        } else {
+            (*a, *b) = (hi, lo);
+        }
+    }
+    Some(if body.contains(':') {
+        format!("{start}:{end}")
+    } else {
+        start.to_string()
+    })
+}
+
+/// 行または列の挿入・削除に伴う数式内の参照の移動
+///
+/// `formula_sheet` は数式のあるシート、`edited_sheet` は行や列を編集したシートの名前。
+/// 編集したシートを指す参照のみ移動し、削除された参照は `#REF!` に置き換える
+pub fn shift_formula(
+    formula: &str,
+    formula_sheet: &str,
+    edited_sheet: &str,
+    axis: Axis,
+    index: u32,
+    amount: i64,
+) -> String {
+    let Ok(tokens) = tokenize(formula) else {
+        return formula.to_string();
+    };
+    let edited: String = edited_sheet.to_lowercase();
+    tokens
+        .iter()
+        .map(|token| {
+            if token.kind != TokenKind::Reference {
+                return token.text.clone();
+            }
+            let (prefix, body) = split_sheet_prefix(&token.text);
+            let (workbook, sheet, last_sheet) = parse_sheet_prefix(prefix);
+            let target: String = sheet.unwrap_or_else(|| formula_sheet.to_string());
+            if workbook.is_some()
+                || last_sheet.is_some()
+                || target.to_lowercase() != edited
+                || body.ends_with("#REF!")
+            {
+                return token.text.clone();
+            }
+            match shift_area_text(body, axis, index, amount) {
+                Some(shifted) => format!("{prefix}{shifted}"),
+                None => format!("{prefix}#REF!"),
+            }
+        })
+        .collect()
+}
+
+/// シート名の変更に伴う数式内の参照の書き換え
+///
+/// `old_name` のシートを指す参照 (3D参照の端を含む) のシート名を `new_name` に置き換える。
+/// シート名の大文字と小文字は区別せず、外部参照は変更しない
+pub fn rename_sheet_in_formula(formula: &str, old_name: &str, new_name: &str) -> String {
+    let Ok(tokens) = tokenize(formula) else {
+        return formula.to_string();
+    };
+    let old: String = old_name.to_lowercase();
+    tokens
+        .iter()
+        .map(|token| {
+            if !matches!(token.kind, TokenKind::Reference | TokenKind::Name) {
+                return token.text.clone();
+            }
+            let (prefix, body) = split_sheet_prefix(&token.text);
+            let (workbook, Some(sheet), last_sheet) = parse_sheet_prefix(prefix) else {
+                return token.text.clone();
+            };
+            let rename = |name: String| {
+                if name.to_lowercase() == old {
+                    new_name.to_string()
+                } else {
+                    name
+                }
+            };
+            if workbook.is_some()
+                || (sheet.to_lowercase() != old
+                    && last_sheet.as_ref().is_none_or(|l| l.to_lowercase() != old))
+            {
+                return token.text.clone();
+            }
+            let prefix: String = match last_sheet {
+                Some(last) => {
+                    let (first, last) = (rename(sheet), rename(last));
+                    let plain: bool =
+                        quote_sheet_name(&first) == first && quote_sheet_name(&last) == last;
+                    let range: String = format!("{first}:{last}");
+                    if plain {
+                        range
+                    } else {
+                        format!("'{}'", range.replace('\'', "''"))
+                    }
+                }
+                None => quote_sheet_name(&rename(sheet)),
+            };
+            format!("{prefix}!{body}")
+        })
+        .collect()
+}
+
+/// 数式の解析エラー
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum FormulaError {
+    #[error("Unterminated string at position {0}")]
+    UnterminatedString(usize),
+    #[error("Unexpected character '{1}' at position {0}")]
+    UnexpectedChar(usize, char),
+    #[error("Unexpected token: {0}")]
+    UnexpectedToken(String),
+    #[error("Unexpected end of formula")]
+    UnexpectedEnd,
+    #[error("Array constant rows must have the same number of columns")]
+    InvalidArray,
+}
+
+impl From<FormulaError> for PyErr {
+    fn from(err: FormulaError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// 数式の字句の種類
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Number,
+    Text,
+    Bool,
+    Error,
+    Reference,
+    Name,
+    Function,
+    StructuredReference,
+    Operator,
+    OpenParen,
+    CloseParen,
+    OpenBrace,
+    CloseBrace,
+    Comma,
+    Semicolon,
+    Whitespace,
+}
+
+impl TokenKind {
+    /// 字句の種類の名前
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenKind::Number => "number",
+            TokenKind::Text => "text",
+            TokenKind::Bool => "bool",
+            TokenKind::Error => "error",
+            TokenKind::Reference => "reference",
+            TokenKind::Name => "name",
+            TokenKind::Function => "function",
+            TokenKind::StructuredReference => "structured_reference",
+            TokenKind::Operator => "operator",
+            TokenKind::OpenParen => "open_paren",
+            TokenKind::CloseParen => "close_paren",
+            TokenKind::OpenBrace => "open_brace",
+            TokenKind::CloseBrace => "close_brace",
+            TokenKind::Comma => "comma",
+            TokenKind::Semicolon => "semicolon",
+            TokenKind::Whitespace => "whitespace",
+        }
+    }
+}
+
+/// 数式の字句
+///
+/// `text` は元の数式の文字列をそのまま保持し、連結すると元の数式に戻る
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+}
+
+/// A1形式の数式の字句への分割
+pub fn tokenize(formula: &str) -> Result<Vec<Token>, FormulaError> {
+    Lexer::new(formula, false).run()
+}
+
+/// R1C1形式の数式の字句への分割
+pub fn tokenize_r1c1(formula: &str) -> Result<Vec<Token>, FormulaError> {
+    Lexer::new(formula, true).run()
+}
+
+/// 参照の構成要素 (セル, 列, 行) の種類
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum PartKind {
+    Cell,
+    Column,
+    Row,
+}
+
+/// 字句解析器
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    r1c1: bool,
+}
+
+impl Lexer {
+    fn new(formula: &str, r1c1: bool) -> Self {
+        Lexer {
+            chars: formula.chars().collect(),
+            pos: 0,
+            r1c1,
+        }
+    }
+
+    fn run(mut self) -> Result<Vec<Token>, FormulaError> {
+        let mut tokens: Vec<Token> = Vec::new();
+        while self.pos < self.chars.len() {
+            let start: usize = self.pos;
+            let c: char = self.chars[start];
+            let next: Option<char> = self.chars.get(start + 1).copied();
+            let (end, kind) = match c {
+                ' ' | '\t' | '\r' | '\n' => {
+                    let mut end: usize = start;
+                    while matches!(self.chars.get(end), Some(' ' | '\t' | '\r' | '\n')) {
+                        end += 1;
+                    }
+                    (end, TokenKind::Whitespace)
+                }
+                '"' => (
+                    skip_quoted(&self.chars, start, '"')
+                        .ok_or(FormulaError::UnterminatedString(start))?,
+                    TokenKind::Text,
+                ),
+                '#' => (
+                    self.scan_error(start)
+                        .ok_or(FormulaError::UnexpectedChar(start, c))?,
+                    TokenKind::Error,
+                ),
+                '(' => (start + 1, TokenKind::OpenParen),
+                ')' => (start + 1, TokenKind::CloseParen),
+                '{' => (start + 1, TokenKind::OpenBrace),
+                '}' => (start + 1, TokenKind::CloseBrace),
+                ',' => (start + 1, TokenKind::Comma),
+                ';' => (start + 1, TokenKind::Semicolon),
+                '<' if matches!(next, Some('=' | '>')) => (start + 2, TokenKind::Operator),
+                '>' if next == Some('=') => (start + 2, TokenKind::Operator),
+                '+' | '-' | '*' | '/' | '^' | '&' | '%' | '=' | '<' | '>' | '@' | ':' => {
+                    (start + 1, TokenKind::Operator)
+                }
+                _ => self.scan_operand(start)?,
+            };
+            tokens.push(Token {
+                kind,
+                text: self.chars[start..end].iter().collect(),
+            });
+            self.pos = end;
+        }
+        Ok(tokens)
+    }
+
+    /// 値・参照・名前・関数の字句の読み取り
+    fn scan_operand(&self, start: usize) -> Result<(usize, TokenKind), FormulaError> {
+        let c: char = self.chars[start];
+        if let Some(prefix_end) = self.scan_sheet_prefix(start) {
+            if let Some(end) = self.scan_reference(prefix_end) {
+                return Ok((end, TokenKind::Reference));
+            }
+            if let Some(end) = self.scan_error(prefix_end) {
+                return Ok((end, TokenKind::Reference));
+            }
+            let end: usize = self.scan_word(prefix_end);
+            if end > prefix_end {
+                return Ok((end, TokenKind::Name));
+            }
+            return Err(match self.chars.get(prefix_end) {
+                Some(&c) => FormulaError::UnexpectedChar(prefix_end, c),
+                None => FormulaError::UnexpectedEnd,
+            });
+        }
+        match c {
+            '[' => {
+                return skip_brackets(&self.chars, start)
+                    .map(|end| (end, TokenKind::StructuredReference))
+                    .ok_or(FormulaError::UnexpectedChar(start, c));
+            }
+            '\'' => return Err(FormulaError::UnexpectedChar(start, c)),
+            _ => {}
+        }
+        if let Some(end) = self.scan_reference(start) {
+            return Ok((end, TokenKind::Reference));
+        }
+        if c.is_ascii_digit() || c == '.' {
+            return self.scan_number(start);
+        }
+        let end: usize = self.scan_word(start);
+        if end == start {
+            return Err(FormulaError::UnexpectedChar(start, c));
+        }
+        let word: String = self.chars[start..end].iter().collect();
+        Ok(match self.chars.get(end) {
+            Some('(') => (end, TokenKind::Function),
+            Some('[') => (
+                skip_brackets(&self.chars, end).ok_or(FormulaError::UnexpectedChar(end, '['))?,
+                TokenKind::StructuredReference,
+            ),
+            _ if word.eq_ignore_ascii_case("TRUE") || word.eq_ignore_ascii_case("FALSE") => {
+                (end, TokenKind::Bool)
+            }
+            _ => (end, TokenKind::Name),
+        })
+    }
+
+    /// 数値の読み取り (例: "1.5E+3")
+    fn scan_number(&self, start: usize) -> Result<(usize, TokenKind), FormulaError> {
+        let digits = |mut pos: usize| {
+            while self.chars.get(pos).is_some_and(|c| c.is_ascii_digit()) {
+                pos += 1;
+            }
+            pos
+        };
+        let mut end: usize = digits(start);
+        if self.chars.get(end) == Some(&'.') {
+            end = digits(end + 1);
+        }
+        if matches!(self.chars.get(end), Some('e' | 'E')) {
+            let sign: usize = match self.chars.get(end + 1) {
+                Some('+' | '-') => end + 2,
+                _ => end + 1,
+            };
+            let exponent_end: usize = digits(sign);
+            if exponent_end > sign {
+                end = exponent_end;
+            }
+        }
+        match self.chars.get(end) {
+            Some(&c) if is_word_char(c) => Err(FormulaError::UnexpectedChar(end, c)),
+            _ => Ok((end, TokenKind::Number)),
+        }
+    }
+
+    /// エラー値の読み取り
+    fn scan_error(&self, start: usize) -> Option<usize> {
+        let rest: String = self.chars[start..].iter().take(16).collect();
+        let rest: String = rest.to_ascii_uppercase();
+        ERROR_CODES
+            .iter()
+            .chain(["#GETTING_DATA"].iter())
+            .find(|code| rest.starts_with(*code))
+            .map(|code| start + code.chars().count())
+    }
+
+    /// 名前に使用できる文字の連続の読み取り
+    fn scan_word(&self, start: usize) -> usize {
+        let mut pos: usize = start;
+        while self.chars.get(pos).is_some_and(|&c| is_word_char(c)) {
+            pos += 1;
+        }
+        pos
+    }
+
+    /// シート接頭辞 (例: "Sheet1!", "'My Sheet'!", "Sheet1:Sheet3!", "[1]Sheet1!") の読み取り
+    ///
+    /// 接頭辞の直後の位置を返却
+    fn scan_sheet_prefix(&self, start: usize) -> Option<usize> {
+        let mut pos: usize = start;
+        match self.chars[start] {
+            '\'' => pos = skip_quoted(&self.chars, start, '\'')?,
+            c => {
+                if c == '[' {
+                    pos = skip_brackets(&self.chars, start)?;
+                }
+                let end: usize = self.scan_word(pos);
+                if end == pos && c != '[' {
+                    return None;
+                }
+                pos = end;
+                // 3D参照 (例: "Sheet1:Sheet3!")
+                if self.chars.get(pos) == Some(&':') {
+                    let end: usize = self.scan_word(pos + 1);
+                    if end > pos + 1 && self.chars.get(end) == Some(&'!') {
+                        pos = end;
+                    }
+                }
+            }
+        }
+        (self.chars.get(pos) == Some(&'!')).then_some(pos + 1)
+    }
+
+    /// セル参照または範囲参照の読み取り
+    fn scan_reference(&self, start: usize) -> Option<usize> {
+        let (end, kind) = self.scan_part(start)?;
+        if self.chars.get(end) == Some(&':')
+            && let Some((range_end, range_kind)) = self.scan_part(end + 1)
+            && range_kind == kind
+        {
+            return Some(range_end);
+        }
+        (kind == PartKind::Cell || self.r1c1).then_some(end)
+    }
+
+    /// 参照の構成要素の読み取り
+    fn scan_part(&self, start: usize) -> Option<(usize, PartKind)> {
+        let (end, kind) = if self.r1c1 {
+            self.scan_r1c1_part(start)?
+        } else {
+            self.scan_a1_part(start)?
+        };
+        let continues: bool = self
+            .chars
+            .get(end)
+            .is_some_and(|&c| is_word_char(c) || matches!(c, '(' | '[' | '!'));
+        (!continues).then_some((end, kind))
+    }
+
+    /// A1形式の参照の構成要素 (例: "$A$1", "A", "1") の読み取り
+    fn scan_a1_part(&self, start: usize) -> Option<(usize, PartKind)> {
+        let mut pos: usize = start;
+        if self.chars.get(pos) == Some(&'$') {
+            pos += 1;
+        }
+        let letters_start: usize = pos;
+        while self.chars.get(pos).is_some_and(|c| c.is_ascii_alphabetic()) {
+            pos += 1;
+        }
+        let letters: String = self.chars[letters_start..pos].iter().collect();
+        let mut row_absolute: bool = false;
+        if !letters.is_empty() && self.chars.get(pos) == Some(&'$') {
+            pos += 1;
+            row_absolute = true;
+        }
+        let digits_start: usize = pos;
+        while self.chars.get(pos).is_some_and(|c| c.is_ascii_digit()) {
+            pos += 1;
+        }
+        let digits: String = self.chars[digits_start..pos].iter().collect();
+        let valid_col: bool = string_to_col(&letters).is_some();
+        let valid_row: bool = digits
+            .parse::<u32>()
+            .is_ok_and(|row| (1..=MAX_ROW).contains(&row));
+        let kind: PartKind = match (letters.is_empty(), digits.is_empty()) {
+            (false, false) if valid_col && valid_row => PartKind::Cell,
+            (false, true) if valid_col && !row_absolute => PartKind::Column,
+            (true, false) if valid_row => PartKind::Row,
+            _ => return None,
+        };
+        Some((pos, kind))
+    }
+
+    /// R1C1形式の参照の構成要素 (例: "R1C1", "R[-1]C", "C2") の読み取り
+    fn scan_r1c1_part(&self, start: usize) -> Option<(usize, PartKind)> {
+        let mut pos: usize = start;
+        let mut has_row: bool = false;
+        let mut has_col: bool = false;
+        if self
+            .chars
+            .get(pos)
+            .is_some_and(|c| c.eq_ignore_ascii_case(&'R'))
+        {
+            pos = self.scan_r1c1_index(pos + 1)?;
+            has_row = true;
+        }
+        if self
+            .chars
+            .get(pos)
+            .is_some_and(|c| c.eq_ignore_ascii_case(&'C'))
+        {
+            pos = self.scan_r1c1_index(pos + 1)?;
+            has_col = true;
+        }
+        let kind: PartKind = match (has_row, has_col) {
+            (true, true) => PartKind::Cell,
+            (true, false) => PartKind::Row,
+            (false, true) => PartKind::Column,
+            (false, false) => return None,
+        };
+        Some((pos, kind))
+    }
+
+    /// R1C1形式の行番号または列番号 (例: "3", "[-1]", 省略) の読み取り
+    fn scan_r1c1_index(&self, start: usize) -> Option<usize> {
+        let digits = |mut pos: usize| {
+            while self.chars.get(pos).is_some_and(|c| c.is_ascii_digit()) {
+                pos += 1;
+            }
+            pos
+        };
+        if self.chars.get(start) != Some(&'[') {
+            return Some(digits(start));
+        }
+        let sign: usize = match self.chars.get(start + 1) {
+            Some('-' | '+') => start + 2,
+            _ => start + 1,
+        };
+        let end: usize = digits(sign);
+        (end > sign && self.chars.get(end) == Some(&']')).then_some(end + 1)
+    }
+}
+
+/// 行または列の座標
+///
+/// R1C1形式の相対参照では `index` は基準セルからのオフセット
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Coord {
+    pub index: i64,
+    pub absolute: bool,
+}
+
+/// セルの座標
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellRef {
+    pub row: Coord,
+    pub col: Coord,
+}
+
+/// 参照する領域
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Area {
+    /// 単一セル (例: "A1")
+    Cell(CellRef),
+    /// セル範囲 (例: "A1:B2")
+    Range(CellRef, CellRef),
+    /// 列全体 (例: "A:C")
+    Columns(Coord, Coord),
+    /// 行全体 (例: "1:3")
+    Rows(Coord, Coord),
+}
+
+/// セル参照
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reference {
+    /// 外部ブック (例: "[1]Sheet1!A1" の "1")
+    pub workbook: Option<String>,
+    /// シート名 (3D参照では先頭のシート)
+    pub sheet: Option<String>,
+    /// 3D参照の末尾のシート名
+    pub last_sheet: Option<String>,
+    pub area: Area,
+    /// R1C1形式かどうか
+    pub r1c1: bool,
+}
+
+/// 単項演算子
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryOp {
+    Plus,
+    Minus,
+    /// 暗黙的なインターセクション (`@`)
+    ImplicitIntersection,
+}
+
+impl UnaryOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UnaryOp::Plus => "+",
+            UnaryOp::Minus => "-",
+            UnaryOp::ImplicitIntersection => "@",
+        }
+    }
+}
+
+/// 二項演算子
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Concat,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Range,
+    Intersection,
+    Union,
+}
+
+impl BinaryOp {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Pow => "^",
+            BinaryOp::Concat => "&",
+            BinaryOp::Eq => "=",
+            BinaryOp::Ne => "<>",
+            BinaryOp::Lt => "<",
+            BinaryOp::Le => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Ge => ">=",
+            BinaryOp::Range => ":",
+            BinaryOp::Intersection => " ",
+            BinaryOp::Union => ",",
+        }
+    }
+
+    /// 演算子の字句からの変換 (参照演算子を除く)
+    fn from_operator(text: &str) -> Option<Self> {
+        Some(match text {
+            "+" => BinaryOp::Add,
+            "-" => BinaryOp::Sub,
+            "*" => BinaryOp::Mul,
+            "/" => BinaryOp::Div,
+            "^" => BinaryOp::Pow,
+            "&" => BinaryOp::Concat,
+            "=" => BinaryOp::Eq,
+            "<>" => BinaryOp::Ne,
+            "<" => BinaryOp::Lt,
+            "<=" => BinaryOp::Le,
+            ">" => BinaryOp::Gt,
+            ">=" => BinaryOp::Ge,
+            _ => return None,
+        })
+    }
+
+    /// 演算子の優先順位 (大きいほど先に結合)
+    fn precedence(&self) -> u8 {
+        match self {
+            BinaryOp::Eq
+            | BinaryOp::Ne
+            | BinaryOp::Lt
+            | BinaryOp::Le
+            | BinaryOp::Gt
+            | BinaryOp::Ge => 1,
+            BinaryOp::Concat => 2,
+            BinaryOp::Add | BinaryOp::Sub => 3,
+            BinaryOp::Mul | BinaryOp::Div => 4,
+            BinaryOp::Pow => 5,
+            BinaryOp::Union => 6,
+            BinaryOp::Intersection => 7,
+            BinaryOp::Range => 8,
+        }
+    }
+}
+
+/// 数式の構文木
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Text(String),
+    Bool(bool),
+    /// エラー値 (例: "#N/A")
+    Error(String),
+    Reference(Reference),
+    /// 定義された名前 (シートスコープの場合はシート名付き)
+    Name {
+        sheet: Option<String>,
+        name: String,
+    },
+    /// テーブルの構造化参照 (例: "Table1[[#Headers],[Col]]")
+    StructuredReference {
+        table: Option<String>,
+        specifier: String,
+    },
+    Function {
+        name: String,
+        args: Vec<Expr>,
+    },
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expr>,
+    },
+    Percent(Box<Expr>),
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    /// 配列定数 (行ごとの要素)
+    Array(Vec<Vec<Expr>>),
+    /// 括弧
+    Group(Box<Expr>),
+    /// 省略された関数の引数
+    Missing,
+}
+
+/// A1形式の数式の構文解析
+///
+/// 先頭の `=` は省略可能
+pub fn parse(formula: &str) -> Result<Expr, FormulaError> {
+    let body: &str = formula.strip_prefix('=').unwrap_or(formula);
+    Parser::new(tokenize(body)?, false).run()
+}
+
+/// R1C1形式の数式の構文解析
+pub fn parse_r1c1(formula: &str) -> Result<Expr, FormulaError> {
+    let body: &str = formula.strip_prefix('=').unwrap_or(formula);
+    Parser::new(tokenize_r1c1(body)?, true).run()
+}
+
+/// 構文解析器
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    r1c1: bool,
+}
+
+impl Parser {
+    fn new(tokens: Vec<Token>, r1c1: bool) -> Self {
+        Parser {
+            tokens,
+            pos: 0,
+            r1c1,
+        }
+    }
+
+    fn run(mut self) -> Result<Expr, FormulaError> {
+        let expr: Expr = self.parse_expression(0)?;
+        match self.next() {
+            Some(token) => Err(FormulaError::UnexpectedToken(token.text)),
+            None => Ok(expr),
+        }
+    }
+
+    /// 空白を除いた次の字句の位置
+    fn peek_index(&self) -> Option<usize> {
+        (self.pos..self.tokens.len()).find(|&i| self.tokens[i].kind != TokenKind::Whitespace)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.peek_index().map(|i| &self.tokens[i])
+    }
+
+    fn peek_is(&self, kind: TokenKind, text: &str) -> bool {
+        self.peek()
+            .is_some_and(|token| token.kind == kind && token.text == text)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let index: usize = self.peek_index()?;
+        self.pos = index + 1;
+        Some(self.tokens[index].clone())
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<(), FormulaError> {
+        match self.next() {
+            Some(token) if token.kind == kind => Ok(()),
+            Some(token) => Err(FormulaError::UnexpectedToken(token.text)),
+            None => Err(FormulaError::UnexpectedEnd),
+        }
+    }
+
+    /// 二項演算の解析 (優先順位による結合)
+    fn parse_expression(&mut self, min_precedence: u8) -> Result<Expr, FormulaError> {
+        let mut left: Expr = self.parse_unary()?;
+        while let Some(op) = self
+            .peek()
+            .filter(|token| token.kind == TokenKind::Operator)
+            .and_then(|token| BinaryOp::from_operator(&token.text))
+        {
+            if op.precedence() < min_precedence {
+                break;
+            }
+            self.next();
+            let right: Expr = self.parse_expression(op.precedence() + 1)?;
+            left = Expr::Binary {
+                op,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+        Ok(left)
+    }
+
+    /// 単項演算の解析
+    fn parse_unary(&mut self) -> Result<Expr, FormulaError> {
+        let op: Option<UnaryOp> = match self.peek() {
+            Some(token) if token.kind == TokenKind::Operator => match token.text.as_str() {
+                "+" => Some(UnaryOp::Plus),
+                "-" => Some(UnaryOp::Minus),
+                "@" => Some(UnaryOp::ImplicitIntersection),
+                _ => None,
+            },
+            _ => None,
+        };
+        match op {
+            Some(op) => {
+                self.next();
+                Ok(Expr::Unary {
+                    op,
+                    operand: Box::new(self.parse_unary()?),
+                })
+            }
+            None => self.parse_postfix(),
+        }
+    }
+
+    /// インターセクションとパーセントの解析
+    fn parse_postfix(&mut self) -> Result<Expr, FormulaError> {
+        let mut expr: Expr = self.parse_range()?;
+        loop {
+            if self.intersection_follows() {
+                let right: Expr = self.parse_range()?;
+                expr = Expr::Binary {
+                    op: BinaryOp::Intersection,
+                    left: Box::new(expr),
+                    right: Box::new(right),
+                };
+            } else if self.peek_is(TokenKind::Operator, "%") {
+                self.next();
+                expr = Expr::Percent(Box::new(expr));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    /// 空白がインターセクション演算子として使われているかどうか
+    fn intersection_follows(&self) -> bool {
+        self.tokens
+            .get(self.pos)
+            .is_some_and(|token| token.kind == TokenKind::Whitespace)
+            && self.peek().is_some_and(|token| {
+                matches!(
+                    token.kind,
+                    TokenKind::Reference
+                        | TokenKind::Name
+                        | TokenKind::Function
+                        | TokenKind::StructuredReference
+                        | TokenKind::OpenParen
+                )
+            })
+    }
+
+    /// 範囲演算子 (`:`) の解析
+    fn parse_range(&mut self) -> Result<Expr, FormulaError> {
+        let mut expr: Expr = self.parse_primary()?;
+        while self.peek_is(TokenKind::Operator, ":") {
+            self.next();
+            let right: Expr = self.parse_primary()?;
+            expr = Expr::Binary {
+                op: BinaryOp::Range,
+                left: Box::new(expr),
+                right: Box::new(right),
+            };
+        }
+        Ok(expr)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, FormulaError> {
+        let token: Token = self.next().ok_or(FormulaError::UnexpectedEnd)?;
+        match token.kind {
+            TokenKind::Function => self.parse_function(token.text),
+            TokenKind::OpenParen => self.parse_group(),
+            TokenKind::OpenBrace => self.parse_array(),
+            _ => literal_expr(token, self.r1c1),
+        }
+    }
+
+    /// 関数呼び出しの引数の解析
+    fn parse_function(&mut self, name: String) -> Result<Expr, FormulaError> {
+        self.expect(TokenKind::OpenParen)?;
+        let mut args: Vec<Expr> = Vec::new();
+        if self.peek().is_some_and(|t| t.kind == TokenKind::CloseParen) {
+            self.next();
+            return Ok(Expr::Function { name, args });
+        }
+        loop {
+            let missing: bool = self
+                .peek()
+                .is_some_and(|t| matches!(t.kind, TokenKind::Comma | TokenKind::CloseParen));
+            args.push(if missing {
+                Expr::Missing
+            } else {
+                self.parse_expression(0)?
+            });
+            match self.next() {
+                Some(token) if token.kind == TokenKind::Comma => continue,
+                Some(token) if token.kind == TokenKind::CloseParen => break,
+                Some(token) => return Err(FormulaError::UnexpectedToken(token.text)),
+                None => return Err(FormulaError::UnexpectedEnd),
+            }
+        }
+        Ok(Expr::Function { name, args })
+    }
+
+    /// 括弧と参照の結合 (例: "(A1,B2)") の解析
+    fn parse_group(&mut self) -> Result<Expr, FormulaError> {
+        let mut expr: Expr = self.parse_expression(0)?;
+        while self.peek().is_some_and(|t| t.kind == TokenKind::Comma) {
+            self.next();
+            let right: Expr = self.parse_expression(0)?;
+            expr = Expr::Binary {
+                op: BinaryOp::Union,
+                left: Box::new(expr),
+                right: Box::new(right),
+            };
+        }
+        self.expect(TokenKind::CloseParen)?;
+        Ok(Expr::Group(Box::new(expr)))
+    }
+
+    /// 配列定数 (例: "{1,2;3,4}") の解析
+    fn parse_array(&mut self) -> Result<Expr, FormulaError> {
+        let mut rows: Vec<Vec<Expr>> = vec![Vec::new()];
+        loop {
+            let mut token: Token = self.next().ok_or(FormulaError::UnexpectedEnd)?;
+            let negative: bool = token.kind == TokenKind::Operator && token.text == "-";
+            if negative {
+                token = self.next().ok_or(FormulaError::UnexpectedEnd)?;
+            }
+            let value: Expr = match (token.kind, negative) {
+                (TokenKind::Number, true) => match literal_expr(token, false)? {
+                    Expr::Number(n) => Expr::Number(-n),
+                    other => other,
+                },
+                (
+                    TokenKind::Number | TokenKind::Text | TokenKind::Bool | TokenKind::Error,
+                    false,
+                ) => literal_expr(token, false)?,
+                _ => return Err(FormulaError::UnexpectedToken(token.text)),
+            };
+            if let Some(row) = rows.last_mut() {
+                row.push(value);
+            }
+            match self.next() {
+                Some(token) if token.kind == TokenKind::Comma => {}
+                Some(token) if token.kind == TokenKind::Semicolon => rows.push(Vec::new()),
+                Some(token) if token.kind == TokenKind::CloseBrace => break,
+                Some(token) => return Err(FormulaError::UnexpectedToken(token.text)),
+                None => return Err(FormulaError::UnexpectedEnd),
+            }
+        }
+        if rows.iter().any(|row| row.len() != rows[0].len()) {
+            return Err(FormulaError::InvalidArray);
+        }
+        Ok(Expr::Array(rows))
+    }
+}
+
+/// 値・参照・名前の字句の構文木への変換
+fn literal_expr(token: Token, r1c1: bool) -> Result<Expr, FormulaError> {
+    let text: &str = &token.text;
+    Ok(match token.kind {
+        TokenKind::Number => Expr::Number(
+            text.parse()
+                .map_err(|_| FormulaError::UnexpectedToken(text.to_string()))?,
+        ),
+        TokenKind::Text => Expr::Text(unquote(text, '"')),
+        TokenKind::Bool => Expr::Bool(text.eq_ignore_ascii_case("TRUE")),
+        TokenKind::Error => Expr::Error(text.to_ascii_uppercase()),
+        TokenKind::Reference => {
+            let (prefix, body) = split_sheet_prefix(text);
+            let (workbook, sheet, last_sheet) = parse_sheet_prefix(prefix);
+            let area: Option<Area> = if r1c1 {
+                parse_r1c1_area(body)
+            } else {
+                parse_a1_area(body)
+            };
+            match area {
+                Some(area) => Expr::Reference(Reference {
+                    workbook,
+                    sheet,
+                    last_sheet,
+                    area,
+                    r1c1,
+                }),
+                None => Expr::Error(body.to_ascii_uppercase()),
+            }
+        }
+        TokenKind::Name => {
+            let (prefix, name) = split_sheet_prefix(text);
+            Expr::Name {
+                sheet: parse_sheet_prefix(prefix).1,
+                name: name.to_string(),
+            }
+        }
+        TokenKind::StructuredReference => {
+            let open: usize = text.find('[').unwrap_or(0);
+            let (table, specifier) = text.split_at(open);
+            Expr::StructuredReference {
+                table: (!table.is_empty()).then(|| table.to_string()),
+                specifier: specifier[1..specifier.len() - 1].to_string(),
+            }
+        }
+        _ => return Err(FormulaError::UnexpectedToken(token.text)),
+    })
+}
+
+/// 引用符の除去とエスケープの解除
+fn unquote(text: &str, quote: char) -> String {
+    let doubled: String = format!("{quote}{quote}");
+    text[1..text.len() - 1].replace(&doubled, &quote.to_string())
+}
+
+/// シート接頭辞のブック名, シート名, 3D参照の末尾のシート名への分解
+fn parse_sheet_prefix(prefix: &str) -> (Option<String>, Option<String>, Option<String>) {
+    let Some(prefix) = prefix.strip_suffix('!') else {
+        return (None, None, None);
+    };
+    let prefix: String = if prefix.starts_with('\'') {
+        unquote(prefix, '\'')
+    } else {
+        prefix.to_string()
+    };
+    let (workbook, sheets) = match prefix.strip_prefix('[').and_then(|p| p.split_once(']')) {
+        Some((workbook, sheets)) => (Some(workbook.to_string()), sheets.to_string()),
+        None => (None, prefix),
+    };
+    let (sheet, last_sheet) = match sheets.split_once(':') {
+        Some((first, last)) => (Some(first.to_string()), Some(last.to_string())),
+        None if sheets.is_empty() => (None, None),
+        None => (Some(sheets), None),
+    };
+    (workbook, sheet, last_sheet)
+}
+
+/// シート接頭辞 (例: "'My Sheet'!") のシート名
+///
+/// 外部参照や3D参照の場合は `None`
+pub fn prefix_sheet_name(prefix: &str) -> Option<String> {
+    match parse_sheet_prefix(prefix) {
+        (None, Some(sheet), None) => Some(sheet),
+        _ => None,
+    }
+}
+
+/// 名前として有効かどうか (定義名の名前の規則)
+///
+/// 先頭は英字・`_`・`\`、以降は英数字・`_`・`.`・`\`・`?` で、セル参照と紛らわしくないもの
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    name.chars().count() <= 255
+        && chars
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '\\')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '\\' | '?'))
+        && split_cell_ref(name).is_none()
+        && parse_r1c1_area(name).is_none()
+        && !matches!(name.to_ascii_uppercase().as_str(), "R" | "C")
+}
+
+/// A1形式の領域の解析
+fn parse_a1_area(body: &str) -> Option<Area> {
+    let parse_coord = |text: &str, max: u32, to_index: fn(&str) -> Option<u32>| {
+        let (absolute, text) = match text.strip_prefix('$') {
+            Some(text) => (true, text),
+            None => (false, text),
+        };
+        let index: u32 = to_index(text).filter(|&i| (1..=max).contains(&i))?;
+        Some(Coord {
+            index: index as i64,
+            absolute,
+        })
+    };
+    let cell = |text: &str| {
+        let (col_abs, col, row_abs, row) = split_cell_ref(text)?;
+        Some(CellRef {
+            row: Coord {
+                index: row as i64,
+                absolute: row_abs,
+            },
+            col: Coord {
+                index: col as i64,
+                absolute: col_abs,
+            },
+        })
+    };
+    let column = |text: &str| parse_coord(text, MAX_COL, string_to_col);
+    let row = |text: &str| parse_coord(text, MAX_ROW, |t| t.parse().ok());
+    match body.split_once(':') {
+        None => cell(body).map(Area::Cell),
+        Some((start, end)) => {
+            if let (Some(start), Some(end)) = (cell(start), cell(end)) {
+                Some(Area::Range(start, end))
+            } else if let (Some(start), Some(end)) = (column(start), column(end)) {
+                Some(Area::Columns(start, end))
+            } else {
+                Some(Area::Rows(row(start)?, row(end)?))
+            }
+        }
+    }
+}
+
+/// R1C1形式の領域の解析
+fn parse_r1c1_area(body: &str) -> Option<Area> {
+    let parse_coord = |text: &str| -> Option<Coord> {
+        match text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            Some(offset) => Some(Coord {
+                index: offset.parse().ok()?,
+                absolute: false,
+            }),
+            None if text.is_empty() => Some(Coord {
+                index: 0,
+                absolute: false,
+            }),
+            None => Some(Coord {
+                index: text.parse().ok()?,
+                absolute: true,
+            }),
+        }
+    };
+    let part = |text: &str| -> Option<(Option<Coord>, Option<Coord>)> {
+        let upper: String = text.to_ascii_uppercase();
+        let (row, col) = match upper.find('C') {
+            Some(pos) => (&upper[..pos], Some(&upper[pos + 1..])),
+            None => (upper.as_str(), None),
+        };
+        let row: Option<Coord> = match row.strip_prefix('R') {
+            Some(row) => Some(parse_coord(row)?),
+            None if row.is_empty() => None,
+            None => return None,
+        };
+        let col: Option<Coord> = match col {
+            Some(col) => Some(parse_coord(col)?),
+            None => None,
+        };
+        Some((row, col))
+    };
+    let (start, end) = body.split_once(':').unwrap_or((body, body));
+    match (part(start)?, part(end)?) {
+        ((Some(r1), Some(c1)), (Some(r2), Some(c2))) => {
+            let first: CellRef = CellRef { row: r1, col: c1 };
+            let last: CellRef = CellRef { row: r2, col: c2 };
+            Some(if body.contains(':') {
+                Area::Range(first, last)
+            } else {
+                Area::Cell(first)
+            })
+        }
+        ((Some(r1), None), (Some(r2), None)) => Some(Area::Rows(r1, r2)),
+        ((None, Some(c1)), (None, Some(c2))) => Some(Area::Columns(c1, c2)),
+        _ => None,
+    }
+}
+
+/// シート名の引用符付け (必要な場合のみ)
+///
+/// 空白や記号を含む名前、セル参照と紛らわしい名前は `'` で囲む
+pub fn quote_sheet_name(name: &str) -> String {
+    let plain: bool = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+        && split_cell_ref(name).is_none()
+        && parse_r1c1_area(name).is_none();
+    if plain {
+        name.to_string()
+    } else {
+        format!("'{}'", name.replace('\'', "''"))
+    }
+}
+
+impl Coord {
+    fn fmt_a1(&self, f: &mut fmt::Formatter<'_>, is_col: bool) -> fmt::Result {
+        if self.absolute {
+            write!(f, "$")?;
+        }
+        if is_col {
+            write!(f, "{}", col_to_string(self.index.max(0) as u32))
+        } else {
+            write!(f, "{}", self.index)
+        }
+    }
+
+    fn fmt_r1c1(&self, f: &mut fmt::Formatter<'_>, axis: char) -> fmt::Result {
+        match (self.absolute, self.index) {
+            (true, index) => write!(f, "{axis}{index}"),
+            (false, 0) => write!(f, "{axis}"),
+            (false, offset) => write!(f, "{axis}[{offset}]"),
+        }
+    }
+}
+
+impl CellRef {
+    fn fmt_with(&self, f: &mut fmt::Formatter<'_>, r1c1: bool) -> fmt::Result {
+        if r1c1 {
+            self.row.fmt_r1c1(f, 'R')?;
+            self.col.fmt_r1c1(f, 'C')
+        } else {
+            self.col.fmt_a1(f, true)?;
+            self.row.fmt_a1(f, false)
+        }
+    }
+}
+
+impl fmt::Display for Reference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.workbook.is_some() || self.sheet.is_some() {
+            let mut prefix: String = String::new();
+            if let Some(workbook) = &self.workbook {
+                prefix.push_str(&format!("[{workbook}]"));
+            }
+            if let Some(sheet) = &self.sheet {
+                prefix.push_str(sheet);
+            }
+            if let Some(last_sheet) = &self.last_sheet {
+                prefix.push_str(&format!(":{last_sheet}"));
+            }
+            let needs_quote: bool = [&self.sheet, &self.last_sheet]
+                .iter()
+                .filter_map(|s| s.as_deref())
+                .any(|s| quote_sheet_name(s) != s);
+            if needs_quote {
+                write!(f, "'{}'!", prefix.replace('\'', "''"))?;
+            } else {
+                write!(f, "{prefix}!")?;
+            }
+        }
+        match &self.area {
+            Area::Cell(cell) => cell.fmt_with(f, self.r1c1),
+            Area::Range(start, end) => {
+                start.fmt_with(f, self.r1c1)?;
+                write!(f, ":")?;
+                end.fmt_with(f, self.r1c1)
+            }
+            Area::Columns(start, end) if self.r1c1 => {
+                start.fmt_r1c1(f, 'C')?;
+                if start != end {
+                    write!(f, ":")?;
+                    end.fmt_r1c1(f, 'C')?;
+                }
+                Ok(())
+            }
+            Area::Rows(start, end) if self.r1c1 => {
+                start.fmt_r1c1(f, 'R')?;
+                if start != end {
+                    write!(f, ":")?;
+                    end.fmt_r1c1(f, 'R')?;
+                }
+                Ok(())
+            }
+            Area::Columns(start, end) => {
+                start.fmt_a1(f, true)?;
+                write!(f, ":")?;
+                end.fmt_a1(f, true)
+            }
+            Area::Rows(start, end) => {
+                start.fmt_a1(f, false)?;
+                write!(f, ":")?;
+                end.fmt_a1(f, false)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Number(n) => write!(f, "{n}"),
+            Expr::Text(text) => write!(f, "\"{}\"", text.replace('"', "\"\"")),
+            Expr::Bool(b) => write!(f, "{}", if *b { "TRUE" } else { "FALSE" }),
+            Expr::Error(code) => write!(f, "{code}"),
+            Expr::Reference(reference) => write!(f, "{reference}"),
+            Expr::Name { sheet, name } => match sheet {
+                Some(sheet) => write!(f, "{}!{name}", quote_sheet_name(sheet)),
+                None => write!(f, "{name}"),
+            },
+            Expr::StructuredReference { table, specifier } => {
+                write!(f, "{}[{specifier}]", table.as_deref().unwrap_or(""))
+            }
+            Expr::Function { name, args } => {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                write!(f, "{name}({})", args.join(","))
+            }
+            Expr::Unary { op, operand } => write!(f, "{}{operand}", op.as_str()),
+            Expr::Percent(operand) => write!(f, "{operand}%"),
+            Expr::Binary { op, left, right } => write!(f, "{left}{}{right}", op.as_str()),
+            Expr::Array(rows) => {
+                let rows: Vec<String> = rows
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|value| value.to_string())
+                            .collect::<Vec<String>>()
+                            .join(",")
+                    })
+                    .collect();
+                write!(f, "{{{}}}", rows.join(";"))
+            }
+            Expr::Group(inner) => write!(f, "({inner})"),
+            Expr::Missing => Ok(()),
+        }
+    }
+}
+
+/// Python向けの構文木のノード
+#[pyclass(frozen)]
+#[derive(Clone, Debug)]
+pub struct FormulaNode {
+    /// ノードの種類 (例: "function", "reference", "binary")
+    #[pyo3(get)]
+    pub kind: String,
+    /// 値, 関数名, 演算子, 参照先などノードの内容
+    #[pyo3(get)]
+    pub value: Option<String>,
+    /// 参照先のシート名
+    #[pyo3(get)]
+    pub sheet: Option<String>,
+    /// 子ノード (関数の引数, 演算の項, 配列定数の行)
+    #[pyo3(get)]
+    pub children: Vec<FormulaNode>,
+    /// ノードを数式として表した文字列
+    #[pyo3(get)]
+    pub formula: String,
+}
+
+#[pymethods]
+impl FormulaNode {
+    fn __repr__(&self) -> String {
+        format!(
+            "FormulaNode(kind={:?}, value={:?}, children={})",
+            self.kind,
+            self.value.as_deref().unwrap_or(""),
+            self.children.len()
+        )
+    }
+}
+
+impl From<&Expr> for FormulaNode {
+    fn from(expr: &Expr) -> Self {
+        let node = |kind: &str, value: Option<String>, children: Vec<FormulaNode>| FormulaNode {
+            kind: kind.to_string(),
+            value,
+            sheet: None,
+            children,
+            formula: expr.to_string(),
+        };
+        match expr {
+            Expr::Number(n) => node("number", Some(n.to_string()), vec![]),
+            Expr::Text(text) => node("text", Some(text.clone()), vec![]),
+            Expr::Bool(b) => node("bool", Some(b.to_string().to_uppercase()), vec![]),
+            Expr::Error(code) => node("error", Some(code.clone()), vec![]),
+            Expr::Reference(reference) => FormulaNode {
+                sheet: reference.sheet.clone(),
+                ..node(
+                    "reference",
+                    Some(
+                        Reference {
+                            workbook: None,
+                            sheet: None,
+                            last_sheet: None,
+                            ..reference.clone()
+                        }
+                        .to_string(),
+                    ),
+                    vec![],
+                )
+            },
+            Expr::Name { sheet, name } => FormulaNode {
+                sheet: sheet.clone(),
+                ..node("name", Some(name.clone()), vec![])
+            },
+            Expr::StructuredReference { table, .. } => FormulaNode {
+                sheet: None,
+                ..node("structured_reference", table.clone(), vec![])
+            },
+            Expr::Function { name, args } => node(
+                "function",
+                Some(name.clone()),
+                args.iter().map(Into::into).collect(),
+            ),
+            Expr::Unary { op, operand } => node(
+                "unary",
+                Some(op.as_str().to_string()),
+                vec![operand.as_ref().into()],
+            ),
+            Expr::Percent(operand) => node(
+                "percent",
+                Some("%".to_string()),
+                vec![operand.as_ref().into()],
+            ),
+            Expr::Binary { op, left, right } => node(
+                "binary",
+                Some(op.as_str().to_string()),
+                vec![left.as_ref().into(), right.as_ref().into()],
+            ),
+            Expr::Array(rows) => node(
+                "array",
+                None,
+                rows.iter()
+                    .map(|row| FormulaNode {
+                        kind: "row".to_string(),
+                        value: None,
+                        sheet: None,
+                        children: row.iter().map(Into::into).collect(),
+                        formula: String::new(),
+                    })
+                    .collect(),
+            ),
+            Expr::Group(inner) => node("group", None, vec![inner.as_ref().into()]),
+            Expr::Missing => node("missing", None, vec![]),
+        }
+    }
+}
+
+/// 数式の構文解析 (Python向け)
+#[pyfunction]
+#[pyo3(signature = (formula, r1c1 = false))]
+pub fn parse_formula(formula: &str, r1c1: bool) -> PyResult<FormulaNode> {
+    let expr: Expr = if r1c1 {
+        parse_r1c1(formula)?
+    } else {
+        parse(formula)?
+    };
+    Ok(FormulaNode::from(&expr))
+}
+
+/// 数式の字句への分割 (Python向け)
+///
+/// 字句の種類と文字列の組を返却
+#[pyfunction]
+#[pyo3(signature = (formula, r1c1 = false))]
+pub fn tokenize_formula(formula: &str, r1c1: bool) -> PyResult<Vec<(String, String)>> {
+    let body: &str = formula.strip_prefix('=').unwrap_or(formula);
+    let tokens: Vec<Token> = if r1c1 {
+        tokenize_r1c1(body)?
+    } else {
+        tokenize(body)?
+    };
+    Ok(tokens
+        .into_iter()
+        .map(|token| (token.kind.as_str().to_string(), token.text))
+        .collect())
+}
@@ -0,0 +1,87 @@
+/// 列の幅の上限 (文字数)
+pub const MAX_COLUMN_WIDTH: f64 = 255.0;
+/// セルの左右の余白 (文字数)
+const CELL_PADDING: f64 = 1.0;
+/// 太字による幅の増加率
+const BOLD_FACTOR: f64 = 1.07;
+/// 基準とするフォントサイズ (ポイント)
+const BASE_FONT_SIZE: f64 = 11.0;
+
+/// 主要なフォントの文字幅の一覧
+///
+/// (小文字のフォント名, Calibri 11pt の数字の幅に対する比率, 等幅フォントかどうか)
+const FONT_METRICS: [(&str, f64, bool); 18] = [
+    ("calibri", 1.0, false),
+    ("arial", 1.1, false),
+    ("helvetica", 1.1, false),
+    ("times new roman", 0.95, false),
+    ("cambria", 1.0, false),
+    ("verdana", 1.2, false),
+    ("tahoma", 1.05, false),
+    ("segoe ui", 1.05, false),
+    ("aptos", 1.05, false),
+    ("consolas", 1.15, true),
+    ("courier new", 1.2, true),
+    ("ms gothic", 1.0, true),
+    ("ｍｓ ゴシック", 1.0, true),
+    ("ms pgothic", 1.0, false),
+    ("ｍｓ ｐゴシック", 1.0, false),
+    ("meiryo", 1.15, false),
+    ("メイリオ", 1.15, false),
+    ("游ゴシック", 1.0, false),
+];
+
+/// 全角文字 (東アジアの文字幅が広い文字) かどうか
+pub fn is_full_width(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3041..=0x33FF
+        | 0x3400..=0x4DBF
+        | 0x4E00..=0x9FFF
+        | 0xA000..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x1F300..=0x1F64F
+        | 0x20000..=0x3FFFD)
+}
+
+/// 文字の幅 (数字の幅を1とする)
+fn char_width(c: char, monospace: bool) -> f64 {
+    if is_full_width(c) {
+        return 2.0;
+    }
+    if monospace {
+        return 1.0;
+    }
+    match c {
+        ' ' => 0.45,
+        'i' | 'j' | 'l' | 't' | 'f' | 'r' | 'I' | '!' | '.' | ',' | ':' | ';' | '\'' | '|' => 0.5,
+        '(' | ')' | '[' | ']' | '{' | '}' | '-' | '/' | '\\' | '"' => 0.6,
+        'm' | 'w' | 'M' | 'W' | '@' | '%' => 1.5,
+        c if c.is_ascii_uppercase() => 1.15,
+        _ => 1.0,
+    }
+}
+
+/// フォントで表示したテキストの幅の推定 (列の幅の単位、余白を含む)
+///
+/// 改行を含む場合は最も長い行の幅
+pub fn text_width(text: &str, font_name: Option<&str>, size: Option<f64>, bold: bool) -> f64 {
+    let name: String = font_name.unwrap_or("Calibri").to_lowercase();
+    let (scale, monospace) = FONT_METRICS
+        .iter()
+        .find(|(n, _, _)| *n == name)
+        .map_or((1.0, false), |&(_, scale, monospace)| (scale, monospace));
+    let longest: f64 = text
+        .lines()
+        .map(|line| line.chars().map(|c| char_width(c, monospace)).sum::<f64>())
+        .fold(0.0, f64::max);
+    let scale: f64 = scale * size.unwrap_or(BASE_FONT_SIZE) / BASE_FONT_SIZE
+        * if bold { BOLD_FACTOR } else { 1.0 };
+    let width: f64 = longest * scale + CELL_PADDING;
+    ((width * 100.0).round() / 100.0).min(MAX_COLUMN_WIDTH)
+}
@@ -0,0 +1,266 @@
+use std::sync::MutexGuard;
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyIterator, PyList};
+use thiserror::Error;
+
+use crate::book::{BookParts, WORKBOOK_CHILD_ORDER};
+use crate::formula::{is_valid_name, parse_range, prefix_sheet_name, split_sheet_prefix};
+use crate::xml::{Xml, XmlElement};
+
+/// 定義された名前の操作のエラー
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum NameError {
+    #[error("Invalid defined name: {0}")]
+    InvalidName(String),
+    #[error("Defined name already exists: {0}")]
+    DuplicateName(String),
+    #[error("Defined name not found: {0}")]
+    NotFound(String),
+    #[error("Sheet index out of range: {0}")]
+    InvalidSheetIndex(usize),
+    #[error("Defined name does not refer to cells: {0}")]
+    InvalidReference(String),
+}
+
+impl From<NameError> for PyErr {
+    fn from(err: NameError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// 定義された名前 (`definedName`)
+#[pyclass]
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct DefinedName {
+    /// 名前
+    #[pyo3(get, set)]
+    pub name: String,
+    /// 参照先または数式 (例: "Sheet1!$A$1")
+    #[pyo3(get, set)]
+    pub value: String,
+    /// 名前の範囲となるシートの位置 (`None` の場合はブック全体)
+    #[pyo3(get, set)]
+    pub local_sheet_id: Option<usize>,
+    /// 名前の一覧に表示しないかどうか
+    #[pyo3(get, set)]
+    pub hidden: bool,
+    /// コメント
+    #[pyo3(get, set)]
+    pub comment: Option<String>,
+}
+
+#[pymethods]
+impl DefinedName {
+    /// 新しい `DefinedName` インスタンスの作成
+    #[new]
+    #[pyo3(signature = (name, value, local_sheet_id=None, hidden=false, comment=None))]
+    fn py_new(
+        name: String,
+        value: String,
+        local_sheet_id: Option<usize>,
+        hidden: bool,
+        comment: Option<String>,
+    ) -> Self {
+        DefinedName {
+            name,
+            value,
+            local_sheet_id,
+            hidden,
+            comment,
+        }
+    }
+
+    /// 参照先のシート名と `$` を除いた範囲の一覧
+    ///
+    /// セル範囲として解釈できない部分 (定数や数式) は除く
+    #[getter]
+    pub fn destinations(&self) -> Vec<(String, String)> {
+        split_areas(&self.value)
+            .iter()
+            .filter_map(|area| {
+                let (prefix, body) = split_sheet_prefix(area.trim());
+                let sheet: String = prefix_sheet_name(prefix)?;
+                let body: String = body.replace('$', "");
+                parse_range(&body).map(|_| (sheet, body))
+            })
+            .collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("DefinedName({:?}, {:?})", self.name, self.value)
+    }
+}
+
+impl DefinedName {
+    /// `definedName` 要素からの作成
+    fn from_element(element: &XmlElement) -> Option<Self> {
+        Some(DefinedName {
+            name: element.get_attribute("name")?.clone(),
+            value: element.text.clone().unwrap_or_default(),
+            local_sheet_id: element
+                .get_attribute("localSheetId")
+                .and_then(|id| id.parse().ok()),
+            hidden: element
+                .get_attribute("hidden")
+                .is_some_and(|v| v == "1" || v == "true"),
+            comment: element.get_attribute("comment").cloned(),
+        })
+    }
+
+    /// `definedName` 要素への変換
+    fn to_element(&self) -> XmlElement {
+        let mut element: XmlElement = XmlElement::new("definedName");
+        element
+            .attributes
+            .insert("name".to_string(), self.name.clone());
+        if let Some(comment) = &self.comment {
+            element
+                .attributes
+                .insert("comment".to_string(), comment.clone());
+        }
+        if let Some(id) = self.local_sheet_id {
+            element
+                .attributes
+                .insert("localSheetId".to_string(), id.to_string());
+        }
+        if self.hidden {
+            element
+                .attributes
+                .insert("hidden".to_string(), "1".to_string());
+        }
+        element.text = Some(self.value.clone());
+        element
+    }
+
+    /// 名前と範囲が一致するかどうか (名前の大文字と小文字は区別しない)
+    fn matches(&self, name: &str, local_sheet_id: Option<usize>) -> bool {
+        self.name.eq_ignore_ascii_case(name) && self.local_sheet_id == local_sheet_id
+    }
+}
+
+/// カンマ区切りの参照の分割 (引用符で囲まれたシート名内のカンマは区切りとしない)
+pub fn split_areas(value: &str) -> Vec<String> {
+    let mut areas: Vec<String> = Vec::new();
+    let mut current: String = String::new();
+    let mut quoted: bool = false;
+    for c in value.chars() {
+        match c {
+            ',' if !quoted => areas.push(std::mem::take(&mut current)),
+            _ => {
+                if c == '\'' {
+                    quoted = !quoted;
+                }
+                current.push(c);
+            }
+        }
+    }
+    areas.push(current);
+    areas
+}
+
+/// ワークブックの定義された名前の一覧 (`workbook.xml` の `definedNames`)
+#[pyclass]
+pub struct DefinedNames {
+    /// ワークブック全体のパーツ
+    book: BookParts,
+}
+
+#[pymethods]
+impl DefinedNames {
+    /// 名前の追加
+    ///
+    /// 同じ範囲に同じ名前がある場合はエラー
+    pub fn add(&self, defined_name: DefinedName) -> Result<(), NameError> {
+        if !is_valid_name(&defined_name.name) && !defined_name.name.starts_with("_xlnm.") {
+            return Err(NameError::InvalidName(defined_name.name));
+        }
+        if let Some(id) = defined_name.local_sheet_id
+            && id >= self.book.sheet_names().len()
+        {
+            return Err(NameError::InvalidSheetIndex(id));
+        }
+        if self
+            .get(&defined_name.name, defined_name.local_sheet_id)
+            .is_some()
+        {
+            return Err(NameError::DuplicateName(defined_name.name));
+        }
+        let mut workbook: MutexGuard<Xml> = self.book.workbook.lock().unwrap();
+        if let Some(wb) = workbook.elements.first_mut() {
+            wb.get_or_create_child_ordered("definedNames", &WORKBOOK_CHILD_ORDER)
+                .children
+                .push(defined_name.to_element());
+        }
+        Ok(())
+    }
+
+    /// 名前の削除
+    #[pyo3(signature = (name, local_sheet_id=None))]
+    pub fn remove(&self, name: &str, local_sheet_id: Option<usize>) -> Result<(), NameError> {
+        if self.get(name, local_sheet_id).is_none() {
+            return Err(NameError::NotFound(name.to_string()));
+        }
+        self.book.set_defined_name(name, local_sheet_id, None);
+        Ok(())
+    }
+
+    /// 名前による取得 (名前の大文字と小文字は区別しない)
+    #[pyo3(signature = (name, local_sheet_id=None))]
+    pub fn get(&self, name: &str, local_sheet_id: Option<usize>) -> Option<DefinedName> {
+        self.values()
+            .into_iter()
+            .find(|dn| dn.matches(name, local_sheet_id))
+    }
+
+    /// 全ての名前の一覧
+    pub fn values(&self) -> Vec<DefinedName> {
+        let workbook: MutexGuard<Xml> = self.book.workbook.lock().unwrap();
+        workbook
+            .elements
+            .first()
+            .and_then(|wb| wb.find_element("definedNames"))
+            .map(|names| {
+                names
+                    .children
+                    .iter()
+                    .filter_map(DefinedName::from_element)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// ブック全体の名前の取得
+    pub fn __getitem__(&self, name: &str) -> Result<DefinedName, NameError> {
+        self.get(name, None)
+            .ok_or_else(|| NameError::NotFound(name.to_string()))
+    }
+
+    /// ブック全体の名前の削除
+    pub fn __delitem__(&self, name: &str) -> Result<(), NameError> {
+        self.remove(name, None)
+    }
+
+    /// ブック全体の名前が存在するかどうか
+    pub fn __contains__(&self, name: &str) -> bool {
+        self.get(name, None).is_some()
+    }
+
+    /// 名前の数
+    pub fn __len__(&self) -> usize {
+        self.values().len()
+    }
+
+    /// 名前のイテレータ
+    pub fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        PyList::new(py, self.values())?.try_iter()
+    }
+}
+
+impl DefinedNames {
+    /// 新しい `DefinedNames` インスタンスの作成
+    pub fn new(book: BookParts) -> Self {
+        DefinedNames { book }
+    }
+}
@@ -0,0 +1,628 @@
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+
+/// 月の名前
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+/// 曜日の名前 (日曜日から)
+const DAY_NAMES: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+/// 曜日の日本語の名前 (日曜日から)
+const JAPANESE_DAY_NAMES: [&str; 7] = ["日", "月", "火", "水", "木", "金", "土"];
+/// 標準の表示形式で表示する最大の文字数
+const GENERAL_MAX_LENGTH: usize = 11;
+
+/// 表示形式の書式文字列の要素
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    /// そのまま表示する文字列
+    Literal(String),
+    /// 数字の位置 (`0`, `#`, `?`)
+    Digit(char),
+    /// 小数点
+    Point,
+    /// 桁区切りまたは千単位の省略
+    Comma,
+    /// パーセント
+    Percent,
+    /// 指数 (`E+` なら正の符号も表示する)
+    Exponent(bool),
+    /// 分数の区切り
+    Slash,
+    /// 日付と時刻の要素 (小文字の同じ文字の繰り返し)
+    Date(String),
+    /// 経過時間 (`[h]`, `[mm]`, `[ss]`)
+    Elapsed(char, usize),
+    /// 午前と午後 (`AM/PM` または `A/P`)
+    AmPm(String),
+    /// 標準の表示形式
+    General,
+    /// 文字列
+    Text,
+}
+
+/// 組み込みの表示形式の書式文字列
+///
+/// 日付の既定の表示形式 (14, 22) は日本語環境の表示に合わせる
+pub fn builtin_format_code(id: u32) -> Option<&'static str> {
+    let code: &str = match id {
+        0 => "General",
+        1 => "0",
+        2 => "0.00",
+        3 => "#,##0",
+        4 => "#,##0.00",
+        9 => "0%",
+        10 => "0.00%",
+        11 => "0.00E+00",
+        12 => "# ?/?",
+        13 => "# ??/??",
+        14 => "yyyy/m/d",
+        15 => "d-mmm-yy",
+        16 => "d-mmm",
+        17 => "mmm-yy",
+        18 => "h:mm AM/PM",
+        19 => "h:mm:ss AM/PM",
+        20 => "h:mm",
+        21 => "h:mm:ss",
+        22 => "yyyy/m/d h:mm",
+        37 => "#,##0 ;(#,##0)",
+        38 => "#,##0 ;[Red](#,##0)",
+        39 => "#,##0.00;(#,##0.00)",
+        40 => "#,##0.00;[Red](#,##0.00)",
+        45 => "mm:ss",
+        46 => "[h]:mm:ss",
+        47 => "mmss.0",
+        48 => "##0.0E+0",
+        49 => "@",
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// 表示形式による数値の表示テキスト
+///
+/// 色と条件の指定は無視し、正・負・ゼロの区分を使い分ける
+pub fn format_number(value: f64, code: &str) -> String {
+    if code.trim().is_empty() {
+        return general(value);
+    }
+    let sections: Vec<String> = split_sections(code);
+    let index: usize = if value < 0.0 && sections.len() >= 2 {
+        1
+    } else if value == 0.0 && sections.len() >= 3 {
+        2
+    } else {
+        0
+    };
+    let tokens: Vec<Token> = tokenize(sections.get(index).map_or("", String::as_str));
+    let is_date: bool = tokens
+        .iter()
+        .any(|t| matches!(t, Token::Date(_) | Token::Elapsed(_, _) | Token::AmPm(_)));
+    if is_date {
+        if value < 0.0 {
+            return general(value);
+        }
+        return render_date(&tokens, value);
+    }
+    let sign: &str = if index == 0 && value < 0.0 { "-" } else { "" };
+    format!("{sign}{}", render_number(&tokens, value.abs()))
+}
+
+/// 標準の表示形式の表示テキスト (最大11文字)
+pub fn general(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e11 {
+        return format!("{value:.0}");
+    }
+    let magnitude: f64 = value.abs();
+    if !(1e-9..1e11).contains(&magnitude) {
+        let text: String = format!("{value:.5E}");
+        let (mantissa, exponent) = text.split_once('E').unwrap_or((&text, "0"));
+        let mantissa: &str = mantissa.trim_end_matches('0').trim_end_matches('.');
+        let exponent: i32 = exponent.parse().unwrap_or(0);
+        let sign: char = if exponent < 0 { '-' } else { '+' };
+        return format!("{mantissa}E{sign}{:02}", exponent.abs());
+    }
+    let integer_length: usize = format!("{:.0}", magnitude.trunc()).len();
+    let sign_length: usize = usize::from(value < 0.0);
+    let decimals: usize = GENERAL_MAX_LENGTH.saturating_sub(integer_length + sign_length + 1);
+    let text: String = format!("{value:.decimals$}");
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
+}
+
+/// `;` による区分への分割 (引用符などの中の `;` は区切りとしない)
+fn split_sections(code: &str) -> Vec<String> {
+    let mut sections: Vec<String> = vec![String::new()];
+    let mut chars = code.chars();
+    let mut quoted: bool = false;
+    while let Some(c) = chars.next() {
+        let current: &mut String = sections.last_mut().unwrap();
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            '\\' | '_' | '*' if !quoted => {
+                current.push(c);
+                current.extend(chars.next());
+            }
+            ';' if !quoted => sections.push(String::new()),
+            _ => current.push(c),
+        }
+    }
+    sections
+}
+
+/// 書式文字列の要素への分解
+fn tokenize(section: &str) -> Vec<Token> {
+    let chars: Vec<char> = section.chars().collect();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut i: usize = 0;
+    let starts_with = |i: usize, s: &str| -> bool {
+        let rest: String = chars[i..].iter().take(s.len()).collect();
+        rest.eq_ignore_ascii_case(s)
+    };
+    while i < chars.len() {
+        let c: char = chars[i];
+        i += 1;
+        match c {
+            '"' => {
+                let text: String = chars[i..].iter().take_while(|&&c| c != '"').collect();
+                i += text.chars().count() + 1;
+                tokens.push(Token::Literal(text));
+            }
+            '\\' => {
+                tokens.extend(chars.get(i).map(|c| Token::Literal(c.to_string())));
+                i += 1;
+            }
+            '_' => {
+                tokens.push(Token::Literal(" ".to_string()));
+                i += 1;
+            }
+            '*' => i += 1,
+            '[' => {
+                let content: String = chars[i..].iter().take_while(|&&c| c != ']').collect();
+                i += content.chars().count() + 1;
+                let lower: String = content.to_lowercase();
+                if let Some(first) = lower.chars().next()
+                    && matches!(first, 'h' | 'm' | 's')
+                    && lower.chars().all(|c| c == first)
+                {
+                    tokens.push(Token::Elapsed(first, lower.len()));
+                } else if let Some(currency) = content.strip_prefix('$') {
+                    let symbol: &str = currency.split('-').next().unwrap_or("");
+                    if !symbol.is_empty() {
+                        tokens.push(Token::Literal(symbol.to_string()));
+                    }
+                }
+            }
+            _ if starts_with(i - 1, "General") => {
+                i += "General".len() - 1;
+                tokens.push(Token::General);
+            }
+            _ if starts_with(i - 1, "AM/PM") => {
+                i += "AM/PM".len() - 1;
+                tokens.push(Token::AmPm("AM/PM".to_string()));
+            }
+            _ if starts_with(i - 1, "A/P") => {
+                i += "A/P".len() - 1;
+                tokens.push(Token::AmPm("A/P".to_string()));
+            }
+            '0' | '#' | '?' => tokens.push(Token::Digit(c)),
+            '.' => tokens.push(Token::Point),
+            ',' => tokens.push(Token::Comma),
+            '%' => tokens.push(Token::Percent),
+            '/' => tokens.push(Token::Slash),
+            '@' => tokens.push(Token::Text),
+            'E' | 'e' if matches!(chars.get(i), Some('+' | '-')) => {
+                tokens.push(Token::Exponent(chars[i] == '+'));
+                i += 1;
+            }
+            _ => {
+                let lower: char = c.to_ascii_lowercase();
+                let repeat: usize = 1 + chars[i..]
+                    .iter()
+                    .take_while(|c| c.to_ascii_lowercase() == lower)
+                    .count();
+                let is_date: bool = match lower {
+                    'y' | 'm' | 'd' | 'h' | 's' | 'e' => true,
+                    'a' => repeat >= 3,
+                    _ => false,
+                };
+                if is_date {
+                    i += repeat - 1;
+                    tokens.push(Token::Date(lower.to_string().repeat(repeat)));
+                } else {
+                    tokens.push(Token::Literal(c.to_string()));
+                }
+            }
+        }
+    }
+    tokens
+}
+
+/// 日付と時刻の表示テキスト
+fn render_date(tokens: &[Token], value: f64) -> String {
+    let total_ms: i64 = (value * 86_400_000.0).round() as i64;
+    let days: i64 = total_ms.div_euclid(86_400_000);
+    let ms_of_day: i64 = total_ms.rem_euclid(86_400_000);
+    let Some(epoch) = NaiveDate::from_ymd_opt(1899, 12, 31) else {
+        return general(value);
+    };
+    let Some(date) =
+        epoch.checked_add_signed(Duration::days(if days >= 60 { days - 1 } else { days }))
+    else {
+        return general(value);
+    };
+    let Some(datetime) = date.and_hms_milli_opt(
+        (ms_of_day / 3_600_000) as u32,
+        (ms_of_day / 60_000 % 60) as u32,
+        (ms_of_day / 1000 % 60) as u32,
+        (ms_of_day % 1000) as u32,
+    ) else {
+        return general(value);
+    };
+    let twelve_hour: bool = tokens.iter().any(|t| matches!(t, Token::AmPm(_)));
+    let dates: Vec<(usize, &str)> = tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(i, t)| match t {
+            Token::Date(d) => Some((i, d.as_str())),
+            Token::Elapsed(c, _) if *c == 'h' => Some((i, "h")),
+            Token::Elapsed(c, _) if *c == 's' => Some((i, "s")),
+            _ => None,
+        })
+        .collect();
+    let mut text: String = String::new();
+    let mut fraction_digits: usize = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        match token {
+            Token::Date(d) => {
+                let position: usize = dates.iter().position(|&(j, _)| j == i).unwrap_or(0);
+                let after_hour: bool = position
+                    .checked_sub(1)
+                    .and_then(|p| dates.get(p))
+                    .is_some_and(|(_, p)| p.starts_with('h'));
+                let before_second: bool = dates
+                    .get(position + 1)
+                    .is_some_and(|(_, n)| n.starts_with('s'));
+                let is_minute: bool = d.len() <= 2 && (after_hour || before_second);
+                text.push_str(&date_part(&datetime, d, is_minute, twelve_hour));
+            }
+            Token::Elapsed(unit, width) => {
+                let elapsed: i64 = match unit {
+                    'h' => total_ms / 3_600_000,
+                    'm' => total_ms / 60_000,
+                    _ => total_ms / 1000,
+                };
+                text.push_str(&format!("{elapsed:0width$}"));
+            }
+            Token::AmPm(marker) => {
+                let pm: bool = datetime.hour() >= 12;
+                text.push_str(match (marker.as_str(), pm) {
+                    ("A/P", false) => "A",
+                    ("A/P", true) => "P",
+                    (_, false) => "AM",
+                    (_, true) => "PM",
+                });
+            }
+            Token::Digit('0') => {
+                let digits: String = format!("{:03}", ms_of_day % 1000);
+                text.extend(digits.chars().nth(fraction_digits));
+                fraction_digits += 1;
+            }
+            Token::Literal(literal) => text.push_str(literal),
+            Token::Point => text.push('.'),
+            Token::Comma => text.push(','),
+            Token::Slash => text.push('/'),
+            Token::Percent => text.push('%'),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// 日付と時刻の要素の表示テキスト
+fn date_part(datetime: &NaiveDateTime, part: &str, is_minute: bool, twelve_hour: bool) -> String {
+    let length: usize = part.len();
+    let weekday: usize = datetime.weekday().num_days_from_sunday() as usize;
+    match part.chars().next() {
+        Some('y') if length <= 2 => format!("{:02}", datetime.year() % 100),
+        Some('y' | 'e') => datetime.year().to_string(),
+        Some('m') if is_minute => format!("{:0length$}", datetime.minute()),
+        Some('m') => {
+            let name: &str = MONTH_NAMES[datetime.month0() as usize];
+            match length {
+                1 | 2 => format!("{:0length$}", datetime.month()),
+                3 => name[..3].to_string(),
+                4 => name.to_string(),
+                _ => name[..1].to_string(),
+            }
+        }
+        Some('d') => match length {
+            1 | 2 => format!("{:0length$}", datetime.day()),
+            3 => DAY_NAMES[weekday][..3].to_string(),
+            _ => DAY_NAMES[weekday].to_string(),
+        },
+        Some('a') if length == 3 => JAPANESE_DAY_NAMES[weekday].to_string(),
+        Some('a') => format!("{}曜日", JAPANESE_DAY_NAMES[weekday]),
+        Some('h') => {
+            let hour: u32 = if twelve_hour {
+                (datetime.hour() + 11) % 12 + 1
+            } else {
+                datetime.hour()
+            };
+            format!("{hour:0width$}", width = length.min(2))
+        }
+        Some('s') => format!("{:0width$}", datetime.second(), width = length.min(2)),
+        _ => String::new(),
+    }
+}
+
+/// 数値の表示テキスト (符号を除く)
+fn render_number(tokens: &[Token], value: f64) -> String {
+    if tokens
+        .iter()
+        .any(|t| matches!(t, Token::General | Token::Text))
+    {
+        return tokens
+            .iter()
+            .map(|t| match t {
+                Token::General | Token::Text => general(value),
+                _ => literal(t),
+            })
+            .collect();
+    }
+    let Some(first_digit) = tokens.iter().position(|t| matches!(t, Token::Digit(_))) else {
+        return tokens.iter().map(literal).collect();
+    };
+    if let Some(slash) = tokens.iter().position(|t| *t == Token::Slash)
+        && slash > first_digit
+    {
+        return render_fraction(tokens, value, first_digit, slash);
+    }
+    let exponent: Option<usize> = tokens.iter().position(|t| matches!(t, Token::Exponent(_)));
+    let point: Option<usize> = tokens
+        .iter()
+        .position(|t| *t == Token::Point)
+        .filter(|&p| p > first_digit && exponent.is_none_or(|e| p < e));
+    let last_digit: usize = tokens[..exponent.unwrap_or(tokens.len())]
+        .iter()
+        .rposition(|t| matches!(t, Token::Digit(_)))
+        .unwrap_or(first_digit);
+    let integer_end: usize = point.or(exponent).unwrap_or(last_digit + 1);
+    let integer_tokens: &[Token] = &tokens[first_digit..integer_end];
+    let last_integer_digit: usize = integer_tokens
+        .iter()
+        .rposition(|t| matches!(t, Token::Digit(_)))
+        .unwrap_or(0);
+    let grouping: bool = integer_tokens[..last_integer_digit].contains(&Token::Comma);
+    let scale: i32 = tokens[first_digit + last_integer_digit..exponent.unwrap_or(tokens.len())]
+        .iter()
+        .skip(1)
+        .take_while(|t| **t == Token::Comma)
+        .count() as i32;
+    let percents: i32 = tokens.iter().filter(|t| **t == Token::Percent).count() as i32;
+    let mut value: f64 = value * 100f64.powi(percents) / 1000f64.powi(scale);
+    let fraction_end: usize = exponent.unwrap_or(tokens.len());
+    let fraction_placeholders: Vec<char> = point
+        .map(|p| {
+            tokens[p + 1..fraction_end]
+                .iter()
+                .filter_map(|t| match t {
+                    Token::Digit(c) => Some(*c),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let integer_count: usize = integer_tokens
+        .iter()
+        .filter(|t| matches!(t, Token::Digit(_)))
+        .count();
+    let mut power: i32 = 0;
+    if exponent.is_some() && value != 0.0 {
+        power = value.log10().floor() as i32;
+        if integer_count > 1 && integer_tokens.contains(&Token::Digit('#')) {
+            power -= power.rem_euclid(integer_count as i32);
+        } else {
+            power -= integer_count.max(1) as i32 - 1;
+        }
+        value /= 10f64.powi(power);
+    }
+    let decimals: usize = fraction_placeholders.len();
+    let rounded: String = format!("{value:.decimals$}");
+    let (integer_digits, fraction_digits) = rounded.split_once('.').unwrap_or((&rounded, ""));
+    let integer_digits: &str = integer_digits.trim_start_matches('0');
+
+    let mut text: String = tokens[..first_digit].iter().map(literal).collect();
+    text.push_str(&render_integer(integer_tokens, integer_digits, grouping));
+    let fraction: Vec<char> = fraction_digits.chars().collect();
+    let significant: usize = fraction
+        .iter()
+        .zip(&fraction_placeholders)
+        .rposition(|(&digit, &placeholder)| digit != '0' || placeholder == '0')
+        .map_or(0, |p| p + 1);
+    let mut fraction_index: usize = 0;
+    let mut exponent_digits: Vec<Token> = Vec::new();
+    for (i, token) in tokens.iter().enumerate().skip(integer_end) {
+        if exponent.is_some_and(|e| i > e) && matches!(token, Token::Digit(_)) {
+            exponent_digits.push(token.clone());
+            if !matches!(tokens.get(i + 1), Some(Token::Digit(_))) {
+                let width: usize = exponent_digits.len();
+                text.push_str(&format!("{:0width$}", power.abs()));
+            }
+            continue;
+        }
+        match token {
+            Token::Digit(placeholder) => {
+                let digit: char = fraction.get(fraction_index).copied().unwrap_or('0');
+                if fraction_index < significant {
+                    text.push(digit);
+                } else if *placeholder == '?' {
+                    text.push(' ');
+                }
+                fraction_index += 1;
+            }
+            Token::Exponent(plus) => {
+                text.push('E');
+                if power < 0 {
+                    text.push('-');
+                } else if *plus {
+                    text.push('+');
+                }
+            }
+            Token::Comma => {}
+            _ => text.push_str(&literal(token)),
+        }
+    }
+    text
+}
+
+/// 整数部の表示テキスト (右の桁から数字の位置に当てはめる)
+fn render_integer(tokens: &[Token], digits: &str, grouping: bool) -> String {
+    let mut digits: Vec<char> = digits.chars().collect();
+    let mut pieces: Vec<String> = Vec::new();
+    let mut count: usize = 0;
+    let mut push_digit = |pieces: &mut Vec<String>, digit: char| {
+        if grouping && count > 0 && count.is_multiple_of(3) {
+            pieces.push(",".to_string());
+        }
+        pieces.push(digit.to_string());
+        count += 1;
+    };
+    let first_digit: usize = tokens
+        .iter()
+        .position(|t| matches!(t, Token::Digit(_)))
+        .unwrap_or(0);
+    for (i, token) in tokens.iter().enumerate().rev() {
+        match token {
+            Token::Digit(placeholder) => {
+                match digits.pop() {
+                    Some(digit) => push_digit(&mut pieces, digit),
+                    None if *placeholder == '0' => push_digit(&mut pieces, '0'),
+                    None if *placeholder == '?' => pieces.push(" ".to_string()),
+                    None => {}
+                }
+                if i == first_digit {
+                    while let Some(digit) = digits.pop() {
+                        push_digit(&mut pieces, digit);
+                    }
+                }
+            }
+            Token::Comma => {}
+            _ => pieces.push(literal(token)),
+        }
+    }
+    pieces.into_iter().rev().collect()
+}
+
+/// 分数の表示テキスト (例: `# ?/?`)
+fn render_fraction(tokens: &[Token], value: f64, first_digit: usize, slash: usize) -> String {
+    let numerator_start: usize = tokens[..slash]
+        .iter()
+        .rposition(|t| !matches!(t, Token::Digit(_)))
+        .map_or(0, |p| p + 1)
+        .max(first_digit);
+    let numerator_width: usize = slash - numerator_start;
+    let has_integer: bool = tokens[first_digit..numerator_start]
+        .iter()
+        .any(|t| matches!(t, Token::Digit(_)));
+    let denominator_tokens: Vec<&Token> = tokens[slash + 1..]
+        .iter()
+        .take_while(|t| matches!(t, Token::Digit(_) | Token::Literal(_)))
+        .take_while(|t| match t {
+            Token::Literal(l) => l.chars().all(|c| c.is_ascii_digit()),
+            _ => true,
+        })
+        .collect();
+    let fixed: Option<u64> = denominator_tokens
+        .iter()
+        .map(|t| literal(t))
+        .collect::<String>()
+        .parse()
+        .ok()
+        .filter(|_| {
+            denominator_tokens
+                .iter()
+                .all(|t| matches!(t, Token::Literal(_)))
+        });
+    let denominator_width: usize = denominator_tokens.len().max(1);
+    let whole: f64 = if has_integer { value.trunc() } else { 0.0 };
+    let fraction: f64 = value - whole;
+    let (mut numerator, denominator) = match fixed {
+        Some(denominator) => ((fraction * denominator as f64).round() as u64, denominator),
+        None => {
+            let max: u64 = 10u64.pow(denominator_width as u32) - 1;
+            (1..=max)
+                .map(|d| ((fraction * d as f64).round() as u64, d))
+                .min_by(|a, b| {
+                    let error = |(n, d): (u64, u64)| (fraction - n as f64 / d as f64).abs();
+                    error(*a).total_cmp(&error(*b))
+                })
+                .unwrap_or((0, 1))
+        }
+    };
+    let mut whole: u64 = whole as u64;
+    if has_integer && numerator == denominator {
+        whole += 1;
+        numerator = 0;
+    }
+    let mut text: String = tokens[..first_digit].iter().map(literal).collect();
+    if has_integer && (whole > 0 || numerator == 0) {
+        text.push_str(&whole.to_string());
+    }
+    if has_integer {
+        let last_integer_digit: usize = tokens[..numerator_start]
+            .iter()
+            .rposition(|t| matches!(t, Token::Digit(_)))
+            .unwrap_or(first_digit);
+        text.extend(
+            tokens[last_integer_digit + 1..numerator_start]
+                .iter()
+                .map(literal),
+        );
+    }
+    if numerator == 0 && has_integer {
+        text.push_str(&" ".repeat(numerator_width + denominator_width + 1));
+    } else {
+        text.push_str(&format!(
+            "{numerator:>numerator_width$}/{denominator:<denominator_width$}"
+        ));
+    }
+    let end: usize = slash + 1 + denominator_tokens.len();
+    text.extend(tokens[end..].iter().map(literal));
+    text
+}
+
+/// 数字以外の要素の表示テキスト
+fn literal(token: &Token) -> String {
+    match token {
+        Token::Literal(text) => text.clone(),
+        Token::Percent => "%".to_string(),
+        Token::Point => ".".to_string(),
+        Token::Slash => "/".to_string(),
+        _ => String::new(),
+    }
+}
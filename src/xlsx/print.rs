@@ -0,0 +1,863 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use thiserror::Error;
+
+use crate::book::BookError;
+use crate::formula::{
+    MAX_COL, MAX_ROW, col_to_string, parse_address, quote_sheet_name, split_sheet_prefix,
+    string_to_col,
+};
+use crate::name::split_areas;
+use crate::sheet::WORKSHEET_CHILD_ORDER;
+use crate::view::SHEET_PR_CHILD_ORDER;
+use crate::xml::{Xml, XmlElement};
+
+/// `headerFooter` の子要素の順序
+const HEADER_FOOTER_CHILD_ORDER: [&str; 6] = [
+    "oddHeader",
+    "oddFooter",
+    "evenHeader",
+    "evenFooter",
+    "firstHeader",
+    "firstFooter",
+];
+
+/// 余白の既定値 (インチ): 左, 右, 上, 下, ヘッダー, フッター
+const DEFAULT_MARGINS: [(&str, f64); 6] = [
+    ("left", 0.7),
+    ("right", 0.7),
+    ("top", 0.75),
+    ("bottom", 0.75),
+    ("header", 0.3),
+    ("footer", 0.3),
+];
+
+/// ヘッダー・フッターのプレースホルダーと制御コードの対応
+const HEADER_FOOTER_CODES: [(&str, &str); 17] = [
+    ("{page}", "&P"),
+    ("{pages}", "&N"),
+    ("{date}", "&D"),
+    ("{time}", "&T"),
+    ("{file}", "&F"),
+    ("{path}", "&Z"),
+    ("{sheet}", "&A"),
+    ("{picture}", "&G"),
+    ("{bold}", "&B"),
+    ("{italic}", "&I"),
+    ("{underline}", "&U"),
+    ("{double_underline}", "&E"),
+    ("{strikethrough}", "&S"),
+    ("{superscript}", "&X"),
+    ("{subscript}", "&Y"),
+    ("{outline}", "&O"),
+    ("{shadow}", "&H"),
+];
+
+/// 印刷設定のエラー
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum PrintError {
+    #[error("Orientation must be 'portrait' or 'landscape': {0}")]
+    InvalidOrientation(String),
+    #[error("Scale must be between 10 and 400: {0}")]
+    InvalidScale(u32),
+    #[error("Margin must not be negative: {0}")]
+    InvalidMargin(f64),
+    #[error("Invalid print area: {0}")]
+    InvalidPrintArea(String),
+    #[error("Invalid print titles: {0}")]
+    InvalidPrintTitles(String),
+    #[error("Page break must specify a row or column")]
+    InvalidPageBreak,
+    #[error(transparent)]
+    Book(#[from] BookError),
+}
+
+impl From<PrintError> for PyErr {
+    fn from(err: PrintError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// ワークシートの子要素の属性の取得
+fn attribute(xml: &Arc<Mutex<Xml>>, path: &str, key: &str) -> Option<String> {
+    xml.lock()
+        .unwrap()
+        .elements
+        .first()?
+        .find_element(path)?
+        .get_attribute(key)
+        .cloned()
+}
+
+/// ワークシートの子要素の更新 (存在しない場合は順序を保って作成)
+fn update(xml: &Arc<Mutex<Xml>>, tag: &str, f: impl FnOnce(&mut XmlElement)) {
+    let mut xml: MutexGuard<Xml> = xml.lock().unwrap();
+    if let Some(worksheet) = xml.elements.first_mut() {
+        f(worksheet.get_or_create_child_ordered(tag, &WORKSHEET_CHILD_ORDER));
+    }
+}
+
+/// 属性の設定 (`None` の場合は削除)
+fn set_attribute(element: &mut XmlElement, key: &str, value: Option<String>) {
+    match value {
+        Some(value) => {
+            element.attributes.insert(key.to_string(), value);
+        }
+        None => {
+            element.attributes.remove(key);
+        }
+    }
+}
+
+/// XMLの真偽値の判定
+fn is_true(value: Option<String>) -> bool {
+    value.is_some_and(|v| v == "1" || v == "true")
+}
+
+/// ページ設定 (`pageSetup`)
+#[pyclass]
+pub struct PageSetup {
+    /// ワークシートのXML
+    xml: Arc<Mutex<Xml>>,
+}
+
+#[pymethods]
+impl PageSetup {
+    /// 印刷の向き ("portrait" または "landscape")
+    #[getter]
+    pub fn get_orientation(&self) -> Option<String> {
+        attribute(&self.xml, "pageSetup", "orientation")
+    }
+
+    #[setter(orientation)]
+    pub fn set_orientation(&self, orientation: Option<String>) -> Result<(), PrintError> {
+        if let Some(o) = orientation.as_deref()
+            && o != "portrait"
+            && o != "landscape"
+        {
+            return Err(PrintError::InvalidOrientation(o.to_string()));
+        }
+        update(&self.xml, "pageSetup", |e| {
+            set_attribute(e, "orientation", orientation)
+        });
+        Ok(())
+    }
+
+    /// 用紙サイズの番号 (例: 9 = A4)
+    #[getter]
+    pub fn get_paper_size(&self) -> Option<u32> {
+        attribute(&self.xml, "pageSetup", "paperSize")?.parse().ok()
+    }
+
+    #[setter(paper_size)]
+    pub fn set_paper_size(&self, paper_size: Option<u32>) {
+        update(&self.xml, "pageSetup", |e| {
+            set_attribute(e, "paperSize", paper_size.map(|p| p.to_string()))
+        });
+    }
+
+    /// 横方向のページ数に合わせて印刷 (0 は自動)
+    #[getter]
+    pub fn get_fit_to_width(&self) -> Option<u32> {
+        attribute(&self.xml, "pageSetup", "fitToWidth")?
+            .parse()
+            .ok()
+    }
+
+    #[setter(fit_to_width)]
+    pub fn set_fit_to_width(&self, pages: Option<u32>) {
+        self.set_fit("fitToWidth", pages);
+    }
+
+    /// 縦方向のページ数に合わせて印刷 (0 は自動)
+    #[getter]
+    pub fn get_fit_to_height(&self) -> Option<u32> {
+        attribute(&self.xml, "pageSetup", "fitToHeight")?
+            .parse()
+            .ok()
+    }
+
+    #[setter(fit_to_height)]
+    pub fn set_fit_to_height(&self, pages: Option<u32>) {
+        self.set_fit("fitToHeight", pages);
+    }
+
+    /// 拡大縮小率 (%)
+    #[getter]
+    pub fn get_scale(&self) -> Option<u32> {
+        attribute(&self.xml, "pageSetup", "scale")?.parse().ok()
+    }
+
+    #[setter(scale)]
+    pub fn set_scale(&self, scale: Option<u32>) -> Result<(), PrintError> {
+        if let Some(s) = scale
+            && !(10..=400).contains(&s)
+        {
+            return Err(PrintError::InvalidScale(s));
+        }
+        update(&self.xml, "pageSetup", |e| {
+            set_attribute(e, "scale", scale.map(|s| s.to_string()))
+        });
+        Ok(())
+    }
+
+    /// ページ数に合わせて印刷するかどうか (`sheetPr>pageSetUpPr@fitToPage`)
+    #[getter]
+    pub fn get_fit_to_page(&self) -> bool {
+        is_true(attribute(&self.xml, "sheetPr>pageSetUpPr", "fitToPage"))
+    }
+
+    #[setter(fit_to_page)]
+    pub fn set_fit_to_page(&self, fit: bool) {
+        update(&self.xml, "sheetPr", |sheet_pr| {
+            let setup: &mut XmlElement =
+                sheet_pr.get_or_create_child_ordered("pageSetUpPr", &SHEET_PR_CHILD_ORDER);
+            set_attribute(setup, "fitToPage", fit.then(|| "1".to_string()));
+        });
+    }
+}
+
+impl PageSetup {
+    /// 新しい `PageSetup` インスタンスの作成
+    pub fn new(xml: Arc<Mutex<Xml>>) -> Self {
+        PageSetup { xml }
+    }
+
+    /// ページ数に合わせる設定 (`fitToPage` も有効にする)
+    fn set_fit(&self, key: &str, pages: Option<u32>) {
+        update(&self.xml, "pageSetup", |e| {
+            set_attribute(e, key, pages.map(|p| p.to_string()))
+        });
+        if pages.is_some() {
+            self.set_fit_to_page(true);
+        }
+    }
+}
+
+/// ページの余白 (`pageMargins`、インチ)
+#[pyclass]
+pub struct PageMargins {
+    /// ワークシートのXML
+    xml: Arc<Mutex<Xml>>,
+}
+
+#[pymethods]
+impl PageMargins {
+    /// 左の余白
+    #[getter]
+    pub fn get_left(&self) -> f64 {
+        self.margin("left")
+    }
+
+    #[setter(left)]
+    pub fn set_left(&self, value: f64) -> Result<(), PrintError> {
+        self.set_margin("left", value)
+    }
+
+    /// 右の余白
+    #[getter]
+    pub fn get_right(&self) -> f64 {
+        self.margin("right")
+    }
+
+    #[setter(right)]
+    pub fn set_right(&self, value: f64) -> Result<(), PrintError> {
+        self.set_margin("right", value)
+    }
+
+    /// 上の余白
+    #[getter]
+    pub fn get_top(&self) -> f64 {
+        self.margin("top")
+    }
+
+    #[setter(top)]
+    pub fn set_top(&self, value: f64) -> Result<(), PrintError> {
+        self.set_margin("top", value)
+    }
+
+    /// 下の余白
+    #[getter]
+    pub fn get_bottom(&self) -> f64 {
+        self.margin("bottom")
+    }
+
+    #[setter(bottom)]
+    pub fn set_bottom(&self, value: f64) -> Result<(), PrintError> {
+        self.set_margin("bottom", value)
+    }
+
+    /// ヘッダーの余白
+    #[getter]
+    pub fn get_header(&self) -> f64 {
+        self.margin("header")
+    }
+
+    #[setter(header)]
+    pub fn set_header(&self, value: f64) -> Result<(), PrintError> {
+        self.set_margin("header", value)
+    }
+
+    /// フッターの余白
+    #[getter]
+    pub fn get_footer(&self) -> f64 {
+        self.margin("footer")
+    }
+
+    #[setter(footer)]
+    pub fn set_footer(&self, value: f64) -> Result<(), PrintError> {
+        self.set_margin("footer", value)
+    }
+}
+
+impl PageMargins {
+    /// 新しい `PageMargins` インスタンスの作成
+    pub fn new(xml: Arc<Mutex<Xml>>) -> Self {
+        PageMargins { xml }
+    }
+
+    /// 余白の取得 (未設定の場合は既定値)
+    fn margin(&self, key: &str) -> f64 {
+        attribute(&self.xml, "pageMargins", key)
+            .and_then(|v| v.parse().ok())
+            .or_else(|| DEFAULT_MARGINS.iter().find(|(k, _)| *k == key).map(|m| m.1))
+            .unwrap_or_default()
+    }
+
+    /// 余白の設定
+    ///
+    /// 全ての属性が必須のため、未設定の余白には既定値を書き込む
+    fn set_margin(&self, key: &str, value: f64) -> Result<(), PrintError> {
+        if value < 0.0 {
+            return Err(PrintError::InvalidMargin(value));
+        }
+        update(&self.xml, "pageMargins", |e| {
+            for (k, default) in DEFAULT_MARGINS {
+                e.attributes
+                    .entry(k.to_string())
+                    .or_insert_with(|| default.to_string());
+            }
+            e.attributes.insert(key.to_string(), value.to_string());
+        });
+        Ok(())
+    }
+}
+
+/// 印刷オプション (`printOptions`)
+#[pyclass]
+pub struct PrintOptions {
+    /// ワークシートのXML
+    xml: Arc<Mutex<Xml>>,
+}
+
+#[pymethods]
+impl PrintOptions {
+    /// 枠線を印刷するかどうか
+    #[getter]
+    pub fn get_grid_lines(&self) -> bool {
+        is_true(attribute(&self.xml, "printOptions", "gridLines"))
+    }
+
+    #[setter(grid_lines)]
+    pub fn set_grid_lines(&self, value: bool) {
+        self.set_flag("gridLines", value);
+    }
+
+    /// 行番号と列番号を印刷するかどうか
+    #[getter]
+    pub fn get_headings(&self) -> bool {
+        is_true(attribute(&self.xml, "printOptions", "headings"))
+    }
+
+    #[setter(headings)]
+    pub fn set_headings(&self, value: bool) {
+        self.set_flag("headings", value);
+    }
+
+    /// ページの水平方向の中央に印刷するかどうか
+    #[getter]
+    pub fn get_horizontal_centered(&self) -> bool {
+        is_true(attribute(&self.xml, "printOptions", "horizontalCentered"))
+    }
+
+    #[setter(horizontal_centered)]
+    pub fn set_horizontal_centered(&self, value: bool) {
+        self.set_flag("horizontalCentered", value);
+    }
+
+    /// ページの垂直方向の中央に印刷するかどうか
+    #[getter]
+    pub fn get_vertical_centered(&self) -> bool {
+        is_true(attribute(&self.xml, "printOptions", "verticalCentered"))
+    }
+
+    #[setter(vertical_centered)]
+    pub fn set_vertical_centered(&self, value: bool) {
+        self.set_flag("verticalCentered", value);
+    }
+}
+
+impl PrintOptions {
+    /// 新しい `PrintOptions` インスタンスの作成
+    pub fn new(xml: Arc<Mutex<Xml>>) -> Self {
+        PrintOptions { xml }
+    }
+
+    /// 真偽値の属性の設定
+    ///
+    /// `gridLines` を印刷するには `gridLinesSet` も必要
+    fn set_flag(&self, key: &str, value: bool) {
+        update(&self.xml, "printOptions", |e| {
+            set_attribute(e, key, value.then(|| "1".to_string()));
+            if key == "gridLines" {
+                set_attribute(e, "gridLinesSet", value.then(|| "1".to_string()));
+            }
+        });
+    }
+}
+
+/// ヘッダーまたはフッターの一つ (左・中央・右の各部分)
+///
+/// テキスト内の `{page}`, `{pages}`, `{date}`, `{time}`, `{file}`, `{path}`, `{sheet}`, `{picture}` は
+/// それぞれ `&P`, `&N`, `&D`, `&T`, `&F`, `&Z`, `&A`, `&G` に変換する。
+/// 書式の切り替えの `{bold}`, `{italic}`, `{underline}`, `{double_underline}`, `{strikethrough}`,
+/// `{superscript}`, `{subscript}`, `{outline}`, `{shadow}` は `&B`, `&I`, `&U`, `&E`, `&S`, `&X`,
+/// `&Y`, `&O`, `&H` に変換する
+#[pyclass]
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct HeaderFooterPart {
+    /// 左部分のテキスト
+    #[pyo3(get, set)]
+    pub left: Option<String>,
+    /// 中央部分のテキスト
+    #[pyo3(get, set)]
+    pub center: Option<String>,
+    /// 右部分のテキスト
+    #[pyo3(get, set)]
+    pub right: Option<String>,
+    /// フォント名とスタイル (例: "Arial,Bold")
+    #[pyo3(get, set)]
+    pub font: Option<String>,
+    /// フォントサイズ (ポイント)
+    #[pyo3(get, set)]
+    pub size: Option<u32>,
+    /// RGB形式のフォントの色 (例: "FF0000")
+    #[pyo3(get, set)]
+    pub color: Option<String>,
+}
+
+#[pymethods]
+impl HeaderFooterPart {
+    /// 新しい `HeaderFooterPart` インスタンスの作成
+    #[new]
+    #[pyo3(signature = (left=None, center=None, right=None, font=None, size=None, color=None))]
+    fn py_new(
+        left: Option<String>,
+        center: Option<String>,
+        right: Option<String>,
+        font: Option<String>,
+        size: Option<u32>,
+        color: Option<String>,
+    ) -> Self {
+        HeaderFooterPart {
+            left,
+            center,
+            right,
+            font,
+            size,
+            color,
+        }
+    }
+
+    /// ヘッダー・フッターの書式文字列 (例: "&L&\"Arial,Bold\"&12Title&RPage &P of &N")
+    pub fn build(&self) -> String {
+        let mut font_codes: String = String::new();
+        if let Some(font) = &self.font {
+            font_codes.push_str(&format!("&\"{font}\""));
+        }
+        if let Some(size) = self.size {
+            font_codes.push_str(&format!("&{size}"));
+        }
+        if let Some(color) = &self.color {
+            font_codes.push_str(&format!("&K{color}"));
+        }
+        [
+            ("&L", &self.left),
+            ("&C", &self.center),
+            ("&R", &self.right),
+        ]
+        .iter()
+        .filter_map(|(code, text)| {
+            let text: String = encode_text(text.as_ref()?);
+            // サイズの直後の数字はサイズの一部になるため空白で区切る
+            let separator: &str = if self.size.is_some()
+                && self.color.is_none()
+                && text.starts_with(|c: char| c.is_ascii_digit())
+            {
+                " "
+            } else {
+                ""
+            };
+            Some(format!("{code}{font_codes}{separator}{text}"))
+        })
+        .collect()
+    }
+
+    /// 書式文字列の解析
+    #[staticmethod]
+    pub fn parse(text: &str) -> HeaderFooterPart {
+        let mut part: HeaderFooterPart = HeaderFooterPart::default();
+        let chars: Vec<char> = text.chars().collect();
+        let mut section: char = 'C';
+        let mut buffer: String = String::new();
+        let mut i: usize = 0;
+        let flush = |part: &mut HeaderFooterPart, section: char, buffer: &mut String| {
+            if buffer.is_empty() {
+                return;
+            }
+            let target: &mut Option<String> = match section {
+                'L' => &mut part.left,
+                'R' => &mut part.right,
+                _ => &mut part.center,
+            };
+            target.get_or_insert_with(String::new).push_str(buffer);
+            buffer.clear();
+        };
+        while i < chars.len() {
+            if chars[i] != '&' || i + 1 >= chars.len() {
+                buffer.push(chars[i]);
+                i += 1;
+                continue;
+            }
+            let code: char = chars[i + 1];
+            i += 2;
+            match code {
+                'L' | 'C' | 'R' => {
+                    flush(&mut part, section, &mut buffer);
+                    section = code;
+                    let target: &mut Option<String> = match section {
+                        'L' => &mut part.left,
+                        'R' => &mut part.right,
+                        _ => &mut part.center,
+                    };
+                    target.get_or_insert_with(String::new);
+                }
+                '&' => buffer.push('&'),
+                '"' => {
+                    let end: usize = chars[i..]
+                        .iter()
+                        .position(|&c| c == '"')
+                        .map_or(chars.len(), |p| i + p);
+                    part.font.get_or_insert(chars[i..end].iter().collect());
+                    i = end + 1;
+                }
+                'K' => {
+                    let end: usize = (i + 6).min(chars.len());
+                    part.color.get_or_insert(chars[i..end].iter().collect());
+                    i = end;
+                }
+                c if c.is_ascii_digit() => {
+                    let start: usize = i - 1;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                    let size: String = chars[start..i].iter().collect();
+                    if part.size.is_none() {
+                        part.size = size.parse().ok();
+                    }
+                    // 数字で始まるテキストとの区切りの空白
+                    if chars.get(i) == Some(&' ')
+                        && chars.get(i + 1).is_some_and(|c| c.is_ascii_digit())
+                    {
+                        i += 1;
+                    }
+                }
+                c => {
+                    let code: String = format!("&{c}");
+                    match HEADER_FOOTER_CODES.iter().find(|(_, k)| *k == code) {
+                        Some((placeholder, _)) => buffer.push_str(placeholder),
+                        None => buffer.push_str(&code),
+                    }
+                }
+            }
+        }
+        flush(&mut part, section, &mut buffer);
+        part
+    }
+
+    fn __repr__(&self) -> String {
+        format!("HeaderFooterPart({:?})", self.build())
+    }
+}
+
+/// テキストの書式文字列への変換 (`&` のエスケープとプレースホルダーの置換)
+fn encode_text(text: &str) -> String {
+    let mut encoded: String = text.replace('&', "&&");
+    for (placeholder, code) in HEADER_FOOTER_CODES {
+        encoded = encoded.replace(placeholder, code);
+    }
+    encoded
+}
+
+/// ヘッダーとフッター (`headerFooter`)
+#[pyclass]
+pub struct HeaderFooter {
+    /// ワークシートのXML
+    xml: Arc<Mutex<Xml>>,
+}
+
+#[pymethods]
+impl HeaderFooter {
+    /// 奇数ページ (または全ページ) のヘッダー
+    #[getter]
+    pub fn get_odd_header(&self) -> Option<HeaderFooterPart> {
+        self.part("oddHeader")
+    }
+
+    #[setter(odd_header)]
+    pub fn set_odd_header(&self, part: Option<HeaderFooterPart>) {
+        self.set_part("oddHeader", part);
+    }
+
+    /// 奇数ページ (または全ページ) のフッター
+    #[getter]
+    pub fn get_odd_footer(&self) -> Option<HeaderFooterPart> {
+        self.part("oddFooter")
+    }
+
+    #[setter(odd_footer)]
+    pub fn set_odd_footer(&self, part: Option<HeaderFooterPart>) {
+        self.set_part("oddFooter", part);
+    }
+
+    /// 偶数ページのヘッダー
+    #[getter]
+    pub fn get_even_header(&self) -> Option<HeaderFooterPart> {
+        self.part("evenHeader")
+    }
+
+    #[setter(even_header)]
+    pub fn set_even_header(&self, part: Option<HeaderFooterPart>) {
+        self.set_part("evenHeader", part);
+    }
+
+    /// 偶数ページのフッター
+    #[getter]
+    pub fn get_even_footer(&self) -> Option<HeaderFooterPart> {
+        self.part("evenFooter")
+    }
+
+    #[setter(even_footer)]
+    pub fn set_even_footer(&self, part: Option<HeaderFooterPart>) {
+        self.set_part("evenFooter", part);
+    }
+
+    /// 先頭ページのヘッダー
+    #[getter]
+    pub fn get_first_header(&self) -> Option<HeaderFooterPart> {
+        self.part("firstHeader")
+    }
+
+    #[setter(first_header)]
+    pub fn set_first_header(&self, part: Option<HeaderFooterPart>) {
+        self.set_part("firstHeader", part);
+    }
+
+    /// 先頭ページのフッター
+    #[getter]
+    pub fn get_first_footer(&self) -> Option<HeaderFooterPart> {
+        self.part("firstFooter")
+    }
+
+    #[setter(first_footer)]
+    pub fn set_first_footer(&self, part: Option<HeaderFooterPart>) {
+        self.set_part("firstFooter", part);
+    }
+}
+
+impl HeaderFooter {
+    /// 新しい `HeaderFooter` インスタンスの作成
+    pub fn new(xml: Arc<Mutex<Xml>>) -> Self {
+        HeaderFooter { xml }
+    }
+
+    /// ヘッダーまたはフッターの取得
+    fn part(&self, tag: &str) -> Option<HeaderFooterPart> {
+        let xml: MutexGuard<Xml> = self.xml.lock().unwrap();
+        let text: &String = xml
+            .elements
+            .first()?
+            .find_element(&format!("headerFooter>{tag}"))?
+            .text
+            .as_ref()?;
+        Some(HeaderFooterPart::parse(text))
+    }
+
+    /// ヘッダーまたはフッターの設定
+    ///
+    /// 偶数ページと先頭ページの設定に合わせて `differentOddEven` と `differentFirst` を更新
+    fn set_part(&self, tag: &str, part: Option<HeaderFooterPart>) {
+        update(&self.xml, "headerFooter", |header_footer| {
+            match part {
+                Some(part) => {
+                    header_footer
+                        .get_or_create_child_ordered(tag, &HEADER_FOOTER_CHILD_ORDER)
+                        .text = Some(part.build());
+                }
+                None => header_footer.children.retain(|c| c.name != tag),
+            }
+            let has = |prefix: &str| {
+                header_footer
+                    .children
+                    .iter()
+                    .any(|c| c.name.starts_with(prefix))
+            };
+            let different_odd_even: bool = has("even");
+            let different_first: bool = has("first");
+            set_attribute(
+                header_footer,
+                "differentOddEven",
+                different_odd_even.then(|| "1".to_string()),
+            );
+            set_attribute(
+                header_footer,
+                "differentFirst",
+                different_first.then(|| "1".to_string()),
+            );
+        });
+    }
+}
+
+/// 印刷範囲の定義名 (`_xlnm.Print_Area`) の値の作成
+///
+/// `areas` はカンマ区切りのセルまたは範囲 (例: "A1:D20,F1:G5")。各範囲は絶対参照に変換
+pub fn print_area_text(sheet_name: &str, areas: &str) -> Result<String, PrintError> {
+    let invalid = || PrintError::InvalidPrintArea(areas.to_string());
+    let prefix: String = format!("{}!", quote_sheet_name(sheet_name));
+    areas
+        .split(',')
+        .map(|area| {
+            let area: String = area.trim().replace('$', "");
+            let (first, last) = area.split_once(':').unwrap_or((&area, &area));
+            let (start, end) = (
+                parse_address(first).ok_or_else(invalid)?,
+                parse_address(last).ok_or_else(invalid)?,
+            );
+            let cell = |(row, col): (u32, u32)| format!("${}${row}", col_to_string(col));
+            Ok(if start == end {
+                format!("{prefix}{}", cell(start))
+            } else {
+                format!("{prefix}{}:{}", cell(start), cell(end))
+            })
+        })
+        .collect::<Result<Vec<String>, PrintError>>()
+        .map(|areas| areas.join(","))
+}
+
+/// 印刷タイトルの定義名 (`_xlnm.Print_Titles`) の値の作成
+///
+/// `cols` は列の範囲 (例: "A:B")、`rows` は行の範囲 (例: "1:2")。どちらもない場合は `None`
+pub fn print_titles_text(
+    sheet_name: &str,
+    rows: Option<&str>,
+    cols: Option<&str>,
+) -> Result<Option<String>, PrintError> {
+    let prefix: String = format!("{}!", quote_sheet_name(sheet_name));
+    let mut parts: Vec<String> = Vec::with_capacity(2);
+    if let Some(cols) = cols {
+        let (first, last) = title_span(cols, |c| string_to_col(c).filter(|&c| c <= MAX_COL))?;
+        parts.push(format!(
+            "{prefix}${}:${}",
+            col_to_string(first),
+            col_to_string(last)
+        ));
+    }
+    if let Some(rows) = rows {
+        let (first, last) = title_span(rows, |r| {
+            r.parse::<u32>().ok().filter(|r| (1..=MAX_ROW).contains(r))
+        })?;
+        parts.push(format!("{prefix}${first}:${last}"));
+    }
+    Ok((!parts.is_empty()).then(|| parts.join(",")))
+}
+
+/// 印刷タイトルの行または列の範囲の解析 (単一の行・列も可)
+fn title_span(text: &str, parse: impl Fn(&str) -> Option<u32>) -> Result<(u32, u32), PrintError> {
+    let body: String = text.trim().replace('$', "");
+    let (first, last) = body.split_once(':').unwrap_or((&body, &body));
+    match (parse(first), parse(last)) {
+        (Some(first), Some(last)) if first <= last => Ok((first, last)),
+        _ => Err(PrintError::InvalidPrintTitles(text.to_string())),
+    }
+}
+
+/// 定義名の値からのシート名と `$` を除いた範囲の一覧
+pub fn split_print_reference(value: &str) -> Vec<String> {
+    split_areas(value)
+        .iter()
+        .map(|area| split_sheet_prefix(area.trim()).1.replace('$', ""))
+        .filter(|area| !area.is_empty())
+        .collect()
+}
+
+/// 印刷タイトルの定義名の値からの行と列の範囲 (`rows`, `cols`)
+pub fn split_print_titles(value: &str) -> (Option<String>, Option<String>) {
+    let mut rows: Option<String> = None;
+    let mut cols: Option<String> = None;
+    for area in split_print_reference(value) {
+        if area.chars().all(|c| c.is_ascii_digit() || c == ':') {
+            rows = Some(area);
+        } else {
+            cols = Some(area);
+        }
+    }
+    (rows, cols)
+}
+
+/// 改ページの位置の一覧 (`rowBreaks` または `colBreaks` の `brk@id`)
+pub fn page_breaks(worksheet: &XmlElement, tag: &str) -> Vec<u32> {
+    worksheet
+        .find_element(tag)
+        .map(|breaks| {
+            breaks
+                .children
+                .iter()
+                .filter_map(|brk| brk.get_attribute("id")?.parse::<u32>().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 手動の改ページの追加 (`id` の後で改ページ、既存の場合は何もしない)
+///
+/// `max` は改ページの終端 (行の改ページでは最終列、列の改ページでは最終行)
+pub fn add_page_break(worksheet: &mut XmlElement, tag: &str, id: u32, max: u32) {
+    let breaks: &mut XmlElement =
+        worksheet.get_or_create_child_ordered(tag, &WORKSHEET_CHILD_ORDER);
+    let ids: Vec<u32> = breaks
+        .children
+        .iter()
+        .filter_map(|brk| brk.get_attribute("id")?.parse::<u32>().ok())
+        .collect();
+    if !ids.contains(&id) {
+        let mut brk: XmlElement = XmlElement::new("brk");
+        brk.attributes.insert("id".to_string(), id.to_string());
+        brk.attributes.insert("max".to_string(), max.to_string());
+        brk.attributes.insert("man".to_string(), "1".to_string());
+        let position: usize = ids.iter().filter(|&&i| i < id).count();
+        breaks.children.insert(position, brk);
+    }
+    let count: String = breaks.children.len().to_string();
+    let manual: String = breaks
+        .children
+        .iter()
+        .filter(|brk| is_true(brk.get_attribute("man").cloned()))
+        .count()
+        .to_string();
+    breaks.attributes.insert("count".to_string(), count);
+    breaks
+        .attributes
+        .insert("manualBreakCount".to_string(), manual);
+}
@@ -0,0 +1,529 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyIterator, PyList};
+use thiserror::Error;
+
+use crate::cell::{Cell, CellValue};
+use crate::formula::{self, MAX_ROW, parse_address, parse_range, string_to_col, to_address};
+use crate::xml::{Xml, XmlElement};
+
+/// セル範囲の操作のエラー
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum RangeError {
+    #[error("Invalid range: {0}")]
+    InvalidRange(String),
+    #[error("Values of {1} rows x {2} columns do not fit in range {0}")]
+    ShapeMismatch(String, usize, usize),
+    #[error("Invalid formula: {0}")]
+    InvalidFormula(String),
+}
+
+impl From<RangeError> for PyErr {
+    fn from(err: RangeError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// 範囲の左上と右下の行と列の番号
+pub type Area = ((u32, u32), (u32, u32));
+
+/// `sheet[...]` のキーの形
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyShape {
+    /// 単一セル (例: "A1")
+    Cell,
+    /// セル範囲 (例: "A1:C3")
+    Area,
+    /// 単一の列 (例: "A")
+    Column,
+    /// 複数の列 (例: "A:C")
+    Columns,
+    /// 単一の行 (例: 5)
+    Row,
+    /// 複数の行 (例: "5:7")
+    Rows,
+}
+
+/// `sheet[...]` のキーの解析
+///
+/// 列全体や行全体の指定は、`extent` (値のある最終行と最終列) までの範囲として扱う
+pub fn parse_key(key: &str, extent: (u32, u32)) -> Option<(KeyShape, Area)> {
+    let key: String = key.replace('$', "");
+    let (max_row, max_col) = (extent.0.max(1), extent.1.max(1));
+    if let Some((row, col)) = parse_address(&key) {
+        return Some((KeyShape::Cell, ((row, col), (row, col))));
+    }
+    let (start, end) = key.split_once(':').unwrap_or((&key, &key));
+    let single: bool = !key.contains(':');
+    if let (Some(c1), Some(c2)) = (string_to_col(start), string_to_col(end)) {
+        let shape: KeyShape = if single {
+            KeyShape::Column
+        } else {
+            KeyShape::Columns
+        };
+        return Some((shape, ((1, c1.min(c2)), (max_row, c1.max(c2)))));
+    }
+    if let (Ok(r1), Ok(r2)) = (start.parse::<u32>(), end.parse::<u32>())
+        && (1..=MAX_ROW).contains(&r1)
+        && (1..=MAX_ROW).contains(&r2)
+    {
+        let shape: KeyShape = if single {
+            KeyShape::Row
+        } else {
+            KeyShape::Rows
+        };
+        return Some((shape, ((r1.min(r2), 1), (r1.max(r2), max_col))));
+    }
+    if single {
+        return None;
+    }
+    parse_range(&key).map(|area| (KeyShape::Area, area))
+}
+
+/// ワークシートの値のあるセルの範囲 (セルがない場合は `None`)
+pub(crate) fn used_area(worksheet: &XmlElement) -> Option<Area> {
+    let sheet_data: &XmlElement = worksheet.find_element("sheetData")?;
+    sheet_data
+        .children
+        .iter()
+        .flat_map(|row| row.children.iter())
+        .filter(|c| c.name == "c")
+        .filter_map(|c| parse_address(c.get_attribute("r")?))
+        .fold(None, |area: Option<Area>, (row, col)| match area {
+            None => Some(((row, col), (row, col))),
+            Some(((r1, c1), (r2, c2))) => {
+                Some(((r1.min(row), c1.min(col)), (r2.max(row), c2.max(col))))
+            }
+        })
+}
+
+/// `sheet[...]` の結果 (単一セル、1行または1列のセル、または行・列ごとのセル)
+#[derive(IntoPyObject)]
+pub enum SheetItem {
+    Cell(Cell),
+    Cells(Vec<Cell>),
+    Grid(Vec<Vec<Cell>>),
+}
+
+/// `sheet[...]` のキー (行番号またはアドレス)
+#[derive(FromPyObject)]
+pub enum SheetIndex {
+    Row(u32),
+    Address(String),
+}
+
+/// ワークシートの矩形のセル範囲
+#[pyclass]
+#[derive(Clone)]
+pub struct Range {
+    /// 範囲が属するワークシートのXML
+    sheet_xml: Arc<Mutex<Xml>>,
+    /// 共有文字列のXML
+    shared_strings: Arc<Mutex<Xml>>,
+    /// 共有文字列のマップ
+    shared_strings_map: Arc<Mutex<HashMap<String, usize>>>,
+    /// スタイルのXML
+    styles: Arc<Mutex<Xml>>,
+    /// 先頭行の番号
+    #[pyo3(get)]
+    pub min_row: u32,
+    /// 先頭列の番号
+    #[pyo3(get)]
+    pub min_col: u32,
+    /// 最終行の番号
+    #[pyo3(get)]
+    pub max_row: u32,
+    /// 最終列の番号
+    #[pyo3(get)]
+    pub max_col: u32,
+}
+
+#[pymethods]
+impl Range {
+    /// 範囲のアドレス (例: "A1:C3")
+    #[getter]
+    pub fn coord(&self) -> String {
+        format!(
+            "{}:{}",
+            to_address(self.min_row, self.min_col),
+            to_address(self.max_row, self.max_col)
+        )
+    }
+
+    /// 行数と列数
+    #[getter]
+    pub fn shape(&self) -> (usize, usize) {
+        (self.height(), self.width())
+    }
+
+    /// 行ごとのセル
+    pub fn rows(&self) -> Vec<Vec<Cell>> {
+        (self.min_row..=self.max_row)
+            .map(|row| {
+                (self.min_col..=self.max_col)
+                    .map(|col| self.cell(row, col))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// 列ごとのセル
+    pub fn cols(&self) -> Vec<Vec<Cell>> {
+        (self.min_col..=self.max_col)
+            .map(|col| {
+                (self.min_row..=self.max_row)
+                    .map(|row| self.cell(row, col))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// 範囲の値の2次元リスト (値のないセルは `None`)
+    #[getter]
+    pub fn values(&self) -> Vec<Vec<Option<CellValue>>> {
+        let mut values: Vec<Vec<Option<CellValue>>> = vec![vec![None; self.width()]; self.height()];
+        let xml: MutexGuard<Xml> = self.sheet_xml.lock().unwrap();
+        let shared_strings: MutexGuard<Xml> = self.shared_strings.lock().unwrap();
+        let Some(sheet_data) = xml
+            .elements
+            .first()
+            .and_then(|ws| ws.find_element("sheetData"))
+        else {
+            return values;
+        };
+        // 行は行番号の昇順のため、範囲の先頭の行まで読み飛ばし、最終行を過ぎたら終える
+        let row_number = |row: &XmlElement| -> u32 {
+            row.get_attribute("r")
+                .and_then(|r| r.parse().ok())
+                .unwrap_or(0)
+        };
+        let start: usize = sheet_data
+            .children
+            .partition_point(|row| row_number(row) < self.min_row);
+        for row in &sheet_data.children[start..] {
+            if row_number(row) > self.max_row {
+                break;
+            }
+            for cell in &row.children {
+                if let Some((row, col)) = cell.get_attribute("r").and_then(|r| parse_address(r))
+                    && self.contains(row, col)
+                {
+                    values[(row - self.min_row) as usize][(col - self.min_col) as usize] =
+                        Cell::read_value(cell, &shared_strings);
+                }
+            }
+        }
+        values
+    }
+
+    /// 範囲の値の2次元リスト (`values` と同じ)
+    #[getter(value)]
+    pub fn get_value(&self) -> Vec<Vec<Option<CellValue>>> {
+        self.values()
+    }
+
+    /// 範囲の値の一括設定 (左上から書き込み、`None` のセルは値を削除)
+    ///
+    /// ワークシートのロックは一度だけ取得し、数式の構文が不正な場合は何も書き込まない
+    #[setter(value)]
+    pub fn set_values(&self, values: Vec<Vec<Option<CellValue>>>) -> Result<(), RangeError> {
+        let width: usize = values.iter().map(Vec::len).max().unwrap_or(0);
+        if values.len() > self.height() || width > self.width() {
+            return Err(RangeError::ShapeMismatch(self.coord(), values.len(), width));
+        }
+        for value in values.iter().flatten() {
+            if let Some(CellValue::Text(text)) = value
+                && let Some(f) = text.strip_prefix('=')
+                && formula::parse(f).is_err()
+            {
+                return Err(RangeError::InvalidFormula(text.clone()));
+            }
+        }
+
+        let mut xml: MutexGuard<Xml> = self.sheet_xml.lock().unwrap();
+        let mut shared_strings_map: MutexGuard<HashMap<String, usize>> =
+            self.shared_strings_map.lock().unwrap();
+        let mut shared_strings: MutexGuard<Xml> = self.shared_strings.lock().unwrap();
+        let Some(worksheet) = xml.elements.first_mut() else {
+            return Ok(());
+        };
+        let sheet_data: &mut XmlElement = worksheet.get_element_mut("sheetData");
+        for (row_num, row_values) in (self.min_row..).zip(&values) {
+            let exists: bool = sheet_data.children.iter().any(|r| {
+                r.get_attribute("r")
+                    .is_some_and(|r| *r == row_num.to_string())
+            });
+            if !exists && row_values.iter().all(Option::is_none) {
+                continue;
+            }
+            let row: &mut XmlElement = Cell::get_or_create_row(sheet_data, row_num);
+            for (col, value) in (self.min_col..).zip(row_values) {
+                let address: String = to_address(row_num, col);
+                if value.is_none()
+                    && !row
+                        .children
+                        .iter()
+                        .any(|c| c.get_attribute("r") == Some(&address))
+                {
+                    continue;
+                }
+                let cell: &mut XmlElement = Cell::get_or_create_cell(row, &address);
+                Cell::write_value(
+                    cell,
+                    value.as_ref(),
+                    &mut shared_strings,
+                    &mut shared_strings_map,
+                )
+                .map_err(|e| RangeError::InvalidFormula(e.to_string()))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 行ごとのセルのイテレータ
+    pub fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyIterator>> {
+        PyList::new(py, self.rows())?.try_iter()
+    }
+
+    /// 行数
+    pub fn __len__(&self) -> usize {
+        self.height()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Range({:?})", self.coord())
+    }
+}
+
+/// 範囲の1行または1列 (セルまたは値)
+#[derive(IntoPyObject)]
+pub enum RangeLine {
+    Cells(Vec<Cell>),
+    Values(Vec<Option<CellValue>>),
+}
+
+/// 範囲の行または列を順に返すイテレータ
+///
+/// 各行・各列は取り出す時点で読み込み、値のないセルは `None` (`values_only` でない場合は空のセル) とする
+#[pyclass]
+pub struct RangeIterator {
+    /// 対象の範囲 (最終行・最終列が先頭より前の場合は空)
+    range: Range,
+    /// 列ごとに返すかどうか
+    by_column: bool,
+    /// セルではなく値を返すかどうか
+    values_only: bool,
+    /// 次に返す行または列の番号
+    next: u32,
+    /// 次に読み込む行の `sheetData` 内の位置 (行ごとの読み込みの再開位置)
+    cursor: usize,
+}
+
+#[pymethods]
+impl RangeIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<RangeLine> {
+        self.next()
+    }
+}
+
+impl RangeIterator {
+    /// 新しい `RangeIterator` インスタンスの作成
+    pub fn new(range: Range, by_column: bool, values_only: bool) -> Self {
+        let next: u32 = if by_column {
+            range.min_col
+        } else {
+            range.min_row
+        };
+        RangeIterator {
+            range,
+            by_column,
+            values_only,
+            next,
+            cursor: 0,
+        }
+    }
+
+    /// 行の値の読み込み
+    fn row_values(&mut self, row_num: u32) -> Vec<Option<CellValue>> {
+        let range: &Range = &self.range;
+        let mut values: Vec<Option<CellValue>> = vec![None; range.width()];
+        let xml: MutexGuard<Xml> = range.sheet_xml.lock().unwrap();
+        let shared_strings: MutexGuard<Xml> = range.shared_strings.lock().unwrap();
+        let Some(sheet_data) = xml
+            .elements
+            .first()
+            .and_then(|ws| ws.find_element("sheetData"))
+        else {
+            return values;
+        };
+        let rows: &[XmlElement] = &sheet_data.children;
+        let number = |row: &XmlElement| -> u32 {
+            row.get_attribute("r")
+                .and_then(|r| r.parse().ok())
+                .unwrap_or(0)
+        };
+        // 前回の位置以降に対象の行がない場合 (行が削除された場合など) は先頭から探す
+        if self.cursor > rows.len()
+            || (self.cursor > 0 && number(&rows[self.cursor - 1]) >= row_num)
+        {
+            self.cursor = 0;
+        }
+        while self.cursor < rows.len() && number(&rows[self.cursor]) < row_num {
+            self.cursor += 1;
+        }
+        if let Some(row) = rows.get(self.cursor).filter(|row| number(row) == row_num) {
+            for cell in &row.children {
+                if let Some((_, col)) = cell.get_attribute("r").and_then(|r| parse_address(r))
+                    && (range.min_col..=range.max_col).contains(&col)
+                {
+                    values[(col - range.min_col) as usize] =
+                        Cell::read_value(cell, &shared_strings);
+                }
+            }
+        }
+        values
+    }
+
+    /// 列の値の読み込み
+    ///
+    /// 範囲の行だけを読み、各行のセルは列番号の順から二分探索する
+    fn column_values(&self, col: u32) -> Vec<Option<CellValue>> {
+        let range: &Range = &self.range;
+        let mut values: Vec<Option<CellValue>> = vec![None; range.height()];
+        let xml: MutexGuard<Xml> = range.sheet_xml.lock().unwrap();
+        let shared_strings: MutexGuard<Xml> = range.shared_strings.lock().unwrap();
+        let Some(sheet_data) = xml
+            .elements
+            .first()
+            .and_then(|ws| ws.find_element("sheetData"))
+        else {
+            return values;
+        };
+        let number = |row: &XmlElement| -> u32 {
+            row.get_attribute("r")
+                .and_then(|r| r.parse().ok())
+                .unwrap_or(0)
+        };
+        let column = |cell: &XmlElement| -> u32 {
+            cell.get_attribute("r")
+                .and_then(|r| parse_address(r))
+                .map_or(0, |(_, col)| col)
+        };
+        let start: usize = sheet_data
+            .children
+            .partition_point(|row| number(row) < range.min_row);
+        for row in &sheet_data.children[start..] {
+            let row_num: u32 = number(row);
+            if row_num > range.max_row {
+                break;
+            }
+            if row_num < range.min_row {
+                continue;
+            }
+            let position: usize = row.children.partition_point(|cell| column(cell) < col);
+            if let Some(cell) = row.children.get(position).filter(|c| column(c) == col) {
+                values[(row_num - range.min_row) as usize] =
+                    Cell::read_value(cell, &shared_strings);
+            }
+        }
+        values
+    }
+}
+
+impl Iterator for RangeIterator {
+    type Item = RangeLine;
+
+    fn next(&mut self) -> Option<RangeLine> {
+        let range: &Range = &self.range;
+        let (last, min_row, max_row, min_col, max_col) = if self.by_column {
+            (
+                range.max_col,
+                range.min_row,
+                range.max_row,
+                self.next,
+                self.next,
+            )
+        } else {
+            (
+                range.max_row,
+                self.next,
+                self.next,
+                range.min_col,
+                range.max_col,
+            )
+        };
+        if self.next > last || range.min_row > range.max_row || range.min_col > range.max_col {
+            return None;
+        }
+        let line: Range = Range {
+            min_row,
+            max_row,
+            min_col,
+            max_col,
+            ..range.clone()
+        };
+        self.next += 1;
+        Some(match (self.values_only, self.by_column) {
+            (false, false) => RangeLine::Cells(line.rows().into_iter().flatten().collect()),
+            (false, true) => RangeLine::Cells(line.cols().into_iter().flatten().collect()),
+            (true, false) => RangeLine::Values(self.row_values(min_row)),
+            (true, true) => RangeLine::Values(self.column_values(min_col)),
+        })
+    }
+}
+
+impl Range {
+    /// 新しい `Range` インスタンスの作成
+    pub fn new(
+        sheet_xml: Arc<Mutex<Xml>>,
+        shared_strings: Arc<Mutex<Xml>>,
+        shared_strings_map: Arc<Mutex<HashMap<String, usize>>>,
+        styles: Arc<Mutex<Xml>>,
+        area: Area,
+    ) -> Self {
+        let ((min_row, min_col), (max_row, max_col)) = area;
+        Range {
+            sheet_xml,
+            shared_strings,
+            shared_strings_map,
+            styles,
+            min_row,
+            min_col,
+            max_row,
+            max_col,
+        }
+    }
+
+    /// 行数
+    fn height(&self) -> usize {
+        (self.max_row - self.min_row + 1) as usize
+    }
+
+    /// 列数
+    fn width(&self) -> usize {
+        (self.max_col - self.min_col + 1) as usize
+    }
+
+    /// 範囲内のセルかどうか
+    fn contains(&self, row: u32, col: u32) -> bool {
+        (self.min_row..=self.max_row).contains(&row) && (self.min_col..=self.max_col).contains(&col)
+    }
+
+    /// 行と列の番号によるセルの取得
+    fn cell(&self, row: u32, col: u32) -> Cell {
+        Cell::new(
+            self.sheet_xml.clone(),
+            self.shared_strings.clone(),
+            self.shared_strings_map.clone(),
+            self.styles.clone(),
+            to_address(row, col),
+        )
+    }
+}
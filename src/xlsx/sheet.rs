@@ -1,18 +1,110 @@
-use std::sync::{Arc, Mutex};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, MutexGuard};
 
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
+use thiserror::Error;
 
+use crate::book::{BookError, BookParts};
 use crate::cell::Cell;
+use crate::dimension::{ColumnDimensions, RowDimensions, update_column};
+use crate::formula::{
+    self, Axis, parse_address, parse_range, shift_area_text, shift_span, to_address, translate,
+};
+use crate::metrics;
+use crate::number_format;
+use crate::print::{self, HeaderFooter, PageMargins, PageSetup, PrintError, PrintOptions};
+use crate::range::{
+    Area, KeyShape, Range, RangeError, RangeIterator, SheetIndex, SheetItem, parse_key, used_area,
+};
+use crate::style::Font;
+use crate::view;
 use crate::xml::{Xml, XmlElement};
 
 use std::collections::HashMap;
 
+/// ワークシートの子要素の順序 (ECMA-376 CT_Worksheet)
+pub(crate) const WORKSHEET_CHILD_ORDER: [&str; 39] = [
+    "sheetPr",
+    "dimension",
+    "sheetViews",
+    "sheetFormatPr",
+    "cols",
+    "sheetData",
+    "sheetCalcPr",
+    "sheetProtection",
+    "protectedRanges",
+    "scenarios",
+    "autoFilter",
+    "sortState",
+    "dataConsolidate",
+    "customSheetViews",
+    "mergeCells",
+    "phoneticPr",
+    "conditionalFormatting",
+    "dataValidations",
+    "hyperlinks",
+    "printOptions",
+    "pageMargins",
+    "pageSetup",
+    "headerFooter",
+    "rowBreaks",
+    "colBreaks",
+    "customProperties",
+    "cellWatches",
+    "ignoredErrors",
+    "smartTags",
+    "drawing",
+    "legacyDrawing",
+    "legacyDrawingHF",
+    "drawingHF",
+    "picture",
+    "oleObjects",
+    "controls",
+    "webPublishItems",
+    "tableParts",
+    "extLst",
+];
+
+/// 印刷範囲の定義名
+const PRINT_AREA: &str = "_xlnm.Print_Area";
+
+/// 印刷タイトルの定義名
+const PRINT_TITLES: &str = "_xlnm.Print_Titles";
+
+/// ワークシートの操作のエラー
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SheetError {
+    #[error("Invalid range: {0}")]
+    InvalidRange(String),
+    #[error("Range {0} overlaps merged cells {1}")]
+    OverlappingMerge(String, String),
+    #[error("Range {0} is not merged")]
+    NotMerged(String),
+    #[error("Invalid pane: {0}")]
+    InvalidPane(String),
+    #[error("Zoom scale must be between 10 and 400: {0}")]
+    InvalidZoom(u32),
+    #[error("Invalid view: {0}")]
+    InvalidView(String),
+    #[error("Invalid ARGB color: {0}")]
+    InvalidColor(String),
+    #[error("Cannot delete the whole range of table {0}")]
+    TableDeleted(String),
+}
+
+impl From<SheetError> for PyErr {
+    fn from(err: SheetError) -> PyErr {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
 /// Excelワークブック内のワークシート
 #[pyclass]
+#[derive(Clone)]
 pub struct Sheet {
-    /// ワークシートの名前
-    #[pyo3(get)]
-    pub name: String,
+    /// ワークシートのパーツのパス (シート名は変更に追従するため毎回ブックから引く)
+    pub(crate) path: String,
     /// ワークシートのXML
     xml: Arc<Mutex<Xml>>,
     /// 共有文字列のXML
@@ -21,18 +113,40 @@ pub struct Sheet {
     shared_strings_map: Arc<Mutex<HashMap<String, usize>>>,
     /// スタイルのXML
     styles: Arc<Mutex<Xml>>,
+    /// セルメタデータのXML (`xl/metadata.xml`)
+    metadata: Arc<Mutex<Xml>>,
+    /// ワークブック全体のパーツ
+    book: BookParts,
 }
 
 #[pymethods]
 impl Sheet {
-    /// アドレスによるセルの取得 (例: "A1")
-    pub fn __getitem__(&self, key: &str) -> Cell {
-        Cell::new(
-            self.xml.clone(),
-            self.shared_strings.clone(),
-            self.styles.clone(),
-            key.to_string(),
-        )
+    /// キーによるセルの取得 (openpyxl と同じ形)
+    ///
+    /// "A1" は単一セル、"A1:C3" と "5:7" は行ごとのセル、"A:C" は列ごとのセル、"A" と行番号は1列・1行のセル
+    #[pyo3(name = "__getitem__")]
+    pub fn get_item(&self, key: SheetIndex) -> Result<SheetItem, RangeError> {
+        let key: String = match key {
+            SheetIndex::Row(row) => row.to_string(),
+            SheetIndex::Address(address) => address,
+        };
+        let (shape, area) =
+            parse_key(&key, self.extent()).ok_or_else(|| RangeError::InvalidRange(key.clone()))?;
+        let range: Range = self.area_range(area);
+        Ok(match shape {
+            KeyShape::Cell => SheetItem::Cell(self.__getitem__(&key)),
+            KeyShape::Area | KeyShape::Rows => SheetItem::Grid(range.rows()),
+            KeyShape::Columns => SheetItem::Grid(range.cols()),
+            KeyShape::Row => SheetItem::Cells(range.rows().into_iter().flatten().collect()),
+            KeyShape::Column => SheetItem::Cells(range.cols().into_iter().flatten().collect()),
+        })
+    }
+
+    /// セル範囲の取得 (例: "A1:C3"、"A:C"、"5:7")
+    pub fn range(&self, key: &str) -> Result<Range, RangeError> {
+        let (_, area) = parse_key(key, self.extent())
+            .ok_or_else(|| RangeError::InvalidRange(key.to_string()))?;
+        Ok(self.area_range(area))
     }
 
     /// 行と列の番号によるセルの取得
@@ -42,134 +156,1462 @@ impl Sheet {
         Cell::new(
             self.xml.clone(),
             self.shared_strings.clone(),
+            self.shared_strings_map.clone(),
             self.styles.clone(),
             address,
         )
     }
 
+    /// シート名 (ブックから削除されたシートは `SheetNotFound`)
+    #[getter]
+    pub fn name(&self) -> Result<String, BookError> {
+        self.book
+            .sheet_name(&self.path)
+            .ok_or_else(|| BookError::SheetNotFound(self.path.clone()))
+    }
+
+    /// シート名
+    #[getter]
+    pub fn get_title(&self) -> Result<String, BookError> {
+        self.name()
+    }
+
+    /// シート名の変更
+    ///
+    /// 他のシートの数式や定義された名前などの参照も新しい名前に書き換える
+    #[setter(title)]
+    pub fn set_title(&self, title: &str) -> Result<(), BookError> {
+        self.book.rename_sheet(&self.name()?, title)
+    }
+
+    /// シートの表示状態 ("visible"、"hidden" または "veryHidden")
+    #[getter]
+    pub fn get_sheet_state(&self) -> Result<String, BookError> {
+        Ok(self.book.sheet_state(&self.name()?))
+    }
+
+    /// シートの表示状態の設定
+    ///
+    /// アクティブなシートと最後の表示されているシートは非表示にできない
+    #[setter(sheet_state)]
+    pub fn set_sheet_state(&self, state: &str) -> Result<(), BookError> {
+        self.book.set_sheet_state(&self.name()?, state)
+    }
+
+    /// 値のあるセルの最初の行 (セルがない場合は1)
+    #[getter]
+    pub fn min_row(&self) -> u32 {
+        self.used_bounds().0.0
+    }
+
+    /// 値のあるセルの最終行 (セルがない場合は1)
+    #[getter]
+    pub fn max_row(&self) -> u32 {
+        self.used_bounds().1.0
+    }
+
+    /// 値のあるセルの最初の列 (セルがない場合は1)
+    #[getter]
+    pub fn min_column(&self) -> u32 {
+        self.used_bounds().0.1
+    }
+
+    /// 値のあるセルの最終列 (セルがない場合は1)
+    #[getter]
+    pub fn max_column(&self) -> u32 {
+        self.used_bounds().1.1
+    }
+
+    /// 値のあるセルの範囲 (例: "A1:F200"、セルがない場合は "A1:A1")
+    #[getter]
+    pub fn dimensions(&self) -> String {
+        let ((min_row, min_col), (max_row, max_col)) = self.used_bounds();
+        format!(
+            "{}:{}",
+            to_address(min_row, min_col),
+            to_address(max_row, max_col)
+        )
+    }
+
     /// シートへの行の追加
     pub fn append(&self, row_data: Vec<String>) {
-        if let Ok(mut xml) = self.xml.lock() {
-            if let Some(worksheet) = xml.elements.first_mut() {
-                let sheet_data: &mut XmlElement = worksheet.get_element_mut("sheetData");
-                let new_row_num: usize = sheet_data
-                    .get_elements("row")
-                    .last()
-                    .and_then(|last_row| last_row.get_attribute("r"))
-                    .and_then(|r| r.parse::<usize>().ok())
-                    .map_or(1, |num| num + 1);
-
-                let mut row_element: XmlElement = XmlElement::new("row");
-                row_element
+        if let Ok(mut xml) = self.xml.lock()
+            && let Some(worksheet) = xml.elements.first_mut()
+        {
+            let sheet_data: &mut XmlElement = worksheet.get_element_mut("sheetData");
+            // 行の並び順によらず、最大の行番号の次の行に追加
+            let new_row_num: usize = sheet_data
+                .get_elements("row")
+                .iter()
+                .filter_map(|row| row.get_attribute("r")?.parse::<usize>().ok())
+                .max()
+                .map_or(1, |num| num + 1);
+
+            let mut row_element: XmlElement = XmlElement::new("row");
+            row_element
+                .attributes
+                .insert("r".to_string(), new_row_num.to_string());
+
+            let cell_count: usize = row_data.len();
+            row_element.children.reserve(cell_count);
+
+            for (i, cell_data) in row_data.iter().enumerate() {
+                let col_str: String = Self::col_to_string(i + 1);
+                let mut cell_element: XmlElement = XmlElement::new("c");
+                cell_element
                     .attributes
-                    .insert("r".to_string(), new_row_num.to_string());
+                    .insert("r".to_string(), format!("{col_str}{new_row_num}"));
 
-                let cell_count: usize = row_data.len();
-                row_element.children.reserve(cell_count);
+                // 共有文字列テーブルへの追加
+                let shared_string_id: usize = self.add_shared_string(cell_data);
 
-                for (i, cell_data) in row_data.iter().enumerate() {
-                    let col_str: String = Self::col_to_string(i + 1);
-                    let mut cell_element: XmlElement = XmlElement::new("c");
-                    cell_element
-                        .attributes
-                        .insert("r".to_string(), format!("{col_str}{new_row_num}"));
+                cell_element
+                    .attributes
+                    .insert("t".to_string(), "s".to_string());
+                let mut v_element: XmlElement = XmlElement::new("v");
+                v_element.text = Some(shared_string_id.to_string());
+                cell_element.children.push(v_element);
+                row_element.children.push(cell_element);
+            }
+            sheet_data.children.push(row_element);
+        }
+    }
 
-                    // 共有文字列テーブルへの追加
-                    let shared_string_id: usize = self.add_shared_string(cell_data);
+    /// 範囲への配列数式の設定
+    ///
+    /// 数式は範囲の左上のセルに `t="array"` として書き込み、範囲内の他のセルの数式と値は削除。
+    /// `dynamic` が真の場合は動的配列数式としてセルメタデータ (`cm`) を付与
+    #[pyo3(signature = (range, formula, dynamic = false))]
+    pub fn set_array_formula(&self, range: &str, formula: &str, dynamic: bool) -> PyResult<()> {
+        let ((top, left), (bottom, right)) = parse_range(range)
+            .ok_or_else(|| PyValueError::new_err(format!("Invalid range: {range}")))?;
+        let range_ref: String = if (top, left) == (bottom, right) {
+            to_address(top, left)
+        } else {
+            format!("{}:{}", to_address(top, left), to_address(bottom, right))
+        };
+        formula::parse(formula)?;
+        let cell_metadata: Option<usize> = dynamic
+            .then(|| Self::ensure_dynamic_array_metadata(&mut self.metadata.lock().unwrap()));
 
-                    cell_element
-                        .attributes
-                        .insert("t".to_string(), "s".to_string());
-                    let mut v_element: XmlElement = XmlElement::new("v");
-                    v_element.text = Some(shared_string_id.to_string());
-                    cell_element.children.push(v_element);
-                    row_element.children.push(cell_element);
+        let mut xml = self.xml.lock().unwrap();
+        let worksheet: &mut XmlElement = xml
+            .elements
+            .first_mut()
+            .ok_or_else(|| PyValueError::new_err("Worksheet XML is empty"))?;
+        let sheet_data: &mut XmlElement = worksheet.get_element_mut("sheetData");
+
+        // 範囲内の他のセルの数式と値の削除
+        for row in sheet_data.children.iter_mut().filter(|r| r.name == "row") {
+            for cell in row.children.iter_mut().filter(|c| c.name == "c") {
+                let Some((r, c)) = cell.attributes.get("r").and_then(|a| parse_address(a)) else {
+                    continue;
+                };
+                if (top..=bottom).contains(&r) && (left..=right).contains(&c) {
+                    cell.children
+                        .retain(|e| e.name != "f" && e.name != "v" && e.name != "is");
+                    cell.attributes.remove("t");
+                    cell.attributes.remove("cm");
                 }
-                sheet_data.children.push(row_element);
             }
         }
+
+        let row: &mut XmlElement = Cell::get_or_create_row(sheet_data, top);
+        let anchor: &mut XmlElement = Cell::get_or_create_cell(row, &to_address(top, left));
+        let mut f_element: XmlElement = XmlElement::new("f");
+        f_element
+            .attributes
+            .insert("t".to_string(), "array".to_string());
+        f_element.attributes.insert("ref".to_string(), range_ref);
+        f_element.text = Some(formula.strip_prefix('=').unwrap_or(formula).to_string());
+        anchor.children.insert(0, f_element);
+        if let Some(index) = cell_metadata {
+            anchor
+                .attributes
+                .insert("cm".to_string(), index.to_string());
+        }
+        Ok(())
     }
 
-    /// シート内の行のイテレータの取得
-    pub fn iter_rows(&self) -> Vec<Vec<String>> {
-        self.xml
-            .lock()
-            .ok()
-            .and_then(|xml| {
-                xml.elements.first().map(|worksheet| {
-                    let sheet_data: &XmlElement = worksheet.get_element("sheetData");
-                    let rows: Vec<&XmlElement> = sheet_data.get_elements("row");
-                    let mut result: Vec<Vec<String>> = Vec::with_capacity(rows.len());
-
-                    for row in rows {
-                        let cells: Vec<&XmlElement> = row.get_elements("c");
-                        let mut row_values: Vec<String> = Vec::with_capacity(cells.len());
-
-                        for cell in cells {
-                            let cell_value: String = self.get_cell_value(cell).unwrap_or_default();
-                            row_values.push(cell_value);
-                        }
-                        result.push(row_values);
-                    }
-                    result
+    /// 行の高さなどの設定の一覧
+    #[getter]
+    pub fn row_dimensions(&self) -> RowDimensions {
+        RowDimensions::new(self.xml.clone())
+    }
+
+    /// 列の幅などの設定の一覧
+    #[getter]
+    pub fn column_dimensions(&self) -> ColumnDimensions {
+        ColumnDimensions::new(self.xml.clone())
+    }
+
+    /// セルの内容からの列の幅の自動調整
+    ///
+    /// 表示形式を適用したテキストとセルのフォントから表示幅を推定し、全角文字は半角2文字分として数える。
+    /// 折り返しのセルは最も長い単語の幅とし、複数列にまたがる結合セルは対象外
+    pub fn auto_fit_columns(&self) {
+        let cell_styles: Vec<(Font, bool, String)> = self.cell_styles();
+        let mut widths: BTreeMap<u32, f64> = BTreeMap::new();
+        {
+            let xml: MutexGuard<Xml> = self.xml.lock().unwrap();
+            let Some(worksheet) = xml.elements.first() else {
+                return;
+            };
+            let merged: Vec<((u32, u32), (u32, u32))> = worksheet
+                .find_element("mergeCells")
+                .map(|m| {
+                    m.children
+                        .iter()
+                        .filter_map(|c| parse_range(c.get_attribute("ref")?))
+                        .collect()
+                })
+                .unwrap_or_default();
+            let cells = worksheet
+                .find_element("sheetData")
+                .into_iter()
+                .flat_map(|sheet_data| sheet_data.children.iter())
+                .flat_map(|row| row.children.iter());
+            for cell in cells {
+                let Some((row, col)) = cell.get_attribute("r").and_then(|r| parse_address(r))
+                else {
+                    continue;
+                };
+                let in_merged_columns: bool =
+                    merged.iter().any(|&((top, left), (bottom, right))| {
+                        left != right
+                            && (top..=bottom).contains(&row)
+                            && (left..=right).contains(&col)
+                    });
+                if in_merged_columns {
+                    continue;
+                }
+                let (font, wrap, num_fmt) = cell
+                    .get_attribute("s")
+                    .and_then(|s| s.parse::<usize>().ok())
+                    .and_then(|s| cell_styles.get(s))
+                    .or(cell_styles.first())
+                    .cloned()
+                    .unwrap_or_default();
+                let Some(text) = self.cell_text(cell, &num_fmt).filter(|t| !t.is_empty()) else {
+                    continue;
+                };
+                let measure = |text: &str| -> f64 {
+                    metrics::text_width(
+                        text,
+                        font.name.as_deref(),
+                        font.size,
+                        font.bold.unwrap_or(false),
+                    )
+                };
+                let width: f64 = if wrap {
+                    text.split_whitespace().map(measure).fold(0.0, f64::max)
+                } else {
+                    measure(&text)
+                };
+                let entry: &mut f64 = widths.entry(col).or_insert(0.0);
+                *entry = entry.max(width);
+            }
+        }
+
+        let mut xml: MutexGuard<Xml> = self.xml.lock().unwrap();
+        let Some(worksheet) = xml.elements.first_mut() else {
+            return;
+        };
+        for (col, width) in widths {
+            update_column(worksheet, col, |element| {
+                for (key, value) in [
+                    ("width", width.to_string()),
+                    ("customWidth", "1".to_string()),
+                    ("bestFit", "1".to_string()),
+                ] {
+                    element.attributes.insert(key.to_string(), value);
+                }
+            });
+        }
+    }
+
+    /// セルの結合 (例: "A1:D1")
+    ///
+    /// Excelと同様に左上以外のセルの値と数式を削除し、既存の結合範囲と重なる場合はエラー
+    pub fn merge_cells(&self, range: &str) -> Result<(), SheetError> {
+        let ((top, left), (bottom, right)) = parse_range(range)
+            .filter(|(start, end)| start != end)
+            .ok_or_else(|| SheetError::InvalidRange(range.to_string()))?;
+        let range_ref: String = format!("{}:{}", to_address(top, left), to_address(bottom, right));
+
+        let mut xml: MutexGuard<Xml> = self.xml.lock().unwrap();
+        let Some(worksheet) = xml.elements.first_mut() else {
+            return Err(SheetError::InvalidRange(range.to_string()));
+        };
+        if let Some(existing) = worksheet
+            .find_element("mergeCells")
+            .into_iter()
+            .flat_map(|m| m.children.iter())
+            .filter_map(|c| c.get_attribute("ref"))
+            .find(|r| {
+                parse_range(r).is_some_and(|((t, l), (b, rt))| {
+                    t <= bottom && top <= b && l <= right && left <= rt
                 })
             })
+        {
+            return Err(SheetError::OverlappingMerge(range_ref, existing.clone()));
+        }
+
+        // 左上以外のセルの値と数式の削除 (スタイルは保持)
+        if let Some(sheet_data) = worksheet.find_element_mut("sheetData") {
+            for cell in sheet_data
+                .children
+                .iter_mut()
+                .flat_map(|r| r.children.iter_mut())
+            {
+                let Some((r, c)) = cell.get_attribute("r").and_then(|a| parse_address(a)) else {
+                    continue;
+                };
+                if (top..=bottom).contains(&r)
+                    && (left..=right).contains(&c)
+                    && (r, c) != (top, left)
+                {
+                    cell.children
+                        .retain(|e| e.name != "f" && e.name != "v" && e.name != "is");
+                    cell.attributes.remove("t");
+                    cell.attributes.remove("cm");
+                }
+            }
+        }
+
+        let merge_cells: &mut XmlElement =
+            worksheet.get_or_create_child_ordered("mergeCells", &WORKSHEET_CHILD_ORDER);
+        let mut merge_cell: XmlElement = XmlElement::new("mergeCell");
+        merge_cell.attributes.insert("ref".to_string(), range_ref);
+        merge_cells.children.push(merge_cell);
+        merge_cells
+            .attributes
+            .insert("count".to_string(), merge_cells.children.len().to_string());
+        Ok(())
+    }
+
+    /// セルの結合の解除
+    ///
+    /// 範囲が結合されていない場合はエラー
+    pub fn unmerge_cells(&self, range: &str) -> Result<(), SheetError> {
+        let bounds: ((u32, u32), (u32, u32)) =
+            parse_range(range).ok_or_else(|| SheetError::InvalidRange(range.to_string()))?;
+        let mut xml: MutexGuard<Xml> = self.xml.lock().unwrap();
+        let merge_cells: &mut XmlElement = xml
+            .elements
+            .first_mut()
+            .and_then(|w| w.find_element_mut("mergeCells"))
+            .ok_or_else(|| SheetError::NotMerged(range.to_string()))?;
+        let position: usize = merge_cells
+            .children
+            .iter()
+            .position(|c| c.get_attribute("ref").and_then(|r| parse_range(r)) == Some(bounds))
+            .ok_or_else(|| SheetError::NotMerged(range.to_string()))?;
+        merge_cells.children.remove(position);
+        merge_cells
+            .attributes
+            .insert("count".to_string(), merge_cells.children.len().to_string());
+        if merge_cells.children.is_empty()
+            && let Some(worksheet) = xml.elements.first_mut()
+        {
+            worksheet.children.retain(|c| c.name != "mergeCells");
+        }
+        Ok(())
+    }
+
+    /// 結合されたセルの範囲の一覧
+    #[getter]
+    pub fn merged_cells(&self) -> Vec<String> {
+        let xml: MutexGuard<Xml> = self.xml.lock().unwrap();
+        xml.elements
+            .first()
+            .and_then(|w| w.find_element("mergeCells"))
+            .map(|m| {
+                m.children
+                    .iter()
+                    .filter_map(|c| c.get_attribute("ref").cloned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 固定されたウィンドウ枠の左上のセル (例: "B2")
+    #[getter]
+    pub fn get_freeze_panes(&self) -> Option<String> {
+        let xml: MutexGuard<Xml> = self.xml.lock().unwrap();
+        view::freeze_panes(xml.elements.first()?)
+    }
+
+    /// ウィンドウ枠の固定 (`None` または "A1" で解除)
+    #[setter(freeze_panes)]
+    pub fn set_freeze_panes(&self, cell: Option<&str>) -> Result<(), SheetError> {
+        let cell: Option<(u32, u32)> = cell
+            .map(|c| parse_address(c).ok_or_else(|| SheetError::InvalidRange(c.to_string())))
+            .transpose()?;
+        self.with_worksheet(|worksheet| view::set_freeze_panes(worksheet, cell));
+        Ok(())
+    }
+
+    /// ウィンドウ枠の分割 (分割位置は1/20ポイント、両方0で解除)
+    #[pyo3(signature = (x_split = 0.0, y_split = 0.0, top_left_cell = None))]
+    pub fn split_panes(
+        &self,
+        x_split: f64,
+        y_split: f64,
+        top_left_cell: Option<&str>,
+    ) -> Result<(), SheetError> {
+        let top_left_cell: Option<(u32, u32)> = top_left_cell
+            .map(|c| parse_address(c).ok_or_else(|| SheetError::InvalidRange(c.to_string())))
+            .transpose()?;
+        self.with_worksheet(|worksheet| {
+            view::set_split_panes(worksheet, x_split, y_split, top_left_cell)
+        });
+        Ok(())
+    }
+
+    /// ウィンドウ枠の分割位置 (`xSplit`, `ySplit`)
+    #[getter]
+    pub fn pane_split(&self) -> Option<(f64, f64)> {
+        let xml: MutexGuard<Xml> = self.xml.lock().unwrap();
+        view::split_panes(xml.elements.first()?)
+    }
+
+    /// アクティブなウィンドウ枠 ("topLeft", "topRight", "bottomLeft", "bottomRight")
+    #[getter]
+    pub fn get_active_pane(&self) -> String {
+        let xml: MutexGuard<Xml> = self.xml.lock().unwrap();
+        xml.elements
+            .first()
+            .map_or_else(|| "topLeft".to_string(), view::active_pane)
+    }
+
+    #[setter(active_pane)]
+    pub fn set_active_pane(&self, pane: &str) -> Result<(), SheetError> {
+        Self::validate_pane(pane)?;
+        self.with_worksheet(|worksheet| view::set_active_pane(worksheet, pane));
+        Ok(())
+    }
+
+    /// ウィンドウ枠の選択範囲の設定
+    ///
+    /// `active_cell` の既定値は範囲の左上、`pane` の既定値はアクティブなウィンドウ枠
+    #[pyo3(signature = (sqref, active_cell = None, pane = None))]
+    pub fn set_selection(
+        &self,
+        sqref: &str,
+        active_cell: Option<&str>,
+        pane: Option<&str>,
+    ) -> Result<(), SheetError> {
+        if !view::is_valid_sqref(sqref) {
+            return Err(SheetError::InvalidRange(sqref.to_string()));
+        }
+        let active_cell: String = match active_cell {
+            Some(cell) if parse_address(cell).is_some() => cell.to_string(),
+            Some(cell) => return Err(SheetError::InvalidRange(cell.to_string())),
+            None => sqref
+                .split([' ', ':'])
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+        };
+        if let Some(pane) = pane {
+            Self::validate_pane(pane)?;
+        }
+        self.with_worksheet(|worksheet| {
+            let pane: String = pane.map_or_else(|| view::active_pane(worksheet), str::to_string);
+            view::set_selection(view::sheet_view_mut(worksheet), &pane, &active_cell, sqref);
+        });
+        Ok(())
+    }
+
+    /// ウィンドウ枠の選択範囲 (アクティブセル, 範囲) の取得
+    #[pyo3(signature = (pane = None))]
+    pub fn get_selection(&self, pane: Option<&str>) -> Option<(String, String)> {
+        let xml: MutexGuard<Xml> = self.xml.lock().unwrap();
+        let worksheet: &XmlElement = xml.elements.first()?;
+        let pane: String = pane.map_or_else(|| view::active_pane(worksheet), str::to_string);
+        view::selection(worksheet, &pane)
+    }
+
+    /// 表示倍率 (%)
+    #[getter]
+    pub fn get_zoom_scale(&self) -> u32 {
+        self.read_worksheet(|w| view::view_attribute(w, "zoomScale"))
+            .and_then(|z| z.parse().ok())
+            .unwrap_or(100)
+    }
+
+    #[setter(zoom_scale)]
+    pub fn set_zoom_scale(&self, zoom: u32) -> Result<(), SheetError> {
+        if !(10..=400).contains(&zoom) {
+            return Err(SheetError::InvalidZoom(zoom));
+        }
+        let value: Option<String> = (zoom != 100).then(|| zoom.to_string());
+        self.with_worksheet(|w| view::set_view_attribute(w, "zoomScale", value));
+        Ok(())
+    }
+
+    /// 枠線を表示するかどうか
+    #[getter]
+    pub fn get_show_grid_lines(&self) -> bool {
+        self.read_worksheet(|w| Some(view::view_flag(w, "showGridLines", true)))
+            .unwrap_or(true)
+    }
+
+    #[setter(show_grid_lines)]
+    pub fn set_show_grid_lines(&self, show: bool) {
+        self.with_worksheet(|w| view::set_view_flag(w, "showGridLines", show, true));
+    }
+
+    /// 行番号と列番号を表示するかどうか
+    #[getter]
+    pub fn get_show_row_col_headers(&self) -> bool {
+        self.read_worksheet(|w| Some(view::view_flag(w, "showRowColHeaders", true)))
+            .unwrap_or(true)
+    }
+
+    #[setter(show_row_col_headers)]
+    pub fn set_show_row_col_headers(&self, show: bool) {
+        self.with_worksheet(|w| view::set_view_flag(w, "showRowColHeaders", show, true));
+    }
+
+    /// 右から左に表示するかどうか
+    #[getter]
+    pub fn get_right_to_left(&self) -> bool {
+        self.read_worksheet(|w| Some(view::view_flag(w, "rightToLeft", false)))
+            .unwrap_or(false)
+    }
+
+    #[setter(right_to_left)]
+    pub fn set_right_to_left(&self, right_to_left: bool) {
+        self.with_worksheet(|w| view::set_view_flag(w, "rightToLeft", right_to_left, false));
+    }
+
+    /// 表示モード ("normal", "pageLayout", "pageBreakPreview")
+    #[getter]
+    pub fn get_view(&self) -> String {
+        self.read_worksheet(|w| view::view_attribute(w, "view"))
+            .unwrap_or_else(|| "normal".to_string())
+    }
+
+    #[setter(view)]
+    pub fn set_view(&self, mode: &str) -> Result<(), SheetError> {
+        if !view::VIEWS.contains(&mode) {
+            return Err(SheetError::InvalidView(mode.to_string()));
+        }
+        let value: Option<String> = (mode != "normal").then(|| mode.to_string());
+        self.with_worksheet(|w| view::set_view_attribute(w, "view", value));
+        Ok(())
+    }
+
+    /// アクティブなウィンドウ枠のアクティブセル
+    #[getter]
+    pub fn get_active_cell(&self) -> String {
+        self.get_selection(None)
+            .map_or_else(|| "A1".to_string(), |(cell, _)| cell)
+    }
+
+    /// アクティブセルの設定 (選択範囲もアクティブセルのみにする)
+    #[setter(active_cell)]
+    pub fn set_active_cell(&self, cell: &str) -> Result<(), SheetError> {
+        self.set_selection(cell, Some(cell), None)
+    }
+
+    /// アクティブなウィンドウ枠の選択範囲 (例: "A1:B3 D4")
+    #[getter]
+    pub fn get_selected_cells(&self) -> String {
+        self.get_selection(None)
+            .map_or_else(|| "A1".to_string(), |(_, sqref)| sqref)
+    }
+
+    /// 選択範囲の設定 (アクティブセルは範囲の左上)
+    #[setter(selected_cells)]
+    pub fn set_selected_cells(&self, sqref: &str) -> Result<(), SheetError> {
+        self.set_selection(sqref, None, None)
+    }
+
+    /// シート見出しの色 (ARGB形式、例: "FF0070C0")
+    #[getter]
+    pub fn get_tab_color(&self) -> Option<String> {
+        self.read_worksheet(view::tab_color)
+    }
+
+    /// シート見出しの色の設定 (RGB形式の6桁も可、`None` で削除)
+    #[setter(tab_color)]
+    pub fn set_tab_color(&self, color: Option<&str>) -> Result<(), SheetError> {
+        let color: Option<String> = match color {
+            Some(c) if c.len() == 6 && c.chars().all(|ch| ch.is_ascii_hexdigit()) => {
+                Some(format!("FF{}", c.to_ascii_uppercase()))
+            }
+            Some(c) if c.len() == 8 && c.chars().all(|ch| ch.is_ascii_hexdigit()) => {
+                Some(c.to_ascii_uppercase())
+            }
+            Some(c) => return Err(SheetError::InvalidColor(c.to_string())),
+            None => None,
+        };
+        self.with_worksheet(|w| view::set_tab_color(w, color.as_deref()));
+        Ok(())
+    }
+
+    /// ページ設定
+    #[getter]
+    pub fn page_setup(&self) -> PageSetup {
+        PageSetup::new(self.xml.clone())
+    }
+
+    /// ページの余白
+    #[getter]
+    pub fn page_margins(&self) -> PageMargins {
+        PageMargins::new(self.xml.clone())
+    }
+
+    /// 印刷オプション
+    #[getter]
+    pub fn print_options(&self) -> PrintOptions {
+        PrintOptions::new(self.xml.clone())
+    }
+
+    /// ヘッダーとフッター
+    #[getter]
+    pub fn header_footer(&self) -> HeaderFooter {
+        HeaderFooter::new(self.xml.clone())
+    }
+
+    /// 印刷範囲 (カンマ区切りの範囲、例: "A1:D20")
+    #[getter]
+    pub fn get_print_area(&self) -> Option<String> {
+        let value: String = self
+            .book
+            .defined_name(PRINT_AREA, Some(self.sheet_index().ok()?))?;
+        Some(print::split_print_reference(&value).join(","))
+    }
+
+    /// 印刷範囲の設定 (`None` で解除)
+    #[setter(print_area)]
+    pub fn set_print_area(&self, areas: Option<&str>) -> Result<(), PrintError> {
+        let index: usize = self.sheet_index()?;
+        let name: String = self.name()?;
+        let value: Option<String> = areas
+            .map(|areas| print::print_area_text(&name, areas))
+            .transpose()?;
+        self.book.set_defined_name(PRINT_AREA, Some(index), value);
+        Ok(())
+    }
+
+    /// 印刷タイトルの行 (例: "1:2")
+    #[getter]
+    pub fn get_print_title_rows(&self) -> Option<String> {
+        self.print_titles().0
+    }
+
+    /// 印刷タイトルの行の設定 (`None` で解除)
+    #[setter(print_title_rows)]
+    pub fn set_print_title_rows(&self, rows: Option<&str>) -> Result<(), PrintError> {
+        let cols: Option<String> = self.print_titles().1;
+        self.set_print_titles(rows, cols.as_deref())
+    }
+
+    /// 印刷タイトルの列 (例: "A:B")
+    #[getter]
+    pub fn get_print_title_cols(&self) -> Option<String> {
+        self.print_titles().1
+    }
+
+    /// 印刷タイトルの列の設定 (`None` で解除)
+    #[setter(print_title_cols)]
+    pub fn set_print_title_cols(&self, cols: Option<&str>) -> Result<(), PrintError> {
+        let rows: Option<String> = self.print_titles().0;
+        self.set_print_titles(rows.as_deref(), cols)
+    }
+
+    /// 手動の改ページの追加
+    ///
+    /// `row` 行目の下、`col` 列目の右で改ページする
+    #[pyo3(signature = (row = None, col = None))]
+    pub fn add_page_break(&self, row: Option<u32>, col: Option<u32>) -> Result<(), PrintError> {
+        if row.is_none() && col.is_none() {
+            return Err(PrintError::InvalidPageBreak);
+        }
+        self.with_worksheet(|w| {
+            if let Some(row) = row {
+                print::add_page_break(w, "rowBreaks", row, formula::MAX_COL - 1);
+            }
+            if let Some(col) = col {
+                print::add_page_break(w, "colBreaks", col, formula::MAX_ROW - 1);
+            }
+        });
+        Ok(())
+    }
+
+    /// 行の改ページの位置の一覧
+    #[getter]
+    pub fn row_breaks(&self) -> Vec<u32> {
+        self.read_worksheet(|w| Some(print::page_breaks(w, "rowBreaks")))
+            .unwrap_or_default()
+    }
+
+    /// 列の改ページの位置の一覧
+    #[getter]
+    pub fn col_breaks(&self) -> Vec<u32> {
+        self.read_worksheet(|w| Some(print::page_breaks(w, "colBreaks")))
             .unwrap_or_default()
     }
+
+    /// `idx` 行目の前への行の挿入
+    ///
+    /// 全シートの数式、結合セル、入力規則、条件付き書式、テーブル、定義された名前の参照を移動
+    #[pyo3(signature = (idx, amount = 1))]
+    pub fn insert_rows(&self, idx: u32, amount: u32) -> PyResult<()> {
+        self.shift_cells(Axis::Row, idx, amount as i64)
+    }
+
+    /// `idx` 行目からの行の削除
+    ///
+    /// 削除された行への参照は `#REF!` に置き換える
+    #[pyo3(signature = (idx, amount = 1))]
+    pub fn delete_rows(&self, idx: u32, amount: u32) -> PyResult<()> {
+        self.shift_cells(Axis::Row, idx, -(amount as i64))
+    }
+
+    /// `idx` 列目の前への列の挿入
+    #[pyo3(signature = (idx, amount = 1))]
+    pub fn insert_cols(&self, idx: u32, amount: u32) -> PyResult<()> {
+        self.shift_cells(Axis::Column, idx, amount as i64)
+    }
+
+    /// `idx` 列目からの列の削除
+    #[pyo3(signature = (idx, amount = 1))]
+    pub fn delete_cols(&self, idx: u32, amount: u32) -> PyResult<()> {
+        self.shift_cells(Axis::Column, idx, -(amount as i64))
+    }
+
+    /// 行ごとのイテレータの取得
+    ///
+    /// 省略した範囲は1行目・1列目から値のある最終行・最終列まで。
+    /// 各行はセルの一覧 (`values_only` の場合は値の一覧) で、値のないセルの位置は空のセル (`None`) とする
+    #[pyo3(signature = (min_row=None, max_row=None, min_col=None, max_col=None, values_only=false))]
+    pub fn iter_rows(
+        &self,
+        min_row: Option<u32>,
+        max_row: Option<u32>,
+        min_col: Option<u32>,
+        max_col: Option<u32>,
+        values_only: bool,
+    ) -> RangeIterator {
+        let range: Range = self.bounded_range(min_row, max_row, min_col, max_col);
+        RangeIterator::new(range, false, values_only)
+    }
+
+    /// 列ごとのイテレータの取得 (範囲と値の扱いは `iter_rows` と同じ)
+    #[pyo3(signature = (min_row=None, max_row=None, min_col=None, max_col=None, values_only=false))]
+    pub fn iter_cols(
+        &self,
+        min_row: Option<u32>,
+        max_row: Option<u32>,
+        min_col: Option<u32>,
+        max_col: Option<u32>,
+        values_only: bool,
+    ) -> RangeIterator {
+        let range: Range = self.bounded_range(min_row, max_row, min_col, max_col);
+        RangeIterator::new(range, true, values_only)
+    }
 }
 
 impl Sheet {
     /// 新しい `Sheet` インスタンスの作成
     pub fn new(
-        name: String,
+        path: String,
         xml: Arc<Mutex<Xml>>,
         shared_strings: Arc<Mutex<Xml>>,
         shared_strings_map: Arc<Mutex<HashMap<String, usize>>>,
         styles: Arc<Mutex<Xml>>,
+        metadata: Arc<Mutex<Xml>>,
+        book: BookParts,
     ) -> Self {
         Sheet {
-            name,
+            path,
             xml,
             shared_strings,
             shared_strings_map,
             styles,
+            metadata,
+            book,
         }
     }
 
-    /// 共有文字列テーブルへの文字列の追加
-    fn add_shared_string(&self, s: &str) -> usize {
-        if let Ok(mut map) = self.shared_strings_map.lock() {
-            if let Some(&id) = map.get(s) {
-                return id;
-            }
-            if let Ok(mut strings) = self.shared_strings.lock() {
-                if strings.elements.is_empty() {
-                    let mut sst: XmlElement = XmlElement::new("sst");
-                    sst.attributes.insert(
-                        "xmlns".to_string(),
-                        "http://schemas.openxmlformats.org/spreadsheetml/2006/main".to_string(),
-                    );
-                    strings.elements.push(sst);
+    /// ブック内でのシートの位置 (定義名の `localSheetId`)
+    fn sheet_index(&self) -> Result<usize, BookError> {
+        let name: String = self.name()?;
+        self.book
+            .sheet_names()
+            .iter()
+            .position(|n| *n == name)
+            .ok_or(BookError::SheetNotFound(name))
+    }
+
+    /// 印刷タイトルの行と列 (`rows`, `cols`)
+    fn print_titles(&self) -> (Option<String>, Option<String>) {
+        self.sheet_index()
+            .ok()
+            .and_then(|index| self.book.defined_name(PRINT_TITLES, Some(index)))
+            .map(|value| print::split_print_titles(&value))
+            .unwrap_or_default()
+    }
+
+    /// 印刷タイトルの行と列の設定
+    fn set_print_titles(&self, rows: Option<&str>, cols: Option<&str>) -> Result<(), PrintError> {
+        let index: usize = self.sheet_index()?;
+        let value: Option<String> = print::print_titles_text(&self.name()?, rows, cols)?;
+        self.book.set_defined_name(PRINT_TITLES, Some(index), value);
+        Ok(())
+    }
+
+    /// アドレスによるセルの取得 (例: "A1")
+    pub fn __getitem__(&self, key: &str) -> Cell {
+        Cell::new(
+            self.xml.clone(),
+            self.shared_strings.clone(),
+            self.shared_strings_map.clone(),
+            self.styles.clone(),
+            key.to_string(),
+        )
+    }
+
+    /// `<dimension ref>` の値のあるセルの範囲への更新 (セルがない場合は "A1")
+    pub(crate) fn update_dimension(worksheet: &mut XmlElement) {
+        let reference: String = match used_area(worksheet) {
+            Some(((min_row, min_col), (max_row, max_col)))
+                if (min_row, min_col) != (max_row, max_col) =>
+            {
+                format!(
+                    "{}:{}",
+                    to_address(min_row, min_col),
+                    to_address(max_row, max_col)
+                )
+            }
+            Some(((row, col), _)) => to_address(row, col),
+            None => "A1".to_string(),
+        };
+        worksheet
+            .get_or_create_child_ordered("dimension", &WORKSHEET_CHILD_ORDER)
+            .attributes
+            .insert("ref".to_string(), reference);
+    }
+
+    /// 値のあるセルの範囲 (セルがない場合は A1 のみ)
+    fn used_bounds(&self) -> Area {
+        self.read_worksheet(used_area).unwrap_or(((1, 1), (1, 1)))
+    }
+
+    /// 値のあるセルの最終行と最終列 (セルがない場合は `(0, 0)`)
+    fn extent(&self) -> (u32, u32) {
+        self.read_worksheet(used_area)
+            .map_or((0, 0), |(_, (row, col))| (row, col))
+    }
+
+    /// 省略した端を1行目・1列目と値のある最終行・最終列で補った範囲
+    ///
+    /// 最終行・最終列が先頭より前になる場合は空の範囲
+    fn bounded_range(
+        &self,
+        min_row: Option<u32>,
+        max_row: Option<u32>,
+        min_col: Option<u32>,
+        max_col: Option<u32>,
+    ) -> Range {
+        let (last_row, last_col) = self.extent();
+        self.area_range((
+            (min_row.unwrap_or(1).max(1), min_col.unwrap_or(1).max(1)),
+            (max_row.unwrap_or(last_row), max_col.unwrap_or(last_col)),
+        ))
+    }
+
+    /// 行と列の番号の範囲の `Range`
+    fn area_range(&self, area: Area) -> Range {
+        Range::new(
+            self.xml.clone(),
+            self.shared_strings.clone(),
+            self.shared_strings_map.clone(),
+            self.styles.clone(),
+            area,
+        )
+    }
+
+    fn with_worksheet(&self, f: impl FnOnce(&mut XmlElement)) {
+        let mut xml: MutexGuard<Xml> = self.xml.lock().unwrap();
+        if let Some(worksheet) = xml.elements.first_mut() {
+            f(worksheet);
+        }
+    }
+
+    /// ワークシートの要素の読み取り
+    fn read_worksheet<T>(&self, f: impl FnOnce(&XmlElement) -> Option<T>) -> Option<T> {
+        let xml: MutexGuard<Xml> = self.xml.lock().unwrap();
+        f(xml.elements.first()?)
+    }
+
+    /// ウィンドウ枠の名前の検証
+    fn validate_pane(pane: &str) -> Result<(), SheetError> {
+        if view::PANES.contains(&pane) {
+            Ok(())
+        } else {
+            Err(SheetError::InvalidPane(pane.to_string()))
+        }
+    }
+
+    /// 行または列の挿入・削除
+    ///
+    /// `amount` が正の場合は挿入、負の場合は削除
+    fn shift_cells(&self, axis: Axis, index: u32, amount: i64) -> PyResult<()> {
+        if !(1..=axis.max()).contains(&index) || amount == 0 {
+            return Err(PyValueError::new_err(format!(
+                "Invalid index or amount: {index}, {amount}"
+            )));
+        }
+
+        let name: String = self.name()?;
+
+        // テーブルの範囲全体の削除は不可 (変更前に検証)
+        let tables: Vec<Arc<Mutex<Xml>>> = self
+            .book
+            .sheet_tables(&self.path)
+            .iter()
+            .filter_map(|table_path| self.book.tables.get(table_path))
+            .collect();
+        for table in &tables {
+            let table: MutexGuard<Xml> = table.lock().unwrap();
+            if let Some(table) = table.elements.first()
+                && let Some(table_ref) = table.get_attribute("ref")
+                && shift_area_text(table_ref, axis, index, amount).is_none()
+            {
+                let name: Option<&String> = table
+                    .get_attribute("displayName")
+                    .or_else(|| table.get_attribute("name"));
+                return Err(SheetError::TableDeleted(name.cloned().unwrap_or_default()).into());
+            }
+        }
+
+        let removed_links: Vec<String> = self
+            .xml
+            .lock()
+            .unwrap()
+            .elements
+            .first_mut()
+            .map(|worksheet| Self::shift_worksheet(worksheet, axis, index, amount))
+            .unwrap_or_default();
+
+        // 削除したハイパーリンクのリレーションシップ
+        if !removed_links.is_empty()
+            && let Some(rels) = self
+                .book
+                .sheet_rels
+                .get(&BookParts::sheet_rels_path(&self.path))
+            && let Some(relationships) = rels.lock().unwrap().elements.first_mut()
+        {
+            relationships.children.retain(|r| {
+                r.get_attribute("Id")
+                    .is_none_or(|id| !removed_links.contains(id))
+            });
+        }
+
+        // テーブルの範囲と列
+        for table in &tables {
+            if let Some(table) = table.lock().unwrap().elements.first_mut() {
+                Self::shift_table(table, axis, index, amount);
+            }
+        }
+
+        // 全シートの数式
+        for (sheet_name, xml) in self.book.sheets() {
+            if let Some(worksheet) = xml.lock().unwrap().elements.first_mut() {
+                Self::map_formulas(worksheet, &|f: &str| {
+                    formula::shift_formula(f, &sheet_name, &name, axis, index, amount)
+                });
+            }
+        }
+
+        // 定義された名前
+        let sheet_names: Vec<String> = self.book.sheet_names();
+        if let Some(defined_names) = self
+            .book
+            .workbook
+            .lock()
+            .unwrap()
+            .elements
+            .first_mut()
+            .and_then(|wb| wb.find_element_mut("definedNames"))
+        {
+            for defined_name in &mut defined_names.children {
+                let scope: &str = defined_name
+                    .get_attribute("localSheetId")
+                    .and_then(|id| id.parse::<usize>().ok())
+                    .and_then(|id| sheet_names.get(id))
+                    .map_or("", String::as_str);
+                if let Some(text) = &defined_name.text {
+                    let shifted: String =
+                        formula::shift_formula(text, scope, &name, axis, index, amount);
+                    defined_name.text = Some(shifted);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// ワークシート内のセル、列の設定、範囲を持つ要素の移動
+    ///
+    /// 削除したハイパーリンクのリレーションシップID (`r:id`) を返却
+    fn shift_worksheet(
+        worksheet: &mut XmlElement,
+        axis: Axis,
+        index: u32,
+        amount: i64,
+    ) -> Vec<String> {
+        let shift_area = |area: &str| shift_area_text(area, axis, index, amount);
+
+        if let Some(sheet_data) = worksheet.find_element_mut("sheetData") {
+            sheet_data.children.retain_mut(|row| {
+                if axis == Axis::Row {
+                    let Some(r) = row.get_attribute("r").and_then(|r| r.parse::<u32>().ok()) else {
+                        return true;
+                    };
+                    let Some((new_r, _)) = shift_span(r, r, index, amount, axis.max()) else {
+                        return false;
+                    };
+                    row.attributes.insert("r".to_string(), new_r.to_string());
+                } else {
+                    row.attributes.remove("spans");
+                }
+                row.children.retain_mut(|cell| {
+                    let Some(address) = cell.get_attribute("r") else {
+                        return true;
+                    };
+                    let Some(shifted) = shift_area(address) else {
+                        return false;
+                    };
+                    cell.attributes.insert("r".to_string(), shifted);
+                    if let Some(f) = cell.children.iter_mut().find(|c| c.name == "f")
+                        && let Some(range) = f.get_attribute("ref")
+                    {
+                        match shift_area(range) {
+                            Some(range) => {
+                                f.attributes.insert("ref".to_string(), range);
+                            }
+                            // 範囲が削除された配列数式は値のみ残す
+                            None => {
+                                cell.children.retain(|c| c.name != "f");
+                                cell.attributes.remove("cm");
+                            }
+                        }
+                    }
+                    true
+                });
+                true
+            });
+        }
+
+        if axis == Axis::Column
+            && let Some(cols) = worksheet.find_element_mut("cols")
+        {
+            cols.children.retain_mut(|col| {
+                let span: Option<(u32, u32)> = col
+                    .get_attribute("min")
+                    .and_then(|min| min.parse().ok())
+                    .zip(col.get_attribute("max").and_then(|max| max.parse().ok()));
+                let Some((min, max)) = span else {
+                    return true;
+                };
+                let Some((min, max)) = shift_span(min, max, index, amount, axis.max()) else {
+                    return false;
+                };
+                col.attributes.insert("min".to_string(), min.to_string());
+                col.attributes.insert("max".to_string(), max.to_string());
+                true
+            });
+        }
+
+        for path in ["dimension", "autoFilter"] {
+            if let Some(element) = worksheet.find_element_mut(path)
+                && let Some(shifted) = element.get_attribute("ref").and_then(|r| shift_area(r))
+            {
+                element.attributes.insert("ref".to_string(), shifted);
+            }
+        }
+
+        // 範囲が削除された要素 (1セルになった結合セルを含む) は除去し、件数を更新
+        let mut removed_links: Vec<String> = Vec::new();
+        for (list, attribute) in [
+            ("mergeCells", "ref"),
+            ("hyperlinks", "ref"),
+            ("dataValidations", "sqref"),
+        ] {
+            let Some(parent) = worksheet.find_element_mut(list) else {
+                continue;
+            };
+            parent.children.retain_mut(|child| {
+                let Some(sqref) = child.get_attribute(attribute) else {
+                    return true;
+                };
+                let shifted: String = Self::shift_sqref(sqref, axis, index, amount);
+                let keep: bool = !shifted.is_empty()
+                    && (list != "mergeCells"
+                        || parse_range(&shifted).is_some_and(|(start, end)| start != end));
+                child.attributes.insert(attribute.to_string(), shifted);
+                if !keep && let Some(r_id) = child.get_attribute("r:id") {
+                    removed_links.push(r_id.clone());
                 }
-                let sst: &mut XmlElement = &mut strings.elements[0];
-                let mut si: XmlElement = XmlElement::new("si");
-                let mut t: XmlElement = XmlElement::new("t");
-                t.text = Some(s.to_string());
-                si.children.push(t);
-                sst.children.push(si);
+                keep
+            });
+            if parent.attributes.contains_key("count") {
+                parent
+                    .attributes
+                    .insert("count".to_string(), parent.children.len().to_string());
+            }
+        }
+        worksheet.children.retain(|c| {
+            !matches!(
+                c.name.as_str(),
+                "mergeCells" | "hyperlinks" | "dataValidations"
+            ) || !c.children.is_empty()
+        });
+        worksheet.children.retain_mut(|child| {
+            if child.name != "conditionalFormatting" {
+                return true;
+            }
+            let Some(sqref) = child.get_attribute("sqref") else {
+                return true;
+            };
+            let shifted: String = Self::shift_sqref(sqref, axis, index, amount);
+            let keep: bool = !shifted.is_empty();
+            child.attributes.insert("sqref".to_string(), shifted);
+            keep
+        });
+
+        // 残ったハイパーリンクと共有するリレーションシップは除かない
+        if let Some(hyperlinks) = worksheet.find_element("hyperlinks") {
+            removed_links.retain(|r_id| {
+                !hyperlinks
+                    .children
+                    .iter()
+                    .any(|h| h.get_attribute("r:id") == Some(r_id))
+            });
+        }
+        removed_links
+    }
+
+    /// 空白区切りの範囲の一覧 (`sqref`) の移動
+    ///
+    /// 削除された範囲は除去
+    fn shift_sqref(sqref: &str, axis: Axis, index: u32, amount: i64) -> String {
+        sqref
+            .split_whitespace()
+            .filter_map(|area| shift_area_text(area, axis, index, amount))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    /// テーブルの範囲と列の移動
+    ///
+    /// 列の挿入・削除がテーブルの範囲内の場合はテーブルの列を追加・削除
+    fn shift_table(table: &mut XmlElement, axis: Axis, index: u32, amount: i64) {
+        let Some(table_ref) = table.get_attribute("ref").cloned() else {
+            return;
+        };
+        let Some(shifted) = shift_area_text(&table_ref, axis, index, amount) else {
+            return;
+        };
+        table.attributes.insert("ref".to_string(), shifted.clone());
+        if let Some(auto_filter) = table.find_element_mut("autoFilter") {
+            auto_filter.attributes.insert("ref".to_string(), shifted);
+        }
+
+        let Some(((_, left), (_, right))) = parse_range(&table_ref) else {
+            return;
+        };
+        if axis != Axis::Column {
+            return;
+        }
+        let Some(columns) = table.find_element_mut("tableColumns") else {
+            return;
+        };
+        if amount > 0 {
+            if index <= left || index > right {
+                return;
+            }
+            let mut next_id: u32 = columns
+                .children
+                .iter()
+                .filter_map(|c| c.get_attribute("id")?.parse().ok())
+                .max()
+                .unwrap_or(0);
+            let position: usize = ((index - left) as usize).min(columns.children.len());
+            for offset in 0..amount as usize {
+                next_id += 1;
+                let mut n: u32 = next_id;
+                let name: String = loop {
+                    let candidate: String = format!("Column{n}");
+                    if !columns
+                        .children
+                        .iter()
+                        .any(|c| c.get_attribute("name") == Some(&candidate))
+                    {
+                        break candidate;
+                    }
+                    n += 1;
+                };
+                let mut column: XmlElement = XmlElement::new("tableColumn");
+                column
+                    .attributes
+                    .insert("id".to_string(), next_id.to_string());
+                column.attributes.insert("name".to_string(), name);
+                columns.children.insert(position + offset, column);
+            }
+        } else {
+            let last: u32 = index.saturating_add(amount.unsigned_abs() as u32 - 1);
+            let mut col: u32 = left;
+            columns.children.retain(|_| {
+                let deleted: bool = (index..=last).contains(&col);
+                col += 1;
+                !deleted
+            });
+        }
+        columns
+            .attributes
+            .insert("count".to_string(), columns.children.len().to_string());
+    }
+
+    /// 要素内の数式 (セル、入力規則、条件付き書式、グラフの系列) の書き換え
+    pub(crate) fn map_formulas(element: &mut XmlElement, f: &dyn Fn(&str) -> String) {
+        for child in &mut element.children {
+            if matches!(
+                child.name.as_str(),
+                "f" | "xm:f" | "c:f" | "formula" | "formula1" | "formula2"
+            ) && let Some(text) = &child.text
+            {
+                child.text = Some(f(text));
+            } else {
+                Self::map_formulas(child, f);
+            }
+        }
+    }
 
-                let count: usize = sst.children.len();
-                sst.attributes
-                    .insert("count".to_string(), count.to_string());
-                sst.attributes
-                    .insert("uniqueCount".to_string(), count.to_string());
+    /// 共有数式のセルごとの数式への展開
+    ///
+    /// 親セル (`ref` を持つセル) の数式を基準に、従属セルの数式を相対参照を移動して復元
+    pub fn expand_shared_formulas(worksheet: &mut XmlElement) {
+        let Some(sheet_data) = worksheet.find_element_mut("sheetData") else {
+            return;
+        };
+
+        // 共有インデックスごとの親セルの位置と数式の収集
+        let mut anchors: HashMap<String, (u32, u32, String)> = HashMap::new();
+        for cell in sheet_data.children.iter().flat_map(|r| r.children.iter()) {
+            let Some(f) = cell.find_element("f") else {
+                continue;
+            };
+            if f.get_attribute("t").map(String::as_str) != Some("shared") {
+                continue;
+            }
+            if let (Some(si), Some(text), Some((row, col))) = (
+                f.get_attribute("si"),
+                f.text.as_ref(),
+                cell.get_attribute("r").and_then(|r| parse_address(r)),
+            ) {
+                anchors.insert(si.clone(), (row, col, text.clone()));
+            }
+        }
 
-                let new_id: usize = count - 1;
-                map.insert(s.to_string(), new_id);
-                return new_id;
+        for cell in sheet_data
+            .children
+            .iter_mut()
+            .flat_map(|r| r.children.iter_mut())
+        {
+            let Some((row, col)) = cell.get_attribute("r").and_then(|r| parse_address(r)) else {
+                continue;
+            };
+            let Some(f) = cell.children.iter_mut().find(|c| c.name == "f") else {
+                continue;
+            };
+            if f.get_attribute("t").map(String::as_str) != Some("shared") {
+                continue;
             }
+            let Some((anchor_row, anchor_col, text)) =
+                f.get_attribute("si").and_then(|si| anchors.get(si))
+            else {
+                continue;
+            };
+            f.text = Some(translate(
+                text,
+                row as i64 - *anchor_row as i64,
+                col as i64 - *anchor_col as i64,
+            ));
+            f.attributes.remove("t");
+            f.attributes.remove("ref");
+            f.attributes.remove("si");
         }
-        0
+    }
+
+    /// セルメタデータ番号 (1始まり) の項目が動的配列プロパティ (`XLDAPR`) かどうか
+    pub(crate) fn is_dynamic_array_metadata(metadata: &Xml, cm: usize) -> bool {
+        let Some(root) = metadata.elements.first() else {
+            return false;
+        };
+        let type_index: Option<String> = root
+            .find_element("metadataTypes")
+            .and_then(|types| {
+                types
+                    .children
+                    .iter()
+                    .position(|t| t.get_attribute("name").map(String::as_str) == Some("XLDAPR"))
+            })
+            .map(|pos| (pos + 1).to_string());
+        root.find_element("cellMetadata")
+            .and_then(|cell_metadata| cell_metadata.children.get(cm.checked_sub(1)?))
+            .and_then(|bk| bk.find_element("rc"))
+            .is_some_and(|rc| type_index.is_some() && rc.get_attribute("t") == type_index.as_ref())
+    }
+
+    /// セルメタデータへの動的配列プロパティの登録とセルメタデータ番号 (1始まり) の返却
+    pub(crate) fn ensure_dynamic_array_metadata(metadata: &mut Xml) -> usize {
+        if metadata.elements.is_empty() {
+            let mut root: XmlElement = XmlElement::new("metadata");
+            root.attributes.insert(
+                "xmlns".to_string(),
+                "http://schemas.openxmlformats.org/spreadsheetml/2006/main".to_string(),
+            );
+            root.attributes.insert(
+                "xmlns:xda".to_string(),
+                "http://schemas.microsoft.com/office/spreadsheetml/2017/dynamicarray".to_string(),
+            );
+            metadata.elements.push(root);
+            metadata
+                .decl
+                .insert("version".to_string(), "1.0".to_string());
+            metadata
+                .decl
+                .insert("encoding".to_string(), "UTF-8".to_string());
+            metadata
+                .decl
+                .insert("standalone".to_string(), "yes".to_string());
+        }
+        let root: &mut XmlElement = &mut metadata.elements[0];
+        root.attributes
+            .entry("xmlns:xda".to_string())
+            .or_insert_with(|| {
+                "http://schemas.microsoft.com/office/spreadsheetml/2017/dynamicarray".to_string()
+            });
+
+        // XLDAPR メタデータ型の取得または作成
+        let order: [&str; 6] = [
+            "metadataTypes",
+            "metadataStrings",
+            "mdxMetadata",
+            "futureMetadata",
+            "cellMetadata",
+            "valueMetadata",
+        ];
+        let types: &mut XmlElement = root.get_or_create_child_ordered("metadataTypes", &order);
+        let type_index: usize = match types
+            .children
+            .iter()
+            .position(|t| t.get_attribute("name").map(String::as_str) == Some("XLDAPR"))
+        {
+            Some(pos) => pos + 1,
+            None => {
+                let mut metadata_type: XmlElement = XmlElement::new("metadataType");
+                for (key, value) in [
+                    ("name", "XLDAPR"),
+                    ("minSupportedVersion", "120000"),
+                    ("copy", "1"),
+                    ("pasteAll", "1"),
+                    ("pasteValues", "1"),
+                    ("merge", "1"),
+                    ("splitFirst", "1"),
+                    ("rowColShift", "1"),
+                    ("clearFormats", "1"),
+                    ("clearComments", "1"),
+                    ("assign", "1"),
+                    ("coerce", "1"),
+                    ("cellMeta", "1"),
+                ] {
+                    metadata_type
+                        .attributes
+                        .insert(key.to_string(), value.to_string());
+                }
+                types.children.push(metadata_type);
+                types
+                    .attributes
+                    .insert("count".to_string(), types.children.len().to_string());
+                types.children.len()
+            }
+        };
+
+        // 動的配列プロパティの将来メタデータの取得または作成
+        let has_future: bool = root.children.iter().any(|c| {
+            c.name == "futureMetadata"
+                && c.get_attribute("name").map(String::as_str) == Some("XLDAPR")
+        });
+        if !has_future {
+            let mut properties: XmlElement = XmlElement::new("xda:dynamicArrayProperties");
+            properties
+                .attributes
+                .insert("fDynamic".to_string(), "1".to_string());
+            properties
+                .attributes
+                .insert("fCollapsed".to_string(), "0".to_string());
+            let mut ext: XmlElement = XmlElement::new("ext");
+            ext.attributes.insert(
+                "uri".to_string(),
+                "{bdbb8cdc-fa1e-496e-a857-3c3f30c029c3}".to_string(),
+            );
+            ext.children.push(properties);
+            let mut ext_lst: XmlElement = XmlElement::new("extLst");
+            ext_lst.children.push(ext);
+            let mut bk: XmlElement = XmlElement::new("bk");
+            bk.children.push(ext_lst);
+            let mut future: XmlElement = XmlElement::new("futureMetadata");
+            future
+                .attributes
+                .insert("name".to_string(), "XLDAPR".to_string());
+            future
+                .attributes
+                .insert("count".to_string(), "1".to_string());
+            future.children.push(bk);
+            let pos: usize = root
+                .children
+                .iter()
+                .position(|c| c.name == "cellMetadata" || c.name == "valueMetadata")
+                .unwrap_or(root.children.len());
+            root.children.insert(pos, future);
+        }
+
+        // 動的配列を指すセルメタデータの取得または作成
+        let cell_metadata: &mut XmlElement =
+            root.get_or_create_child_ordered("cellMetadata", &order);
+        let type_str: String = type_index.to_string();
+        if let Some(pos) = cell_metadata.children.iter().position(|bk| {
+            bk.find_element("rc")
+                .is_some_and(|rc| rc.get_attribute("t") == Some(&type_str))
+        }) {
+            return pos + 1;
+        }
+        let mut rc: XmlElement = XmlElement::new("rc");
+        rc.attributes.insert("t".to_string(), type_str);
+        rc.attributes.insert("v".to_string(), "0".to_string());
+        let mut bk: XmlElement = XmlElement::new("bk");
+        bk.children.push(rc);
+        cell_metadata.children.push(bk);
+        cell_metadata.attributes.insert(
+            "count".to_string(),
+            cell_metadata.children.len().to_string(),
+        );
+        cell_metadata.children.len()
+    }
+
+    /// 共有文字列テーブルへの文字列の追加
+    fn add_shared_string(&self, s: &str) -> usize {
+        let mut map: MutexGuard<HashMap<String, usize>> = self.shared_strings_map.lock().unwrap();
+        let mut strings: MutexGuard<Xml> = self.shared_strings.lock().unwrap();
+        Cell::intern_shared_string(&mut strings, &mut map, &Cell::shared_string_element(s))
     }
 
     #[cfg(test)]
@@ -197,7 +1639,7 @@ impl Sheet {
 
     /// セルの値を取得
     fn get_cell_value(&self, cell: &XmlElement) -> Option<String> {
-        let value_element: &XmlElement = cell.get_element("v");
+        let value_element: &XmlElement = cell.find_element("v")?;
         let value: String = value_element.text.as_ref()?.clone();
         match cell.get_attribute("t").map(String::as_str) {
             Some("s") => {
@@ -208,6 +1650,100 @@ impl Sheet {
         }
     }
 
+    /// 表示されるセルのテキストの取得
+    ///
+    /// 数値は標準の表示形式と同様に11文字までに丸める
+    fn cell_text(&self, cell: &XmlElement, num_fmt: &str) -> Option<String> {
+        match cell.get_attribute("t").map(String::as_str) {
+            Some("inlineStr") => {
+                let inline: &XmlElement = cell.find_element("is")?;
+                let texts = inline
+                    .children
+                    .iter()
+                    .flat_map(|c| {
+                        if c.name == "r" {
+                            c.children.iter().collect()
+                        } else {
+                            vec![c]
+                        }
+                    })
+                    .filter(|c| c.name == "t")
+                    .map(|t| t.get_text());
+                Some(texts.collect())
+            }
+            Some("b") => {
+                let value: String = self.get_cell_value(cell)?;
+                Some(if value == "1" { "TRUE" } else { "FALSE" }.to_string())
+            }
+            Some("s" | "str" | "e") => self.get_cell_value(cell),
+            _ => {
+                let value: String = self.get_cell_value(cell)?;
+                let Ok(number) = value.parse::<f64>() else {
+                    return Some(value);
+                };
+                Some(number_format::format_number(number, num_fmt))
+            }
+        }
+    }
+
+    /// `cellXfs` の順のフォント、折り返しの有無、表示形式の書式文字列の一覧
+    fn cell_styles(&self) -> Vec<(Font, bool, String)> {
+        let styles: MutexGuard<Xml> = self.styles.lock().unwrap();
+        let Some(style_sheet) = styles.elements.first() else {
+            return Vec::new();
+        };
+        let fonts: Vec<Font> = style_sheet
+            .find_element("fonts")
+            .map(|fonts| fonts.children.iter().map(Font::from_xml_element).collect())
+            .unwrap_or_default();
+        let num_fmts: Vec<(u32, String)> = style_sheet
+            .find_element("numFmts")
+            .map(|num_fmts| {
+                num_fmts
+                    .children
+                    .iter()
+                    .filter_map(|f| {
+                        let id: u32 = f.get_attribute("numFmtId")?.parse().ok()?;
+                        Some((id, f.get_attribute("formatCode")?.clone()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        style_sheet
+            .find_element("cellXfs")
+            .map(|xfs| {
+                xfs.children
+                    .iter()
+                    .map(|xf| {
+                        let font: Font = xf
+                            .get_attribute("fontId")
+                            .and_then(|id| id.parse::<usize>().ok())
+                            .and_then(|id| fonts.get(id))
+                            .or(fonts.first())
+                            .cloned()
+                            .unwrap_or_default();
+                        let wrap: bool = xf
+                            .find_element("alignment")
+                            .and_then(|a| a.get_attribute("wrapText"))
+                            .is_some_and(|w| w == "1" || w == "true");
+                        let num_fmt: String = xf
+                            .get_attribute("numFmtId")
+                            .and_then(|id| id.parse::<u32>().ok())
+                            .and_then(|id| {
+                                num_fmts
+                                    .iter()
+                                    .find(|(custom, _)| *custom == id)
+                                    .map(|(_, code)| code.clone())
+                                    .or(number_format::builtin_format_code(id).map(String::from))
+                            })
+                            .unwrap_or_else(|| "General".to_string());
+                        (font, wrap, num_fmt)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// インデックスによる共有文字列の取得
     fn get_shared_string_by_index(&self, index: usize) -> Option<String> {
         self.shared_strings.lock().ok().and_then(|shared_strings| {
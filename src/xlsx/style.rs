@@ -1,5 +1,7 @@
 use pyo3::prelude::*;
 
+use crate::xml::XmlElement;
+
 /// セルのフォントプロパティ
 #[pyclass]
 #[derive(Clone, Debug, PartialEq, Default)]
@@ -152,3 +154,161 @@ impl PatternFill {
         }
     }
 }
+
+/// `styleSheet` の子要素の順序
+const STYLESHEET_CHILD_ORDER: [&str; 11] = [
+    "numFmts",
+    "fonts",
+    "fills",
+    "borders",
+    "cellStyleXfs",
+    "cellXfs",
+    "cellStyles",
+    "dxfs",
+    "tableStyles",
+    "colors",
+    "extLst",
+];
+
+/// 組み込みの表示形式の最大ID (これより大きいIDはブックごとのユーザー定義)
+const MAX_BUILTIN_NUM_FMT_ID: u32 = 163;
+
+/// 一覧の要素への追加 (同じ要素がある場合はそのインデックス) と `count` の更新
+fn intern_child(target: &mut XmlElement, tag: &str, element: XmlElement) -> usize {
+    let list: &mut XmlElement = target.get_or_create_child_ordered(tag, &STYLESHEET_CHILD_ORDER);
+    if let Some(index) = list.children.iter().position(|c| *c == element) {
+        return index;
+    }
+    list.children.push(element);
+    let count: usize = list.children.len();
+    list.attributes
+        .insert("count".to_string(), count.to_string());
+    count - 1
+}
+
+/// 一覧の要素の取得 (例: `fonts` の `index` 番目)
+fn style_child<'a>(stylesheet: &'a XmlElement, tag: &str, index: usize) -> Option<&'a XmlElement> {
+    stylesheet.find_element(tag)?.children.get(index)
+}
+
+/// 取り込み元の `numFmts` で定義された表示形式の書式文字列
+fn source_num_fmt_code(source: &XmlElement, id: u32) -> Option<&String> {
+    source
+        .find_element("numFmts")?
+        .children
+        .iter()
+        .find(|f| f.get_attribute("numFmtId") == Some(&id.to_string()))?
+        .get_attribute("formatCode")
+}
+
+/// 表示形式の取り込み
+///
+/// `numFmts` で定義された表示形式は組み込みのIDの範囲でも書式文字列で取り込み、
+/// 定義のない組み込みの表示形式はそのままのIDを返す
+fn import_num_fmt(target: &mut XmlElement, source: &XmlElement, id: u32) -> u32 {
+    match source_num_fmt_code(source, id) {
+        Some(code) => intern_num_fmt(target, code),
+        None if id <= MAX_BUILTIN_NUM_FMT_ID => id,
+        None => 0,
+    }
+}
+
+/// 書式文字列の表示形式のIDの取得
+///
+/// 同じ書式の表示形式がある場合はそのID、なければ未使用のIDで追加する
+fn intern_num_fmt(target: &mut XmlElement, code: &str) -> u32 {
+    let num_fmts: &mut XmlElement =
+        target.get_or_create_child_ordered("numFmts", &STYLESHEET_CHILD_ORDER);
+    let ids: Vec<u32> = num_fmts
+        .children
+        .iter()
+        .filter_map(|f| f.get_attribute("numFmtId")?.parse().ok())
+        .collect();
+    if let Some(existing) = num_fmts
+        .children
+        .iter()
+        .find(|f| f.get_attribute("formatCode").is_some_and(|c| c == code))
+        .and_then(|f| f.get_attribute("numFmtId")?.parse().ok())
+    {
+        return existing;
+    }
+    let new_id: u32 = ids
+        .into_iter()
+        .max()
+        .unwrap_or(0)
+        .max(MAX_BUILTIN_NUM_FMT_ID)
+        + 1;
+    let mut num_fmt: XmlElement = XmlElement::new("numFmt");
+    num_fmt
+        .attributes
+        .insert("numFmtId".to_string(), new_id.to_string());
+    num_fmt
+        .attributes
+        .insert("formatCode".to_string(), code.to_string());
+    num_fmts.children.push(num_fmt);
+    let count: usize = num_fmts.children.len();
+    num_fmts
+        .attributes
+        .insert("count".to_string(), count.to_string());
+    new_id
+}
+
+/// 別のブックのセルの書式 (`cellXfs` の `index` 番目) の取り込み
+///
+/// 参照するフォント・塗りつぶし・罫線・表示形式も取り込み、取り込んだ書式のインデックスを返す。
+/// セルのスタイル (`xfId`) は標準スタイルに置き換える
+pub fn import_cell_xf(target: &mut XmlElement, source: &XmlElement, index: usize) -> usize {
+    let Some(mut xf) = style_child(source, "cellXfs", index).cloned() else {
+        return 0;
+    };
+    for (key, tag) in [
+        ("fontId", "fonts"),
+        ("fillId", "fills"),
+        ("borderId", "borders"),
+    ] {
+        let Some(id) = xf
+            .get_attribute(key)
+            .and_then(|id| id.parse::<usize>().ok())
+        else {
+            continue;
+        };
+        let new_id: usize = match style_child(source, tag, id) {
+            Some(element) => intern_child(target, tag, element.clone()),
+            None => 0,
+        };
+        xf.attributes.insert(key.to_string(), new_id.to_string());
+    }
+    if let Some(id) = xf
+        .get_attribute("numFmtId")
+        .and_then(|id| id.parse::<u32>().ok())
+    {
+        let new_id: u32 = import_num_fmt(target, source, id);
+        xf.attributes
+            .insert("numFmtId".to_string(), new_id.to_string());
+    }
+    xf.attributes.insert("xfId".to_string(), "0".to_string());
+    intern_child(target, "cellXfs", xf)
+}
+
+/// 別のブックの差分書式 (`dxfs` の `index` 番目、条件付き書式で使用) の取り込み
+pub fn import_dxf(target: &mut XmlElement, source: &XmlElement, index: usize) -> usize {
+    let Some(mut dxf) = style_child(source, "dxfs", index).cloned() else {
+        return 0;
+    };
+    // 差分書式の表示形式は書式文字列を含むため、IDのみ取り込み先で使用するものに揃える
+    if let Some(num_fmt) = dxf.children.iter_mut().find(|c| c.name == "numFmt")
+        && num_fmt
+            .get_attribute("numFmtId")
+            .and_then(|id| id.parse::<u32>().ok())
+            .is_some_and(|id| {
+                id > MAX_BUILTIN_NUM_FMT_ID || source_num_fmt_code(source, id).is_some()
+            })
+        && let Some(code) = num_fmt.get_attribute("formatCode").cloned()
+    {
+        let new_id: u32 = intern_num_fmt(target, &code);
+        num_fmt
+            .attributes
+            .insert("numFmtId".to_string(), new_id.to_string());
+    }
+    intern_child(target, "dxfs", dxf)
+}
@@ -1,6 +1,10 @@
 #[cfg(test)]
 mod tests {
-    use crate::book::Book;
+    use crate::book::{Book, BookError, SheetKey};
+    use crate::name::DefinedName;
+    use crate::sheet::Sheet;
+    use crate::view;
+    use crate::xml::{Xml, XmlElement};
     use std::{fs, path::Path};
 
     fn setup_book(test_name: &str) -> Book {
@@ -96,7 +100,7 @@ mod tests {
         let sheet = book.create_sheet("TestSheet".to_string(), sheet_count_before);
 
         // Assert
-        assert_eq!(sheet.name, "TestSheet");
+        assert_eq!(sheet.name().unwrap(), "TestSheet");
         assert_eq!(book.sheetnames().len(), sheet_count_before + 1);
         assert!(book.__contains__("TestSheet".to_string()));
         cleanup(book);
@@ -179,16 +183,18 @@ mod tests {
     fn test_delete_sheet() {
         // 観点: シートを削除できるか
         let mut book = setup_book("delete_sheet");
+        book.create_sheet("シート2".to_string(), 1);
         let sheet_count_before = book.sheetnames().len();
         assert!(book.__contains__("シート1".to_string()));
 
         // Act
         let sheet_to_delete = book.__getitem__("シート1".to_string());
-        book.__delitem__(sheet_to_delete.name.clone());
+        book.__delitem__(sheet_to_delete.name().unwrap()).unwrap();
 
         // Assert
         assert_eq!(book.sheetnames().len(), sheet_count_before - 1);
         assert!(!book.__contains__("シート1".to_string()));
+        assert!(book.__delitem__("シート2".to_string()).is_err());
 
         cleanup(book);
     }
@@ -221,7 +227,7 @@ mod tests {
         assert_eq!(sheetnames.len(), 2);
         assert_eq!(sheetnames[0], "NewSheetAt0");
         assert_eq!(sheetnames[1], "シート1");
-        assert_eq!(new_sheet.name, "NewSheetAt0");
+        assert_eq!(new_sheet.name().unwrap(), "NewSheetAt0");
 
         cleanup(book);
     }
@@ -252,4 +258,674 @@ mod tests {
 
         cleanup(book);
     }
+
+    #[test]
+    fn test_full_calc_on_load() {
+        // 観点: 読み込み時の再計算フラグを切り替えられるか
+        let mut book = setup_book("full_calc_on_load");
+        let copy_path = format!("{}.copy.xlsx", book.path);
+
+        // Act
+        book.set_full_calc_on_load(true);
+        book.copy(&copy_path);
+
+        // Assert
+        let mut book_copied = Book::new(&copy_path);
+        assert!(book_copied.full_calc_on_load());
+        book_copied.set_full_calc_on_load(false);
+        assert!(!book_copied.full_calc_on_load());
+
+        cleanup(book);
+        let _ = fs::remove_file(copy_path);
+    }
+
+    #[test]
+    fn test_active_sheet() {
+        // 観点: アクティブなシートを設定でき、tabSelected が揃い、削除時に activeTab が補正されるか
+        let mut book = Book::new("");
+        book.create_sheet("A".to_string(), 0);
+        let second = book.create_sheet("B".to_string(), 1);
+        book.create_sheet("C".to_string(), 2);
+        let tab_selected = |book: &Book, name: &str| {
+            let xml = book.get_sheet_by_name(name).unwrap().get_xml();
+            let xml = xml.lock().unwrap();
+            view::view_flag(&xml.elements[0], "tabSelected", false)
+        };
+
+        // Act
+        book.set_active(SheetKey::Index(2)).unwrap();
+        book.set_active(SheetKey::Name("B".to_string())).unwrap();
+
+        // Assert
+        assert_eq!(book.active().unwrap().name().unwrap(), "B");
+        assert!(tab_selected(&book, "B"));
+        assert!(!tab_selected(&book, "C"));
+        assert_eq!(
+            book.set_active(SheetKey::Index(5)),
+            Err(BookError::SheetIndexOutOfRange(5))
+        );
+
+        book.create_sheet("First".to_string(), 0);
+        assert_eq!(book.active().unwrap().name().unwrap(), "B");
+        book.remove(&second).unwrap();
+        assert_eq!(book.active().unwrap().name().unwrap(), "C");
+        assert!(tab_selected(&book, "C"));
+        book.set_active(SheetKey::Sheet(book.get_sheet_by_name("A").unwrap()))
+            .unwrap();
+        assert_eq!(book.active().unwrap().name().unwrap(), "A");
+    }
+
+    #[test]
+    fn test_active_sheet_after_chartsheet() {
+        // 観点: グラフシートを含むブックで、sheets 要素の位置どおりのワークシートに tabSelected が付くか
+        let mut book = Book::new("");
+        book.create_sheet("A".to_string(), 0);
+        book.create_sheet("B".to_string(), 1);
+        {
+            let mut workbook = book.workbook.lock().unwrap();
+            let sheets = workbook.elements[0].find_element_mut("sheets").unwrap();
+            let chart = Xml::new(r#"<x><sheet name="Chart" sheetId="9" r:id="rId99"/></x>"#)
+                .unwrap()
+                .elements[0]
+                .children[0]
+                .clone();
+            sheets.children.insert(0, chart);
+        }
+        let tab_selected = |book: &Book, name: &str| {
+            let xml = book.get_sheet_by_name(name).unwrap().get_xml();
+            let xml = xml.lock().unwrap();
+            view::view_flag(&xml.elements[0], "tabSelected", false)
+        };
+
+        // Act
+        book.set_active(SheetKey::Name("B".to_string())).unwrap();
+
+        // Assert
+        assert!(tab_selected(&book, "B"));
+        assert!(!tab_selected(&book, "A"));
+    }
+
+    #[test]
+    fn test_rename_sheet() {
+        // 観点: シート名を変更すると数式・入力規則・定義された名前の参照が書き換わり、不正な名前や重複を拒否するか
+        let mut book = Book::new("");
+        let data = book.create_sheet("Data".to_string(), 0);
+        let summary = book.create_sheet("Summary".to_string(), 1);
+        summary
+            .__getitem__("A1")
+            .set_value("=SUM(Data!A1:A3)".to_string())
+            .unwrap();
+        {
+            let xml = summary.get_xml();
+            let mut xml = xml.lock().unwrap();
+            let mut validations = XmlElement::new("dataValidations");
+            let mut validation = XmlElement::new("dataValidation");
+            let mut formula1 = XmlElement::new("formula1");
+            formula1.text = Some("Data!$A$1:$A$3".to_string());
+            validation.children.push(formula1);
+            validations.children.push(validation);
+            xml.elements[0].children.push(validations);
+        }
+        book.defined_names()
+            .add(DefinedName {
+                name: "Items".to_string(),
+                value: "Data!$A$1:$A$3".to_string(),
+                ..Default::default()
+            })
+            .unwrap();
+
+        // Act
+        data.set_title("Master Data").unwrap();
+
+        // Assert
+        assert_eq!(book.sheetnames(), vec!["Master Data", "Summary"]);
+        assert_eq!(data.get_title().unwrap(), "Master Data");
+        let summary = book.get_sheet_by_name("Summary").unwrap();
+        assert_eq!(
+            summary.__getitem__("A1").formula().as_deref(),
+            Some("SUM('Master Data'!A1:A3)")
+        );
+        {
+            let xml = summary.get_xml();
+            let xml = xml.lock().unwrap();
+            assert_eq!(
+                xml.elements[0]
+                    .find_element("dataValidations>dataValidation>formula1")
+                    .unwrap()
+                    .get_text(),
+                "'Master Data'!$A$1:$A$3"
+            );
+        }
+        assert_eq!(
+            book.defined_names().__getitem__("Items").unwrap().value,
+            "'Master Data'!$A$1:$A$3"
+        );
+        assert_eq!(
+            book.rename_sheet("Summary", "master data"),
+            Err(BookError::DuplicateSheetName("master data".to_string()))
+        );
+        assert_eq!(
+            book.rename_sheet("Summary", "Q1/Q2"),
+            Err(BookError::InvalidSheetName("Q1/Q2".to_string()))
+        );
+        assert_eq!(
+            book.rename_sheet("Summary", &"x".repeat(32)),
+            Err(BookError::InvalidSheetName("x".repeat(32)))
+        );
+        book.rename_sheet("Summary", "SUMMARY").unwrap();
+        assert_eq!(book.sheetnames(), vec!["Master Data", "SUMMARY"]);
+    }
+
+    #[test]
+    fn test_copy_worksheet() {
+        // 観点: シートと関連するテーブル・図形・グラフ・画像が新しい名前で複製され、テーブル名が重複しないか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Data".to_string(), 0);
+        sheet.__getitem__("A1").set_value("1".to_string()).unwrap();
+        book.add_table("Data".to_string(), "Sales".to_string(), "A1:C5".to_string());
+        book.drawings.insert(
+            "xl/drawings/drawing1.xml".to_string(),
+            Xml::new("<xdr:wsDr/>").unwrap(),
+        );
+        book.drawings.insert(
+            "xl/drawings/_rels/drawing1.xml.rels".to_string(),
+            Xml::new(
+                r#"<Relationships><Relationship Id="rId1" Type="chart" Target="../charts/chart1.xml"/><Relationship Id="rId2" Type="image" Target="../media/image1.png"/></Relationships>"#,
+            )
+            .unwrap(),
+        );
+        book.charts.insert(
+            "xl/charts/chart1.xml".to_string(),
+            Xml::new("<c:chartSpace/>").unwrap(),
+        );
+        book.files
+            .insert("xl/media/image1.png".to_string(), vec![0x89, 0x50]);
+        {
+            let rels = book
+                .sheet_rels
+                .get("xl/worksheets/_rels/sheet1.xml.rels")
+                .unwrap();
+            let mut rels = rels.lock().unwrap();
+            let mut drawing = XmlElement::new("Relationship");
+            drawing
+                .attributes
+                .insert("Id".to_string(), "rId9".to_string());
+            drawing
+                .attributes
+                .insert("Type".to_string(), "drawing".to_string());
+            drawing
+                .attributes
+                .insert("Target".to_string(), "../drawings/drawing1.xml".to_string());
+            rels.elements[0].children.push(drawing);
+        }
+
+        // Act
+        let copy = book.copy_worksheet(&sheet, None).unwrap();
+
+        // Assert
+        assert_eq!(copy.name().unwrap(), "Data Copy");
+        assert_eq!(book.sheetnames(), vec!["Data", "Data Copy"]);
+        assert_eq!(
+            book.get_sheet_paths().get("Data Copy").map(String::as_str),
+            Some("xl/worksheets/sheet2.xml")
+        );
+        assert_eq!(copy.__getitem__("A1").value().as_deref(), Some("1"));
+        let table = book.tables.get("xl/tables/table2.xml").unwrap();
+        let table = table.lock().unwrap();
+        assert_eq!(table.elements[0].get_attribute("name").unwrap(), "Sales_2");
+        assert_eq!(table.elements[0].get_attribute("id").unwrap(), "2");
+        let rels = book
+            .sheet_rels
+            .get("xl/worksheets/_rels/sheet2.xml.rels")
+            .unwrap();
+        let targets: Vec<String> = rels.lock().unwrap().elements[0]
+            .children
+            .iter()
+            .filter_map(|r| r.get_attribute("Target").cloned())
+            .collect();
+        assert!(targets.contains(&"../tables/table2.xml".to_string()));
+        assert!(targets.contains(&"../drawings/drawing2.xml".to_string()));
+        let drawing_rels = &book.drawings["xl/drawings/_rels/drawing2.xml.rels"];
+        let targets: Vec<&String> = drawing_rels.elements[0]
+            .children
+            .iter()
+            .filter_map(|r| r.get_attribute("Target"))
+            .collect();
+        assert_eq!(targets, vec!["../charts/chart2.xml", "../media/image2.png"]);
+        assert!(book.charts.contains_key("xl/charts/chart2.xml"));
+        assert_eq!(book.files["xl/media/image2.png"], vec![0x89, 0x50]);
+        assert_eq!(
+            book.copy_worksheet(&sheet, Some("data copy".to_string()))
+                .err(),
+            Some(BookError::DuplicateSheetName("data copy".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_import_sheet() {
+        // 観点: 別のブックのシートの書式・共有文字列・テーブルが取り込み先のインデックスに付け替えられるか
+        let mut source = Book::new("");
+        let sheet = source.create_sheet("Data".to_string(), 0);
+        sheet
+            .__getitem__("A1")
+            .set_value("World".to_string())
+            .unwrap();
+        sheet
+            .__getitem__("B1")
+            .set_value("Hello".to_string())
+            .unwrap();
+        source.add_table("Data".to_string(), "Sales".to_string(), "A1:B3".to_string());
+        {
+            let mut styles = source.styles.lock().unwrap();
+            let root = &mut styles.elements[0];
+            let fonts = root.find_element_mut("fonts").unwrap();
+            fonts.children.push(
+                Xml::new(r#"<font><b/><sz val="14"/></font>"#)
+                    .unwrap()
+                    .elements[0]
+                    .clone(),
+            );
+            root.children.insert(
+                0,
+                Xml::new(
+                    r#"<numFmts count="1"><numFmt numFmtId="164" formatCode="0.000"/></numFmts>"#,
+                )
+                .unwrap()
+                .elements[0]
+                    .clone(),
+            );
+            root.find_element_mut("cellXfs").unwrap().children.push(
+                Xml::new(r#"<xf numFmtId="164" fontId="1" fillId="0" borderId="0" xfId="0"></xf>"#)
+                    .unwrap()
+                    .elements[0]
+                    .clone(),
+            );
+            let ws = source.worksheets.get("xl/worksheets/sheet1.xml").unwrap();
+            let mut ws = ws.lock().unwrap();
+            let row = &mut ws.elements[0]
+                .find_element_mut("sheetData")
+                .unwrap()
+                .children[0];
+            row.children[0]
+                .attributes
+                .insert("s".to_string(), "1".to_string());
+        }
+        let mut book = Book::new("");
+        let existing = book.create_sheet("Data".to_string(), 0);
+        existing
+            .__getitem__("A1")
+            .set_value("Hello".to_string())
+            .unwrap();
+
+        // Act
+        let imported = book.import_sheet(&source, "Data", None).unwrap();
+
+        // Assert
+        assert_eq!(book.sheetnames(), vec!["Data", "Data Copy"]);
+        assert_eq!(imported.__getitem__("A1").value().as_deref(), Some("World"));
+        assert_eq!(imported.__getitem__("B1").value().as_deref(), Some("Hello"));
+        assert_eq!(
+            book.shared_strings.lock().unwrap().elements[0]
+                .children
+                .len(),
+            2
+        );
+        let styles = book.styles.lock().unwrap();
+        let root = &styles.elements[0];
+        let xfs = &root.find_element("cellXfs").unwrap().children;
+        assert_eq!(xfs.len(), 2);
+        assert_eq!(xfs[1].get_attribute("fontId").unwrap(), "1");
+        assert_eq!(xfs[1].get_attribute("numFmtId").unwrap(), "164");
+        let num_fmt = &root.find_element("numFmts").unwrap().children[0];
+        assert_eq!(num_fmt.get_attribute("formatCode").unwrap(), "0.000");
+        let ws = book.worksheets.get("xl/worksheets/sheet2.xml").unwrap();
+        let ws = ws.lock().unwrap();
+        let cell = &ws.elements[0].find_element("sheetData").unwrap().children[0].children[0];
+        assert_eq!(cell.get_attribute("s").unwrap(), "1");
+        let table = book.tables.get("xl/tables/table1.xml").unwrap();
+        let table = table.lock().unwrap();
+        assert_eq!(table.elements[0].get_attribute("name").unwrap(), "Sales");
+    }
+
+    #[test]
+    fn test_import_sheet_formats_and_metadata() {
+        // 観点: 組み込みのIDの範囲でも numFmts で定義された表示形式は書式文字列で取り込み、動的配列以外のセルメタデータの参照は外すか
+        let mut source = Book::new("");
+        let sheet = source.create_sheet("Data".to_string(), 0);
+        sheet.__getitem__("A1").set_number_value(1.0);
+        sheet.__getitem__("B1").set_number_value(2.0);
+        {
+            let mut styles = source.styles.lock().unwrap();
+            let root = &mut styles.elements[0];
+            root.children.insert(
+                0,
+                Xml::new(
+                    r#"<numFmts count="1"><numFmt numFmtId="100" formatCode="0.0000"/></numFmts>"#,
+                )
+                .unwrap()
+                .elements[0]
+                    .clone(),
+            );
+            root.find_element_mut("cellXfs").unwrap().children.push(
+                Xml::new(r#"<xf numFmtId="100" fontId="0" fillId="0" borderId="0" xfId="0"></xf>"#)
+                    .unwrap()
+                    .elements[0]
+                    .clone(),
+            );
+            *source.metadata.lock().unwrap() = Xml::new(
+                r#"<metadata><metadataTypes count="2"><metadataType name="XLRICHVALUE"/><metadataType name="XLDAPR"/></metadataTypes><cellMetadata count="2"><bk><rc t="1" v="0"/></bk><bk><rc t="2" v="0"/></bk></cellMetadata></metadata>"#,
+            )
+            .unwrap();
+            let ws = source.worksheets.get("xl/worksheets/sheet1.xml").unwrap();
+            let mut ws = ws.lock().unwrap();
+            let row = &mut ws.elements[0]
+                .find_element_mut("sheetData")
+                .unwrap()
+                .children[0];
+            row.children[0]
+                .attributes
+                .insert("cm".to_string(), "1".to_string());
+            row.children[1]
+                .attributes
+                .insert("cm".to_string(), "2".to_string());
+            row.children[1]
+                .attributes
+                .insert("s".to_string(), "1".to_string());
+        }
+        let mut book = Book::new("");
+
+        // Act
+        book.import_sheet(&source, "Data", None).unwrap();
+
+        // Assert
+        let ws = book.worksheets.get("xl/worksheets/sheet1.xml").unwrap();
+        let ws = ws.lock().unwrap();
+        let row = &ws.elements[0].find_element("sheetData").unwrap().children[0];
+        assert!(row.children[0].get_attribute("cm").is_none());
+        let metadata = book.metadata.lock().unwrap().clone();
+        let cm: usize = row.children[1]
+            .get_attribute("cm")
+            .unwrap()
+            .parse()
+            .unwrap();
+        assert!(Sheet::is_dynamic_array_metadata(&metadata, cm));
+        let styles = book.styles.lock().unwrap();
+        let root = &styles.elements[0];
+        let xf = &root.find_element("cellXfs").unwrap().children[1];
+        let num_fmt = &root.find_element("numFmts").unwrap().children[0];
+        assert_eq!(num_fmt.get_attribute("formatCode").unwrap(), "0.0000");
+        assert_eq!(
+            xf.get_attribute("numFmtId"),
+            num_fmt.get_attribute("numFmtId")
+        );
+        assert_ne!(xf.get_attribute("numFmtId").unwrap(), "100");
+    }
+
+    #[test]
+    fn test_move_sheet() {
+        // 観点: シートの移動と並べ替えで sheets の順序・localSheetId・activeTab・app.xml のシート名の一覧が揃うか
+        let mut book = Book::new("");
+        book.create_sheet("A".to_string(), 0);
+        book.create_sheet("B".to_string(), 1);
+        book.create_sheet("C".to_string(), 2);
+        book.set_active(SheetKey::Name("C".to_string())).unwrap();
+        book.defined_names()
+            .add(DefinedName {
+                name: "Local".to_string(),
+                value: "C!$A$1".to_string(),
+                local_sheet_id: Some(2),
+                ..Default::default()
+            })
+            .unwrap();
+        book.files.insert(
+            "docProps/app.xml".to_string(),
+            br#"<Properties xmlns:vt="http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes"><TitlesOfParts><vt:vector size="4" baseType="lpstr"><vt:lpstr>A</vt:lpstr><vt:lpstr>B</vt:lpstr><vt:lpstr>C</vt:lpstr><vt:lpstr>C!Print_Area</vt:lpstr></vt:vector></TitlesOfParts></Properties>"#
+                .to_vec(),
+        );
+        let titles = |book: &Book| -> Vec<String> {
+            let app = Xml::new(&String::from_utf8(book.files["docProps/app.xml"].clone()).unwrap())
+                .unwrap();
+            app.elements[0]
+                .find_element("TitlesOfParts>vt:vector")
+                .unwrap()
+                .children
+                .iter()
+                .filter_map(|t| t.text.clone())
+                .collect()
+        };
+
+        // Act
+        book.move_sheet(SheetKey::Name("C".to_string()), -5)
+            .unwrap();
+
+        // Assert
+        assert_eq!(book.sheetnames(), vec!["C", "A", "B"]);
+        assert_eq!(book.active().unwrap().name().unwrap(), "C");
+        assert_eq!(
+            book.defined_names().get("Local", Some(0)).unwrap().value,
+            "C!$A$1"
+        );
+        assert_eq!(titles(&book), vec!["C", "A", "B", "C!Print_Area"]);
+
+        book.reorder_sheets(vec!["B".to_string(), "A".to_string(), "C".to_string()])
+            .unwrap();
+        assert_eq!(book.sheetnames(), vec!["B", "A", "C"]);
+        assert_eq!(book.active().unwrap().name().unwrap(), "C");
+        assert!(book.defined_names().get("Local", Some(2)).is_some());
+        assert_eq!(titles(&book), vec!["B", "A", "C", "C!Print_Area"]);
+        assert_eq!(
+            book.reorder_sheets(vec!["A".to_string(), "B".to_string()]),
+            Err(BookError::InvalidSheetOrder)
+        );
+        assert_eq!(
+            book.reorder_sheets(vec!["A".to_string(), "B".to_string(), "X".to_string()]),
+            Err(BookError::SheetNotFound("X".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_unique_part_allocation() {
+        // 観点: シートの削除後やリレーションシップが既にある場合も、sheetId・rId・パーツ名・テーブルIDが重複しないか
+        let mut book = Book::new("");
+        let first = book.create_sheet("A".to_string(), 0);
+        book.create_sheet("B".to_string(), 1);
+        book.remove(&first).unwrap();
+
+        // Act
+        book.create_sheet("C".to_string(), 2);
+        {
+            let rels = book
+                .sheet_rels
+                .get_or_insert_with("xl/worksheets/_rels/sheet2.xml.rels", || {
+                    Xml::new(r#"<Relationships><Relationship Id="rId1" Type="drawing" Target="../drawings/drawing1.xml"></Relationship></Relationships>"#).unwrap()
+                });
+            assert_eq!(rels.lock().unwrap().elements[0].children.len(), 1);
+        }
+        book.add_table("B".to_string(), "T1".to_string(), "A1:C3".to_string());
+        book.add_table("B".to_string(), "T2".to_string(), "E1:G3".to_string());
+
+        // Assert
+        let paths = book.get_sheet_paths();
+        assert_eq!(paths["B"], "xl/worksheets/sheet2.xml");
+        assert_eq!(paths["C"], "xl/worksheets/sheet1.xml");
+        let ids: Vec<String> = book
+            .sheet_tags()
+            .iter()
+            .map(|s| s.get_attribute("sheetId").unwrap().clone())
+            .collect();
+        assert_eq!(ids, vec!["2", "3"]);
+        let rids: Vec<String> = book
+            .get_relationships()
+            .iter()
+            .map(|r| r.get_attribute("Id").unwrap().clone())
+            .collect();
+        assert_eq!(rids, vec!["rId2", "rId3"]);
+
+        let rels = book
+            .sheet_rels
+            .get("xl/worksheets/_rels/sheet2.xml.rels")
+            .unwrap();
+        let targets: Vec<(String, String)> = rels.lock().unwrap().elements[0]
+            .children
+            .iter()
+            .map(|r| {
+                (
+                    r.get_attribute("Id").unwrap().clone(),
+                    r.get_attribute("Target").unwrap().clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            targets,
+            vec![
+                ("rId1".to_string(), "../drawings/drawing1.xml".to_string()),
+                ("rId2".to_string(), "../tables/table1.xml".to_string()),
+                ("rId3".to_string(), "../tables/table2.xml".to_string()),
+            ]
+        );
+        let ws = book.worksheets.get("xl/worksheets/sheet2.xml").unwrap();
+        let ws = ws.lock().unwrap();
+        let table_parts = ws.elements[0].find_element("tableParts").unwrap();
+        assert_eq!(table_parts.get_attribute("count").unwrap(), "2");
+        assert_eq!(
+            table_parts.children[1].get_attribute("r:id").unwrap(),
+            "rId3"
+        );
+        let table = book.tables.get("xl/tables/table2.xml").unwrap();
+        assert_eq!(
+            table.lock().unwrap().elements[0]
+                .get_attribute("id")
+                .unwrap(),
+            "2"
+        );
+    }
+
+    #[test]
+    fn test_remove_sheet_parts() {
+        // 観点: シートの削除で、そのシートだけが参照するパーツと範囲の名前が削除され、他のシートと共有するパーツは残るか
+        let mut book = Book::new("");
+        let removed = book.create_sheet("A".to_string(), 0);
+        book.create_sheet("B".to_string(), 1);
+        book.add_table("A".to_string(), "Sales".to_string(), "A1:C3".to_string());
+        let rels = |targets: &[(&str, &str)]| {
+            let children: String = targets
+                .iter()
+                .enumerate()
+                .map(|(i, (kind, target))| {
+                    format!(
+                        r#"<Relationship Id="rId{}" Type="{kind}" Target="{target}"/>"#,
+                        i + 1
+                    )
+                })
+                .collect();
+            Xml::new(&format!("<Relationships>{children}</Relationships>")).unwrap()
+        };
+        {
+            let sheet_rels = book
+                .sheet_rels
+                .get("xl/worksheets/_rels/sheet1.xml.rels")
+                .unwrap();
+            let mut sheet_rels = sheet_rels.lock().unwrap();
+            let extra = rels(&[
+                ("drawing", "../drawings/drawing1.xml"),
+                ("comments", "../comments1.xml"),
+            ]);
+            for (id, mut relationship) in (8..).zip(extra.elements[0].children.clone()) {
+                relationship
+                    .attributes
+                    .insert("Id".to_string(), format!("rId{id}"));
+                sheet_rels.elements[0].children.push(relationship);
+            }
+        }
+        book.sheet_rels.insert(
+            "xl/worksheets/_rels/sheet2.xml.rels".to_string(),
+            rels(&[("drawing", "../drawings/drawing2.xml")]),
+        );
+        for (drawing, targets) in [
+            (
+                "drawing1",
+                vec![
+                    ("chart", "../charts/chart1.xml"),
+                    ("image", "../media/image1.png"),
+                ],
+            ),
+            ("drawing2", vec![("image", "../media/image1.png")]),
+        ] {
+            book.drawings.insert(
+                format!("xl/drawings/{drawing}.xml"),
+                Xml::new("<xdr:wsDr></xdr:wsDr>").unwrap(),
+            );
+            book.drawings.insert(
+                format!("xl/drawings/_rels/{drawing}.xml.rels"),
+                rels(&targets),
+            );
+        }
+        book.charts.insert(
+            "xl/charts/chart1.xml".to_string(),
+            Xml::new("<c:chartSpace></c:chartSpace>").unwrap(),
+        );
+        book.files
+            .insert("xl/comments1.xml".to_string(), b"<comments/>".to_vec());
+        book.files
+            .insert("xl/media/image1.png".to_string(), vec![0x89, 0x50]);
+        book.defined_names()
+            .add(DefinedName {
+                name: "_xlnm.Print_Area".to_string(),
+                value: "A!$A$1:$C$3".to_string(),
+                local_sheet_id: Some(0),
+                ..Default::default()
+            })
+            .unwrap();
+
+        // Act
+        book.remove(&removed).unwrap();
+
+        // Assert
+        assert_eq!(book.sheetnames(), vec!["B"]);
+        assert!(book.worksheets.get("xl/worksheets/sheet1.xml").is_none());
+        assert!(
+            book.sheet_rels
+                .get("xl/worksheets/_rels/sheet1.xml.rels")
+                .is_none()
+        );
+        assert!(book.tables.is_empty());
+        assert!(book.charts.is_empty());
+        assert!(!book.drawings.contains_key("xl/drawings/drawing1.xml"));
+        assert!(
+            !book
+                .drawings
+                .contains_key("xl/drawings/_rels/drawing1.xml.rels")
+        );
+        assert!(book.drawings.contains_key("xl/drawings/drawing2.xml"));
+        assert!(!book.files.contains_key("xl/comments1.xml"));
+        assert!(book.files.contains_key("xl/media/image1.png"));
+        assert_eq!(book.defined_names().__len__(), 0);
+    }
+
+    #[test]
+    fn test_remove_sheet_saved() {
+        // 観点: 削除したシートとその図形が保存したファイルに元のファイルから書き出されず、コンテンツタイプからも除かれるか
+        let mut book = setup_book("remove_sheet_saved");
+        let copy_path = format!("{}.copy.xlsx", book.path);
+        book.create_sheet("B".to_string(), 1);
+        let removed = book.get_sheet_by_name("シート1").unwrap();
+
+        // Act
+        book.remove(&removed).unwrap();
+        book.copy(&copy_path);
+
+        // Assert
+        let archive = zip::ZipArchive::new(fs::File::open(&copy_path).unwrap()).unwrap();
+        let names: Vec<&str> = archive.file_names().collect();
+        assert!(!names.contains(&"xl/worksheets/_rels/sheet1.xml.rels"));
+        assert!(!names.contains(&"xl/drawings/drawing1.xml"));
+        let copied = Book::new(&copy_path);
+        assert_eq!(copied.sheetnames(), vec!["B"]);
+        let content_types = copied.content_types.to_buf().unwrap();
+        let content_types = String::from_utf8(content_types).unwrap();
+        assert!(!content_types.contains("/xl/drawings/drawing1.xml"));
+
+        cleanup(book);
+        let _ = fs::remove_file(copy_path);
+    }
 }
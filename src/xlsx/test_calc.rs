@@ -0,0 +1,559 @@
+#[cfg(test)]
+mod tests {
+    use crate::book::Book;
+    use crate::calc::CalcError;
+    use crate::sheet::Sheet;
+    use crate::xml::Xml;
+
+    /// セルの値と数式を書き込んで計算したシート
+    fn calculated(cells: &[(&str, &str)]) -> Sheet {
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        for (address, value) in cells {
+            sheet
+                .__getitem__(address)
+                .set_value(value.to_string())
+                .unwrap();
+        }
+        book.calculate_formulas().unwrap();
+        sheet
+    }
+
+    /// 計算結果の検証 (アドレスと期待する値の組)
+    fn assert_values(sheet: &Sheet, expected: &[(&str, &str)]) {
+        for (address, value) in expected {
+            assert_eq!(
+                sheet.__getitem__(address).value().as_deref(),
+                Some(*value),
+                "{address}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_calculate_formulas() {
+        // 観点: 算術・文字列・検索・条件付き集計・日付の数式を依存関係の順に計算し、キャッシュ値を書き込めるか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        let data = book.create_sheet("Data".to_string(), 1);
+        for (address, value) in [
+            ("A1", "1"),
+            ("A2", "2"),
+            ("A3", "3"),
+            ("B1", "apple"),
+            ("B2", "banana"),
+            ("B3", "cherry"),
+            ("D1", "=C1+1"),
+            ("C1", "=SUM(A1:A3)*2"),
+            ("C2", "=VLOOKUP(2,A1:B3,2,FALSE)"),
+            ("C3", "=IF(C1>10,\"big\",\"small\")&\"!\""),
+            ("C4", "=COUNTIFS(A1:A3,\">1\",B1:B3,\"*an*\")"),
+            ("C5", "=DAY(EOMONTH(DATE(2024,1,31),1))"),
+            ("C6", "=_xlfn.XLOOKUP(\"cherry\",B1:B3,A1:A3)"),
+            ("C7", "=INDEX(A1:B3,MATCH(\"banana\",B1:B3,0),1)"),
+        ] {
+            sheet
+                .__getitem__(address)
+                .set_value(value.to_string())
+                .unwrap();
+        }
+        data.__getitem__("A1")
+            .set_value("=Sheet1!C1/4".to_string())
+            .unwrap();
+
+        // Act
+        let count = book.calculate_formulas().unwrap();
+
+        // Assert
+        assert_eq!(count, 9);
+        let expected = [
+            ("C1", "12"),
+            ("C2", "banana"),
+            ("C3", "big!"),
+            ("C4", "1"),
+            ("C5", "29"),
+            ("C6", "3"),
+            ("C7", "2"),
+            ("D1", "13"),
+        ];
+        for (address, value) in expected {
+            assert_eq!(
+                sheet.__getitem__(address).value().unwrap(),
+                value,
+                "{address}"
+            );
+        }
+        assert_eq!(data.__getitem__("A1").value().unwrap(), "3");
+        assert_eq!(sheet.__getitem__("C1").formula().unwrap(), "SUM(A1:A3)*2");
+        assert!(!book.full_calc_on_load());
+    }
+
+    #[test]
+    fn test_calculate_circular_reference() {
+        // 観点: 循環参照を検出し、セルを変更せずにエラーを返すか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        sheet
+            .__getitem__("A1")
+            .set_value("=B1+1".to_string())
+            .unwrap();
+        sheet
+            .__getitem__("B1")
+            .set_value("=A1".to_string())
+            .unwrap();
+        sheet
+            .__getitem__("C1")
+            .set_value("=1+1".to_string())
+            .unwrap();
+
+        // Act
+        let result = book.calculate_formulas();
+
+        // Assert
+        assert_eq!(
+            result,
+            Err(CalcError::CircularReference(
+                "Sheet1!A1, Sheet1!B1".to_string()
+            ))
+        );
+        assert!(sheet.__getitem__("C1").value().is_none());
+    }
+
+    #[test]
+    fn test_calculate_math() {
+        // 観点: 丸め・剰余・集計の関数が Excel と同じ結果になり、数値は有効桁数15桁で書き込まれるか
+        let sheet = calculated(&[
+            ("A1", "1"),
+            ("A2", "2"),
+            ("A3", "abc"),
+            ("B1", "=ROUND(2.345,2)"),
+            ("B2", "=ROUND(-2.5,0)"),
+            ("B3", "=ROUNDUP(1.21,1)"),
+            ("B4", "=ROUNDDOWN(-1.29,1)"),
+            ("B5", "=ROUND(1234.5,-2)"),
+            ("B6", "=MOD(-3,2)"),
+            ("B7", "=INT(-1.5)"),
+            ("B8", "=SQRT(-1)"),
+            ("B9", "=POWER(2,10)"),
+            ("B10", "=0.1+0.2"),
+            ("C1", "=AVERAGE(A1:A3)"),
+            ("C2", "=COUNT(A1:A4)"),
+            ("C3", "=COUNTA(A1:A4)"),
+            ("C4", "=COUNTBLANK(A1:A4)"),
+            ("C5", "=SUMPRODUCT(A1:A2,A1:A2)"),
+            ("C6", "=MAX(A1:A3)-MIN(A1:A3)"),
+            ("C7", "=MOD(1,0)"),
+        ]);
+
+        // Assert
+        assert_values(
+            &sheet,
+            &[
+                ("B1", "2.35"),
+                ("B2", "-3"),
+                ("B3", "1.3"),
+                ("B4", "-1.2"),
+                ("B5", "1200"),
+                ("B6", "1"),
+                ("B7", "-2"),
+                ("B8", "#NUM!"),
+                ("B9", "1024"),
+                ("B10", "0.3"),
+                ("C1", "1.5"),
+                ("C2", "2"),
+                ("C3", "3"),
+                ("C4", "1"),
+                ("C5", "5"),
+                ("C6", "1"),
+                ("C7", "#DIV/0!"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_calculate_text() {
+        // 観点: 文字列関数が文字数・位置・置換を Excel と同じく扱い、見つからない場合は #VALUE! になるか
+        let sheet = calculated(&[
+            ("A1", "Hello World"),
+            ("B1", "=LEN(A1)"),
+            ("B2", "=UPPER(A1)&LOWER(\"X\")"),
+            ("B3", "=TRIM(\"  a   b  \")"),
+            ("B4", "=LEFT(A1,4)&\"|\"&RIGHT(A1)"),
+            ("B5", "=MID(A1,7,3)"),
+            ("B6", "=REPT(\"ab\",3)"),
+            ("B7", "=SUBSTITUTE(\"a-b-c\",\"-\",\"+\")"),
+            ("B8", "=SUBSTITUTE(\"a-b-c\",\"-\",\"+\",2)"),
+            ("B9", "=FIND(\"o\",A1,6)"),
+            ("B10", "=SEARCH(\"WORLD\",A1)"),
+            ("B11", "=FIND(\"z\",A1)"),
+            ("B12", "=EXACT(\"a\",\"A\")"),
+            ("B13", "=VALUE(\"1.5\")*2"),
+            ("B14", "=CONCATENATE(\"a\",1,TRUE)"),
+            ("B15", "=TEXTJOIN(\",\",TRUE,\"a\",\"\",\"b\")"),
+            ("B16", "=MID(A1,20,2)"),
+        ]);
+
+        // Assert
+        assert_values(
+            &sheet,
+            &[
+                ("B1", "11"),
+                ("B2", "HELLO WORLDx"),
+                ("B3", "a b"),
+                ("B4", "Hell|d"),
+                ("B5", "Wor"),
+                ("B6", "ababab"),
+                ("B7", "a+b+c"),
+                ("B8", "a-b+c"),
+                ("B9", "8"),
+                ("B10", "7"),
+                ("B11", "#VALUE!"),
+                ("B12", "0"),
+                ("B13", "3"),
+                ("B14", "a1TRUE"),
+                ("B15", "a,b"),
+                ("B16", ""),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_calculate_lookup() {
+        // 観点: 行・列の検索の完全一致と近似一致、見つからない場合の #N/A と既定値を扱えるか
+        let sheet = calculated(&[
+            ("A1", "10"),
+            ("B1", "20"),
+            ("C1", "30"),
+            ("A2", "low"),
+            ("B2", "mid"),
+            ("C2", "high"),
+            ("A4", "1"),
+            ("A5", "5"),
+            ("A6", "10"),
+            ("B4", "x"),
+            ("B5", "y"),
+            ("B6", "z"),
+            ("D1", "=HLOOKUP(20,A1:C2,2,FALSE)"),
+            ("D2", "=HLOOKUP(25,A1:C2,2,TRUE)"),
+            ("D3", "=VLOOKUP(7,A4:B6,2)"),
+            ("D4", "=VLOOKUP(99,A4:B6,2,FALSE)"),
+            ("D5", "=MATCH(6,A4:A6,1)"),
+            ("D6", "=_xlfn.XLOOKUP(99,A4:A6,B4:B6,\"none\")"),
+            ("D7", "=INDEX(A4:B6,3,2)"),
+            ("D8", "=CHOOSE(2,\"a\",\"b\",\"c\")"),
+            ("D9", "=INDEX(A4:B6,4,1)"),
+        ]);
+
+        // Assert
+        assert_values(
+            &sheet,
+            &[
+                ("D1", "mid"),
+                ("D2", "mid"),
+                ("D3", "y"),
+                ("D4", "#N/A"),
+                ("D5", "2"),
+                ("D6", "none"),
+                ("D7", "z"),
+                ("D8", "b"),
+                ("D9", "#REF!"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_calculate_conditional_aggregates() {
+        // 観点: 条件付き集計が比較演算子・ワイルドカード・複数条件を扱い、該当がない平均は #DIV/0! になるか
+        let sheet = calculated(&[
+            ("A1", "apple"),
+            ("A2", "banana"),
+            ("A3", "apricot"),
+            ("A4", "cherry"),
+            ("B1", "1"),
+            ("B2", "2"),
+            ("B3", "3"),
+            ("B4", "4"),
+            ("C1", "=SUMIF(A1:A4,\"a*\",B1:B4)"),
+            ("C2", "=SUMIF(B1:B4,\">=2\")"),
+            ("C3", "=SUMIFS(B1:B4,A1:A4,\"<>banana\",B1:B4,\"<4\")"),
+            ("C4", "=AVERAGEIF(A1:A4,\"?????\",B1:B4)"),
+            ("C5", "=AVERAGEIFS(B1:B4,A1:A4,\"a*\",B1:B4,\">1\")"),
+            ("C6", "=AVERAGEIFS(B1:B4,A1:A4,\"zzz\")"),
+            ("C7", "=COUNTIF(A1:A4,\"*an*\")"),
+            ("C8", "=COUNTIFS(A1:A4,\"a*\",B1:B4,3)"),
+        ]);
+
+        // Assert
+        assert_values(
+            &sheet,
+            &[
+                ("C1", "4"),
+                ("C2", "9"),
+                ("C3", "4"),
+                ("C4", "1"),
+                ("C5", "3"),
+                ("C6", "#DIV/0!"),
+                ("C7", "1"),
+                ("C8", "1"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_calculate_dates() {
+        // 観点: 日付のシリアル値を1900年うるう年の扱いを含めて計算し、月末の繰り上げと曜日の種類を扱えるか
+        let sheet = calculated(&[
+            ("A1", "=DATE(2024,1,31)"),
+            ("B1", "=EDATE(A1,1)"),
+            ("B2", "=DAY(EDATE(A1,-2))"),
+            ("B3", "=EOMONTH(A1,0)-A1"),
+            ("B4", "=WEEKDAY(DATE(2024,1,1))"),
+            ("B5", "=WEEKDAY(DATE(2024,1,1),2)"),
+            ("B6", "=WEEKDAY(DATE(2024,1,1),3)"),
+            ("B7", "=DATE(1900,3,1)"),
+            ("B8", "=YEAR(A1)*100+MONTH(DATE(2024,14,1))"),
+            ("B9", "=DAYS(DATE(2024,3,1),DATE(2024,2,1))"),
+            ("B10", "=HOUR(TIME(13,45,30))*100+MINUTE(TIME(13,45,30))"),
+            ("B11", "=EDATE(-1,1)"),
+        ]);
+
+        // Assert
+        assert_values(
+            &sheet,
+            &[
+                ("A1", "45322"),
+                ("B1", "45351"),
+                ("B2", "30"),
+                ("B3", "0"),
+                ("B4", "2"),
+                ("B5", "1"),
+                ("B6", "0"),
+                ("B7", "61"),
+                ("B8", "202402"),
+                ("B9", "29"),
+                ("B10", "1345"),
+                ("B11", "#NUM!"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_calculate_errors() {
+        // 観点: エラー値が参照先の数式に伝播し、IFERROR・IFNA・ISERROR で捕捉でき、未知の関数は計算しないか
+        let sheet = calculated(&[
+            ("A1", "=1/0"),
+            ("A2", "=A1+1"),
+            ("A3", "=IFERROR(A2,\"caught\")"),
+            ("A4", "=IFNA(NA(),\"na\")"),
+            ("A5", "=IFNA(A1,\"na\")"),
+            ("A6", "=ISERROR(A2)"),
+            ("A7", "=\"a\"+1"),
+            ("A8", "=UNKNOWNFUNC(1)"),
+            ("A9", "=SUM(A1,1)"),
+            ("A10", "=Missing!A1"),
+            ("A11", "=ISNA(VLOOKUP(\"x\",A1:A2,1,FALSE))"),
+        ]);
+
+        // Assert
+        assert_values(
+            &sheet,
+            &[
+                ("A1", "#DIV/0!"),
+                ("A2", "#DIV/0!"),
+                ("A3", "caught"),
+                ("A4", "na"),
+                ("A5", "#DIV/0!"),
+                ("A6", "1"),
+                ("A7", "#VALUE!"),
+                ("A9", "#DIV/0!"),
+                ("A10", "#REF!"),
+                ("A11", "1"),
+            ],
+        );
+        assert!(sheet.__getitem__("A8").value().is_none());
+    }
+
+    #[test]
+    fn test_calculate_circular_reference_variants() {
+        // 観点: 自己参照・範囲を介した循環・シートをまたぐ循環を検出し、循環に含まれるセルだけを列挙するか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        let other = book.create_sheet("Other".to_string(), 1);
+        sheet
+            .__getitem__("A1")
+            .set_value("=A1+1".to_string())
+            .unwrap();
+
+        // Act & Assert
+        assert_eq!(
+            book.calculate_formulas(),
+            Err(CalcError::CircularReference("Sheet1!A1".to_string()))
+        );
+        sheet.__getitem__("A1").set_value("1".to_string()).unwrap();
+        sheet
+            .__getitem__("B3")
+            .set_value("=SUM(B1:B2)".to_string())
+            .unwrap();
+        sheet
+            .__getitem__("B1")
+            .set_value("=Other!A1".to_string())
+            .unwrap();
+        other
+            .__getitem__("A1")
+            .set_value("=Sheet1!B3*2".to_string())
+            .unwrap();
+        sheet
+            .__getitem__("C1")
+            .set_value("=A1*2".to_string())
+            .unwrap();
+        assert_eq!(
+            book.calculate_formulas(),
+            Err(CalcError::CircularReference(
+                "Sheet1!B1, Sheet1!B3, Other!A1".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_calculate_array_formulas() {
+        // 観点: 配列数式の結果を範囲の各セルに書き込み、範囲内のセルを参照する数式が配列数式の後に計算されるか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        for (address, value) in [("A1", "1"), ("A2", "2"), ("A3", "3"), ("D1", "=C2+C3")] {
+            sheet
+                .__getitem__(address)
+                .set_value(value.to_string())
+                .unwrap();
+        }
+        sheet.set_array_formula("C1:C3", "=A1:A3*2", false).unwrap();
+        sheet
+            .set_array_formula("E1:F1", "=SUM(A1:A3*A1:A3)", false)
+            .unwrap();
+
+        // Act
+        let count = book.calculate_formulas().unwrap();
+
+        // Assert
+        assert_eq!(count, 6);
+        assert_values(
+            &sheet,
+            &[
+                ("C1", "2"),
+                ("C2", "4"),
+                ("C3", "6"),
+                ("D1", "10"),
+                ("E1", "14"),
+                ("F1", "14"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_calculate_long_chain() {
+        // 観点: 多数の数式の依存関係を記述順によらず解決できるか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        sheet.__getitem__("A1").set_value("1".to_string()).unwrap();
+        for row in (2..=2000).rev() {
+            sheet
+                .cell(row, 1)
+                .set_value(format!("=A{}+1", row - 1))
+                .unwrap();
+        }
+
+        // Act
+        let count = book.calculate_formulas().unwrap();
+
+        // Assert
+        assert_eq!(count, 1999);
+        assert_eq!(sheet.cell(2000, 1).value().as_deref(), Some("2000"));
+    }
+
+    #[test]
+    fn test_calculate_unsupported_keeps_cached_values() {
+        // 観点: 対応していない関数・構造化参照・外部参照を使う数式とそれに依存する数式のキャッシュ値を変更せず、読み込み時の再計算を設定するか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        sheet.__getitem__("A1").set_value("3".to_string()).unwrap();
+        for (address, formula, cached, cached_type) in [
+            ("B1", "TEXT(A1,\"0.00\")", "3.00", Some("str")),
+            ("B2", "OFFSET(A1,0,0)", "3", None),
+            ("B3", "B2+1", "4", None),
+            ("B4", "IFERROR(INDIRECT(\"A1\"),0)", "3", None),
+            ("B5", "SUM(Table1[Amount])", "30", None),
+            ("B6", "[1]Sheet1!A1", "7", None),
+            ("B7", "A1*2", "0", None),
+        ] {
+            sheet
+                .__getitem__(address)
+                .set_formula(
+                    formula,
+                    Some(cached.to_string()),
+                    cached_type.map(str::to_string),
+                )
+                .unwrap();
+        }
+
+        // Act
+        let count = book.calculate_formulas().unwrap();
+
+        // Assert
+        assert_eq!(count, 1);
+        assert_values(
+            &sheet,
+            &[
+                ("B1", "3.00"),
+                ("B2", "3"),
+                ("B3", "4"),
+                ("B4", "3"),
+                ("B5", "30"),
+                ("B6", "7"),
+                ("B7", "6"),
+            ],
+        );
+        let xml = sheet.get_xml();
+        let xml = xml.lock().unwrap();
+        let b1 = &xml.elements[0].find_element("sheetData").unwrap().children[0].children[1];
+        assert_eq!(b1.get_attribute("t").map(String::as_str), Some("str"));
+        assert!(book.full_calc_on_load());
+    }
+
+    #[test]
+    fn test_calculate_names_after_chartsheet() {
+        // 観点: グラフシートが前にあるブックで、シートスコープの名前を sheets 要素の位置どおりのワークシートで解決するか
+        let mut book = Book::new("");
+        let first = book.create_sheet("A".to_string(), 0);
+        let second = book.create_sheet("B".to_string(), 1);
+        {
+            let mut workbook = book.workbook.lock().unwrap();
+            let sheets = workbook.elements[0].find_element_mut("sheets").unwrap();
+            let chart = Xml::new(r#"<x><sheet name="Chart" sheetId="9" r:id="rId99"/></x>"#)
+                .unwrap()
+                .elements[0]
+                .children[0]
+                .clone();
+            sheets.children.insert(0, chart);
+        }
+        let parts = book.parts();
+        parts.set_defined_name("Rate", None, Some("1".to_string()));
+        parts.set_defined_name("Rate", Some(1), Some("10".to_string()));
+        parts.set_defined_name("Rate", Some(2), Some("20".to_string()));
+        parts.set_defined_name("Rate", Some(0), Some("99".to_string()));
+        first
+            .__getitem__("A1")
+            .set_value("=Rate".to_string())
+            .unwrap();
+        second
+            .__getitem__("A1")
+            .set_value("=Rate+A2".to_string())
+            .unwrap();
+        second.__getitem__("A2").set_value("5".to_string()).unwrap();
+
+        // Act
+        book.calculate_formulas().unwrap();
+
+        // Assert
+        assert_eq!(first.__getitem__("A1").value().as_deref(), Some("10"));
+        assert_eq!(second.__getitem__("A1").value().as_deref(), Some("25"));
+    }
+}
@@ -1,6 +1,8 @@
 #[cfg(test)]
 mod tests {
     use crate::book::Book;
+    use crate::cell::{CellError, CellValue};
+    use crate::xml::Xml;
     use std::fs;
 
     fn setup_book(test_name: &str) -> Book {
@@ -51,7 +53,7 @@ mod tests {
 
         // Act
         let mut cell = sheet.__getitem__("A1");
-        cell.set_value("999".to_string());
+        cell.set_value("999".to_string()).unwrap();
         book.copy(&copy_path);
 
         // Assert
@@ -73,7 +75,7 @@ mod tests {
 
         // Act
         let mut cell = sheet.__getitem__("B1");
-        cell.set_value("new_string".to_string());
+        cell.set_value("new_string".to_string()).unwrap();
         book.copy(&copy_path);
 
         // Assert
@@ -95,9 +97,9 @@ mod tests {
 
         // Act
         let mut cell_c1 = sheet.__getitem__("C1");
-        cell_c1.set_value("12345".to_string());
+        cell_c1.set_value("12345".to_string()).unwrap();
         let mut cell_d1 = sheet.__getitem__("D1");
-        cell_d1.set_value("new_cell_string".to_string());
+        cell_d1.set_value("new_cell_string".to_string()).unwrap();
         book.copy(&copy_path);
 
         // Assert
@@ -121,7 +123,7 @@ mod tests {
 
         // Act
         let mut cell = sheet.__getitem__("E1");
-        cell.set_value("2024-01-01 12:30:00".to_string());
+        cell.set_value("2024-01-01 12:30:00".to_string()).unwrap();
         book.copy(&copy_path);
 
         // Assert
@@ -144,9 +146,9 @@ mod tests {
 
         // Act
         let mut cell_f1 = sheet.__getitem__("F1");
-        cell_f1.set_value("true".to_string());
+        cell_f1.set_value("true".to_string()).unwrap();
         let mut cell_g1 = sheet.__getitem__("G1");
-        cell_g1.set_value("false".to_string());
+        cell_g1.set_value("false".to_string()).unwrap();
         book.copy(&copy_path);
 
         // Assert
@@ -170,18 +172,134 @@ mod tests {
 
         // Act
         let mut cell = sheet.__getitem__("H1");
-        cell.set_value("=SUM(A1:A2)".to_string());
+        cell.set_value("=SUM(A1:A2)".to_string()).unwrap();
+        let invalid = cell.set_value("=SUM(A1:A2".to_string());
         book.copy(&copy_path);
 
         // Assert
         let book_reloaded = Book::new(&copy_path);
         let sheet_reloaded = book_reloaded.__getitem__("シート1".to_string());
-        let _cell_reloaded = sheet_reloaded.__getitem__("H1");
-        // TODO: 実際に計算された値を取得する方法は未実装
-        // とりあえず、数式が設定されているかを確認
-        // assert_eq!(cell_reloaded.formula().unwrap(), "SUM(A1:A2)");
+        let cell_reloaded = sheet_reloaded.__getitem__("H1");
+        assert!(invalid.is_err());
+        assert_eq!(cell_reloaded.formula().unwrap(), "SUM(A1:A2)");
+        assert!(cell_reloaded.value().is_none());
 
         let _ = fs::remove_file(&book.path);
         let _ = fs::remove_file(copy_path);
     }
+
+    #[test]
+    fn test_set_error_value() {
+        // 観点: エラー値を設定し、文字列と区別して読み取れるか
+        let book = setup_book("set_error");
+        let sheet = book.__getitem__("シート1".to_string());
+        let copy_path = format!("{}.copy.xlsx", book.path);
+
+        // Act
+        let mut cell_i1 = sheet.__getitem__("I1");
+        cell_i1
+            .set_cell_value(CellValue::Error(CellError::from_code("#N/A").unwrap()))
+            .unwrap();
+        let mut cell_j1 = sheet.__getitem__("J1");
+        cell_j1
+            .set_cell_value(CellValue::Text("#N/A".to_string()))
+            .unwrap();
+        book.copy(&copy_path);
+
+        // Assert
+        let book_reloaded = Book::new(&copy_path);
+        let sheet_reloaded = book_reloaded.__getitem__("シート1".to_string());
+        let cell_i1_reloaded = sheet_reloaded.__getitem__("I1");
+        let cell_j1_reloaded = sheet_reloaded.__getitem__("J1");
+        assert_eq!(cell_i1_reloaded.value().unwrap(), "#N/A");
+        assert_eq!(
+            cell_i1_reloaded.get_value(),
+            Some(CellValue::Error(CellError::from_code("#N/A").unwrap()))
+        );
+        assert_eq!(
+            cell_j1_reloaded.get_value(),
+            Some(CellValue::Text("#N/A".to_string()))
+        );
+        assert!(CellError::from_code("#FOO!").is_none());
+
+        let _ = fs::remove_file(&book.path);
+        let _ = fs::remove_file(copy_path);
+    }
+
+    #[test]
+    fn test_set_formula_with_cached_value() {
+        // 観点: 数式とキャッシュ値を別々に読み書きできるか
+        let book = setup_book("set_formula_cached");
+        let sheet = book.__getitem__("シート1".to_string());
+        let copy_path = format!("{}.copy.xlsx", book.path);
+
+        // Act
+        let mut cell_h1 = sheet.__getitem__("H1");
+        cell_h1
+            .set_formula("=SUM(A1:A2)", Some("3".to_string()), None)
+            .unwrap();
+        let mut cell_h2 = sheet.__getitem__("H2");
+        cell_h2
+            .set_formula(
+                "\"a\"&\"b\"",
+                Some("ab".to_string()),
+                Some("str".to_string()),
+            )
+            .unwrap();
+        let invalid = cell_h2.set_formula("1", Some("1".to_string()), Some("x".to_string()));
+        book.copy(&copy_path);
+
+        // Assert
+        assert!(invalid.is_err());
+        let book_reloaded = Book::new(&copy_path);
+        let sheet_reloaded = book_reloaded.__getitem__("シート1".to_string());
+        let cell_h1_reloaded = sheet_reloaded.__getitem__("H1");
+        let cell_h2_reloaded = sheet_reloaded.__getitem__("H2");
+        assert_eq!(cell_h1_reloaded.formula().unwrap(), "SUM(A1:A2)");
+        assert_eq!(cell_h1_reloaded.value().unwrap(), "3");
+        assert_eq!(cell_h2_reloaded.formula().unwrap(), "\"a\"&\"b\"");
+        assert_eq!(cell_h2_reloaded.value().unwrap(), "ab");
+
+        let _ = fs::remove_file(&book.path);
+        let _ = fs::remove_file(copy_path);
+    }
+
+    #[test]
+    fn test_set_value_over_inline_string_and_dynamic_array() {
+        // 観点: インライン文字列や動的配列のセルに値を書き込むと `is` と `cm` を除き、`v` を先頭に置くか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Data".to_string(), 0);
+        {
+            let xml = sheet.get_xml();
+            let mut xml = xml.lock().unwrap();
+            let sheet_data = xml.elements[0].get_element_mut("sheetData");
+            *sheet_data = Xml::new(
+                r#"<sheetData><row r="1"><c r="A1" t="inlineStr"><is><t>old</t></is><extLst/></c><c r="B1" cm="1"><f t="array" ref="B1:B2">SEQUENCE(2)</f><v>1</v></c></row></sheetData>"#,
+            )
+            .unwrap()
+            .elements[0]
+            .clone();
+        }
+
+        // Act
+        sheet.__getitem__("A1").set_number_value(5.0);
+        sheet
+            .__getitem__("B1")
+            .set_value("new".to_string())
+            .unwrap();
+
+        // Assert
+        let xml = sheet.get_xml();
+        let xml = xml.lock().unwrap();
+        let row = &xml.elements[0].find_element("sheetData").unwrap().children[0];
+        let a1 = &row.children[0];
+        let a1_children: Vec<&str> = a1.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(a1_children, vec!["v", "extLst"]);
+        assert!(a1.get_attribute("t").is_none());
+        let b1 = &row.children[1];
+        let b1_children: Vec<&str> = b1.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(b1_children, vec!["v"]);
+        assert!(b1.get_attribute("cm").is_none());
+        assert_eq!(b1.get_attribute("t").map(String::as_str), Some("s"));
+    }
 }
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod tests {
+    use crate::book::Book;
+
+    #[test]
+    fn test_row_dimensions() {
+        // 観点: 行の高さ・非表示・アウトライン・スタイルを設定して取得でき、既定の高さが得られるか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        let rows = sheet.row_dimensions();
+
+        // Act
+        let row = rows.__getitem__(3).unwrap();
+        row.set_height(Some(24.5));
+        row.set_hidden(true);
+        row.set_outline_level(2).unwrap();
+        row.set_style(Some(1));
+
+        // Assert
+        let row = rows.__getitem__(3).unwrap();
+        assert_eq!(row.get_height(), Some(24.5));
+        assert!(row.get_hidden());
+        assert_eq!(row.get_outline_level(), 2);
+        assert_eq!(row.get_style(), Some(1));
+        assert_eq!(rows.__getitem__(4).unwrap().get_height(), None);
+        assert!(row.set_outline_level(8).is_err());
+        assert_eq!(rows.get_default_height(), 15.0);
+        rows.set_default_height(18.0);
+        assert_eq!(rows.get_default_height(), 18.0);
+    }
+
+    #[test]
+    fn test_row_dimension_cleared() {
+        // 観点: 行の設定をすべて解除すると、セルのない行要素が残らないか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        sheet.__getitem__("A1").set_value("1".to_string()).unwrap();
+        let rows = sheet.row_dimensions();
+        for index in [1, 2] {
+            let row = rows.__getitem__(index).unwrap();
+            row.set_height(Some(20.0));
+            row.set_outline_level(1).unwrap();
+        }
+
+        // Act
+        for index in [1, 2] {
+            let row = rows.__getitem__(index).unwrap();
+            row.set_height(None);
+            row.set_outline_level(0).unwrap();
+        }
+
+        // Assert
+        let xml = sheet.get_xml();
+        let xml = xml.lock().unwrap();
+        let rows = xml.elements[0].get_elements("sheetData>row");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].get_attribute("r").unwrap(), "1");
+    }
+
+    #[test]
+    fn test_column_dimensions_split_and_merge() {
+        // 観点: 範囲の列の設定を変更すると分割され、同じ設定に戻すと結合されるか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        let columns = sheet.column_dimensions();
+        for key in ["B", "C", "D"] {
+            columns.__getitem__(key).unwrap().set_width(Some(12.0));
+        }
+        let spans = || {
+            let xml = sheet.get_xml();
+            let xml = xml.lock().unwrap();
+            xml.elements[0]
+                .get_element("cols")
+                .children
+                .iter()
+                .map(|c| {
+                    format!(
+                        "{}-{}",
+                        c.get_attribute("min").unwrap(),
+                        c.get_attribute("max").unwrap()
+                    )
+                })
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(spans(), vec!["2-4"]);
+
+        // Act
+        columns.__getitem__("C").unwrap().set_hidden(true);
+
+        // Assert
+        assert_eq!(spans(), vec!["2-2", "3-3", "4-4"]);
+        assert!(columns.__getitem__("C").unwrap().get_hidden());
+        assert_eq!(columns.__getitem__("D").unwrap().get_width(), Some(12.0));
+        assert_eq!(columns.__getitem__("E").unwrap().get_width(), None);
+
+        columns.__getitem__("C").unwrap().set_hidden(false);
+        assert_eq!(spans(), vec!["2-4"]);
+        assert_eq!(columns.get_default_width(), 8.43);
+        columns.set_default_width(10.5);
+        assert_eq!(columns.get_default_width(), 10.5);
+        assert!(columns.__getitem__("1").is_err());
+    }
+}
@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod tests {
+    use crate::formula::{
+        Area, BinaryOp, Expr, FormulaError, TokenKind, col_to_string, parse, parse_r1c1,
+        parse_range, rename_sheet_in_formula, string_to_col, tokenize, translate,
+    };
+
+    #[test]
+    fn test_column_conversion() {
+        // 観点: 列番号とアルファベットを相互に変換できるか
+
+        // Assert
+        assert_eq!(col_to_string(1), "A");
+        assert_eq!(col_to_string(28), "AB");
+        assert_eq!(col_to_string(16384), "XFD");
+        assert_eq!(string_to_col("xfd"), Some(16384));
+        assert_eq!(string_to_col("XFE"), None);
+        assert_eq!(parse_range("C3:A1"), Some(((1, 1), (3, 3))));
+    }
+
+    #[test]
+    fn test_translate_relative_references() {
+        // 観点: 相対参照のみが移動し、固定参照・文字列・関数名はそのままか
+
+        // Act
+        let translated = translate("SUM(A1:$B$2)+Sheet2!C3*LOG10(D$4)&\"A1\"", 2, 1);
+
+        // Assert
+        assert_eq!(translated, "SUM(B3:$B$2)+Sheet2!D5*LOG10(E$4)&\"A1\"");
+    }
+
+    #[test]
+    fn test_translate_whole_rows_and_columns() {
+        // 観点: 行全体・列全体の参照と範囲外の参照を扱えるか
+
+        // Act & Assert
+        assert_eq!(translate("SUM(A:A)+SUM(1:$2)", 1, 1), "SUM(B:B)+SUM(2:$2)");
+        assert_eq!(translate("'My Sheet'!A2", -1, 0), "'My Sheet'!A1");
+        assert_eq!(translate("A1+1", -1, 0), "#REF!+1");
+    }
+
+    #[test]
+    fn test_tokenize() {
+        // 観点: 参照・関数・構造化参照・文字列が字句として分割され、連結すると元に戻るか
+
+        // Act
+        let formula = "SUM('My Sheet'!A1:B2,Sheet1:Sheet3!C1)&Table1[[#Headers],[Col]]&\"a\"\"b\"";
+        let tokens = tokenize(formula).unwrap();
+
+        // Assert
+        let kinds: Vec<TokenKind> = tokens.iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TokenKind::Function,
+                TokenKind::OpenParen,
+                TokenKind::Reference,
+                TokenKind::Comma,
+                TokenKind::Reference,
+                TokenKind::CloseParen,
+                TokenKind::Operator,
+                TokenKind::StructuredReference,
+                TokenKind::Operator,
+                TokenKind::Text,
+            ]
+        );
+        assert_eq!(
+            tokens.iter().map(|t| t.text.as_str()).collect::<String>(),
+            formula
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        // 観点: 演算子の優先順位, 3D参照, 配列定数, エラー値, 名前を構文木に変換できるか
+
+        // Act
+        let expr = parse("=1+2*3^2").unwrap();
+        let Expr::Binary { op, right, .. } = &expr else {
+            panic!("unexpected expr: {expr:?}");
+        };
+
+        // Assert
+        assert_eq!(*op, BinaryOp::Add);
+        assert!(matches!(
+            **right,
+            Expr::Binary {
+                op: BinaryOp::Mul,
+                ..
+            }
+        ));
+        let Expr::Reference(reference) = parse("'Jan 1:Jan 3'!$A$1:B2").unwrap() else {
+            panic!("not a reference");
+        };
+        assert_eq!(reference.sheet.as_deref(), Some("Jan 1"));
+        assert_eq!(reference.last_sheet.as_deref(), Some("Jan 3"));
+        assert!(matches!(reference.area, Area::Range(..)));
+        assert_eq!(
+            parse("IF(ISNA(x),{1,-2;\"a\",TRUE},#N/A)")
+                .unwrap()
+                .to_string(),
+            "IF(ISNA(x),{1,-2;\"a\",TRUE},#N/A)"
+        );
+        assert!(matches!(
+            parse("Sheet2!Rate").unwrap(),
+            Expr::Name { sheet: Some(_), .. }
+        ));
+        assert_eq!(
+            parse_r1c1("SUM(R[-1]C:R1C1)").unwrap().to_string(),
+            "SUM(R[-1]C:R1C1)"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_formula() {
+        // 観点: 構文が不正な数式をエラーにするか
+
+        // Assert
+        assert_eq!(parse("SUM(A1"), Err(FormulaError::UnexpectedEnd));
+        assert!(matches!(
+            parse("\"abc"),
+            Err(FormulaError::UnterminatedString(0))
+        ));
+        assert_eq!(parse("{1,2;3}"), Err(FormulaError::InvalidArray));
+        assert!(parse("1+*2").is_err());
+    }
+
+    #[test]
+    fn test_rename_sheet_in_formula() {
+        // 観点: 対象のシートへの参照のみ書き換え、必要に応じて引用符を付け外しするか
+
+        // Act & Assert
+        assert_eq!(
+            rename_sheet_in_formula("Data!A1+'data'!B2+Other!C3+\"Data!A1\"", "Data", "My Data"),
+            "'My Data'!A1+'My Data'!B2+Other!C3+\"Data!A1\""
+        );
+        assert_eq!(
+            rename_sheet_in_formula("SUM('Old Name'!A1:B2)+'[1]Old Name'!A1", "Old Name", "New"),
+            "SUM(New!A1:B2)+'[1]Old Name'!A1"
+        );
+        assert_eq!(
+            rename_sheet_in_formula("SUM(Jan:Mar!A1)", "Mar", "Q1 End"),
+            "SUM('Jan:Q1 End'!A1)"
+        );
+    }
+}
@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests {
+    use crate::book::Book;
+    use crate::name::{DefinedName, NameError};
+
+    fn defined_name(name: &str, value: &str, local_sheet_id: Option<usize>) -> DefinedName {
+        DefinedName {
+            name: name.to_string(),
+            value: value.to_string(),
+            local_sheet_id,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_defined_names() {
+        // 観点: 名前の追加・取得・削除ができ、範囲・非表示・コメントが保持され、不正な名前や重複を拒否するか
+        let mut book = Book::new("");
+        book.create_sheet("Sheet1".to_string(), 0);
+        let names = book.defined_names();
+
+        // Act
+        names
+            .add(DefinedName {
+                hidden: true,
+                comment: Some("税率".to_string()),
+                ..defined_name("Rate", "Sheet1!$B$2", None)
+            })
+            .unwrap();
+        names
+            .add(defined_name("Rate", "Sheet1!$C$2", Some(0)))
+            .unwrap();
+
+        // Assert
+        assert_eq!(names.__len__(), 2);
+        let rate = names.__getitem__("rate").unwrap();
+        assert_eq!(rate.value, "Sheet1!$B$2");
+        assert!(rate.hidden);
+        assert_eq!(rate.comment.as_deref(), Some("税率"));
+        assert_eq!(names.get("Rate", Some(0)).unwrap().value, "Sheet1!$C$2");
+        assert_eq!(
+            names.add(defined_name("RATE", "1", None)),
+            Err(NameError::DuplicateName("RATE".to_string()))
+        );
+        assert_eq!(
+            names.add(defined_name("A1", "1", None)),
+            Err(NameError::InvalidName("A1".to_string()))
+        );
+        assert_eq!(
+            names.add(defined_name("Other", "1", Some(3))),
+            Err(NameError::InvalidSheetIndex(3))
+        );
+
+        names.remove("Rate", None).unwrap();
+        assert!(!names.__contains__("Rate"));
+        assert_eq!(names.__len__(), 1);
+        assert_eq!(
+            names.remove("Rate", None),
+            Err(NameError::NotFound("Rate".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_resolve_name() {
+        // 観点: 名前の参照先のセルを取得でき、シート範囲の名前が優先されるか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("My Sheet".to_string(), 0);
+        sheet.__getitem__("A1").set_value("10".to_string()).unwrap();
+        sheet.__getitem__("B1").set_value("20".to_string()).unwrap();
+        sheet.__getitem__("C1").set_value("30".to_string()).unwrap();
+        let names = book.defined_names();
+        names
+            .add(defined_name("Input", "'My Sheet'!$A$1:$B$1", None))
+            .unwrap();
+        names
+            .add(defined_name("Input", "'My Sheet'!$C$1", Some(0)))
+            .unwrap();
+        names.add(defined_name("Const", "0.1", None)).unwrap();
+
+        // Act
+        let cells = book.resolve_name("Input", None).unwrap();
+        let local = book.resolve_name("Input", Some("My Sheet")).unwrap();
+
+        // Assert
+        let values: Vec<String> = cells.iter().filter_map(|c| c.value()).collect();
+        assert_eq!(values, vec!["10", "20"]);
+        assert_eq!(local.len(), 1);
+        assert_eq!(local[0].value().as_deref(), Some("30"));
+        assert_eq!(
+            book.resolve_name("Const", None).err(),
+            Some(NameError::InvalidReference("0.1".to_string()))
+        );
+        assert_eq!(
+            book.resolve_name("Missing", None).err(),
+            Some(NameError::NotFound("Missing".to_string()))
+        );
+    }
+}
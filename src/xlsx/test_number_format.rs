@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use crate::number_format::{builtin_format_code, format_number, general};
+
+    #[test]
+    fn test_format_number() {
+        // 観点: 桁区切り・小数・パーセント・指数・千単位の省略・リテラルを書式どおりに表示するか
+        let cases: [(f64, &str, &str); 12] = [
+            (1234567.891, "#,##0.00", "1,234,567.89"),
+            (0.0, "#,##0", "0"),
+            (0.125, "0.0%", "12.5%"),
+            (12345.0, "0.00E+00", "1.23E+04"),
+            (12345.0, "##0.0E+0", "12.3E+3"),
+            (1234567.0, "#,##0,\"千円\"", "1,235千円"),
+            (3.5, "0.0#", "3.5"),
+            (3.0, "0.??", "3.  "),
+            (42.0, "\"No.\"000", "No.042"),
+            (1500.0, "[$¥-411]#,##0", "¥1,500"),
+            (1234.0, "00000", "01234"),
+            (0.5, "# ?/?", " 1/2"),
+        ];
+
+        for (value, code, expected) in cases {
+            // Act
+            let text = format_number(value, code);
+
+            // Assert
+            assert_eq!(text, expected, "{value} {code}");
+        }
+    }
+
+    #[test]
+    fn test_format_number_sections() {
+        // 観点: 正・負・ゼロの区分を使い分け、負の区分では符号を付けないか
+        let code = "#,##0;[Red](#,##0);\"-\"";
+
+        // Act & Assert
+        assert_eq!(format_number(1200.0, code), "1,200");
+        assert_eq!(format_number(-1200.0, code), "(1,200)");
+        assert_eq!(format_number(0.0, code), "-");
+        assert_eq!(format_number(-5.0, "0.0"), "-5.0");
+        assert_eq!(format_number(-5.0, "0;0"), "5");
+        assert_eq!(format_number(1.5, "General"), "1.5");
+    }
+
+    #[test]
+    fn test_format_date() {
+        // 観点: 日付と時刻の要素、月と分の区別、午前と午後、経過時間、日本語の曜日を表示するか
+        let cases: [(f64, &str, &str); 9] = [
+            (45292.0, "yyyy/m/d", "2024/1/1"),
+            (45292.0, "d-mmm-yy", "1-Jan-24"),
+            (45292.0, "dddd, mmmm dd", "Monday, January 01"),
+            (45292.0, "yyyy\"年\"m\"月\"d\"日\"(aaa)", "2024年1月1日(月)"),
+            (45292.75, "h:mm AM/PM", "6:00 PM"),
+            (45292.5104166667, "hh:mm:ss", "12:15:00"),
+            (1.5, "[h]:mm", "36:00"),
+            (0.000_011_574_074, "mm:ss.0", "00:01.0"),
+            (60.0, "yyyy-mm-dd", "1900-02-28"),
+        ];
+
+        for (value, code, expected) in cases {
+            // Act
+            let text = format_number(value, code);
+
+            // Assert
+            assert_eq!(text, expected, "{value} {code}");
+        }
+    }
+
+    #[test]
+    fn test_general_and_builtin() {
+        // 観点: 標準の表示形式が11文字に収まり、組み込みの表示形式のIDを書式文字列にできるか
+        // Act & Assert
+        assert_eq!(general(12345.0), "12345");
+        assert_eq!(general(0.1 + 0.2), "0.3");
+        assert_eq!(general(123456.7891234), "123456.7891");
+        assert_eq!(general(123456789012.0), "1.23457E+11");
+        assert_eq!(builtin_format_code(10), Some("0.00%"));
+        assert_eq!(builtin_format_code(14), Some("yyyy/m/d"));
+        assert_eq!(builtin_format_code(164), None);
+    }
+}
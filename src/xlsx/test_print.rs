@@ -0,0 +1,192 @@
+#[cfg(test)]
+mod tests {
+    use crate::book::Book;
+    use crate::print::{HeaderFooterPart, PrintError};
+
+    #[test]
+    fn test_page_setup_and_margins() {
+        // 観点: ページ設定・余白・印刷オプションを設定でき、必須属性と fitToPage が補われるか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        let setup = sheet.page_setup();
+        let margins = sheet.page_margins();
+        let options = sheet.print_options();
+
+        // Act
+        setup
+            .set_orientation(Some("landscape".to_string()))
+            .unwrap();
+        setup.set_paper_size(Some(9));
+        setup.set_fit_to_width(Some(1));
+        setup.set_fit_to_height(Some(0));
+        margins.set_left(0.5).unwrap();
+        options.set_grid_lines(true);
+        options.set_horizontal_centered(true);
+
+        // Assert
+        assert_eq!(setup.get_orientation().as_deref(), Some("landscape"));
+        assert_eq!(setup.get_paper_size(), Some(9));
+        assert_eq!(setup.get_fit_to_width(), Some(1));
+        assert_eq!(setup.get_fit_to_height(), Some(0));
+        assert!(setup.get_fit_to_page());
+        assert_eq!(margins.get_left(), 0.5);
+        assert_eq!(margins.get_top(), 0.75);
+        assert!(options.get_grid_lines());
+        assert!(options.get_horizontal_centered());
+        assert!(!options.get_vertical_centered());
+        {
+            let xml = sheet.get_xml();
+            let xml = xml.lock().unwrap();
+            let worksheet = &xml.elements[0];
+            assert_eq!(worksheet.get_element("pageMargins").attributes.len(), 6);
+            let names: Vec<&str> = worksheet.children.iter().map(|c| c.name.as_str()).collect();
+            let position = |name: &str| names.iter().position(|n| *n == name).unwrap();
+            assert!(position("printOptions") < position("pageMargins"));
+            assert!(position("pageMargins") < position("pageSetup"));
+        }
+        assert_eq!(
+            setup.set_orientation(Some("sideways".to_string())),
+            Err(PrintError::InvalidOrientation("sideways".to_string()))
+        );
+        assert_eq!(
+            setup.set_scale(Some(500)),
+            Err(PrintError::InvalidScale(500))
+        );
+    }
+
+    #[test]
+    fn test_header_footer() {
+        // 観点: 左・中央・右の部分とフォント・ページ番号の制御コードを組み立て、解析して戻せるか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        let header_footer = sheet.header_footer();
+        let header = HeaderFooterPart {
+            left: Some("R&D".to_string()),
+            right: Some("Page {page} of {pages}".to_string()),
+            font: Some("Arial,Bold".to_string()),
+            size: Some(12),
+            ..Default::default()
+        };
+
+        // Act
+        header_footer.set_odd_header(Some(header.clone()));
+        header_footer.set_first_footer(Some(HeaderFooterPart {
+            center: Some("{date}".to_string()),
+            ..Default::default()
+        }));
+
+        // Assert
+        assert_eq!(
+            header.build(),
+            "&L&\"Arial,Bold\"&12R&&D&R&\"Arial,Bold\"&12Page &P of &N"
+        );
+        assert_eq!(header_footer.get_odd_header(), Some(header));
+        assert_eq!(
+            header_footer.get_first_footer().unwrap().center.as_deref(),
+            Some("{date}")
+        );
+        let xml = sheet.get_xml();
+        let xml = xml.lock().unwrap();
+        let element = xml.elements[0].get_element("headerFooter");
+        assert_eq!(element.get_attribute("differentFirst").unwrap(), "1");
+        assert!(element.get_attribute("differentOddEven").is_none());
+        assert_eq!(element.get_element("firstFooter").get_text(), "&C&D");
+    }
+
+    #[test]
+    fn test_header_footer_round_trip() {
+        // 観点: 書式の切り替えコードとサイズの直後の数字で始まるテキストを、組み立てと解析で往復できるか
+        let dated = HeaderFooterPart {
+            center: Some("2024 Report".to_string()),
+            size: Some(12),
+            ..Default::default()
+        };
+        let texts = [
+            "&L&BBold&B plain&R&I&UNote",
+            "&C&\"Arial\"&10&E&S&X&Y&O&H{&G}",
+            "&C&12 2024 Report",
+            "&R&KFF00002&&3",
+        ];
+
+        // Act
+        let built = dated.build();
+        let styled = HeaderFooterPart::parse(texts[0]);
+
+        // Assert
+        assert_eq!(built, "&C&12 2024 Report");
+        assert_eq!(HeaderFooterPart::parse(&built), dated);
+        assert_eq!(styled.left.as_deref(), Some("{bold}Bold{bold} plain"));
+        assert_eq!(styled.right.as_deref(), Some("{italic}{underline}Note"));
+        for text in texts {
+            assert_eq!(HeaderFooterPart::parse(text).build(), text);
+        }
+    }
+
+    #[test]
+    fn test_print_area_and_titles() {
+        // 観点: 印刷範囲・印刷タイトルがシート単位の定義名として保存され、シート削除で localSheetId が詰められるか
+        let mut book = Book::new("");
+        let first = book.create_sheet("First".to_string(), 0);
+        let sheet = book.create_sheet("My Sheet".to_string(), 1);
+
+        // Act
+        first.set_print_area(Some("A1:B2")).unwrap();
+        sheet.set_print_area(Some("A1:D20,F1:G5")).unwrap();
+        sheet.set_print_title_rows(Some("1:2")).unwrap();
+        sheet.set_print_title_cols(Some("A:B")).unwrap();
+
+        // Assert
+        let parts = book.parts();
+        assert_eq!(
+            parts.defined_name("_xlnm.Print_Area", Some(1)).as_deref(),
+            Some("'My Sheet'!$A$1:$D$20,'My Sheet'!$F$1:$G$5")
+        );
+        assert_eq!(
+            parts.defined_name("_xlnm.Print_Titles", Some(1)).as_deref(),
+            Some("'My Sheet'!$A:$B,'My Sheet'!$1:$2")
+        );
+        assert_eq!(sheet.get_print_area().as_deref(), Some("A1:D20,F1:G5"));
+        assert_eq!(sheet.get_print_title_rows().as_deref(), Some("1:2"));
+        assert_eq!(sheet.get_print_title_cols().as_deref(), Some("A:B"));
+        assert_eq!(
+            sheet.set_print_area(Some("A1:ZZZZ1")),
+            Err(PrintError::InvalidPrintArea("A1:ZZZZ1".to_string()))
+        );
+
+        book.remove(&first).unwrap();
+        assert!(parts.defined_name("_xlnm.Print_Area", Some(1)).is_none());
+        assert_eq!(sheet.get_print_area().as_deref(), Some("A1:D20,F1:G5"));
+        sheet.set_print_title_rows(None).unwrap();
+        assert!(sheet.get_print_title_rows().is_none());
+        assert_eq!(sheet.get_print_title_cols().as_deref(), Some("A:B"));
+    }
+
+    #[test]
+    fn test_add_page_break() {
+        // 観点: 改ページが位置順に重複なく追加され、件数の属性が更新されるか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+
+        // Act
+        sheet.add_page_break(Some(20), None).unwrap();
+        sheet.add_page_break(Some(10), Some(3)).unwrap();
+        sheet.add_page_break(Some(20), None).unwrap();
+
+        // Assert
+        assert_eq!(sheet.row_breaks(), vec![10, 20]);
+        assert_eq!(sheet.col_breaks(), vec![3]);
+        assert_eq!(
+            sheet.add_page_break(None, None),
+            Err(PrintError::InvalidPageBreak)
+        );
+        let xml = sheet.get_xml();
+        let xml = xml.lock().unwrap();
+        let row_breaks = xml.elements[0].get_element("rowBreaks");
+        assert_eq!(row_breaks.get_attribute("count").unwrap(), "2");
+        assert_eq!(row_breaks.get_attribute("manualBreakCount").unwrap(), "2");
+        assert_eq!(
+            row_breaks.children[0].get_attribute("max").unwrap(),
+            "16383"
+        );
+    }
+}
@@ -0,0 +1,239 @@
+#[cfg(test)]
+mod tests {
+    use crate::book::Book;
+    use crate::cell::{CellError, CellValue};
+    use crate::range::{KeyShape, RangeError, RangeLine, SheetIndex, SheetItem, parse_key};
+
+    fn text(value: &str) -> Option<CellValue> {
+        Some(CellValue::Text(value.to_string()))
+    }
+
+    #[test]
+    fn test_parse_key() {
+        // 観点: セル・範囲・列・行のキーを openpyxl と同じ形として解析し、列全体と行全体は値のある範囲までとするか
+
+        // Act & Assert
+        assert_eq!(
+            parse_key("B2", (10, 5)),
+            Some((KeyShape::Cell, ((2, 2), (2, 2))))
+        );
+        assert_eq!(
+            parse_key("C3:A1", (10, 5)),
+            Some((KeyShape::Area, ((1, 1), (3, 3))))
+        );
+        assert_eq!(
+            parse_key("B", (10, 5)),
+            Some((KeyShape::Column, ((1, 2), (10, 2))))
+        );
+        assert_eq!(
+            parse_key("A:C", (0, 0)),
+            Some((KeyShape::Columns, ((1, 1), (1, 3))))
+        );
+        assert_eq!(
+            parse_key("5", (10, 5)),
+            Some((KeyShape::Row, ((5, 1), (5, 5))))
+        );
+        assert_eq!(
+            parse_key("$5:$7", (10, 5)),
+            Some((KeyShape::Rows, ((5, 1), (7, 5))))
+        );
+        assert_eq!(parse_key("0", (10, 5)), None);
+        assert_eq!(parse_key("A1:", (10, 5)), None);
+    }
+
+    #[test]
+    fn test_range_values() {
+        // 観点: 範囲の値を一括で読み書きでき、None で値を削除し、大きすぎる値や不正な数式を書き込まないか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Data".to_string(), 0);
+        let range = sheet.range("A1:C2").unwrap();
+
+        // Act
+        range
+            .set_values(vec![
+                vec![text("Name"), text("1.5"), text("=B1*2")],
+                vec![
+                    text("true"),
+                    None,
+                    Some(CellValue::Error(CellError::from_code("#N/A").unwrap())),
+                ],
+            ])
+            .unwrap();
+
+        // Assert
+        assert_eq!(sheet.__getitem__("A1").value().as_deref(), Some("Name"));
+        assert_eq!(sheet.__getitem__("C1").formula().as_deref(), Some("B1*2"));
+        assert_eq!(
+            range.values(),
+            vec![
+                vec![text("Name"), text("1.5"), None],
+                vec![
+                    text("1"),
+                    None,
+                    Some(CellValue::Error(CellError::from_code("#N/A").unwrap()))
+                ],
+            ]
+        );
+        range.set_values(vec![vec![None, text("2")]]).unwrap();
+        assert_eq!(range.values()[0][..2], [None, text("2")]);
+        assert_eq!(
+            range.set_values(vec![vec![None; 4]]),
+            Err(RangeError::ShapeMismatch("A1:C2".to_string(), 1, 4))
+        );
+        assert_eq!(
+            range.set_values(vec![vec![text("x"), text("=SUM(")]]),
+            Err(RangeError::InvalidFormula("=SUM(".to_string()))
+        );
+        assert_eq!(range.values()[0][1], text("2"));
+    }
+
+    #[test]
+    fn test_range_values_shared_strings_and_rows() {
+        // 観点: 範囲への文字列の書き込みが共有文字列の対応表を使って重複せず、範囲外の行の値を読み込まないか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Data".to_string(), 0);
+        sheet.append(vec!["before".to_string()]);
+        let range = sheet.range("A2:B3").unwrap();
+
+        // Act
+        range
+            .set_values(vec![vec![text("before"), text("new")], vec![text("new")]])
+            .unwrap();
+        sheet
+            .__getitem__("A5")
+            .set_value("new".to_string())
+            .unwrap();
+
+        // Assert
+        let shared_strings = book.shared_strings.lock().unwrap().clone();
+        assert_eq!(shared_strings.elements[0].children.len(), 2);
+        assert_eq!(
+            range.values(),
+            vec![vec![text("before"), text("new")], vec![text("new"), None]]
+        );
+        assert_eq!(
+            sheet.range("A4:A5").unwrap().values(),
+            vec![vec![None], vec![text("new")]]
+        );
+    }
+
+    #[test]
+    fn test_sheet_getitem() {
+        // 観点: sheet[...] がキーの形に応じて単一セル・1列・1行・行ごとのセルを返すか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Data".to_string(), 0);
+        sheet
+            .range("A1:B3")
+            .unwrap()
+            .set_values(vec![
+                vec![text("a1"), text("b1")],
+                vec![text("a2"), text("b2")],
+                vec![text("a3"), text("b3")],
+            ])
+            .unwrap();
+
+        // Act
+        let column = sheet
+            .get_item(SheetIndex::Address("B".to_string()))
+            .unwrap();
+        let row = sheet.get_item(SheetIndex::Row(2)).unwrap();
+        let area = sheet
+            .get_item(SheetIndex::Address("A2:B3".to_string()))
+            .unwrap();
+
+        // Assert
+        let SheetItem::Cells(column) = column else {
+            panic!("column should be a list of cells");
+        };
+        let values: Vec<Option<String>> = column.iter().map(|c| c.value()).collect();
+        assert_eq!(
+            values,
+            vec![
+                Some("b1".to_string()),
+                Some("b2".to_string()),
+                Some("b3".to_string())
+            ]
+        );
+        let SheetItem::Cells(row) = row else {
+            panic!("row should be a list of cells");
+        };
+        assert_eq!(row.len(), 2);
+        assert_eq!(row[1].value().as_deref(), Some("b2"));
+        let SheetItem::Grid(area) = area else {
+            panic!("area should be rows of cells");
+        };
+        assert_eq!(area.len(), 2);
+        assert_eq!(area[1][0].value().as_deref(), Some("a3"));
+        assert!(matches!(
+            sheet.get_item(SheetIndex::Address("A1".to_string())),
+            Ok(SheetItem::Cell(_))
+        ));
+        assert!(matches!(
+            sheet.get_item(SheetIndex::Address("1A".to_string())),
+            Err(RangeError::InvalidRange(_))
+        ));
+    }
+
+    #[test]
+    fn test_iter_rows_and_cols() {
+        // 観点: 値のないセルの位置を None で埋めて列がずれず、範囲の指定と列ごとの走査ができるか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Data".to_string(), 0);
+        sheet
+            .range("A1:C3")
+            .unwrap()
+            .set_values(vec![
+                vec![text("a1"), None, text("c1")],
+                vec![None, None, None],
+                vec![None, text("b3"), None],
+            ])
+            .unwrap();
+        let values = |line: RangeLine| match line {
+            RangeLine::Values(values) => values,
+            RangeLine::Cells(_) => panic!("line should be a list of values"),
+        };
+
+        // Act
+        let rows: Vec<Vec<Option<CellValue>>> = sheet
+            .iter_rows(None, None, None, None, true)
+            .map(values)
+            .collect();
+        let cols: Vec<Vec<Option<CellValue>>> = sheet
+            .iter_cols(Some(2), None, Some(2), Some(3), true)
+            .map(values)
+            .collect();
+
+        // Assert
+        assert_eq!(
+            rows,
+            vec![
+                vec![text("a1"), None, text("c1")],
+                vec![None, None, None],
+                vec![None, text("b3"), None],
+            ]
+        );
+        assert_eq!(cols, vec![vec![None, text("b3")], vec![None, None]]);
+        assert_eq!(
+            sheet
+                .iter_cols(None, None, None, None, true)
+                .map(values)
+                .collect::<Vec<_>>(),
+            vec![
+                vec![text("a1"), None, None],
+                vec![None, None, text("b3")],
+                vec![text("c1"), None, None],
+            ]
+        );
+        let Some(RangeLine::Cells(cells)) =
+            sheet.iter_rows(Some(3), None, None, None, false).next()
+        else {
+            panic!("row should be a list of cells");
+        };
+        assert_eq!(cells.len(), 3);
+        assert_eq!(cells[1].value().as_deref(), Some("b3"));
+        assert_eq!(sheet.iter_rows(Some(4), None, None, None, true).count(), 0);
+        let mut empty = Book::new("");
+        let empty = empty.create_sheet("Empty".to_string(), 0);
+        assert_eq!(empty.iter_rows(None, None, None, None, true).count(), 0);
+    }
+}
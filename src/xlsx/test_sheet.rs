@@ -1,6 +1,15 @@
 #[cfg(test)]
 mod tests {
-    use crate::book::Book;
+    use crate::book::{Book, BookError, SheetKey};
+    use crate::cell::{Cell, CellValue};
+    use crate::range::RangeLine;
+    use crate::sheet::{Sheet, SheetError};
+    use crate::xml::{Xml, XmlElement};
+    use std::fs;
+
+    fn text(value: &str) -> Option<CellValue> {
+        Some(CellValue::Text(value.to_string()))
+    }
 
     #[test]
     fn test_getitem() {
@@ -39,11 +48,15 @@ mod tests {
         sheet.append(new_row);
 
         // Assert
-        let sheet_row_len = sheet.iter_rows().len();
-        let binding = sheet.iter_rows();
-        let appended_row = binding.get(sheet_row_len - 1).unwrap();
-        assert_eq!(appended_row[0], "foo".to_string());
-        assert_eq!(appended_row[1], "bar".to_string());
+        let appended_row = sheet
+            .iter_rows(None, None, None, None, false)
+            .last()
+            .unwrap();
+        let RangeLine::Cells(appended_row) = appended_row else {
+            panic!("row should be a list of cells");
+        };
+        assert_eq!(appended_row[0].value().as_deref(), Some("foo"));
+        assert_eq!(appended_row[1].value().as_deref(), Some("bar"));
     }
 
     #[test]
@@ -51,12 +64,561 @@ mod tests {
         // 観点: 行をイテレートできるか
         let book = Book::new("data/sample.xlsx");
         let sheet = book.__getitem__("シート1".to_string());
-        let rows = sheet.iter_rows();
-        let first_row = rows.first().unwrap();
-        assert_eq!(first_row[0], "1.0".to_string());
-        assert_eq!(first_row[1], "3.0".to_string());
-        let second_row = rows.get(1).unwrap();
-        assert_eq!(second_row[0], "2.0".to_string());
-        assert_eq!(second_row[1], "4.0".to_string());
+
+        // Act
+        let rows: Vec<RangeLine> = sheet.iter_rows(None, None, None, None, true).collect();
+
+        // Assert
+        let RangeLine::Values(first_row) = &rows[0] else {
+            panic!("row should be a list of values");
+        };
+        assert_eq!(first_row[..2], [text("1.0"), text("3.0")]);
+        let RangeLine::Values(second_row) = &rows[1] else {
+            panic!("row should be a list of values");
+        };
+        assert_eq!(second_row[..2], [text("2.0"), text("4.0")]);
+    }
+
+    #[test]
+    fn test_dimensions() {
+        // 観点: 値のあるセルの範囲を返し、行が順不同でも最大の行番号の次の行に追加するか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Data".to_string(), 0);
+        assert_eq!(sheet.dimensions(), "A1:A1");
+        {
+            let xml = sheet.get_xml();
+            let mut xml = xml.lock().unwrap();
+            let sheet_data = xml.elements[0].get_element_mut("sheetData");
+            *sheet_data = Xml::new(
+                r#"<sheetData><row r="5"><c r="C5"><v>1</v></c></row><row r="2"><c r="F2"><v>2</v></c></row></sheetData>"#,
+            )
+            .unwrap()
+            .elements[0]
+            .clone();
+        }
+
+        // Act
+        sheet.append(vec!["foo".to_string()]);
+
+        // Assert
+        assert_eq!(sheet.__getitem__("A6").value().as_deref(), Some("foo"));
+        assert_eq!((sheet.min_row(), sheet.max_row()), (2, 6));
+        assert_eq!((sheet.min_column(), sheet.max_column()), (1, 6));
+        assert_eq!(sheet.dimensions(), "A2:F6");
+    }
+
+    #[test]
+    fn test_dimension_saved() {
+        // 観点: 保存時に <dimension ref> が値のあるセルの範囲に更新されるか
+        let test_path = "data/test_sheet_dimension_saved.xlsx";
+        fs::copy("data/sample.xlsx", test_path).unwrap();
+        let book = Book::new(test_path);
+        let sheet = book.__getitem__("シート1".to_string());
+        let copy_path = format!("{test_path}.copy.xlsx");
+        sheet.cell(40, 8).set_value("x".to_string()).unwrap();
+        let expected = format!("{}:H40", sheet.dimensions().split(':').next().unwrap());
+
+        // Act
+        book.copy(&copy_path);
+
+        // Assert
+        let book_reloaded = Book::new(&copy_path);
+        let sheet_reloaded = book_reloaded.__getitem__("シート1".to_string());
+        let xml = sheet_reloaded.get_xml();
+        let xml = xml.lock().unwrap();
+        let dimension = xml.elements[0].get_element("dimension");
+        assert_eq!(dimension.get_attribute("ref"), Some(&expected));
+
+        let _ = fs::remove_file(test_path);
+        let _ = fs::remove_file(copy_path);
+    }
+
+    #[test]
+    fn test_expand_shared_formulas() {
+        // 観点: 共有数式の従属セルがセルごとの数式に展開されるか
+        let mut xml = Xml::new(
+            r#"<worksheet><sheetData>
+<row r="1"><c r="B1"><f t="shared" ref="B1:B3" si="0">A1*2</f><v>2</v></c></row>
+<row r="2"><c r="B2"><f t="shared" si="0"/><v>4</v></c></row>
+<row r="3"><c r="B3"><f t="shared" si="0"/><v>6</v></c></row>
+</sheetData></worksheet>"#,
+        )
+        .unwrap();
+
+        // Act
+        Sheet::expand_shared_formulas(&mut xml.elements[0]);
+
+        // Assert
+        let rows = xml.elements[0].get_elements("sheetData>row");
+        let formulas: Vec<String> = rows
+            .iter()
+            .map(|r| r.get_element("c>f").get_text().to_string())
+            .collect();
+        assert_eq!(formulas, vec!["A1*2", "A2*2", "A3*2"]);
+        assert!(rows[1].get_element("c>f").get_attribute("t").is_none());
+        assert_eq!(rows[2].get_element("c>v").get_text(), "6");
+    }
+
+    #[test]
+    fn test_set_array_formula() {
+        // 観点: 配列数式と動的配列数式を書き込み、保存後も読み取れるか
+        let test_path = "data/test_sheet_array_formula.xlsx";
+        fs::copy("data/sample.xlsx", test_path).unwrap();
+        let book = Book::new(test_path);
+        let sheet = book.__getitem__("シート1".to_string());
+        let copy_path = format!("{test_path}.copy.xlsx");
+
+        // Act
+        sheet.set_array_formula("C1:C2", "=A1:A2*2", false).unwrap();
+        sheet
+            .set_array_formula("D1:D2", "_xlfn._xlws.SORT(A1:A2)", true)
+            .unwrap();
+        assert!(sheet.set_array_formula("foo", "1", false).is_err());
+        book.copy(&copy_path);
+
+        // Assert
+        let book_reloaded = Book::new(&copy_path);
+        let sheet_reloaded = book_reloaded.__getitem__("シート1".to_string());
+        assert_eq!(
+            sheet_reloaded.__getitem__("C1").formula().unwrap(),
+            "A1:A2*2"
+        );
+        let xml = sheet_reloaded.get_xml();
+        let xml = xml.lock().unwrap();
+        let cells = xml.elements[0].get_elements("sheetData>row>c");
+        let d1 = cells
+            .iter()
+            .find(|c| c.get_attribute("r").unwrap() == "D1")
+            .unwrap();
+        assert_eq!(d1.get_attribute("cm").unwrap(), "1");
+        assert_eq!(d1.get_element("f").get_attribute("ref").unwrap(), "D1:D2");
+
+        let metadata = book_reloaded.metadata.lock().unwrap();
+        let cell_metadata = metadata.elements[0].get_element("cellMetadata");
+        assert_eq!(cell_metadata.children.len(), 1);
+        let content_types = &book_reloaded.content_types.elements[0];
+        assert!(content_types.children.iter().any(|o| {
+            o.get_attribute("PartName").map(String::as_str) == Some("/xl/metadata.xml")
+        }));
+        assert!(
+            book_reloaded
+                .get_relationships()
+                .iter()
+                .any(|r| { r.get_attribute("Target").map(String::as_str) == Some("metadata.xml") })
+        );
+
+        let _ = fs::remove_file(test_path);
+        let _ = fs::remove_file(copy_path);
+    }
+
+    #[test]
+    fn test_insert_rows() {
+        // 観点: 行の挿入でセル、同一シート・他シートの数式、結合セル、定義された名前が移動するか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        let other = book.create_sheet("Other".to_string(), 1);
+        sheet.__getitem__("A1").set_value("1".to_string()).unwrap();
+        sheet.__getitem__("A2").set_value("2".to_string()).unwrap();
+        sheet
+            .__getitem__("B1")
+            .set_value("=SUM(A1:A2)+$A$2".to_string())
+            .unwrap();
+        other
+            .__getitem__("A1")
+            .set_value("=Sheet1!A2*2".to_string())
+            .unwrap();
+        {
+            let xml = sheet.get_xml();
+            let mut xml = xml.lock().unwrap();
+            let mut merge_cell = XmlElement::new("mergeCell");
+            merge_cell
+                .attributes
+                .insert("ref".to_string(), "C2:D3".to_string());
+            let mut merge_cells = XmlElement::new("mergeCells");
+            merge_cells.children.push(merge_cell);
+            xml.elements[0].children.push(merge_cells);
+        }
+        {
+            let mut workbook = book.workbook.lock().unwrap();
+            let mut defined_name = XmlElement::new("definedName");
+            defined_name
+                .attributes
+                .insert("name".to_string(), "Total".to_string());
+            defined_name.text = Some("Sheet1!$A$2".to_string());
+            let mut defined_names = XmlElement::new("definedNames");
+            defined_names.children.push(defined_name);
+            workbook.elements[0].children.push(defined_names);
+        }
+
+        // Act
+        sheet.insert_rows(2, 3).unwrap();
+
+        // Assert
+        assert_eq!(sheet.__getitem__("A1").value().unwrap(), "1");
+        assert!(sheet.__getitem__("A2").value().is_none());
+        assert_eq!(sheet.__getitem__("A5").value().unwrap(), "2");
+        assert_eq!(
+            sheet.__getitem__("B1").formula().unwrap(),
+            "SUM(A1:A5)+$A$5"
+        );
+        assert_eq!(other.__getitem__("A1").formula().unwrap(), "Sheet1!A5*2");
+        let xml = sheet.get_xml();
+        let xml = xml.lock().unwrap();
+        let merge_cell = xml.elements[0].get_element("mergeCells>mergeCell");
+        assert_eq!(merge_cell.get_attribute("ref").unwrap(), "C5:D6");
+        let workbook = book.workbook.lock().unwrap();
+        let defined_name = workbook.elements[0].get_element("definedNames>definedName");
+        assert_eq!(defined_name.text.as_deref(), Some("Sheet1!$A$5"));
+    }
+
+    #[test]
+    fn test_delete_cols() {
+        // 観点: 列の削除で削除された参照が #REF! になり、範囲と入力規則の範囲が縮むか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        sheet.__getitem__("B1").set_value("1".to_string()).unwrap();
+        sheet.__getitem__("D1").set_value("2".to_string()).unwrap();
+        sheet
+            .__getitem__("E1")
+            .set_value("=B1+SUM(A1:D1)+D1".to_string())
+            .unwrap();
+        {
+            let xml = sheet.get_xml();
+            let mut xml = xml.lock().unwrap();
+            let mut validation = XmlElement::new("dataValidation");
+            validation
+                .attributes
+                .insert("sqref".to_string(), "B2 A3:D3".to_string());
+            let mut validations = XmlElement::new("dataValidations");
+            validations
+                .attributes
+                .insert("count".to_string(), "1".to_string());
+            validations.children.push(validation);
+            xml.elements[0].children.push(validations);
+        }
+
+        // Act
+        sheet.delete_cols(2, 1).unwrap();
+
+        // Assert
+        assert!(sheet.__getitem__("B1").formula().is_none());
+        assert_eq!(sheet.__getitem__("C1").value().unwrap(), "2");
+        assert_eq!(
+            sheet.__getitem__("D1").formula().unwrap(),
+            "#REF!+SUM(A1:C1)+C1"
+        );
+        let xml = sheet.get_xml();
+        let xml = xml.lock().unwrap();
+        let validation = xml.elements[0].get_element("dataValidations>dataValidation");
+        assert_eq!(validation.get_attribute("sqref").unwrap(), "A3:C3");
+    }
+
+    #[test]
+    fn test_delete_rows_cleanup() {
+        // 観点: 行の削除で範囲を失った配列数式・ハイパーリンクとそのリレーションシップ・1セルになった結合セルを除去し、テーブル全体の削除は拒否するか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        sheet.__getitem__("A5").set_value("id".to_string()).unwrap();
+        sheet
+            .__getitem__("B5")
+            .set_value("name".to_string())
+            .unwrap();
+        book.add_table("Sheet1".to_string(), "T".to_string(), "A5:B6".to_string());
+        {
+            let xml = sheet.get_xml();
+            let mut xml = xml.lock().unwrap();
+            let worksheet = &mut xml.elements[0];
+            let row = Cell::get_or_create_row(worksheet.get_element_mut("sheetData"), 1);
+            *row = Xml::new(
+                r#"<row r="1"><c r="E1"><f t="array" ref="E2:E3">1</f><v>1</v></c></row>"#,
+            )
+            .unwrap()
+            .elements[0]
+                .clone();
+            worksheet.children.extend(
+                Xml::new(
+                    r#"<x><mergeCells count="2"><mergeCell ref="A2:A4"/><mergeCell ref="C1:D4"/></mergeCells><hyperlinks><hyperlink ref="A2" r:id="rId9"/><hyperlink ref="A7" r:id="rId10"/></hyperlinks></x>"#,
+                )
+                .unwrap()
+                .elements[0]
+                .children
+                .clone(),
+            );
+        }
+        let rels_path = "xl/worksheets/_rels/sheet1.xml.rels";
+        {
+            let rels = book.sheet_rels.get(rels_path).unwrap();
+            let mut rels = rels.lock().unwrap();
+            for id in ["rId9", "rId10"] {
+                let mut relationship = XmlElement::new("Relationship");
+                relationship
+                    .attributes
+                    .insert("Id".to_string(), id.to_string());
+                rels.elements[0].children.push(relationship);
+            }
+        }
+
+        // Act
+        sheet.delete_rows(2, 2).unwrap();
+
+        // Assert
+        let xml = sheet.get_xml();
+        {
+            let xml = xml.lock().unwrap();
+            let worksheet = &xml.elements[0];
+            let e1 = worksheet.get_element("sheetData>row>c");
+            assert!(e1.find_element("f").is_none());
+            assert_eq!(e1.get_element("v").get_text(), "1");
+            let merges: Vec<&String> = worksheet
+                .get_elements("mergeCells>mergeCell")
+                .iter()
+                .filter_map(|m| m.get_attribute("ref"))
+                .collect();
+            assert_eq!(merges, vec!["C1:D2"]);
+            let links = worksheet.get_elements("hyperlinks>hyperlink");
+            assert_eq!(links.len(), 1);
+            assert_eq!(links[0].get_attribute("ref").unwrap(), "A5");
+        }
+        let rels = book.sheet_rels.get(rels_path).unwrap();
+        let ids: Vec<String> = rels.lock().unwrap().elements[0]
+            .children
+            .iter()
+            .filter_map(|r| r.get_attribute("Id").cloned())
+            .collect();
+        assert!(!ids.contains(&"rId9".to_string()));
+        assert!(ids.contains(&"rId10".to_string()));
+        let table = book.tables.get("xl/tables/table1.xml").unwrap();
+        assert_eq!(
+            table.lock().unwrap().elements[0]
+                .get_attribute("ref")
+                .unwrap(),
+            "A3:B4"
+        );
+        assert!(sheet.delete_rows(3, 2).is_err());
+        assert_eq!(sheet.__getitem__("A3").value().as_deref(), Some("id"));
+    }
+
+    #[test]
+    fn test_auto_fit_columns() {
+        // 観点: 全角文字を2文字分として列の幅を推定し、複数列の結合セルを除外して <cols> に書き込むか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        sheet.append(vec!["abc".to_string(), "日本語テキスト".to_string()]);
+        sheet.append(vec!["12345".to_string()]);
+        sheet.append(vec!["merged across columns".to_string()]);
+        {
+            let xml = sheet.get_xml();
+            let mut xml = xml.lock().unwrap();
+            let mut merge_cell = XmlElement::new("mergeCell");
+            merge_cell
+                .attributes
+                .insert("ref".to_string(), "A3:B3".to_string());
+            let mut merge_cells = XmlElement::new("mergeCells");
+            merge_cells.children.push(merge_cell);
+            xml.elements[0].children.push(merge_cells);
+        }
+
+        // Act
+        sheet.auto_fit_columns();
+
+        // Assert
+        let columns = sheet.column_dimensions();
+        let a = columns.__getitem__("A").unwrap();
+        let b = columns.__getitem__("B").unwrap();
+        assert_eq!(a.get_width(), Some(6.0));
+        assert_eq!(b.get_width(), Some(15.0));
+        assert!(b.get_best_fit());
+        assert_eq!(columns.__getitem__("C").unwrap().get_width(), None);
+    }
+
+    #[test]
+    fn test_auto_fit_columns_formatted() {
+        // 観点: 表示形式を適用したテキストで幅を推定し、折り返しのセルは最も長い単語の幅になるか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        sheet.append(vec![
+            String::new(),
+            String::new(),
+            "a longer sentence that wraps".to_string(),
+            String::new(),
+        ]);
+        sheet.__getitem__("A1").set_number_value(45292.0);
+        sheet.__getitem__("B1").set_number_value(0.125);
+        sheet.__getitem__("D1").set_number_value(1234567.0);
+        {
+            let mut styles = book.styles.lock().unwrap();
+            let root = &mut styles.elements[0];
+            root.children.insert(
+                0,
+                Xml::new(
+                    r##"<numFmts count="1"><numFmt numFmtId="164" formatCode="#,##0.0"/></numFmts>"##,
+                )
+                .unwrap()
+                .elements[0]
+                    .clone(),
+            );
+            let xfs = Xml::new(
+                r#"<x><xf numFmtId="14" fontId="0"/><xf numFmtId="10" fontId="0"/><xf numFmtId="0" fontId="0"><alignment wrapText="1"/></xf><xf numFmtId="164" fontId="0"/></x>"#,
+            )
+            .unwrap();
+            let cell_xfs = root.find_element_mut("cellXfs").unwrap();
+            cell_xfs.children.extend(xfs.elements[0].children.clone());
+            let styled = cell_xfs.children.len() - 4;
+            let xml = sheet.get_xml();
+            let mut xml = xml.lock().unwrap();
+            let row = &mut xml.elements[0]
+                .find_element_mut("sheetData")
+                .unwrap()
+                .children[0];
+            for (i, cell) in row.children.iter_mut().enumerate() {
+                cell.attributes
+                    .insert("s".to_string(), (styled + i).to_string());
+            }
+        }
+
+        // Act
+        sheet.auto_fit_columns();
+
+        // Assert
+        let columns = sheet.column_dimensions();
+        let width = |column: &str| columns.__getitem__(column).unwrap().get_width();
+        assert_eq!(width("A"), Some(8.2));
+        assert_eq!(width("B"), Some(7.0));
+        assert_eq!(width("C"), Some(8.5));
+        assert_eq!(width("D"), Some(10.5));
+    }
+
+    #[test]
+    fn test_merge_cells() {
+        // 観点: 結合で左上以外の値が消え、重なる結合はエラーになり、行の挿入後も結合範囲が保たれるか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+        sheet.append(vec!["anchor".to_string(), "other".to_string()]);
+
+        // Act
+        sheet.merge_cells("A1:B2").unwrap();
+
+        // Assert
+        assert_eq!(sheet.__getitem__("A1").value().unwrap(), "anchor");
+        assert!(sheet.__getitem__("B1").value().is_none());
+        assert_eq!(
+            sheet.merge_cells("B2:C3"),
+            Err(SheetError::OverlappingMerge(
+                "B2:C3".to_string(),
+                "A1:B2".to_string()
+            ))
+        );
+        sheet.insert_rows(1, 1).unwrap();
+        assert_eq!(sheet.merged_cells(), vec!["A2:B3"]);
+        assert_eq!(
+            sheet.unmerge_cells("A1:B2"),
+            Err(SheetError::NotMerged("A1:B2".to_string()))
+        );
+        sheet.unmerge_cells("A2:B3").unwrap();
+        assert!(sheet.merged_cells().is_empty());
+    }
+
+    #[test]
+    fn test_stale_handle_after_rename() {
+        // 観点: 別のハンドルでシート名を変更した後も古いハンドルが新しい名前で動作し、削除後はエラーになるか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Data".to_string(), 0);
+        let other = book.create_sheet("Other".to_string(), 1);
+        sheet.__getitem__("A1").set_value("1".to_string()).unwrap();
+        other
+            .__getitem__("A1")
+            .set_value("=Data!A1".to_string())
+            .unwrap();
+
+        // Act
+        book.get_sheet_by_name("Data")
+            .unwrap()
+            .set_title("Renamed")
+            .unwrap();
+        sheet.insert_rows(1, 1).unwrap();
+        sheet.set_print_area(Some("A1:B2")).unwrap();
+
+        // Assert
+        assert_eq!(sheet.get_title().unwrap(), "Renamed");
+        assert_eq!(
+            other.__getitem__("A1").formula().as_deref(),
+            Some("Renamed!A2")
+        );
+        assert_eq!(sheet.get_print_area().as_deref(), Some("A1:B2"));
+        assert_eq!(sheet.get_sheet_state().unwrap(), "visible");
+        book.remove(&sheet).unwrap();
+        assert!(sheet.get_title().is_err());
+        assert!(sheet.set_print_area(Some("A1:B2")).is_err());
+        assert!(sheet.set_print_title_rows(Some("1:1")).is_err());
+        assert!(sheet.set_sheet_state("hidden").is_err());
+        assert!(sheet.get_sheet_state().is_err());
+    }
+
+    #[test]
+    fn test_sheet_state() {
+        // 観点: シートの表示状態を設定でき、アクティブなシートや最後の表示されているシートは非表示にできないか
+        let mut book = Book::new("");
+        let first = book.create_sheet("A".to_string(), 0);
+        let second = book.create_sheet("B".to_string(), 1);
+        let third = book.create_sheet("C".to_string(), 2);
+
+        // Act
+        second.set_sheet_state("hidden").unwrap();
+        third.set_sheet_state("veryHidden").unwrap();
+
+        // Assert
+        assert_eq!(first.get_sheet_state().unwrap(), "visible");
+        assert_eq!(second.get_sheet_state().unwrap(), "hidden");
+        assert_eq!(third.get_sheet_state().unwrap(), "veryHidden");
+        assert_eq!(book.sheetnames(), vec!["A", "B", "C"]);
+        assert_eq!(book.visible_sheetnames(), vec!["A"]);
+        assert_eq!(
+            first.set_sheet_state("hidden"),
+            Err(BookError::ActiveSheetHidden("A".to_string()))
+        );
+        assert_eq!(
+            second.set_sheet_state("shown"),
+            Err(BookError::InvalidSheetState("shown".to_string()))
+        );
+
+        second.set_sheet_state("visible").unwrap();
+        book.set_active(SheetKey::Name("B".to_string())).unwrap();
+        first.set_sheet_state("hidden").unwrap();
+        assert_eq!(book.visible_sheetnames(), vec!["B"]);
+        let tag = &book.sheet_tags()[1];
+        assert!(tag.get_attribute("state").is_none());
+        assert_eq!(
+            book.set_active(SheetKey::Name("C".to_string())),
+            Err(BookError::HiddenSheetActivated("C".to_string()))
+        );
+        assert_eq!(
+            book.set_active(SheetKey::Index(0)),
+            Err(BookError::HiddenSheetActivated("A".to_string()))
+        );
+        assert_eq!(book.active().unwrap().name().unwrap(), "B");
+    }
+
+    #[test]
+    fn test_remove_with_hidden_sheets() {
+        // 観点: アクティブなシートの削除後に表示されているシートがアクティブになり、最後の表示されているシートは削除できないか
+        let mut book = Book::new("");
+        let first = book.create_sheet("A".to_string(), 0);
+        let second = book.create_sheet("B".to_string(), 1);
+        let third = book.create_sheet("C".to_string(), 2);
+        let fourth = book.create_sheet("D".to_string(), 3);
+        second.set_sheet_state("hidden").unwrap();
+        fourth.set_sheet_state("veryHidden").unwrap();
+
+        // Act
+        book.remove(&first).unwrap();
+
+        // Assert
+        assert_eq!(book.sheetnames(), vec!["B", "C", "D"]);
+        assert_eq!(book.active().unwrap().name().unwrap(), "C");
+        assert_eq!(book.remove(&third), Err(BookError::LastVisibleSheet));
+        assert_eq!(book.sheetnames(), vec!["B", "C", "D"]);
+        book.remove(&fourth).unwrap();
+        assert_eq!(book.sheetnames(), vec!["B", "C"]);
+        assert_eq!(book.active().unwrap().name().unwrap(), "C");
     }
 }
@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests {
+    use crate::book::Book;
+    use crate::sheet::SheetError;
+
+    #[test]
+    fn test_freeze_panes() {
+        // 観点: ウィンドウ枠の固定で pane と選択範囲が書き込まれ、取得・解除できるか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+
+        // Act
+        sheet.set_freeze_panes(Some("B2")).unwrap();
+
+        // Assert
+        assert_eq!(sheet.get_freeze_panes().as_deref(), Some("B2"));
+        assert_eq!(sheet.get_active_pane(), "bottomRight");
+        {
+            let xml = sheet.get_xml();
+            let xml = xml.lock().unwrap();
+            let worksheet = &xml.elements[0];
+            let names: Vec<&str> = worksheet.children.iter().map(|c| c.name.as_str()).collect();
+            let views = names.iter().position(|n| *n == "sheetViews").unwrap();
+            let data = names.iter().position(|n| *n == "sheetData").unwrap();
+            assert!(views < data);
+            let view = worksheet.get_element("sheetViews>sheetView");
+            let pane = view.get_element("pane");
+            assert_eq!(pane.get_attribute("xSplit").unwrap(), "1");
+            assert_eq!(pane.get_attribute("ySplit").unwrap(), "1");
+            assert_eq!(pane.get_attribute("state").unwrap(), "frozen");
+            assert_eq!(view.get_elements("selection").len(), 3);
+        }
+        assert_eq!(
+            sheet.get_selection(None),
+            Some(("B2".to_string(), "B2".to_string()))
+        );
+        sheet.set_freeze_panes(None).unwrap();
+        assert_eq!(sheet.get_freeze_panes(), None);
+        assert_eq!(sheet.get_active_pane(), "topLeft");
+        assert!(sheet.set_freeze_panes(Some("2B")).is_err());
+    }
+
+    #[test]
+    fn test_split_panes_and_selection() {
+        // 観点: 分割したウィンドウ枠ごとに選択範囲を設定でき、アクティブな枠を切り替えられるか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+
+        // Act
+        sheet.split_panes(2400.0, 1800.0, Some("D5")).unwrap();
+        sheet
+            .set_selection("E6:F8", None, Some("bottomRight"))
+            .unwrap();
+        sheet.set_selection("A1", None, Some("topLeft")).unwrap();
+        sheet.set_active_pane("topLeft").unwrap();
+
+        // Assert
+        assert_eq!(sheet.pane_split(), Some((2400.0, 1800.0)));
+        assert_eq!(sheet.get_freeze_panes(), None);
+        assert_eq!(sheet.get_active_pane(), "topLeft");
+        assert_eq!(
+            sheet.get_selection(Some("bottomRight")),
+            Some(("E6".to_string(), "E6:F8".to_string()))
+        );
+        assert_eq!(
+            sheet.get_selection(None),
+            Some(("A1".to_string(), "A1".to_string()))
+        );
+        assert_eq!(
+            sheet.set_active_pane("middle"),
+            Err(SheetError::InvalidPane("middle".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_sheet_view_settings() {
+        // 観点: 表示倍率・枠線・見出し・右から左・表示モード・見出しの色を設定でき、sheetPr が先頭に置かれるか
+        let mut book = Book::new("");
+        let sheet = book.create_sheet("Sheet1".to_string(), 0);
+
+        // Act
+        sheet.set_zoom_scale(85).unwrap();
+        sheet.set_show_grid_lines(false);
+        sheet.set_show_row_col_headers(false);
+        sheet.set_right_to_left(true);
+        sheet.set_view("pageBreakPreview").unwrap();
+        sheet.set_active_cell("C3").unwrap();
+        sheet.set_tab_color(Some("0070c0")).unwrap();
+
+        // Assert
+        assert_eq!(sheet.get_zoom_scale(), 85);
+        assert!(!sheet.get_show_grid_lines());
+        assert!(!sheet.get_show_row_col_headers());
+        assert!(sheet.get_right_to_left());
+        assert_eq!(sheet.get_view(), "pageBreakPreview");
+        assert_eq!(sheet.get_active_cell(), "C3");
+        assert_eq!(sheet.get_selected_cells(), "C3");
+        assert_eq!(sheet.get_tab_color().as_deref(), Some("FF0070C0"));
+        {
+            let xml = sheet.get_xml();
+            let xml = xml.lock().unwrap();
+            assert_eq!(xml.elements[0].children[0].name, "sheetPr");
+        }
+        assert_eq!(sheet.set_zoom_scale(5), Err(SheetError::InvalidZoom(5)));
+        assert!(sheet.set_view("outline").is_err());
+        assert!(sheet.set_tab_color(Some("blue")).is_err());
+        sheet.set_show_grid_lines(true);
+        sheet.set_tab_color(None).unwrap();
+        assert!(sheet.get_show_grid_lines());
+        assert_eq!(sheet.get_tab_color(), None);
+    }
+}
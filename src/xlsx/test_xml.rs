@@ -64,4 +64,39 @@ mod tests {
         // Assert
         assert!(!buf.is_empty());
     }
+
+    #[test]
+    fn test_xml_read_entities() {
+        // 観点: 実体参照を含むテキストが欠けずに読み取れること
+
+        // Act
+        let xml: Xml = Xml::new(r#"<f>"a"&amp;"b"&#x41;</f>"#).unwrap();
+
+        // Assert
+        assert_eq!(xml.elements[0].get_text(), r#""a"&"b"A"#);
+        let buf = String::from_utf8(xml.to_buf().unwrap()).unwrap();
+        assert!(buf.contains("&amp;"));
+    }
+
+    #[test]
+    fn test_xml_round_trip_indented() {
+        // 観点: 字下げされたXMLの要素間の空白が親要素のテキストに連結されず、書き出しと再読み込みで変わらないこと
+        let indented: &str = "<sheetData>\n    <row r=\"1\">\n        <c r=\"A1\">\n            <v>1.0</v>\n        </c>\n        <c r=\"B1\" />\n    </row>\n</sheetData>";
+
+        // Act
+        let xml: Xml = Xml::new(indented).unwrap();
+        let buf: String = String::from_utf8(xml.to_buf().unwrap()).unwrap();
+        let reloaded: Xml = Xml::new(&buf).unwrap();
+
+        // Assert
+        let sheet_data = &xml.elements[0];
+        assert!(sheet_data.text.is_none());
+        assert!(sheet_data.children[0].text.is_none());
+        assert!(sheet_data.children[0].children[0].text.is_none());
+        assert_eq!(sheet_data.get_element("row>c>v").get_text(), "1.0");
+        assert!(buf.ends_with(
+            r#"<sheetData><row r="1"><c r="A1"><v>1.0</v></c><c r="B1"/></row></sheetData>"#
+        ));
+        assert_eq!(String::from_utf8(reloaded.to_buf().unwrap()).unwrap(), buf);
+    }
 }
@@ -0,0 +1,324 @@
+use crate::formula::{parse_address, to_address};
+use crate::sheet::WORKSHEET_CHILD_ORDER;
+use crate::xml::XmlElement;
+
+/// `sheetView` の子要素の順序
+pub const SHEET_VIEW_CHILD_ORDER: [&str; 4] = ["pane", "selection", "pivotSelection", "extLst"];
+
+/// `sheetPr` の子要素の順序
+pub const SHEET_PR_CHILD_ORDER: [&str; 3] = ["tabColor", "outlinePr", "pageSetUpPr"];
+
+/// シートの表示モードの一覧
+pub const VIEWS: [&str; 3] = ["normal", "pageLayout", "pageBreakPreview"];
+
+/// ウィンドウ枠の名前の一覧
+pub const PANES: [&str; 4] = ["topLeft", "topRight", "bottomLeft", "bottomRight"];
+
+/// 最初の `sheetView` 要素の取得
+pub fn sheet_view(worksheet: &XmlElement) -> Option<&XmlElement> {
+    worksheet.find_element("sheetViews>sheetView")
+}
+
+/// 最初の `sheetView` 要素の取得または作成
+pub fn sheet_view_mut(worksheet: &mut XmlElement) -> &mut XmlElement {
+    let sheet_views: &mut XmlElement =
+        worksheet.get_or_create_child_ordered("sheetViews", &WORKSHEET_CHILD_ORDER);
+    if !sheet_views.children.iter().any(|c| c.name == "sheetView") {
+        let mut view: XmlElement = XmlElement::new("sheetView");
+        view.attributes
+            .insert("workbookViewId".to_string(), "0".to_string());
+        sheet_views.children.insert(0, view);
+    }
+    sheet_views
+        .children
+        .iter_mut()
+        .find(|c| c.name == "sheetView")
+        .expect("sheetView exists")
+}
+
+/// `sheetView` の属性の取得
+pub fn view_attribute(worksheet: &XmlElement, key: &str) -> Option<String> {
+    sheet_view(worksheet)?.get_attribute(key).cloned()
+}
+
+/// `sheetView` の真偽値の属性の取得
+pub fn view_flag(worksheet: &XmlElement, key: &str, default: bool) -> bool {
+    view_attribute(worksheet, key).map_or(default, |v| v == "1" || v == "true")
+}
+
+/// `sheetView` の属性の設定 (`None` の場合は削除)
+pub fn set_view_attribute(worksheet: &mut XmlElement, key: &str, value: Option<String>) {
+    let view: &mut XmlElement = sheet_view_mut(worksheet);
+    match value {
+        Some(value) => {
+            view.attributes.insert(key.to_string(), value);
+        }
+        None => {
+            view.attributes.remove(key);
+        }
+    }
+}
+
+/// `sheetView` の真偽値の属性の設定 (既定値と同じ場合は削除)
+pub fn set_view_flag(worksheet: &mut XmlElement, key: &str, value: bool, default: bool) {
+    let value: Option<String> =
+        (value != default).then(|| if value { "1" } else { "0" }.to_string());
+    set_view_attribute(worksheet, key, value);
+}
+
+/// シート見出しの色 (ARGB形式)
+pub fn tab_color(worksheet: &XmlElement) -> Option<String> {
+    worksheet
+        .find_element("sheetPr>tabColor")?
+        .get_attribute("rgb")
+        .cloned()
+}
+
+/// シート見出しの色の設定 (`None` の場合は削除)
+pub fn set_tab_color(worksheet: &mut XmlElement, color: Option<&str>) {
+    match color {
+        Some(color) => {
+            let sheet_pr: &mut XmlElement =
+                worksheet.get_or_create_child_ordered("sheetPr", &WORKSHEET_CHILD_ORDER);
+            let tab_color: &mut XmlElement =
+                sheet_pr.get_or_create_child_ordered("tabColor", &SHEET_PR_CHILD_ORDER);
+            tab_color.attributes = [("rgb".to_string(), color.to_string())].into();
+        }
+        None => {
+            if let Some(sheet_pr) = worksheet.find_element_mut("sheetPr") {
+                sheet_pr.children.retain(|c| c.name != "tabColor");
+            }
+        }
+    }
+}
+
+/// 分割の有無から決まる既定のアクティブなウィンドウ枠
+fn default_active_pane(x_split: bool, y_split: bool) -> &'static str {
+    match (x_split, y_split) {
+        (true, true) => "bottomRight",
+        (true, false) => "topRight",
+        (false, true) => "bottomLeft",
+        (false, false) => "topLeft",
+    }
+}
+
+/// 固定されたウィンドウ枠の左上のセル
+///
+/// 固定されていない場合は `None`
+pub fn freeze_panes(worksheet: &XmlElement) -> Option<String> {
+    let pane: &XmlElement = sheet_view(worksheet)?.find_element("pane")?;
+    if !matches!(
+        pane.get_attribute("state").map(String::as_str),
+        Some("frozen" | "frozenSplit")
+    ) {
+        return None;
+    }
+    if let Some(cell) = pane.get_attribute("topLeftCell") {
+        return Some(cell.clone());
+    }
+    let split = |key: &str| -> u32 {
+        pane.get_attribute(key)
+            .and_then(|v| v.parse::<f64>().ok())
+            .map_or(0, |v| v as u32)
+    };
+    Some(to_address(split("ySplit") + 1, split("xSplit") + 1))
+}
+
+/// ウィンドウ枠の固定
+///
+/// `cell` より上の行と左の列を固定し、`None` または "A1" の場合は解除
+pub fn set_freeze_panes(worksheet: &mut XmlElement, cell: Option<(u32, u32)>) {
+    let view: &mut XmlElement = sheet_view_mut(worksheet);
+    let Some((row, col)) = cell.filter(|&c| c != (1, 1)) else {
+        remove_pane(view);
+        return;
+    };
+    let (x_split, y_split) = (col - 1, row - 1);
+    let top_left: String = to_address(row, col);
+    let mut attributes: Vec<(&str, String)> = Vec::with_capacity(5);
+    if x_split > 0 {
+        attributes.push(("xSplit", x_split.to_string()));
+    }
+    if y_split > 0 {
+        attributes.push(("ySplit", y_split.to_string()));
+    }
+    attributes.push(("topLeftCell", top_left.clone()));
+    let active_pane: &str = default_active_pane(x_split > 0, y_split > 0);
+    attributes.push(("activePane", active_pane.to_string()));
+    attributes.push(("state", "frozen".to_string()));
+    set_pane(view, attributes);
+
+    // Excelと同様に固定で生じるウィンドウ枠ごとの選択範囲を作成
+    view.children.retain(|c| c.name != "selection");
+    let panes: Vec<&str> = match (x_split > 0, y_split > 0) {
+        (true, true) => vec!["topRight", "bottomLeft", "bottomRight"],
+        _ => vec![active_pane],
+    };
+    for pane in panes {
+        let cell: String = if pane == active_pane {
+            top_left.clone()
+        } else {
+            "A1".to_string()
+        };
+        set_selection(view, pane, &cell, &cell);
+    }
+}
+
+/// ウィンドウ枠の分割
+///
+/// `x_split` と `y_split` は分割位置 (1/20 ポイント)。両方が0の場合は分割を解除
+pub fn set_split_panes(
+    worksheet: &mut XmlElement,
+    x_split: f64,
+    y_split: f64,
+    top_left_cell: Option<(u32, u32)>,
+) {
+    let view: &mut XmlElement = sheet_view_mut(worksheet);
+    if x_split <= 0.0 && y_split <= 0.0 {
+        remove_pane(view);
+        return;
+    }
+    let mut attributes: Vec<(&str, String)> = Vec::with_capacity(4);
+    if x_split > 0.0 {
+        attributes.push(("xSplit", x_split.to_string()));
+    }
+    if y_split > 0.0 {
+        attributes.push(("ySplit", y_split.to_string()));
+    }
+    if let Some((row, col)) = top_left_cell {
+        attributes.push(("topLeftCell", to_address(row, col)));
+    }
+    let active_pane: &str = default_active_pane(x_split > 0.0, y_split > 0.0);
+    attributes.push(("activePane", active_pane.to_string()));
+    set_pane(view, attributes);
+}
+
+/// 分割されたウィンドウ枠の分割位置 (1/20 ポイント)
+///
+/// 分割されていない場合や固定されている場合は `None`
+pub fn split_panes(worksheet: &XmlElement) -> Option<(f64, f64)> {
+    let pane: &XmlElement = sheet_view(worksheet)?.find_element("pane")?;
+    if pane
+        .get_attribute("state")
+        .is_some_and(|state| state != "split")
+    {
+        return None;
+    }
+    let split = |key: &str| -> f64 {
+        pane.get_attribute(key)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0.0)
+    };
+    Some((split("xSplit"), split("ySplit")))
+}
+
+/// アクティブなウィンドウ枠
+pub fn active_pane(worksheet: &XmlElement) -> String {
+    sheet_view(worksheet)
+        .and_then(|view| view.find_element("pane"))
+        .and_then(|pane| pane.get_attribute("activePane").cloned())
+        .unwrap_or_else(|| "topLeft".to_string())
+}
+
+/// アクティブなウィンドウ枠の設定
+///
+/// ウィンドウ枠が分割・固定されていない場合は何もしない
+pub fn set_active_pane(worksheet: &mut XmlElement, pane: &str) {
+    if let Some(element) = sheet_view_mut(worksheet)
+        .children
+        .iter_mut()
+        .find(|c| c.name == "pane")
+    {
+        element
+            .attributes
+            .insert("activePane".to_string(), pane.to_string());
+    }
+}
+
+/// ウィンドウ枠の選択範囲 (`activeCell`, `sqref`)
+pub fn selection(worksheet: &XmlElement, pane: &str) -> Option<(String, String)> {
+    let selection: &XmlElement = sheet_view(worksheet)?
+        .children
+        .iter()
+        .find(|c| c.name == "selection" && selection_pane(c) == pane)?;
+    let sqref: String = selection
+        .get_attribute("sqref")
+        .cloned()
+        .unwrap_or_else(|| "A1".to_string());
+    let active_cell: String = selection
+        .get_attribute("activeCell")
+        .cloned()
+        .unwrap_or_else(|| sqref.split([' ', ':']).next().unwrap_or("A1").to_string());
+    Some((active_cell, sqref))
+}
+
+/// ウィンドウ枠の選択範囲の設定
+pub fn set_selection(view: &mut XmlElement, pane: &str, active_cell: &str, sqref: &str) {
+    let position: usize = match view
+        .children
+        .iter()
+        .position(|c| c.name == "selection" && selection_pane(c) == pane)
+    {
+        Some(pos) => pos,
+        None => {
+            // 他の選択範囲はウィンドウ枠の順に並べる
+            let rank = |name: &str| PANES.iter().position(|p| *p == name);
+            let pos: usize = view
+                .children
+                .iter()
+                .position(|c| {
+                    (c.name == "selection" && rank(selection_pane(c)) > rank(pane))
+                        || c.name == "pivotSelection"
+                        || c.name == "extLst"
+                })
+                .unwrap_or(view.children.len());
+            view.children.insert(pos, XmlElement::new("selection"));
+            pos
+        }
+    };
+    let selection: &mut XmlElement = &mut view.children[position];
+    if pane == "topLeft" {
+        selection.attributes.remove("pane");
+    } else {
+        selection
+            .attributes
+            .insert("pane".to_string(), pane.to_string());
+    }
+    selection
+        .attributes
+        .insert("activeCell".to_string(), active_cell.to_string());
+    selection
+        .attributes
+        .insert("sqref".to_string(), sqref.to_string());
+}
+
+/// 選択範囲の属するウィンドウ枠の名前
+fn selection_pane(selection: &XmlElement) -> &str {
+    selection
+        .get_attribute("pane")
+        .map_or("topLeft", String::as_str)
+}
+
+/// `pane` 要素の置き換え
+fn set_pane(view: &mut XmlElement, attributes: Vec<(&str, String)>) {
+    let pane: &mut XmlElement = view.get_or_create_child_ordered("pane", &SHEET_VIEW_CHILD_ORDER);
+    pane.attributes = attributes
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+}
+
+/// `pane` 要素と、左上以外のウィンドウ枠の選択範囲の削除
+fn remove_pane(view: &mut XmlElement) {
+    view.children
+        .retain(|c| c.name != "pane" && !(c.name == "selection" && selection_pane(c) != "topLeft"));
+}
+
+/// セルアドレスまたは空白区切りの範囲の一覧の検証
+pub fn is_valid_sqref(sqref: &str) -> bool {
+    !sqref.trim().is_empty()
+        && sqref.split_whitespace().all(|area| {
+            let (start, end) = area.split_once(':').unwrap_or((area, area));
+            parse_address(start).is_some() && parse_address(end).is_some()
+        })
+}
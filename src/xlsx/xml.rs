@@ -1,5 +1,6 @@
 use pyo3::prelude::*;
 use quick_xml::encoding::EncodingError;
+use quick_xml::escape::resolve_predefined_entity;
 use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
 use quick_xml::{Reader, Writer};
 use std::collections::HashMap;
@@ -84,6 +85,53 @@ impl XmlElement {
         current_element
     }
 
+    /// パスによる要素の検索
+    ///
+    /// 要素が存在しない場合は `None`
+    pub fn find_element(&self, path: &str) -> Option<&XmlElement> {
+        let mut current_element: &XmlElement = self;
+        for tag in path.split('>') {
+            current_element = current_element.children.iter().find(|c| c.name == tag)?;
+        }
+        Some(current_element)
+    }
+
+    /// パスによる要素の可変参照の検索
+    ///
+    /// 要素が存在しない場合は `None`
+    pub fn find_element_mut(&mut self, path: &str) -> Option<&mut XmlElement> {
+        let mut current_element: &mut XmlElement = self;
+        for tag in path.split('>') {
+            current_element = current_element
+                .children
+                .iter_mut()
+                .find(|c| c.name == tag)?;
+        }
+        Some(current_element)
+    }
+
+    /// タグ名による子要素への可変参照の取得
+    ///
+    /// 子要素が存在しない場合は `order` で定められた順序の位置に作成
+    pub fn get_or_create_child_ordered(
+        &mut self,
+        tag_name: &str,
+        order: &[&str],
+    ) -> &mut XmlElement {
+        if let Some(pos) = self.children.iter().position(|c| c.name == tag_name) {
+            return &mut self.children[pos];
+        }
+        let rank = |name: &str| order.iter().position(|o| *o == name);
+        let new_rank: Option<usize> = rank(tag_name);
+        let pos: usize = self
+            .children
+            .iter()
+            .position(|c| matches!((rank(&c.name), new_rank), (Some(r), Some(n)) if r > n))
+            .unwrap_or(self.children.len());
+        self.children.insert(pos, XmlElement::new(tag_name));
+        &mut self.children[pos]
+    }
+
     pub fn get_attribute(&self, key: &str) -> Option<&String> {
         self.attributes.get(key)
     }
@@ -204,17 +252,41 @@ impl Xml {
 
         loop {
             match reader.read_event_into(&mut buf)? {
-                Event::Start(e) => children.push(Self::parse_element(reader, &e)?),
+                // 子要素の間の字下げなどは連結せず、実体参照やCDATAと隣り合うテキストのみ連結
+                Event::Start(e) => {
+                    text = None;
+                    children.push(Self::parse_element(reader, &e)?);
+                }
                 Event::Text(e) => {
-                    text = Some(e.decode()?.to_string());
+                    text.get_or_insert_with(String::new).push_str(&e.decode()?);
+                }
+                Event::CData(e) => {
+                    text.get_or_insert_with(String::new).push_str(&e.decode()?);
+                }
+                // 実体参照は独立したイベントとして通知されるため、テキストに連結
+                Event::GeneralRef(e) => {
+                    let entity: String = e.decode()?.to_string();
+                    let resolved: String = match e.resolve_char_ref()? {
+                        Some(ch) => ch.to_string(),
+                        None => resolve_predefined_entity(&entity)
+                            .map_or_else(|| format!("&{entity};"), str::to_string),
+                    };
+                    text.get_or_insert_with(String::new).push_str(&resolved);
                 }
                 Event::End(e) if e.name() == start_tag.name() => break,
-                Event::Empty(e) => children.push(Self::parse_empty_element(&e)?),
+                Event::Empty(e) => {
+                    text = None;
+                    children.push(Self::parse_empty_element(&e)?);
+                }
                 Event::Eof => break,
                 _ => {}
             }
             buf.clear();
         }
+        // 子要素を持つ要素の末尾の字下げは保持しない
+        if !children.is_empty() && text.as_deref().is_some_and(|t| t.trim().is_empty()) {
+            text = None;
+        }
 
         Ok(XmlElement {
             name,